@@ -0,0 +1,241 @@
+use super::zip::ZipEncoder;
+use crate::aws::{download, AwsS3Error};
+use bytes::Bytes;
+use futures::prelude::*;
+use gotham::hyper::body::Sender;
+use gotham::hyper::{Body, Error as HyperError};
+use image::imageops::FilterType;
+use image::{GenericImageView, ImageError, ImageOutputFormat};
+use photo_core::models::Photo;
+use serde::Deserialize;
+use snafu::{Backtrace, OptionExt, ResultExt};
+use std::collections::HashSet;
+
+/// Size of the photos inside an album archive.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DownloadSize {
+    Original,
+    Large,
+    Medium,
+    Small,
+}
+
+impl DownloadSize {
+    /// Longest side of the derivative, `None` for the original file.
+    fn max_dimension(self) -> Option<u32> {
+        match self {
+            DownloadSize::Original => None,
+            DownloadSize::Large => Some(2048),
+            DownloadSize::Medium => Some(1280),
+            DownloadSize::Small => Some(640),
+        }
+    }
+}
+
+impl Default for DownloadSize {
+    fn default() -> Self {
+        DownloadSize::Original
+    }
+}
+
+/// Streams a ZIP archive with the given photos. Photos are fetched from S3 one at a time while
+/// the archive is being sent, so only a single photo is ever held in memory, and only when it
+/// needs to be resized.
+pub fn album_archive(mut photos: Vec<Photo>, size: DownloadSize) -> Body {
+    let (mut sender, body) = Body::channel();
+
+    photos.sort_by(|a, b| {
+        a.index_in_album
+            .cmp(&b.index_in_album)
+            .then(a.created_at.cmp(&b.created_at))
+    });
+
+    tokio::spawn(async move {
+        if let Err(e) = write_archive(&mut sender, photos, size).await {
+            error!("Could not stream album archive: {}", e);
+            sender.abort();
+        }
+    });
+
+    body
+}
+
+async fn write_archive(sender: &mut Sender, photos: Vec<Photo>, size: DownloadSize) -> Result<()> {
+    let mut zip = ZipEncoder::new();
+    let mut names = HashSet::new();
+
+    for photo in photos {
+        let name = unique_name(&mut names, &photo, size);
+        sender
+            .send_data(zip.start_entry(&name, photo.created_at))
+            .await
+            .context(SendIssue)?;
+
+        let mut stream = download(photo.s3_id.clone()).await.context(AwsS3Issue)?;
+        let mut hasher = crc32fast::Hasher::new();
+        let mut length: u64 = 0;
+
+        match size.max_dimension() {
+            None => {
+                while let Some(chunk) = stream.next().await {
+                    let chunk = chunk.context(ReadIssue)?;
+                    hasher.update(&chunk);
+                    length += chunk.len() as u64;
+                    sender.send_data(chunk).await.context(SendIssue)?;
+                }
+            }
+            Some(max) => {
+                let mut original = Vec::new();
+                while let Some(chunk) = stream.next().await {
+                    original.extend_from_slice(&chunk.context(ReadIssue)?);
+                }
+
+                let resized = tokio::task::spawn_blocking(move || resize(&original, max))
+                    .await
+                    .context(ResizeTaskIssue)?
+                    .context(ResizeIssue)?;
+                hasher.update(&resized);
+                length += resized.len() as u64;
+                sender.send_data(resized).await.context(SendIssue)?;
+            }
+        }
+
+        let descriptor = zip
+            .finish_entry(hasher.finalize(), length)
+            .context(EntryTooLarge { name })?;
+        sender.send_data(descriptor).await.context(SendIssue)?;
+    }
+
+    sender.send_data(zip.finish()).await.context(SendIssue)?;
+
+    Ok(())
+}
+
+/// Fits the image within `max` pixels and encodes it as JPEG. Smaller images are not enlarged.
+fn resize(original: &[u8], max: u32) -> std::result::Result<Bytes, ImageError> {
+    let image = image::load_from_memory(original)?;
+    let image = if image.width() > max || image.height() > max {
+        image.resize(max, max, FilterType::Lanczos3)
+    } else {
+        image
+    };
+
+    let mut buf = Vec::new();
+    image.write_to(&mut buf, ImageOutputFormat::Jpeg(90))?;
+
+    Ok(Bytes::from(buf))
+}
+
+/// Names entries after their position and title, e.g. `003 - First dance.jpg`, adding a suffix
+/// when two photos would end up with the same name.
+fn unique_name(names: &mut HashSet<String>, photo: &Photo, size: DownloadSize) -> String {
+    let filename = photo.filename.as_deref().unwrap_or("");
+    let (stem, extension) = match filename.rfind('.') {
+        Some(idx) => (&filename[..idx], filename[idx + 1..].to_lowercase()),
+        None => (filename, String::from("jpg")),
+    };
+    let extension = match size {
+        DownloadSize::Original => extension,
+        _ => String::from("jpg"),
+    };
+
+    let title = photo
+        .title
+        .as_deref()
+        .map(sanitize)
+        .filter(|t| !t.is_empty())
+        .unwrap_or_else(|| sanitize(stem));
+    let base = if title.is_empty() {
+        format!("{:03}", photo.index_in_album)
+    } else {
+        format!("{:03} - {}", photo.index_in_album, title)
+    };
+
+    let mut name = format!("{}.{}", base, extension);
+    let mut copy = 1;
+    while names.contains(&name) {
+        copy += 1;
+        name = format!("{} ({}).{}", base, copy, extension);
+    }
+    names.insert(name.clone());
+
+    name
+}
+
+/// Replaces the characters that can't be used in file names on common file systems.
+pub fn sanitize(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect::<String>()
+        .trim()
+        .trim_matches('.')
+        .to_string()
+}
+
+pub type Result<T, E = ArchiveError> = std::result::Result<T, E>;
+
+#[derive(Debug, Snafu)]
+pub enum ArchiveError {
+    #[snafu(display("Could not get photo from S3: {}", source))]
+    AwsS3Issue {
+        source: AwsS3Error,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Could not read photo: {}", source))]
+    ReadIssue {
+        source: std::io::Error,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Could not resize photo: {}", source))]
+    ResizeIssue {
+        source: ImageError,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Resize task failed: {}", source))]
+    ResizeTaskIssue {
+        source: tokio::task::JoinError,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Photo {} is too big for the archive", name))]
+    EntryTooLarge { name: String, backtrace: Backtrace },
+
+    #[snafu(display("Could not send archive: {}", source))]
+    SendIssue {
+        source: HyperError,
+        backtrace: Backtrace,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sanitize_replaces_reserved_characters() {
+        assert_eq!(sanitize("Paris / Lyon: day 1?"), "Paris _ Lyon_ day 1_");
+        assert_eq!(sanitize("\"quoted\"\r\n"), "_quoted___");
+    }
+
+    #[test]
+    fn sanitize_trims_spaces_and_dots() {
+        assert_eq!(sanitize("  ..hidden.  "), "hidden");
+        assert_eq!(sanitize("Été 2020"), "Été 2020");
+    }
+
+    #[test]
+    fn sizes_fit_their_longest_side() {
+        assert_eq!(DownloadSize::Original.max_dimension(), None);
+        assert_eq!(DownloadSize::Small.max_dimension(), Some(640));
+        assert_eq!(DownloadSize::default(), DownloadSize::Original);
+    }
+}
@@ -0,0 +1,4 @@
+mod album;
+mod zip;
+
+pub use self::album::*;
@@ -0,0 +1,322 @@
+use bytes::Bytes;
+use chrono::{Datelike, NaiveDateTime, Timelike};
+
+const LOCAL_FILE_HEADER: u32 = 0x0403_4b50;
+const DATA_DESCRIPTOR: u32 = 0x0807_4b50;
+const CENTRAL_DIRECTORY_HEADER: u32 = 0x0201_4b50;
+const ZIP64_END_OF_CENTRAL_DIRECTORY: u32 = 0x0606_4b50;
+const ZIP64_END_OF_CENTRAL_DIRECTORY_LOCATOR: u32 = 0x0706_4b50;
+const END_OF_CENTRAL_DIRECTORY: u32 = 0x0605_4b50;
+
+const VERSION_DEFAULT: u16 = 20;
+const VERSION_ZIP64: u16 = 45;
+/// Sizes and CRC come after the data, names are UTF-8.
+const FLAGS: u16 = 0x0008 | 0x0800;
+const METHOD_STORED: u16 = 0;
+const ZIP64_EXTRA_FIELD: u16 = 0x0001;
+
+/// Writes a ZIP archive one piece at a time, so entries can be sent while their data is still
+/// being fetched. Entries are stored without compression since photos are already compressed;
+/// their CRC and size are written in a data descriptor after the data. Offsets past 4 GiB use
+/// ZIP64 records, while each entry must stay below 4 GiB.
+pub struct ZipEncoder {
+    offset: u64,
+    entries: Vec<ZipEntry>,
+}
+
+struct ZipEntry {
+    name: String,
+    time: u16,
+    date: u16,
+    offset: u64,
+    crc: u32,
+    size: u32,
+}
+
+impl ZipEncoder {
+    pub fn new() -> Self {
+        Self {
+            offset: 0,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Starts a new entry, returning its local header. The entry data must be sent right after.
+    pub fn start_entry(&mut self, name: &str, modified: NaiveDateTime) -> Bytes {
+        let (time, date) = dos_datetime(modified);
+        let entry = ZipEntry {
+            name: name.to_string(),
+            time,
+            date,
+            offset: self.offset,
+            crc: 0,
+            size: 0,
+        };
+
+        let mut buf = Vec::with_capacity(30 + entry.name.len());
+        put_u32(&mut buf, LOCAL_FILE_HEADER);
+        put_u16(&mut buf, VERSION_DEFAULT);
+        put_u16(&mut buf, FLAGS);
+        put_u16(&mut buf, METHOD_STORED);
+        put_u16(&mut buf, entry.time);
+        put_u16(&mut buf, entry.date);
+        put_u32(&mut buf, 0);
+        put_u32(&mut buf, 0);
+        put_u32(&mut buf, 0);
+        put_u16(&mut buf, entry.name.len() as u16);
+        put_u16(&mut buf, 0);
+        buf.extend_from_slice(entry.name.as_bytes());
+
+        self.offset += buf.len() as u64;
+        self.entries.push(entry);
+
+        Bytes::from(buf)
+    }
+
+    /// Finishes the current entry once all its data was sent, returning its data descriptor.
+    /// Returns `None` when the entry is too big to be described without ZIP64.
+    pub fn finish_entry(&mut self, crc: u32, size: u64) -> Option<Bytes> {
+        let entry = self.entries.last_mut()?;
+        if size > u64::from(u32::MAX) {
+            return None;
+        }
+
+        entry.crc = crc;
+        entry.size = size as u32;
+
+        let mut buf = Vec::with_capacity(16);
+        put_u32(&mut buf, DATA_DESCRIPTOR);
+        put_u32(&mut buf, entry.crc);
+        put_u32(&mut buf, entry.size);
+        put_u32(&mut buf, entry.size);
+
+        self.offset += size + buf.len() as u64;
+
+        Some(Bytes::from(buf))
+    }
+
+    /// Returns the central directory, which closes the archive.
+    pub fn finish(self) -> Bytes {
+        let mut buf = Vec::new();
+        let directory_offset = self.offset;
+
+        for entry in &self.entries {
+            let zip64 = entry.offset >= u64::from(u32::MAX);
+
+            put_u32(&mut buf, CENTRAL_DIRECTORY_HEADER);
+            put_u16(&mut buf, VERSION_ZIP64);
+            put_u16(
+                &mut buf,
+                if zip64 {
+                    VERSION_ZIP64
+                } else {
+                    VERSION_DEFAULT
+                },
+            );
+            put_u16(&mut buf, FLAGS);
+            put_u16(&mut buf, METHOD_STORED);
+            put_u16(&mut buf, entry.time);
+            put_u16(&mut buf, entry.date);
+            put_u32(&mut buf, entry.crc);
+            put_u32(&mut buf, entry.size);
+            put_u32(&mut buf, entry.size);
+            put_u16(&mut buf, entry.name.len() as u16);
+            put_u16(&mut buf, if zip64 { 12 } else { 0 });
+            put_u16(&mut buf, 0);
+            put_u16(&mut buf, 0);
+            put_u16(&mut buf, 0);
+            put_u32(&mut buf, 0);
+            put_u32(&mut buf, if zip64 { u32::MAX } else { entry.offset as u32 });
+            buf.extend_from_slice(entry.name.as_bytes());
+
+            if zip64 {
+                put_u16(&mut buf, ZIP64_EXTRA_FIELD);
+                put_u16(&mut buf, 8);
+                put_u64(&mut buf, entry.offset);
+            }
+        }
+
+        let directory_size = buf.len() as u64;
+        let count = self.entries.len() as u64;
+        let zip64 = count >= u64::from(u16::MAX)
+            || directory_size >= u64::from(u32::MAX)
+            || directory_offset >= u64::from(u32::MAX);
+
+        if zip64 {
+            let record_offset = directory_offset + directory_size;
+
+            put_u32(&mut buf, ZIP64_END_OF_CENTRAL_DIRECTORY);
+            put_u64(&mut buf, 44);
+            put_u16(&mut buf, VERSION_ZIP64);
+            put_u16(&mut buf, VERSION_ZIP64);
+            put_u32(&mut buf, 0);
+            put_u32(&mut buf, 0);
+            put_u64(&mut buf, count);
+            put_u64(&mut buf, count);
+            put_u64(&mut buf, directory_size);
+            put_u64(&mut buf, directory_offset);
+
+            put_u32(&mut buf, ZIP64_END_OF_CENTRAL_DIRECTORY_LOCATOR);
+            put_u32(&mut buf, 0);
+            put_u64(&mut buf, record_offset);
+            put_u32(&mut buf, 1);
+        }
+
+        put_u32(&mut buf, END_OF_CENTRAL_DIRECTORY);
+        put_u16(&mut buf, 0);
+        put_u16(&mut buf, 0);
+        put_u16(&mut buf, count.min(u64::from(u16::MAX)) as u16);
+        put_u16(&mut buf, count.min(u64::from(u16::MAX)) as u16);
+        put_u32(&mut buf, directory_size.min(u64::from(u32::MAX)) as u32);
+        put_u32(&mut buf, directory_offset.min(u64::from(u32::MAX)) as u32);
+        put_u16(&mut buf, 0);
+
+        Bytes::from(buf)
+    }
+}
+
+/// MS-DOS time and date, the only timestamp every ZIP reader understands.
+fn dos_datetime(datetime: NaiveDateTime) -> (u16, u16) {
+    let year = datetime.year().max(1980) - 1980;
+    let time = (datetime.hour() << 11) | (datetime.minute() << 5) | (datetime.second() / 2);
+    let date = ((year as u32) << 9) | (datetime.month() << 5) | datetime.day();
+
+    (time as u16, date as u16)
+}
+
+fn put_u16(buf: &mut Vec<u8>, value: u16) {
+    buf.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_u32(buf: &mut Vec<u8>, value: u32) {
+    buf.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_u64(buf: &mut Vec<u8>, value: u64) {
+    buf.extend_from_slice(&value.to_le_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn modified() -> NaiveDateTime {
+        NaiveDate::from_ymd(2020, 11, 28).and_hms(14, 30, 10)
+    }
+
+    fn u16_at(buf: &[u8], at: usize) -> u16 {
+        u16::from_le_bytes([buf[at], buf[at + 1]])
+    }
+
+    fn u32_at(buf: &[u8], at: usize) -> u32 {
+        u32::from_le_bytes([buf[at], buf[at + 1], buf[at + 2], buf[at + 3]])
+    }
+
+    fn u64_at(buf: &[u8], at: usize) -> u64 {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&buf[at..at + 8]);
+        u64::from_le_bytes(bytes)
+    }
+
+    #[test]
+    fn writes_a_stored_archive() {
+        let data = b"hello";
+        let mut hasher = crc32fast::Hasher::new();
+        hasher.update(data);
+        let crc = hasher.finalize();
+        let mut zip = ZipEncoder::new();
+
+        let mut archive = zip.start_entry("001 - hello.txt", modified()).to_vec();
+        archive.extend_from_slice(data);
+        archive.extend_from_slice(&zip.finish_entry(crc, data.len() as u64).unwrap());
+        let directory_offset = archive.len();
+        archive.extend_from_slice(&zip.finish());
+
+        assert_eq!(u32_at(&archive, 0), LOCAL_FILE_HEADER);
+        assert_eq!(u16_at(&archive, 6), FLAGS);
+        assert_eq!(&archive[30..45], b"001 - hello.txt");
+        assert_eq!(&archive[45..50], data);
+        assert_eq!(u32_at(&archive, 50), DATA_DESCRIPTOR);
+        assert_eq!(u32_at(&archive, 54), crc);
+        assert_eq!(u32_at(&archive, 58), 5);
+
+        let directory = &archive[directory_offset..];
+        assert_eq!(u32_at(directory, 0), CENTRAL_DIRECTORY_HEADER);
+        assert_eq!(u32_at(directory, 16), crc);
+        assert_eq!(u32_at(directory, 20), 5);
+        assert_eq!(u32_at(directory, 42), 0);
+
+        let end = &directory[46 + 15..];
+        assert_eq!(end.len(), 22);
+        assert_eq!(u32_at(end, 0), END_OF_CENTRAL_DIRECTORY);
+        assert_eq!(u16_at(end, 10), 1);
+        assert_eq!(u32_at(end, 12), 46 + 15);
+        assert_eq!(u32_at(end, 16), directory_offset as u32);
+    }
+
+    #[test]
+    fn offsets_past_4_gib_use_zip64() {
+        let mut zip = ZipEncoder::new();
+        let big = u64::from(u32::MAX);
+
+        zip.start_entry("a.jpg", modified());
+        zip.finish_entry(1, big).unwrap();
+        zip.start_entry("b.jpg", modified());
+        zip.finish_entry(2, 10).unwrap();
+        let second_offset = 30 + 5 + big + 16;
+        let directory_offset = second_offset + 30 + 5 + 10 + 16;
+        let directory = zip.finish();
+
+        // The first entry fits in 32 bits, the second one needs the ZIP64 extra field.
+        assert_eq!(u32_at(&directory, 42), 0);
+        let second = 46 + 5;
+        assert_eq!(u16_at(&directory, second + 6), VERSION_ZIP64);
+        assert_eq!(u16_at(&directory, second + 30), 12);
+        assert_eq!(u32_at(&directory, second + 42), u32::MAX);
+        assert_eq!(u16_at(&directory, second + 46 + 5), ZIP64_EXTRA_FIELD);
+        assert_eq!(u64_at(&directory, second + 46 + 5 + 4), second_offset);
+
+        let directory_size = (second + 46 + 5 + 12) as u64;
+        let record = directory_size as usize;
+        assert_eq!(u32_at(&directory, record), ZIP64_END_OF_CENTRAL_DIRECTORY);
+        assert_eq!(u64_at(&directory, record + 24), 2);
+        assert_eq!(u64_at(&directory, record + 40), directory_size);
+        assert_eq!(u64_at(&directory, record + 48), directory_offset);
+
+        let locator = record + 56;
+        assert_eq!(
+            u32_at(&directory, locator),
+            ZIP64_END_OF_CENTRAL_DIRECTORY_LOCATOR
+        );
+        assert_eq!(
+            u64_at(&directory, locator + 8),
+            directory_offset + directory_size
+        );
+
+        let end = locator + 20;
+        assert_eq!(u32_at(&directory, end), END_OF_CENTRAL_DIRECTORY);
+        assert_eq!(u32_at(&directory, end + 16), u32::MAX);
+        assert_eq!(directory.len(), end + 22);
+    }
+
+    #[test]
+    fn entries_over_4_gib_are_rejected() {
+        let mut zip = ZipEncoder::new();
+        zip.start_entry("huge.mov", modified());
+
+        assert!(zip.finish_entry(0, u64::from(u32::MAX) + 1).is_none());
+    }
+
+    #[test]
+    fn dates_use_ms_dos_format() {
+        let (time, date) = dos_datetime(modified());
+
+        assert_eq!(time, (14 << 11) | (30 << 5) | 5);
+        assert_eq!(date, (40 << 9) | (11 << 5) | 28);
+        assert_eq!(
+            dos_datetime(NaiveDate::from_ymd(1970, 1, 1).and_hms(0, 0, 0)).1,
+            (1 << 5) | 1
+        );
+    }
+}
@@ -0,0 +1,77 @@
+use crate::utils::get_url;
+use http::header::COOKIE;
+use http::{HeaderMap, HeaderValue};
+use photo_core::models::OAUTH_STATE_EXPIRY;
+
+/// Cookie holding the OAuth2 state of a login, so the redirect is only accepted by the browser
+/// that started it.
+const STATE_COOKIE: &str = "oauth_state";
+
+pub fn state_cookie(state: &str) -> HeaderValue {
+    cookie_header(state, OAUTH_STATE_EXPIRY)
+}
+
+pub fn expired_state_cookie() -> HeaderValue {
+    cookie_header("", 0)
+}
+
+pub fn state_from_cookies(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get_all(COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|pair| {
+            let mut parts = pair.trim().splitn(2, '=');
+            match (parts.next(), parts.next()) {
+                (Some(STATE_COOKIE), Some(value)) if !value.is_empty() => Some(value.to_string()),
+                _ => None,
+            }
+        })
+        .next()
+}
+
+fn cookie_header(value: &str, max_age: i64) -> HeaderValue {
+    let secure = if get_url().starts_with("https://") {
+        "; Secure"
+    } else {
+        ""
+    };
+    let cookie = format!(
+        "{}={}; Path=/; Max-Age={}; HttpOnly; SameSite=Lax{}",
+        STATE_COOKIE, value, max_age, secure
+    );
+
+    HeaderValue::from_str(&cookie).expect("Invalid state cookie")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(cookies: &[&str]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for cookie in cookies {
+            headers.append(COOKIE, HeaderValue::from_str(cookie).unwrap());
+        }
+
+        headers
+    }
+
+    #[test]
+    fn finds_the_state_among_other_cookies() {
+        let headers = headers(&["theme=dark; oauth_state=abc123", "lang=fr"]);
+
+        assert_eq!(state_from_cookies(&headers), Some(String::from("abc123")));
+    }
+
+    #[test]
+    fn ignores_missing_and_cleared_states() {
+        assert_eq!(state_from_cookies(&headers(&[])), None);
+        assert_eq!(state_from_cookies(&headers(&["oauth_state="])), None);
+        assert_eq!(
+            state_from_cookies(&headers(&["not_oauth_state=abc123"])),
+            None
+        );
+    }
+}
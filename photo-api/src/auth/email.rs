@@ -0,0 +1,21 @@
+use super::Profile;
+use photo_core::models::User;
+use serde::{Deserialize, Serialize};
+
+/// Query of the login link sent by email.
+#[derive(Deserialize, Serialize, StateData, StaticResponseExtender)]
+pub struct EmailLoginExtractor {
+    pub token: String,
+}
+
+/// Profile of a user who proved they own the email by opening a login link.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EmailProfile {
+    pub email: String,
+}
+
+impl Profile for EmailProfile {
+    fn new_user(&self) -> User {
+        User::new(self.email.clone(), None)
+    }
+}
@@ -5,8 +5,8 @@ use oauth2::{
     basic::{BasicClient, BasicErrorResponseType, BasicTokenType},
     reqwest::{async_http_client, Error as Oauth2ReqwestError},
     AsyncCodeTokenRequest, AuthUrl, AuthorizationCode, ClientId, ClientSecret, CsrfToken,
-    EmptyExtraTokenFields, RedirectUrl, RequestTokenError, Scope, StandardErrorResponse,
-    StandardTokenResponse, TokenResponse, TokenUrl,
+    EmptyExtraTokenFields, PkceCodeChallenge, PkceCodeVerifier, RedirectUrl, RequestTokenError,
+    Scope, StandardErrorResponse, StandardTokenResponse, TokenResponse, TokenUrl,
 };
 use photo_core::models::User;
 use reqwest::Error as ReqwestError;
@@ -47,25 +47,34 @@ pub fn build_client() -> Result<BasicClient> {
     Ok(client)
 }
 
-pub fn gen_authorize_url(client: BasicClient) -> (url::Url, CsrfToken) {
-    client
+/// Builds the URL to send the user to, along with the CSRF state and the PKCE verifier that must
+/// be kept until Google redirects back.
+pub fn gen_authorize_url(client: BasicClient) -> (url::Url, CsrfToken, PkceCodeVerifier) {
+    let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
+
+    let (url, csrf_token) = client
         .authorize_url(CsrfToken::new_random)
+        .set_pkce_challenge(pkce_challenge)
         .add_scope(Scope::new(
             "https://www.googleapis.com/auth/userinfo.email".to_string(),
         ))
         .add_scope(Scope::new(
             "https://www.googleapis.com/auth/userinfo.profile".to_string(),
         ))
-        .url()
+        .url();
+
+    (url, csrf_token, pkce_verifier)
 }
 
 pub async fn exchange_token(
-    extractor: &GoogleRedirectExtractor,
+    code: &str,
     client: &BasicClient,
+    pkce_verifier: PkceCodeVerifier,
 ) -> Result<BasicToken> {
-    let code = AuthorizationCode::new(extractor.code.to_owned());
+    let code = AuthorizationCode::new(code.to_owned());
     let token = client
         .exchange_code(code)
+        .set_pkce_verifier(pkce_verifier)
         .request_async(async_http_client)
         .await
         .context(OAuth2Request)?;
@@ -92,12 +101,17 @@ pub async fn get_user_profile(token: &BasicToken) -> Result<GoogleProfile> {
     Ok(profile)
 }
 
+/// Google sends either `code` or, when the user refused, `error`.
 #[derive(Deserialize, Serialize, StateData, StaticResponseExtender)]
 pub struct GoogleRedirectExtractor {
-    state: String,
-    code: String,
+    pub state: String,
+    pub code: Option<String>,
+    pub error: Option<String>,
+    #[serde(default)]
     scope: Vec<String>,
+    #[serde(default)]
     prompt: String,
+    #[serde(default)]
     authuser: i32,
 }
 
@@ -0,0 +1,121 @@
+use crate::utils::get_url;
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use std::env;
+
+/// Audience of the tokens that authenticate a user against the API.
+pub const AUTH_AUDIENCE: &str = "photo-api";
+
+/// Audience of the tokens that unlock a password protected album.
+pub const ALBUM_AUDIENCE: &str = "photo-api:album";
+
+/// A secret used to sign tokens, identified by the `kid` header of the tokens it signed.
+pub struct SigningKey {
+    pub kid: String,
+    secret: String,
+}
+
+impl SigningKey {
+    pub fn encoding_key(&self) -> EncodingKey {
+        EncodingKey::from_secret(self.secret.as_ref())
+    }
+
+    pub fn decoding_key(&self) -> DecodingKey<'_> {
+        DecodingKey::from_secret(self.secret.as_ref())
+    }
+
+    pub fn header(&self) -> Header {
+        let mut header = Header::new(Algorithm::HS256);
+        header.kid = Some(self.kid.clone());
+
+        header
+    }
+}
+
+lazy_static! {
+    /// Keys read from `TOKEN_KEYS`, as comma separated `kid:secret` pairs. The first one signs new
+    /// tokens, the others are only kept to verify tokens signed before a rotation. Without it, the
+    /// `TOKEN_SECRET` is used as the only key.
+    static ref KEYS: Vec<SigningKey> = {
+        let keys = match env::var("TOKEN_KEYS") {
+            Ok(keys) => parse_keys(&keys),
+            Err(_) => vec![SigningKey {
+                kid: String::from("default"),
+                secret: get_secret(),
+            }],
+        };
+
+        if keys.is_empty() {
+            panic!("TOKEN_KEYS variable does not contain any kid:secret pair");
+        }
+
+        keys
+    };
+}
+
+/// Reads comma separated `kid:secret` pairs, skipping the ones missing either part.
+fn parse_keys(value: &str) -> Vec<SigningKey> {
+    value
+        .split(',')
+        .filter_map(|pair| {
+            let mut parts = pair.trim().splitn(2, ':');
+            match (parts.next(), parts.next()) {
+                (Some(kid), Some(secret)) if !kid.is_empty() && !secret.is_empty() => {
+                    Some(SigningKey {
+                        kid: kid.to_string(),
+                        secret: secret.to_string(),
+                    })
+                }
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+pub fn get_secret() -> String {
+    env::var("TOKEN_SECRET").expect("TOKEN_SECRET variable is not defined")
+}
+
+/// Key new tokens are signed with.
+pub fn current_key() -> &'static SigningKey {
+    &KEYS[0]
+}
+
+pub fn find_key(kid: &str) -> Option<&'static SigningKey> {
+    KEYS.iter().find(|key| key.kid == kid)
+}
+
+/// Issuer of the tokens, the public URL of the API.
+pub fn issuer() -> String {
+    get_url()
+}
+
+/// Validation of tokens issued by this API for the given audience.
+pub fn validation(audience: &str) -> Validation {
+    let mut validation = Validation::new(Algorithm::HS256);
+    validation.iss = Some(issuer());
+    validation.set_audience(&[audience]);
+
+    validation
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_keys_in_order() {
+        let keys = parse_keys("2021-01:new-secret, 2020-12:old:secret");
+
+        let kids: Vec<&str> = keys.iter().map(|k| k.kid.as_str()).collect();
+        assert_eq!(kids, vec!["2021-01", "2020-12"]);
+        assert_eq!(keys[1].secret, "old:secret");
+    }
+
+    #[test]
+    fn skips_incomplete_pairs() {
+        let keys = parse_keys("missing-secret,:no-kid,empty:,valid:secret");
+
+        assert_eq!(keys.len(), 1);
+        assert_eq!(keys[0].kid, "valid");
+    }
+}
@@ -1,6 +1,11 @@
 // use oauth2::{basic::BasicTokenType, EmptyExtraTokenFields, StandardTokenResponse};
 
+pub mod csrf;
+pub mod email;
 pub mod google;
+pub mod keys;
+pub mod oidc;
+pub mod throttle;
 
 mod token;
 pub use self::token::*;
@@ -0,0 +1,584 @@
+use super::Profile;
+use crate::utils::get_url;
+use jsonwebtoken::errors::Error as JwtError;
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use oauth2::{
+    basic::{BasicErrorResponse, BasicErrorResponseType, BasicTokenType},
+    reqwest::{async_http_client, Error as Oauth2ReqwestError},
+    AsyncCodeTokenRequest, AuthType, AuthUrl, AuthorizationCode, Client, ClientId, ClientSecret,
+    CsrfToken, ExtraTokenFields, PkceCodeChallenge, PkceCodeVerifier, RedirectUrl,
+    RequestTokenError, Scope, StandardErrorResponse, StandardTokenResponse, TokenUrl,
+};
+use photo_core::models::User;
+use reqwest::Error as ReqwestError;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use snafu::{Backtrace, OptionExt, ResultExt};
+use std::collections::HashMap;
+use std::env;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use url::ParseError;
+
+/// How long the discovery documents and keys of the providers are used before being read again.
+const CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// An OpenID Connect provider, configured with `OIDC_PROVIDERS` as a comma separated list of
+/// names, each one with its own variables:
+///
+/// - `OIDC_<NAME>_ISSUER`: issuer URL, the discovery document is read from it.
+/// - `OIDC_<NAME>_CLIENT_ID` and optionally `OIDC_<NAME>_CLIENT_SECRET`.
+/// - `OIDC_<NAME>_SCOPES`: space separated, `openid email profile` by default.
+/// - `OIDC_<NAME>_EMAIL_CLAIM` and `OIDC_<NAME>_PICTURE_CLAIM`: claims of the ID token holding
+///   the email and the avatar of the user, `email` and `picture` by default.
+pub struct OidcProvider {
+    pub name: String,
+    issuer: String,
+    client_id: String,
+    client_secret: Option<String>,
+    scopes: Vec<String>,
+    email_claim: String,
+    picture_claim: String,
+}
+
+impl OidcProvider {
+    fn from_env(name: &str) -> Self {
+        let var = |key: &str| env::var(format!("OIDC_{}_{}", name.to_uppercase(), key)).ok();
+        let required = |key: &str| {
+            var(key).unwrap_or_else(|| {
+                panic!(
+                    "Missing OIDC_{}_{} environment variable.",
+                    name.to_uppercase(),
+                    key
+                )
+            })
+        };
+
+        OidcProvider {
+            name: name.to_lowercase(),
+            issuer: required("ISSUER").trim_end_matches('/').to_string(),
+            client_id: required("CLIENT_ID"),
+            client_secret: var("CLIENT_SECRET").filter(|s| !s.is_empty()),
+            scopes: var("SCOPES")
+                .unwrap_or(String::from("openid email profile"))
+                .split_whitespace()
+                .map(String::from)
+                .collect(),
+            email_claim: var("EMAIL_CLAIM").unwrap_or(String::from("email")),
+            picture_claim: var("PICTURE_CLAIM").unwrap_or(String::from("picture")),
+        }
+    }
+
+    /// Provider saved with the pending logins, so a state is only accepted back by this one.
+    pub fn state_key(&self) -> String {
+        format!("oidc:{}", self.name)
+    }
+
+    fn redirect_url(&self) -> String {
+        format!("{}/oidc/{}/redirect", get_url(), self.name)
+    }
+}
+
+lazy_static! {
+    static ref PROVIDERS: Vec<OidcProvider> = env::var("OIDC_PROVIDERS")
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(OidcProvider::from_env)
+        .collect();
+}
+
+lazy_static! {
+    /// Discovery documents by issuer.
+    static ref DISCOVERIES: Cache<Discovery> = Mutex::new(HashMap::new());
+
+    /// Keys the ID tokens are signed with, by JWKS URI.
+    static ref KEY_SETS: Cache<Jwks> = Mutex::new(HashMap::new());
+}
+
+type Cache<T> = Mutex<HashMap<String, (Instant, Arc<T>)>>;
+
+fn cached<T>(cache: &Cache<T>, key: &str) -> Option<Arc<T>> {
+    cache
+        .lock()
+        .unwrap()
+        .get(key)
+        .filter(|(since, _)| since.elapsed() < CACHE_TTL)
+        .map(|(_, value)| value.clone())
+}
+
+fn store<T>(cache: &Cache<T>, key: &str, value: T) -> Arc<T> {
+    let value = Arc::new(value);
+    cache
+        .lock()
+        .unwrap()
+        .insert(String::from(key), (Instant::now(), value.clone()));
+
+    value
+}
+
+pub fn find_provider(name: &str) -> Option<&'static OidcProvider> {
+    PROVIDERS.iter().find(|provider| provider.name == name)
+}
+
+/// The parts of the discovery document needed for the authorization code flow.
+#[derive(Debug, Deserialize)]
+pub struct Discovery {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+}
+
+/// Reads the discovery document of the provider, or reuses the one read in the last hour. Its
+/// issuer must be the configured one, as it is the issuer the ID tokens are checked against.
+pub async fn discover(provider: &OidcProvider) -> Result<Arc<Discovery>> {
+    if let Some(discovery) = cached(&DISCOVERIES, &provider.issuer) {
+        return Ok(discovery);
+    }
+
+    let url = format!("{}/.well-known/openid-configuration", provider.issuer);
+    let response = reqwest::get(&url).await.context(ReqwestIssue)?;
+    let discovery: Discovery = response.json().await.context(JsonParse)?;
+
+    if discovery.issuer.trim_end_matches('/') != provider.issuer {
+        return IssuerMismatch {
+            expected: provider.issuer.clone(),
+            found: discovery.issuer,
+        }
+        .fail();
+    }
+
+    Ok(store(&DISCOVERIES, &provider.issuer, discovery))
+}
+
+/// Finds the key a token was signed with. The keys are cached, and read again when the key is not
+/// known yet as providers rotate them.
+async fn find_key(jwks_uri: &str, kid: &Option<String>) -> Result<Jwk> {
+    if let Some(key) = cached(&KEY_SETS, jwks_uri).and_then(|jwks| jwks.find(kid)) {
+        return Ok(key);
+    }
+
+    let response = reqwest::get(jwks_uri).await.context(ReqwestIssue)?;
+    let jwks: Jwks = response.json().await.context(JsonParse)?;
+
+    store(&KEY_SETS, jwks_uri, jwks)
+        .find(kid)
+        .context(NoMatchingKey { kid: kid.clone() })
+}
+
+pub fn build_client(provider: &OidcProvider, discovery: &Discovery) -> Result<OidcClient> {
+    let auth_url = AuthUrl::new(discovery.authorization_endpoint.clone()).context(UrlParseError)?;
+    let token_url = TokenUrl::new(discovery.token_endpoint.clone()).context(UrlParseError)?;
+    let redirect_url = RedirectUrl::new(provider.redirect_url()).context(UrlParseError)?;
+
+    let client = OidcClient::new(
+        ClientId::new(provider.client_id.clone()),
+        provider.client_secret.clone().map(ClientSecret::new),
+        auth_url,
+        Some(token_url),
+    )
+    .set_redirect_url(redirect_url);
+
+    // Public clients have no secret to authenticate with, their id goes in the request body.
+    let client = match provider.client_secret {
+        Some(_) => client,
+        None => client.set_auth_type(AuthType::RequestBody),
+    };
+
+    Ok(client)
+}
+
+/// Builds the URL to send the user to, along with the CSRF state and the PKCE verifier that must
+/// be kept until the provider redirects back. The state is also the nonce of the ID token, so the
+/// token can only be used for the login that asked for it.
+pub fn gen_authorize_url(
+    provider: &OidcProvider,
+    client: &OidcClient,
+) -> (url::Url, CsrfToken, PkceCodeVerifier) {
+    let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
+    let csrf_token = CsrfToken::new_random();
+    let nonce = csrf_token.secret().clone();
+
+    let mut request = client
+        .authorize_url(move || csrf_token)
+        .set_pkce_challenge(pkce_challenge)
+        .add_extra_param("nonce", nonce);
+    for scope in provider.scopes.iter() {
+        request = request.add_scope(Scope::new(scope.clone()));
+    }
+    let (url, csrf_token) = request.url();
+
+    (url, csrf_token, pkce_verifier)
+}
+
+pub async fn exchange_token(
+    code: &str,
+    client: &OidcClient,
+    pkce_verifier: PkceCodeVerifier,
+) -> Result<OidcToken> {
+    let code = AuthorizationCode::new(code.to_owned());
+    let token = client
+        .exchange_code(code)
+        .set_pkce_verifier(pkce_verifier)
+        .request_async(async_http_client)
+        .await
+        .context(OAuth2Request)?;
+
+    Ok(token)
+}
+
+/// Validates the ID token of the response against the keys published by the provider, and reads
+/// the profile of the user from its claims.
+pub async fn get_user_profile(
+    provider: &OidcProvider,
+    discovery: &Discovery,
+    token: &OidcToken,
+    nonce: &str,
+) -> Result<OidcProfile> {
+    let id_token = token.extra_fields().id_token.as_str();
+    let header = decode_header(id_token).context(InvalidIdToken)?;
+
+    let jwk = find_key(&discovery.jwks_uri, &header.kid).await?;
+    let (modulus, exponent) = match (&jwk.n, &jwk.e) {
+        (Some(n), Some(e)) => (n, e),
+        _ => {
+            return NoMatchingKey {
+                kid: header.kid.clone(),
+            }
+            .fail()
+        }
+    };
+
+    // Only RSA keys can be built from a JWK, refuse anything else so `alg: none` or HMAC signed
+    // with the public key can't get through.
+    let algorithm = match header.alg {
+        Algorithm::RS256
+        | Algorithm::RS384
+        | Algorithm::RS512
+        | Algorithm::PS256
+        | Algorithm::PS384
+        | Algorithm::PS512 => header.alg,
+        _ => {
+            return NoMatchingKey {
+                kid: header.kid.clone(),
+            }
+            .fail()
+        }
+    };
+
+    let mut validation = Validation::new(algorithm);
+    validation.iss = Some(discovery.issuer.clone());
+    validation.set_audience(&[provider.client_id.as_str()]);
+
+    let claims = decode::<HashMap<String, Value>>(
+        id_token,
+        &DecodingKey::from_rsa_components(modulus, exponent),
+        &validation,
+    )
+    .context(InvalidIdToken)?
+    .claims;
+
+    if claims.get("nonce").and_then(Value::as_str) != Some(nonce) {
+        return InvalidNonce.fail();
+    }
+
+    let email = claims
+        .get(&provider.email_claim)
+        .and_then(Value::as_str)
+        .context(MissingClaim {
+            claim: provider.email_claim.clone(),
+        })?;
+
+    // Without the claim, nothing tells the provider checked the user owns the email.
+    if claims.get("email_verified").and_then(Value::as_bool) != Some(true) {
+        return EmailNotVerified.fail();
+    }
+
+    let picture = claims
+        .get(&provider.picture_claim)
+        .and_then(Value::as_str)
+        .map(String::from);
+
+    Ok(OidcProfile {
+        email: email.to_string(),
+        picture,
+    })
+}
+
+/// The provider sends either `code` or, when the user refused, `error`.
+#[derive(Deserialize, Serialize, StateData, StaticResponseExtender)]
+pub struct OidcRedirectExtractor {
+    pub state: String,
+    pub code: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Deserialize, StateData, StaticResponseExtender)]
+pub struct OidcPathExtractor {
+    pub provider: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct IdTokenFields {
+    id_token: String,
+}
+
+impl ExtraTokenFields for IdTokenFields {}
+
+pub type OidcToken = StandardTokenResponse<IdTokenFields, BasicTokenType>;
+
+pub type OidcClient = Client<BasicErrorResponse, OidcToken, BasicTokenType>;
+
+#[derive(Debug, Deserialize)]
+struct Jwks {
+    keys: Vec<Jwk>,
+}
+
+impl Jwks {
+    /// The RSA key with the given id, or the first one when the token names none.
+    fn find(&self, kid: &Option<String>) -> Option<Jwk> {
+        self.keys
+            .iter()
+            .filter(|key| key.kty == "RSA")
+            .find(|key| kid.is_none() || key.kid == *kid)
+            .cloned()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Jwk {
+    kty: String,
+    kid: Option<String>,
+    n: Option<String>,
+    e: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OidcProfile {
+    email: String,
+    picture: Option<String>,
+}
+
+impl Profile for OidcProfile {
+    fn new_user(&self) -> User {
+        let email = self.email.clone();
+        let picture = self.picture.clone();
+
+        User::new(email, picture)
+    }
+}
+
+pub type Result<T, E = OidcError> = std::result::Result<T, E>;
+
+#[derive(Debug, Snafu)]
+pub enum OidcError {
+    #[snafu(display("Could not parse URL {}", source))]
+    UrlParseError {
+        source: ParseError,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Could not complete oauth2 request {}", source))]
+    OAuth2Request {
+        #[snafu(source(from(TokenError, failure::Fail::compat)))]
+        source: failure::Compat<TokenError>,
+    },
+
+    #[snafu(display("Could not complete request {}", source))]
+    ReqwestIssue {
+        source: ReqwestError,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Could not parse JSON {}", source))]
+    JsonParse { source: ReqwestError },
+
+    #[snafu(display("Discovery issuer {} does not match {}", found, expected))]
+    IssuerMismatch { expected: String, found: String },
+
+    #[snafu(display("No usable key {:?} in the provider JWKS", kid))]
+    NoMatchingKey { kid: Option<String> },
+
+    #[snafu(display("Invalid ID token {}", source))]
+    InvalidIdToken { source: JwtError },
+
+    #[snafu(display("ID token nonce does not match the login"))]
+    InvalidNonce,
+
+    #[snafu(display("ID token has no {} claim", claim))]
+    MissingClaim { claim: String },
+
+    #[snafu(display("Email of the user is not verified"))]
+    EmailNotVerified,
+}
+
+type TokenError = RequestTokenError<
+    Oauth2ReqwestError<ReqwestError>,
+    StandardErrorResponse<BasicErrorResponseType>,
+>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::service::{make_service_fn, service_fn};
+    use hyper::{Body, Request, Response, Server};
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use oauth2::AccessToken;
+    use serde_json::json;
+    use std::convert::Infallible;
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Key the mock provider signs ID tokens with, only used by these tests.
+    const PRIVATE_KEY: &str = include_str!("../../tests/fixtures/oidc_key.pem");
+    const MODULUS: &str = "0i2UJ38usNLBeHgtWoLWytsUeEKFxCrc9i8wfB2VRfaq7H1Nci8pX7ZufSquwHbCu5nMQQbBbNnFxgL3F7eiGsz6xFsMseTT-BWBkB-mXXdf4jwO65_Bxugue_km1JCxlHIgQAuq5E3PTYnFhD50r3NtDzKUKlOibUkbVWf6lICAHKH9QwUZrArkOk4MKAmoX-M5X01qU6x0LC6zhYKfRsWV1hw5GPPoEPkQ1JTCefG0c1YcWB-LYv9pUqABBzpXmUvonlyutoEeg12-ElNe_PYIurKOlFZy2IYkkvr0BKrG3pFlMx91StOpBFVktKP4O54nj1I0OK2P4VE7O2_rhw";
+    const EXPONENT: &str = "AQAB";
+    const KID: &str = "test-key";
+    const CLIENT_ID: &str = "photos";
+    const NONCE: &str = "login-state";
+
+    /// Identity provider serving its discovery document and keys on a local port. Counts the
+    /// requests it gets, to check what is cached.
+    struct MockIdp {
+        issuer: String,
+        requests: Arc<AtomicUsize>,
+    }
+
+    impl MockIdp {
+        fn start() -> MockIdp {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let issuer = format!("http://{}", listener.local_addr().unwrap());
+            let requests = Arc::new(AtomicUsize::new(0));
+
+            let base = issuer.clone();
+            let counter = requests.clone();
+            let make_service = make_service_fn(move |_| {
+                let base = base.clone();
+                let counter = counter.clone();
+
+                async move {
+                    Ok::<_, Infallible>(service_fn(move |req: Request<Body>| {
+                        counter.fetch_add(1, Ordering::SeqCst);
+                        let body = match req.uri().path() {
+                            "/.well-known/openid-configuration" => json!({
+                                "issuer": base,
+                                "authorization_endpoint": format!("{}/authorize", base),
+                                "token_endpoint": format!("{}/token", base),
+                                "jwks_uri": format!("{}/jwks", base),
+                            }),
+                            _ => json!({
+                                "keys": [{ "kty": "RSA", "kid": KID, "n": MODULUS, "e": EXPONENT }],
+                            }),
+                        };
+
+                        async move { Ok::<_, Infallible>(Response::new(Body::from(body.to_string()))) }
+                    }))
+                }
+            });
+            tokio::spawn(Server::from_tcp(listener).unwrap().serve(make_service));
+
+            MockIdp { issuer, requests }
+        }
+
+        fn provider(&self) -> OidcProvider {
+            OidcProvider {
+                name: String::from("mock"),
+                issuer: self.issuer.clone(),
+                client_id: String::from(CLIENT_ID),
+                client_secret: None,
+                scopes: vec![String::from("openid")],
+                email_claim: String::from("email"),
+                picture_claim: String::from("picture"),
+            }
+        }
+
+        /// Token response with an ID token signed by the provider, with `claims` on top of the
+        /// ones every token has.
+        fn token(&self, claims: Value) -> OidcToken {
+            let mut all = json!({
+                "iss": self.issuer,
+                "aud": CLIENT_ID,
+                "exp": chrono::Utc::now().timestamp() + 600,
+                "nonce": NONCE,
+                "email": "ada@example.com",
+            });
+            for (claim, value) in claims.as_object().unwrap() {
+                all[claim] = value.clone();
+            }
+
+            let mut header = Header::new(Algorithm::RS256);
+            header.kid = Some(String::from(KID));
+            let key = EncodingKey::from_rsa_pem(PRIVATE_KEY.as_bytes()).unwrap();
+            let id_token = encode(&header, &all, &key).unwrap();
+
+            OidcToken::new(
+                AccessToken::new(String::from("access")),
+                BasicTokenType::Bearer,
+                IdTokenFields { id_token },
+            )
+        }
+
+        async fn profile(&self, claims: Value) -> Result<OidcProfile> {
+            let provider = self.provider();
+            let discovery = discover(&provider).await?;
+
+            get_user_profile(&provider, &discovery, &self.token(claims), NONCE).await
+        }
+    }
+
+    #[tokio::test]
+    async fn accepts_verified_email() {
+        let idp = MockIdp::start();
+
+        let profile = idp
+            .profile(json!({ "email_verified": true }))
+            .await
+            .unwrap();
+
+        assert_eq!(profile.email, "ada@example.com");
+    }
+
+    #[tokio::test]
+    async fn rejects_missing_email_verified() {
+        let idp = MockIdp::start();
+
+        let result = idp.profile(json!({})).await;
+
+        assert!(matches!(result, Err(OidcError::EmailNotVerified)));
+    }
+
+    #[tokio::test]
+    async fn rejects_unverified_email() {
+        let idp = MockIdp::start();
+
+        let result = idp.profile(json!({ "email_verified": false })).await;
+
+        assert!(matches!(result, Err(OidcError::EmailNotVerified)));
+    }
+
+    #[tokio::test]
+    async fn rejects_nonce_of_another_login() {
+        let idp = MockIdp::start();
+
+        let claims = json!({ "email_verified": true, "nonce": "other-login" });
+        let result = idp.profile(claims).await;
+
+        assert!(matches!(result, Err(OidcError::InvalidNonce)));
+    }
+
+    #[tokio::test]
+    async fn caches_discovery_and_keys() {
+        let idp = MockIdp::start();
+
+        for _ in 0..3 {
+            idp.profile(json!({ "email_verified": true }))
+                .await
+                .unwrap();
+        }
+
+        // One request for the discovery document, one for the keys.
+        assert_eq!(idp.requests.load(Ordering::SeqCst), 2);
+    }
+}
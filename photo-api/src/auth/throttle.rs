@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+lazy_static! {
+    /// Failed attempts to unlock password protected albums and share links.
+    pub static ref UNLOCK_THROTTLE: Throttle = Throttle::new(5, Duration::from_secs(15 * 60));
+
+    /// Failed attempts per album from any address, so changing IP addresses doesn't lift the
+    /// limit. Never reset on success, the attempts only expire with the window.
+    ///
+    /// This is a trade-off: anyone who knows the album can block unlocking it for every viewer,
+    /// including the ones with the right password, by failing 50 times. Viewers who already
+    /// unlocked it keep their token, and the owner can always see the album while signed in.
+    /// Brute forcing the password from many addresses is considered the bigger risk.
+    pub static ref ALBUM_UNLOCK_THROTTLE: Throttle =
+        Throttle::new(50, Duration::from_secs(15 * 60));
+
+    /// Login links requested by email, so the API can't be used to flood an inbox.
+    pub static ref LOGIN_LINK_THROTTLE: Throttle = Throttle::new(5, Duration::from_secs(15 * 60));
+}
+
+/// Keeps track of failed attempts per key (e.g. resource + IP address) in memory and blocks the
+/// key once it reaches `max_attempts` within `window`.
+pub struct Throttle {
+    max_attempts: u32,
+    window: Duration,
+    attempts: Mutex<HashMap<String, (u32, Instant)>>,
+}
+
+impl Throttle {
+    pub fn new(max_attempts: u32, window: Duration) -> Self {
+        Self {
+            max_attempts,
+            window,
+            attempts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn is_blocked(&self, key: &str) -> bool {
+        let mut attempts = self.attempts.lock().unwrap();
+        self.clear_expired(&mut attempts);
+
+        match attempts.get(key) {
+            Some((count, _)) => *count >= self.max_attempts,
+            None => false,
+        }
+    }
+
+    pub fn fail(&self, key: &str) {
+        let mut attempts = self.attempts.lock().unwrap();
+        self.clear_expired(&mut attempts);
+
+        let entry = attempts
+            .entry(String::from(key))
+            .or_insert((0, Instant::now()));
+        entry.0 += 1;
+    }
+
+    pub fn reset(&self, key: &str) {
+        self.attempts.lock().unwrap().remove(key);
+    }
+
+    fn clear_expired(&self, attempts: &mut HashMap<String, (u32, Instant)>) {
+        let window = self.window;
+        attempts.retain(|_, (_, since)| since.elapsed() < window);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blocks_after_max_attempts() {
+        let throttle = Throttle::new(2, Duration::from_secs(60));
+
+        throttle.fail("album:1:10.0.0.1");
+        assert!(!throttle.is_blocked("album:1:10.0.0.1"));
+
+        throttle.fail("album:1:10.0.0.1");
+        assert!(throttle.is_blocked("album:1:10.0.0.1"));
+        assert!(!throttle.is_blocked("album:1:10.0.0.2"));
+    }
+
+    #[test]
+    fn reset_clears_attempts() {
+        let throttle = Throttle::new(1, Duration::from_secs(60));
+
+        throttle.fail("album:1:10.0.0.1");
+        throttle.reset("album:1:10.0.0.1");
+
+        assert!(!throttle.is_blocked("album:1:10.0.0.1"));
+    }
+
+    #[test]
+    fn attempts_expire_after_window() {
+        let throttle = Throttle::new(1, Duration::from_millis(10));
+
+        throttle.fail("album:1:10.0.0.1");
+        std::thread::sleep(Duration::from_millis(20));
+
+        assert!(!throttle.is_blocked("album:1:10.0.0.1"));
+    }
+}
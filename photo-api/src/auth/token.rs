@@ -1,7 +1,10 @@
-use jsonwebtoken::{encode, EncodingKey, Header};
-use photo_core::models::User;
+use super::keys::{current_key, find_key, issuer, validation, ALBUM_AUDIENCE, AUTH_AUDIENCE};
+use jsonwebtoken::errors::{Error as JwtError, ErrorKind as JwtErrorKind};
+use jsonwebtoken::{decode, decode_header, encode, TokenData};
+use photo_core::helpers::uuid::Uuid;
+use photo_core::models::{Album, Session, ShareLink, User};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use std::env;
 use std::time::Duration;
 use std::time::{SystemTime, UNIX_EPOCH};
 
@@ -10,14 +13,24 @@ pub struct AuthUser {
     email: String,
     user_id: String,
     exp: u64,
+    iss: String,
+    aud: String,
+    /// Unique id of the token, so it can be denied before it expires.
+    jti: String,
+    /// Session the token was issued for, the token stops working once it is revoked.
+    session_id: String,
 }
 
 impl AuthUser {
-    pub fn new(user: &User, expire_in: u64) -> Self {
+    pub fn new(user: &User, session: &Session, expire_in: u64) -> Self {
         AuthUser {
             email: user.email.clone(),
             user_id: user.id.to_string(),
             exp: seconds_from_now(expire_in),
+            iss: issuer(),
+            aud: String::from(AUTH_AUDIENCE),
+            jti: Uuid::new_v4().to_string(),
+            session_id: session.id.to_string(),
         }
     }
 
@@ -25,20 +38,133 @@ impl AuthUser {
         self.email.clone()
     }
 
+    pub fn session_id(&self) -> String {
+        self.session_id.clone()
+    }
+
+    pub fn jti(&self) -> String {
+        self.jti.clone()
+    }
+
+    pub fn exp(&self) -> u64 {
+        self.exp
+    }
+
     // pub fn user_id(&self) -> Uuid {
     //   Uuid::parse_str(&self.user_id).unwrap()
     // }
 }
 
-pub fn get_secret() -> String {
-    env::var("TOKEN_SECRET").expect("TOKEN_SECRET variable is not defined")
+/// Seconds an access token is valid for, clients renew it with their refresh token.
+pub const ACCESS_TOKEN_EXPIRY: u64 = 3600;
+
+/// Seconds an album token is valid for.
+pub const ALBUM_TOKEN_EXPIRY: u64 = 1800;
+
+/// Claims of the short-lived token given after unlocking a password protected album. It only
+/// grants read access to that album and can't be used as an `AuthUser` token.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct AlbumAccess {
+    album_id: String,
+    /// Share link the album was unlocked through. The token is then only valid along with that
+    /// link, so its expiration, views and revocation keep applying.
+    share_link_id: Option<String>,
+    exp: u64,
+    iss: String,
+    aud: String,
+}
+
+impl AlbumAccess {
+    pub fn new(album: &Album, expire_in: u64) -> Self {
+        AlbumAccess {
+            album_id: album.id.to_string(),
+            share_link_id: None,
+            exp: seconds_from_now(expire_in),
+            iss: issuer(),
+            aud: String::from(ALBUM_AUDIENCE),
+        }
+    }
+
+    pub fn for_share_link(link: &ShareLink, expire_in: u64) -> Self {
+        AlbumAccess {
+            album_id: link.album_id.to_string(),
+            share_link_id: Some(link.id.to_string()),
+            exp: seconds_from_now(expire_in),
+            iss: issuer(),
+            aud: String::from(ALBUM_AUDIENCE),
+        }
+    }
+
+    /// Whether the token grants access to the album by itself, tokens of share links don't.
+    pub fn grants(&self, album: &Album) -> bool {
+        self.share_link_id.is_none() && self.album_id == album.id.to_string()
+    }
+
+    /// Whether the token was given by unlocking the share link.
+    pub fn grants_link(&self, link: &ShareLink) -> bool {
+        self.share_link_id == Some(link.id.to_string())
+            && self.album_id == link.album_id.to_string()
+    }
+}
+
+pub fn encode_token(user: &User, session: &Session, expire_in: u64) -> String {
+    let key = current_key();
+
+    encode(
+        &key.header(),
+        &AuthUser::new(user, session, expire_in),
+        &key.encoding_key(),
+    )
+    .unwrap()
+}
+
+/// Decodes a user token, checking it was signed by one of the known keys for the API audience.
+pub fn decode_token(token: &str) -> Result<TokenData<AuthUser>, JwtError> {
+    decode_signed(token, AUTH_AUDIENCE)
+}
+
+pub fn encode_album_token(album: &Album, expire_in: u64) -> String {
+    let key = current_key();
+
+    encode(
+        &key.header(),
+        &AlbumAccess::new(album, expire_in),
+        &key.encoding_key(),
+    )
+    .unwrap()
+}
+
+pub fn encode_share_link_token(link: &ShareLink, expire_in: u64) -> String {
+    let key = current_key();
+
+    encode(
+        &key.header(),
+        &AlbumAccess::for_share_link(link, expire_in),
+        &key.encoding_key(),
+    )
+    .unwrap()
 }
 
-pub fn encode_token(user: &User, expire_in: u64) -> String {
-    let secret = get_secret();
-    let key = EncodingKey::from_secret(secret.as_ref());
+/// Decodes an album token, returns `None` when it is invalid or expired.
+pub fn decode_album_token(token: &str) -> Option<AlbumAccess> {
+    decode_signed::<AlbumAccess>(token, ALBUM_AUDIENCE)
+        .map(|data| data.claims)
+        .ok()
+}
+
+/// Verifies the token with the key named by its `kid` header.
+fn decode_signed<T: DeserializeOwned>(
+    token: &str,
+    audience: &str,
+) -> Result<TokenData<T>, JwtError> {
+    let header = decode_header(token)?;
+    let key = header
+        .kid
+        .as_deref()
+        .and_then(find_key)
+        .ok_or_else(|| JwtError::from(JwtErrorKind::InvalidSignature))?;
 
-    encode(&Header::default(), &AuthUser::new(user, expire_in), &key).unwrap()
+    decode::<T>(token, &key.decoding_key(), &validation(audience))
 }
 
 fn seconds_from_now(secs: u64) -> u64 {
@@ -46,3 +172,99 @@ fn seconds_from_now(secs: u64) -> u64 {
         SystemTime::now().duration_since(UNIX_EPOCH).unwrap() + Duration::from_secs(secs);
     expiry_time.as_secs()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::set_test_env;
+    use jsonwebtoken::{EncodingKey, Header};
+    use photo_core::models::Studio;
+    use serde_json::json;
+
+    fn setup() -> (User, Session) {
+        set_test_env();
+        let user = User::new(String::from("owner@example.com"), None);
+        let (session, _) = Session::new(&user, None, None);
+
+        (user, session)
+    }
+
+    #[test]
+    fn user_tokens_are_decoded() {
+        let (user, session) = setup();
+
+        let token = encode_token(&user, &session, ACCESS_TOKEN_EXPIRY);
+        let claims = decode_token(&token).unwrap().claims;
+
+        assert_eq!(claims.email(), user.email);
+        assert_eq!(claims.session_id(), session.id.to_string());
+    }
+
+    #[test]
+    fn user_tokens_without_session_are_rejected() {
+        let (user, _) = setup();
+        let claims = json!({
+            "email": user.email,
+            "user_id": user.id.to_string(),
+            "exp": seconds_from_now(ACCESS_TOKEN_EXPIRY),
+            "iss": issuer(),
+            "aud": AUTH_AUDIENCE,
+            "jti": Uuid::new_v4().to_string(),
+        });
+        let key = current_key();
+
+        let token = encode(&key.header(), &claims, &key.encoding_key()).unwrap();
+
+        assert!(decode_token(&token).is_err());
+    }
+
+    #[test]
+    fn album_tokens_are_not_user_tokens() {
+        let (user, _) = setup();
+        let studio = Studio::new(String::from("Studio"));
+        let album = Album::new(&user, &studio, String::from("Wedding"), None);
+
+        let token = encode_album_token(&album, ALBUM_TOKEN_EXPIRY);
+
+        assert!(decode_token(&token).is_err());
+        assert!(decode_album_token(&token).unwrap().grants(&album));
+    }
+
+    #[test]
+    fn share_link_tokens_only_grant_their_link() {
+        let (user, _) = setup();
+        let studio = Studio::new(String::from("Studio"));
+        let album = Album::new(&user, &studio, String::from("Wedding"), None);
+        let link = ShareLink::new(&album, None, None);
+        let other_link = ShareLink::new(&album, None, None);
+
+        let link_token = decode_album_token(&encode_share_link_token(&link, ALBUM_TOKEN_EXPIRY));
+        let album_token = decode_album_token(&encode_album_token(&album, ALBUM_TOKEN_EXPIRY));
+
+        let link_token = link_token.unwrap();
+        assert!(link_token.grants_link(&link));
+        assert!(!link_token.grants_link(&other_link));
+        assert!(!link_token.grants(&album));
+        assert!(!album_token.unwrap().grants_link(&link));
+    }
+
+    #[test]
+    fn tokens_of_unknown_keys_are_rejected() {
+        let (user, session) = setup();
+        let claims = AuthUser::new(&user, &session, ACCESS_TOKEN_EXPIRY);
+        let mut header = current_key().header();
+        header.kid = Some(String::from("retired"));
+        let unknown_kid = encode(&header, &claims, &current_key().encoding_key()).unwrap();
+        let wrong_secret = encode(
+            &current_key().header(),
+            &claims,
+            &EncodingKey::from_secret(b"other-secret"),
+        )
+        .unwrap();
+        let no_kid = encode(&Header::default(), &claims, &current_key().encoding_key()).unwrap();
+
+        assert!(decode_token(&unknown_kid).is_err());
+        assert!(decode_token(&wrong_secret).is_err());
+        assert!(decode_token(&no_kid).is_err());
+    }
+}
@@ -4,10 +4,10 @@ use hyper_tls::HttpsConnector;
 use rusoto_core::{ByteStream, HttpClient, Region, RusotoError};
 use rusoto_credential::EnvironmentProvider;
 use rusoto_s3::{
-    DeleteObjectError, DeleteObjectRequest, PutObjectError, PutObjectOutput, PutObjectRequest,
-    S3Client, S3,
+    DeleteObjectError, DeleteObjectRequest, GetObjectError, GetObjectRequest, PutObjectError,
+    PutObjectOutput, PutObjectRequest, S3Client, S3,
 };
-use snafu::{Backtrace, ResultExt};
+use snafu::{Backtrace, OptionExt, ResultExt};
 use std::env;
 
 pub async fn upload(
@@ -101,6 +101,29 @@ pub async fn delete(key: String) -> Result<()> {
     Ok(())
 }
 
+/// Fetches an object, returning its body as a stream so it does not need to fit in memory.
+pub async fn download(key: String) -> Result<ByteStream> {
+    let hyper_builder = Client::builder();
+    let https_connector = HttpsConnector::new();
+    let http_client = HttpClient::from_builder(hyper_builder, https_connector);
+
+    let credentials_provider = EnvironmentProvider::default();
+    let s3 = S3Client::new_with(http_client, credentials_provider, Region::default());
+
+    let bucket = env::var("AWS_S3_BUCKET_NAME").context(NoBucket)?;
+
+    let input = GetObjectRequest {
+        bucket,
+        key,
+        ..Default::default()
+    };
+
+    let s3_object = s3.get_object(input).await.context(S3DownloadIssue)?;
+    let body = s3_object.body.context(S3EmptyObject)?;
+
+    Ok(body)
+}
+
 pub type Result<T> = std::result::Result<T, AwsS3Error>;
 
 #[derive(Debug, Snafu)]
@@ -117,6 +140,15 @@ pub enum AwsS3Error {
         backtrace: Backtrace,
     },
 
+    #[snafu(display("Could not download file from S3: {}", source))]
+    S3DownloadIssue {
+        source: RusotoError<GetObjectError>,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("S3 object has no body"))]
+    S3EmptyObject { backtrace: Backtrace },
+
     #[snafu(display("Could not delete file from S3: {}", source))]
     S3DeleteIssue {
         source: RusotoError<DeleteObjectError>,
@@ -1,18 +1,38 @@
 use crate::connection::Repo;
-use photo_core::models::{Album, AlbumWithPhotos, ModelError, Photo, User};
+use photo_core::helpers::precondition::Precondition;
+use photo_core::models::{
+    transaction, Album, AlbumChanges, AlbumNode, AlbumSummary, AuditAction, AuditContext,
+    ModelError, PhotoPage, PhotoPagination, Role, Studio, User,
+};
 use snafu::{Backtrace, ResultExt};
 
+/// Creates an album in the given studio, or in the personal studio of the user when there is
+/// none. The user must be able to edit the studio.
 pub async fn create(
     repo: Repo,
+    audit: &AuditContext,
     user: &User,
+    studio_id: Option<String>,
     name: String,
     description: Option<String>,
 ) -> Result<Album> {
     let user = user.clone();
+    let audit = audit.clone();
     repo.run(move |conn| {
-        let album = Album::new(&user, name, description);
+        let studio = match studio_id {
+            Some(s_id) => Studio::find_for_member(&conn, &user, &s_id, Role::Editor),
+            None => Studio::find_personal(&conn, &user),
+        }
+        .context(Model)?;
+        let album = Album::new(&user, &studio, name, description);
 
-        let album = album.insert(&conn).context(Model)?;
+        let album = transaction(&conn, || {
+            let album = album.insert(&conn)?;
+            audit.record(&conn, AuditAction::Create, None, Some(&album))?;
+
+            Ok(album)
+        })
+        .context(Model)?;
 
         Ok(album)
     })
@@ -21,48 +41,88 @@ pub async fn create(
 
 pub async fn update(
     repo: Repo,
+    audit: &AuditContext,
     album: &Album,
     name: String,
     description: Option<String>,
 ) -> Result<Album> {
     let album = album.clone();
+    let audit = audit.clone();
     repo.run(move |conn| {
-        let album = album.update(&conn, name, description).context(Model)?;
-        Ok(album)
+        let updated = transaction(&conn, || {
+            let updated = album.update(&conn, name, description)?;
+            audit.record(&conn, AuditAction::Update, Some(&album), Some(&updated))?;
+
+            Ok(updated)
+        })
+        .context(Model)?;
+
+        Ok(updated)
+    })
+    .await
+}
+
+pub async fn patch(
+    repo: Repo,
+    audit: &AuditContext,
+    album: &Album,
+    changes: AlbumChanges,
+    precondition: Option<Precondition>,
+) -> Result<Album> {
+    let album = album.clone();
+    let audit = audit.clone();
+    repo.run(move |conn| {
+        let updated = transaction(&conn, || {
+            let updated = album.patch(&conn, &changes, precondition.as_ref())?;
+            audit.record(&conn, AuditAction::Update, Some(&album), Some(&updated))?;
+
+            Ok(updated)
+        })
+        .context(Model)?;
+
+        Ok(updated)
     })
     .await
 }
 
-pub async fn delete(repo: Repo, album: &Album) -> Result<()> {
+pub async fn delete(repo: Repo, audit: &AuditContext, album: &Album) -> Result<()> {
     let album = album.clone();
+    let audit = audit.clone();
     repo.run(move |conn| {
-        album.delete(&conn).context(Model)?;
+        transaction(&conn, || {
+            album.delete(&conn)?;
+            audit.record(&conn, AuditAction::Delete, Some(&album), None)
+        })
+        .context(Model)?;
 
         Ok(())
     })
     .await
 }
 
-pub async fn find_by_id(repo: Repo, id: String) -> Result<Album> {
+/// Finds an album of a studio of the user, failing with `AlbumNotFound` for other studios and
+/// with `Forbidden` when the user has a lower role than `role`.
+pub async fn find_for(repo: Repo, user: &User, id: String, role: Role) -> Result<Album> {
+    let user = user.clone();
     repo.run(move |conn| {
-        let album = Album::find_by_id(&conn, &id).context(Model)?;
+        let album = Album::find_for_member(&conn, &user, &id, role).context(Model)?;
 
         Ok(album)
     })
     .await
 }
 
-pub async fn find_by_name(repo: Repo, user: &User, name: String) -> Result<AlbumWithPhotos> {
+pub async fn find_by_slug(repo: Repo, user: &User, slug: String) -> Result<Album> {
     let user = user.clone();
     repo.run(move |conn| {
-        let album = Album::find_by_name(&conn, &user, &name).context(Model)?;
+        let album = Album::find_by_slug(&conn, &user, &slug).context(Model)?;
 
         Ok(album)
     })
     .await
 }
 
-pub async fn find_all(repo: Repo, user: &User) -> Result<Vec<AlbumWithPhotos>> {
+pub async fn find_all(repo: Repo, user: &User) -> Result<Vec<AlbumSummary>> {
     let user = user.clone();
     repo.run(move |conn| {
         let albums = Album::find_all(&conn, &user).context(Model)?;
@@ -72,7 +132,85 @@ pub async fn find_all(repo: Repo, user: &User) -> Result<Vec<AlbumWithPhotos>> {
     .await
 }
 
-pub async fn find_main_public(repo: Repo, user: &User) -> Result<AlbumWithPhotos> {
+pub async fn find_tree(repo: Repo, user: &User) -> Result<Vec<AlbumNode>> {
+    let user = user.clone();
+    repo.run(move |conn| {
+        let tree = Album::find_tree(&conn, &user).context(Model)?;
+
+        Ok(tree)
+    })
+    .await
+}
+
+pub async fn find_children(
+    repo: Repo,
+    user: &User,
+    parent: Option<&Album>,
+) -> Result<Vec<AlbumSummary>> {
+    let user = user.clone();
+    let parent = parent.cloned();
+    repo.run(move |conn| {
+        let children = Album::find_children(&conn, &user, parent.as_ref()).context(Model)?;
+
+        Ok(children)
+    })
+    .await
+}
+
+pub async fn move_to(
+    repo: Repo,
+    audit: &AuditContext,
+    user: &User,
+    album: &Album,
+    parent_id: Option<String>,
+) -> Result<Album> {
+    let user = user.clone();
+    let album = album.clone();
+    let audit = audit.clone();
+    repo.run(move |conn| {
+        let parent = match parent_id {
+            Some(p_id) => {
+                Some(Album::find_for_member(&conn, &user, &p_id, Role::Editor).context(Model)?)
+            }
+            None => None,
+        };
+
+        let updated = transaction(&conn, || {
+            let updated = album.move_to(&conn, parent.as_ref())?;
+            audit.record(&conn, AuditAction::Update, Some(&album), Some(&updated))?;
+
+            Ok(updated)
+        })
+        .context(Model)?;
+
+        Ok(updated)
+    })
+    .await
+}
+
+pub async fn set_password(
+    repo: Repo,
+    audit: &AuditContext,
+    album: &Album,
+    password: Option<String>,
+) -> Result<Album> {
+    let album = album.clone();
+    let audit = audit.clone();
+    repo.run(move |conn| {
+        let updated = transaction(&conn, || {
+            let updated = album.set_password(&conn, password.as_deref())?;
+            audit.record(&conn, AuditAction::Update, Some(&album), Some(&updated))?;
+
+            Ok(updated)
+        })
+        .context(Model)?;
+
+        Ok(updated)
+    })
+    .await
+}
+
+pub async fn find_main_public(repo: Repo, user: &User) -> Result<Album> {
     let user = user.clone();
     repo.run(move |conn| {
         let album = Album::find_main_public(&conn, &user).context(Model)?;
@@ -82,12 +220,20 @@ pub async fn find_main_public(repo: Repo, user: &User) -> Result<AlbumWithPhotos
     .await
 }
 
-pub async fn photos(repo: Repo, id: String) -> Result<Vec<Photo>> {
+/// Page of the photos of the album, only the ones labeled with the tag when there is one.
+pub async fn photos(
+    repo: Repo,
+    album: &Album,
+    tag: Option<String>,
+    pagination: PhotoPagination,
+) -> Result<PhotoPage> {
+    let album = album.clone();
     repo.run(move |conn| {
-        let album = Album::find_by_id(&conn, &id).context(Model)?;
-        let photos = album.photos(&conn).context(Model)?;
+        let page = album
+            .photo_page(&conn, tag.as_deref(), &pagination)
+            .context(Model)?;
 
-        Ok(photos)
+        Ok(page)
     })
     .await
 }
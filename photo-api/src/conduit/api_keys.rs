@@ -0,0 +1,61 @@
+use crate::connection::Repo;
+use photo_core::models::{ApiKey, ApiKeyScope, ModelError, User};
+use snafu::{Backtrace, ResultExt};
+
+/// Creates an API key for the user, returned along with the key itself.
+pub async fn create(
+    repo: Repo,
+    user: &User,
+    name: String,
+    scope: ApiKeyScope,
+) -> Result<(ApiKey, String)> {
+    let user = user.clone();
+    repo.run(move |conn| {
+        let (api_key, key) = ApiKey::new(&user, name, scope);
+        let api_key = api_key.insert(&conn).context(Model)?;
+
+        Ok((api_key, key))
+    })
+    .await
+}
+
+pub async fn find_all(repo: Repo, user: &User) -> Result<Vec<ApiKey>> {
+    let user = user.clone();
+    repo.run(move |conn| {
+        let list = ApiKey::find_by_user(&conn, &user).context(Model)?;
+
+        Ok(list)
+    })
+    .await
+}
+
+pub async fn authenticate(repo: Repo, key: String) -> Result<ApiKey> {
+    repo.run(move |conn| {
+        let api_key = ApiKey::authenticate(&conn, &key).context(Model)?;
+
+        Ok(api_key)
+    })
+    .await
+}
+
+pub async fn revoke(repo: Repo, user: &User, id: String) -> Result<()> {
+    let user = user.clone();
+    repo.run(move |conn| {
+        ApiKey::revoke(&conn, &user, &id).context(Model)?;
+
+        Ok(())
+    })
+    .await
+}
+
+pub type Result<T, E = ApiKeyError> = std::result::Result<T, E>;
+
+#[derive(Debug, Snafu)]
+pub enum ApiKeyError {
+    #[snafu(display("Problem with model: {}", cause))]
+    Model {
+        #[snafu(source)]
+        cause: ModelError,
+        backtrace: Backtrace,
+    },
+}
@@ -0,0 +1,29 @@
+use crate::connection::Repo;
+use photo_core::models::{AuditEvent, AuditFilter, AuditPage, ModelError};
+use snafu::{Backtrace, ResultExt};
+
+pub async fn find(
+    repo: Repo,
+    filter: AuditFilter,
+    limit: i64,
+    cursor: Option<String>,
+) -> Result<AuditPage> {
+    repo.run(move |conn| {
+        let page = AuditEvent::find(&conn, &filter, limit, cursor.as_deref()).context(Model)?;
+
+        Ok(page)
+    })
+    .await
+}
+
+pub type Result<T, E = AuditError> = std::result::Result<T, E>;
+
+#[derive(Debug, Snafu)]
+pub enum AuditError {
+    #[snafu(display("Problem with model: {}", cause))]
+    Model {
+        #[snafu(source)]
+        cause: ModelError,
+        backtrace: Backtrace,
+    },
+}
@@ -1,11 +1,33 @@
 use crate::connection::Repo;
-use photo_core::models::{BookMe, ModelError, User};
+use photo_core::models::{transaction, AuditAction, AuditContext, BookMe, ModelError, User};
 use snafu::{Backtrace, ResultExt};
 
-pub async fn update_or_create(repo: Repo, user: &User, email: String) -> Result<BookMe> {
+pub async fn update_or_create(
+    repo: Repo,
+    audit: &AuditContext,
+    user: &User,
+    email: String,
+) -> Result<BookMe> {
     let user = user.clone();
+    let audit = audit.clone();
     repo.run(move |conn| {
-        let info = BookMe::update_or_create(&conn, &email, &user).context(Model)?;
+        let info = transaction(&conn, || {
+            let previous = match BookMe::find_by_user(&conn, &user) {
+                Ok(info) => Some(info),
+                Err(ModelError::BookMeNotFound) => None,
+                Err(e) => return Err(e),
+            };
+            let info = BookMe::update_or_create(&conn, &email, &user)?;
+
+            let action = match previous {
+                Some(_) => AuditAction::Update,
+                None => AuditAction::Create,
+            };
+            audit.record(&conn, action, previous.as_ref(), Some(&info))?;
+
+            Ok(info)
+        })
+        .context(Model)?;
 
         Ok(info)
     })
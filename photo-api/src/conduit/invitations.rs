@@ -0,0 +1,51 @@
+use crate::connection::Repo;
+use chrono::NaiveDateTime;
+use photo_core::models::{Invitation, ModelError, User};
+use snafu::{Backtrace, ResultExt};
+
+pub async fn invite(
+    repo: Repo,
+    admin: &User,
+    email: String,
+    expires_at: Option<NaiveDateTime>,
+) -> Result<Invitation> {
+    let admin = admin.clone();
+    repo.run(move |conn| {
+        let invitation = Invitation::new(email, Some(&admin), expires_at)
+            .insert(&conn)
+            .context(Model)?;
+
+        Ok(invitation)
+    })
+    .await
+}
+
+pub async fn find_all(repo: Repo) -> Result<Vec<Invitation>> {
+    repo.run(move |conn| {
+        let list = Invitation::find_all(&conn).context(Model)?;
+
+        Ok(list)
+    })
+    .await
+}
+
+pub async fn revoke(repo: Repo, id: String) -> Result<()> {
+    repo.run(move |conn| {
+        Invitation::revoke(&conn, &id).context(Model)?;
+
+        Ok(())
+    })
+    .await
+}
+
+pub type Result<T, E = InvitationError> = std::result::Result<T, E>;
+
+#[derive(Debug, Snafu)]
+pub enum InvitationError {
+    #[snafu(display("Problem with model: {}", cause))]
+    Model {
+        #[snafu(source)]
+        cause: ModelError,
+        backtrace: Backtrace,
+    },
+}
@@ -0,0 +1,37 @@
+use crate::connection::Repo;
+use photo_core::models::{LoginCode, ModelError, User};
+use snafu::{Backtrace, ResultExt};
+
+/// Creates a login code for the user, returning the code to hand to the client.
+pub async fn create(repo: Repo, user: &User) -> Result<String> {
+    let user = user.clone();
+    repo.run(move |conn| {
+        let (login_code, code) = LoginCode::new(&user);
+        login_code.insert(&conn).context(Model)?;
+
+        Ok(code)
+    })
+    .await
+}
+
+/// Uses up a login code, returning the user it was created for.
+pub async fn take(repo: Repo, code: String) -> Result<User> {
+    repo.run(move |conn| {
+        let user = LoginCode::take(&conn, &code).context(Model)?;
+
+        Ok(user)
+    })
+    .await
+}
+
+pub type Result<T, E = LoginCodeError> = std::result::Result<T, E>;
+
+#[derive(Debug, Snafu)]
+pub enum LoginCodeError {
+    #[snafu(display("Problem with model: {}", cause))]
+    Model {
+        #[snafu(source)]
+        cause: ModelError,
+        backtrace: Backtrace,
+    },
+}
@@ -0,0 +1,46 @@
+use crate::connection::Repo;
+use photo_core::models::{Invitation, LoginLink, ModelError, User};
+use snafu::{Backtrace, ResultExt};
+
+/// Creates a login link for the email, returning its token. Returns `None` when the email has no
+/// account and no invitation, so no link is sent.
+pub async fn create(repo: Repo, email: String) -> Result<Option<String>> {
+    repo.run(move |conn| {
+        let exists = match User::find_by_email(&conn, &email) {
+            Ok(_) => true,
+            Err(ModelError::UserNotFound) => false,
+            Err(e) => return Err(e).context(Model),
+        };
+
+        if !exists && !Invitation::is_invited(&conn, &email).context(Model)? {
+            return Ok(None);
+        }
+
+        let (link, token) = LoginLink::new(email);
+        link.insert(&conn).context(Model)?;
+
+        Ok(Some(token))
+    })
+    .await
+}
+
+pub async fn take(repo: Repo, token: String) -> Result<LoginLink> {
+    repo.run(move |conn| {
+        let link = LoginLink::take(&conn, &token).context(Model)?;
+
+        Ok(link)
+    })
+    .await
+}
+
+pub type Result<T, E = LoginLinkError> = std::result::Result<T, E>;
+
+#[derive(Debug, Snafu)]
+pub enum LoginLinkError {
+    #[snafu(display("Problem with model: {}", cause))]
+    Model {
+        #[snafu(source)]
+        cause: ModelError,
+        backtrace: Backtrace,
+    },
+}
@@ -1,4 +1,16 @@
 pub mod albums;
+pub mod api_keys;
+pub mod audit;
 pub mod book_me;
+pub mod invitations;
+pub mod login_codes;
+pub mod login_links;
+pub mod oauth_states;
 pub mod photos;
+pub mod proofing;
+pub mod search;
+pub mod sessions;
+pub mod share_links;
+pub mod studios;
+pub mod tags;
 pub mod users;
@@ -0,0 +1,40 @@
+use crate::connection::Repo;
+use photo_core::models::{ModelError, OAuthState};
+use snafu::{Backtrace, ResultExt};
+
+pub async fn save(
+    repo: Repo,
+    provider: String,
+    state: String,
+    pkce_verifier: String,
+) -> Result<OAuthState> {
+    repo.run(move |conn| {
+        let saved = OAuthState::new(provider, state, pkce_verifier)
+            .insert(&conn)
+            .context(Model)?;
+
+        Ok(saved)
+    })
+    .await
+}
+
+pub async fn take(repo: Repo, provider: String, state: String) -> Result<OAuthState> {
+    repo.run(move |conn| {
+        let found = OAuthState::take(&conn, &provider, &state).context(Model)?;
+
+        Ok(found)
+    })
+    .await
+}
+
+pub type Result<T, E = OAuthStateError> = std::result::Result<T, E>;
+
+#[derive(Debug, Snafu)]
+pub enum OAuthStateError {
+    #[snafu(display("Problem with model: {}", cause))]
+    Model {
+        #[snafu(source)]
+        cause: ModelError,
+        backtrace: Backtrace,
+    },
+}
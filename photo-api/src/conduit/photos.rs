@@ -1,9 +1,16 @@
 use crate::connection::Repo;
-use photo_core::models::{Album, ModelError, Photo, User};
+use chrono::NaiveDateTime;
+use photo_core::helpers::precondition::Precondition;
+use photo_core::models::{
+    transaction, Album, AuditAction, AuditContext, BulkOutcome, ModelError, Photo, PhotoChanges,
+    PhotoImage, PhotoOperation, PhotoPage, PhotoPagination, PhotoUpload, PhotoVersion, Role, Tag,
+    User,
+};
 use snafu::{Backtrace, ResultExt};
 
 pub async fn create(
     repo: Repo,
+    audit: &AuditContext,
     album: &Album,
     user: &User,
     index_in_album: i32,
@@ -15,10 +22,25 @@ pub async fn create(
     width: i32,
     height: i32,
     is_favorite: bool,
+    filename: Option<String>,
+    tags: Option<Vec<String>>,
+    taken_at: Option<NaiveDateTime>,
 ) -> Result<Photo> {
     let album = album.clone();
     let user = user.clone();
+    let audit = audit.clone();
     repo.run(move |conn| {
+        // Metadata read from the file fills whatever the client did not send.
+        let upload = PhotoUpload::take(&conn, &user, &s3_id).context(Model)?;
+        let (title, description, tags) = match upload {
+            Some(upload) => (
+                title.or_else(|| upload.title.clone()),
+                description.or_else(|| upload.description.clone()),
+                tags.unwrap_or_else(|| upload.keywords()),
+            ),
+            None => (title, description, tags.unwrap_or_default()),
+        };
+
         let photo = Photo::new(
             &album,
             &user,
@@ -31,16 +53,49 @@ pub async fn create(
             width,
             height,
             is_favorite,
+            filename,
+            taken_at,
         );
-        let photo = photo.insert(&conn).context(Model)?;
+        let photo = transaction(&conn, || {
+            let photo = photo.insert(&conn)?;
+
+            if !tags.is_empty() {
+                Tag::add_to_photos(&conn, &user, &tags, &[photo.clone()])?;
+            }
+
+            audit.record(&conn, AuditAction::Create, None, Some(&photo))?;
+
+            Ok(photo)
+        })
+        .context(Model)?;
 
         Ok(photo)
     })
     .await
 }
 
+/// Keeps the metadata of an uploaded file until the photo is created.
+pub async fn save_upload(
+    repo: Repo,
+    user: &User,
+    s3_id: String,
+    title: Option<String>,
+    description: Option<String>,
+    keywords: Vec<String>,
+) -> Result<PhotoUpload> {
+    let user = user.clone();
+    repo.run(move |conn| {
+        let upload = PhotoUpload::new(&user, s3_id, title, description, &keywords);
+        let upload = upload.insert(&conn).context(Model)?;
+
+        Ok(upload)
+    })
+    .await
+}
+
 pub async fn update(
     repo: Repo,
+    audit: &AuditContext,
     photo: &Photo,
     index_in_album: i32,
     is_favorite: bool,
@@ -48,36 +103,175 @@ pub async fn update(
     description: Option<String>,
 ) -> Result<Photo> {
     let photo = photo.clone();
+    let audit = audit.clone();
     repo.run(move |conn| {
-        let photo = photo
-            .update(&conn, index_in_album, is_favorite, title, description)
-            .context(Model)?;
+        let updated = transaction(&conn, || {
+            let updated = photo.update(&conn, index_in_album, is_favorite, title, description)?;
+            audit.record(&conn, AuditAction::Update, Some(&photo), Some(&updated))?;
 
-        Ok(photo)
+            Ok(updated)
+        })
+        .context(Model)?;
+
+        Ok(updated)
+    })
+    .await
+}
+
+pub async fn patch(
+    repo: Repo,
+    audit: &AuditContext,
+    photo: &Photo,
+    changes: PhotoChanges,
+    precondition: Option<Precondition>,
+) -> Result<Photo> {
+    let photo = photo.clone();
+    let audit = audit.clone();
+    repo.run(move |conn| {
+        let updated = transaction(&conn, || {
+            let updated = photo.patch(&conn, &changes, precondition.as_ref())?;
+            audit.record(&conn, AuditAction::Update, Some(&photo), Some(&updated))?;
+
+            Ok(updated)
+        })
+        .context(Model)?;
+
+        Ok(updated)
     })
     .await
 }
 
-pub async fn find_by_id(repo: Repo, id: String) -> Result<Photo> {
+/// Finds a photo of a studio of the user, failing with `PhotoNotFound` for other studios and with
+/// `Forbidden` when the user has a lower role than `role`.
+pub async fn find_for(repo: Repo, user: &User, id: String, role: Role) -> Result<Photo> {
+    let user = user.clone();
     repo.run(move |conn| {
-        let photo = Photo::find_by_id(&conn, &id).context(Model)?;
+        let photo = Photo::find_for_member(&conn, &user, &id, role).context(Model)?;
 
         Ok(photo)
     })
     .await
 }
 
-pub async fn delete(repo: Repo, photo: &Photo) -> Result<()> {
+pub async fn delete(repo: Repo, audit: &AuditContext, photo: &Photo) -> Result<()> {
     let photo = photo.clone();
+    let audit = audit.clone();
 
     repo.run(move |conn| {
-        photo.delete(&conn).context(Model)?;
+        transaction(&conn, || {
+            photo.delete(&conn)?;
+            audit.record(&conn, AuditAction::Delete, Some(&photo), None)
+        })
+        .context(Model)?;
 
         Ok(())
     })
     .await
 }
 
+/// Applies the operation and records the change of every photo it touched, deleted photos have
+/// nothing after the change.
+pub async fn bulk(
+    repo: Repo,
+    audit: &AuditContext,
+    user: &User,
+    ids: Vec<String>,
+    operation: PhotoOperation,
+) -> Result<BulkOutcome> {
+    let user = user.clone();
+    let audit = audit.clone();
+    repo.run(move |conn| {
+        let outcome = transaction(&conn, || {
+            let outcome = Photo::bulk(&conn, &user, &ids, &operation)?;
+
+            for (before, after) in outcome.changes.iter() {
+                let action = match after {
+                    Some(_) => AuditAction::Update,
+                    None => AuditAction::Delete,
+                };
+                audit.record(&conn, action, Some(before), after.as_ref())?;
+            }
+
+            Ok(outcome)
+        })
+        .context(Model)?;
+
+        Ok(outcome)
+    })
+    .await
+}
+
+pub async fn replace_image(
+    repo: Repo,
+    audit: &AuditContext,
+    photo: &Photo,
+    image: PhotoImage,
+) -> Result<Photo> {
+    let photo = photo.clone();
+    let audit = audit.clone();
+    repo.run(move |conn| {
+        let updated = transaction(&conn, || {
+            let updated = photo.replace_image(&conn, &image)?;
+            audit.record(&conn, AuditAction::Update, Some(&photo), Some(&updated))?;
+
+            Ok(updated)
+        })
+        .context(Model)?;
+
+        Ok(updated)
+    })
+    .await
+}
+
+pub async fn versions(repo: Repo, photo: &Photo) -> Result<Vec<PhotoVersion>> {
+    let photo = photo.clone();
+    repo.run(move |conn| {
+        let versions = PhotoVersion::find_by_photo(&conn, &photo).context(Model)?;
+
+        Ok(versions)
+    })
+    .await
+}
+
+pub async fn revert(
+    repo: Repo,
+    audit: &AuditContext,
+    photo: &Photo,
+    version_id: String,
+) -> Result<Photo> {
+    let photo = photo.clone();
+    let audit = audit.clone();
+    repo.run(move |conn| {
+        let version = PhotoVersion::find_by_id(&conn, &photo, &version_id).context(Model)?;
+        let updated = transaction(&conn, || {
+            let updated = photo.revert(&conn, &version)?;
+            audit.record(&conn, AuditAction::Update, Some(&photo), Some(&updated))?;
+
+            Ok(updated)
+        })
+        .context(Model)?;
+
+        Ok(updated)
+    })
+    .await
+}
+
+/// Favorite photos across albums, along with the albums the photos of the page belong to.
+pub async fn favorites(
+    repo: Repo,
+    user: &User,
+    public_only: bool,
+    pagination: PhotoPagination,
+) -> Result<(PhotoPage, Vec<Album>)> {
+    let user = user.clone();
+    repo.run(move |conn| {
+        let found = Photo::find_favorites(&conn, &user, public_only, &pagination).context(Model)?;
+
+        Ok(found)
+    })
+    .await
+}
+
 pub type Result<T, E = PhotoError> = std::result::Result<T, E>;
 
 #[derive(Debug, Snafu)]
@@ -0,0 +1,106 @@
+use crate::connection::Repo;
+use photo_core::models::{
+    Album, ModelError, Photo, ProofingClient, ProofingComment, ProofingSummary, Role, ShareLink,
+    User,
+};
+use snafu::{Backtrace, ResultExt};
+
+/// Registers the client and returns their summary along with the secret they act with.
+pub async fn register(
+    repo: Repo,
+    link: &ShareLink,
+    name: String,
+    email: String,
+) -> Result<(ProofingSummary, String)> {
+    let link = link.clone();
+    repo.run(move |conn| {
+        let (client, secret) =
+            ProofingClient::register(&conn, &link, &name, &email).context(Model)?;
+        let summary = client.summary(&conn).context(Model)?;
+
+        Ok((summary, secret))
+    })
+    .await
+}
+
+pub async fn select(
+    repo: Repo,
+    link: &ShareLink,
+    album: &Album,
+    secret: String,
+    photo_id: String,
+    selected: bool,
+) -> Result<ProofingSummary> {
+    let link = link.clone();
+    let album = album.clone();
+    repo.run(move |conn| {
+        let client = ProofingClient::authenticate(&conn, &link, &secret).context(Model)?;
+        let photo = Photo::find_in_album(&conn, &album, &photo_id).context(Model)?;
+        client.select(&conn, &photo, selected).context(Model)?;
+        let summary = client.summary(&conn).context(Model)?;
+
+        Ok(summary)
+    })
+    .await
+}
+
+pub async fn comment(
+    repo: Repo,
+    link: &ShareLink,
+    album: &Album,
+    secret: String,
+    photo_id: String,
+    body: String,
+) -> Result<ProofingComment> {
+    let link = link.clone();
+    let album = album.clone();
+    repo.run(move |conn| {
+        let client = ProofingClient::authenticate(&conn, &link, &secret).context(Model)?;
+        let photo = Photo::find_in_album(&conn, &album, &photo_id).context(Model)?;
+        let comment = client.comment(&conn, &photo, body).context(Model)?;
+
+        Ok(comment)
+    })
+    .await
+}
+
+pub async fn set_enabled(
+    repo: Repo,
+    user: &User,
+    link_id: String,
+    enabled: bool,
+) -> Result<ShareLink> {
+    let user = user.clone();
+    repo.run(move |conn| {
+        let link =
+            ShareLink::find_for_member(&conn, &user, &link_id, Role::Editor).context(Model)?;
+        let link = link.set_proofing(&conn, enabled).context(Model)?;
+
+        Ok(link)
+    })
+    .await
+}
+
+pub async fn summaries(repo: Repo, user: &User, link_id: String) -> Result<Vec<ProofingSummary>> {
+    let user = user.clone();
+    repo.run(move |conn| {
+        let link =
+            ShareLink::find_for_member(&conn, &user, &link_id, Role::Viewer).context(Model)?;
+        let summaries = ProofingClient::summaries_by_link(&conn, &link).context(Model)?;
+
+        Ok(summaries)
+    })
+    .await
+}
+
+pub type Result<T, E = ProofingError> = std::result::Result<T, E>;
+
+#[derive(Debug, Snafu)]
+pub enum ProofingError {
+    #[snafu(display("Problem with model: {}", cause))]
+    Model {
+        #[snafu(source)]
+        cause: ModelError,
+        backtrace: Backtrace,
+    },
+}
@@ -0,0 +1,25 @@
+use crate::connection::Repo;
+use photo_core::models::{ModelError, SearchResults, User};
+use snafu::{Backtrace, ResultExt};
+
+pub async fn find(repo: Repo, user: &User, terms: String, limit: i64) -> Result<SearchResults> {
+    let user = user.clone();
+    repo.run(move |conn| {
+        let results = SearchResults::find(&conn, &user, &terms, limit).context(Model)?;
+
+        Ok(results)
+    })
+    .await
+}
+
+pub type Result<T, E = SearchError> = std::result::Result<T, E>;
+
+#[derive(Debug, Snafu)]
+pub enum SearchError {
+    #[snafu(display("Problem with model: {}", cause))]
+    Model {
+        #[snafu(source)]
+        cause: ModelError,
+        backtrace: Backtrace,
+    },
+}
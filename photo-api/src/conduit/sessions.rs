@@ -0,0 +1,107 @@
+use crate::connection::Repo;
+use chrono::NaiveDateTime;
+use photo_core::models::{ModelError, RevokedToken, Session, User};
+use snafu::{Backtrace, ResultExt};
+
+/// Starts a session, returning it with its refresh token.
+pub async fn start(
+    repo: Repo,
+    user: &User,
+    user_agent: Option<String>,
+    ip: Option<String>,
+) -> Result<(Session, String)> {
+    let user = user.clone();
+    repo.run(move |conn| {
+        let (session, token) = Session::new(&user, user_agent, ip);
+        let session = session.insert(&conn).context(Model)?;
+
+        Ok((session, token))
+    })
+    .await
+}
+
+pub async fn rotate(
+    repo: Repo,
+    token: String,
+    user_agent: Option<String>,
+    ip: Option<String>,
+) -> Result<(Session, User, String)> {
+    repo.run(move |conn| {
+        let rotated = Session::rotate(&conn, &token, user_agent, ip).context(Model)?;
+
+        Ok(rotated)
+    })
+    .await
+}
+
+pub async fn find_by_user(repo: Repo, user: &User) -> Result<Vec<Session>> {
+    let user = user.clone();
+    repo.run(move |conn| {
+        let list = Session::find_by_user(&conn, &user).context(Model)?;
+
+        Ok(list)
+    })
+    .await
+}
+
+pub async fn is_active(repo: Repo, id: String) -> Result<bool> {
+    repo.run(move |conn| {
+        let active = Session::is_active(&conn, &id).context(Model)?;
+
+        Ok(active)
+    })
+    .await
+}
+
+pub async fn revoke(repo: Repo, user: &User, id: String) -> Result<()> {
+    let user = user.clone();
+    repo.run(move |conn| {
+        Session::revoke(&conn, &user, &id).context(Model)?;
+
+        Ok(())
+    })
+    .await
+}
+
+pub async fn revoke_all(repo: Repo, user: &User) -> Result<()> {
+    let user = user.clone();
+    repo.run(move |conn| {
+        Session::revoke_all(&conn, &user).context(Model)?;
+
+        Ok(())
+    })
+    .await
+}
+
+/// Denies an access token until it expires.
+pub async fn revoke_token(repo: Repo, jti: String, expires_at: NaiveDateTime) -> Result<()> {
+    repo.run(move |conn| {
+        RevokedToken::new(jti, expires_at)
+            .insert(&conn)
+            .context(Model)?;
+
+        Ok(())
+    })
+    .await
+}
+
+pub async fn is_token_revoked(repo: Repo, jti: String) -> Result<bool> {
+    repo.run(move |conn| {
+        let revoked = RevokedToken::is_revoked(&conn, &jti).context(Model)?;
+
+        Ok(revoked)
+    })
+    .await
+}
+
+pub type Result<T, E = SessionError> = std::result::Result<T, E>;
+
+#[derive(Debug, Snafu)]
+pub enum SessionError {
+    #[snafu(display("Problem with model: {}", cause))]
+    Model {
+        #[snafu(source)]
+        cause: ModelError,
+        backtrace: Backtrace,
+    },
+}
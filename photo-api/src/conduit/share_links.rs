@@ -0,0 +1,120 @@
+use crate::connection::Repo;
+use chrono::NaiveDateTime;
+use photo_core::models::{Album, ModelError, PhotoPage, PhotoPagination, Role, ShareLink, User};
+use snafu::{Backtrace, ResultExt};
+
+pub async fn create(
+    repo: Repo,
+    album: &Album,
+    expires_at: Option<NaiveDateTime>,
+    max_views: Option<i32>,
+) -> Result<ShareLink> {
+    let album = album.clone();
+    repo.run(move |conn| {
+        let link = ShareLink::new(&album, expires_at, max_views);
+        let link = link.insert(&conn).context(Model)?;
+
+        Ok(link)
+    })
+    .await
+}
+
+pub async fn find_by_album(repo: Repo, album: &Album) -> Result<Vec<ShareLink>> {
+    let album = album.clone();
+    repo.run(move |conn| {
+        let links = ShareLink::find_by_album(&conn, &album).context(Model)?;
+
+        Ok(links)
+    })
+    .await
+}
+
+pub async fn revoke(repo: Repo, user: &User, id: String) -> Result<ShareLink> {
+    let user = user.clone();
+    repo.run(move |conn| {
+        let link = ShareLink::find_for_member(&conn, &user, &id, Role::Editor).context(Model)?;
+        let link = link.revoke(&conn).context(Model)?;
+
+        Ok(link)
+    })
+    .await
+}
+
+pub async fn find_valid(repo: Repo, token: String) -> Result<(ShareLink, Album)> {
+    repo.run(move |conn| {
+        let found = ShareLink::find_valid(&conn, &token).context(Model)?;
+
+        Ok(found)
+    })
+    .await
+}
+
+/// Returns the requested page of the photos of the album behind the link. Only the first page
+/// counts as a visit, the following ones are part of the same view.
+pub async fn open(
+    repo: Repo,
+    link: &ShareLink,
+    album: &Album,
+    tag: Option<String>,
+    pagination: PhotoPagination,
+) -> Result<PhotoPage> {
+    let link = link.clone();
+    let album = album.clone();
+    repo.run(move |conn| {
+        let page = album
+            .photo_page(&conn, tag.as_deref(), &pagination)
+            .context(Model)?;
+        if pagination.cursor.is_none() {
+            link.add_view(&conn).context(Model)?;
+        }
+
+        Ok(page)
+    })
+    .await
+}
+
+pub async fn set_password(
+    repo: Repo,
+    user: &User,
+    id: String,
+    password: Option<String>,
+) -> Result<ShareLink> {
+    let user = user.clone();
+    repo.run(move |conn| {
+        let link = ShareLink::find_for_member(&conn, &user, &id, Role::Editor).context(Model)?;
+        let link = link
+            .set_password(&conn, password.as_deref())
+            .context(Model)?;
+
+        Ok(link)
+    })
+    .await
+}
+
+pub async fn set_allow_downloads(
+    repo: Repo,
+    user: &User,
+    id: String,
+    enabled: bool,
+) -> Result<ShareLink> {
+    let user = user.clone();
+    repo.run(move |conn| {
+        let link = ShareLink::find_for_member(&conn, &user, &id, Role::Editor).context(Model)?;
+        let link = link.set_allow_downloads(&conn, enabled).context(Model)?;
+
+        Ok(link)
+    })
+    .await
+}
+
+pub type Result<T, E = ShareLinkError> = std::result::Result<T, E>;
+
+#[derive(Debug, Snafu)]
+pub enum ShareLinkError {
+    #[snafu(display("Problem with model: {}", cause))]
+    Model {
+        #[snafu(source)]
+        cause: ModelError,
+        backtrace: Backtrace,
+    },
+}
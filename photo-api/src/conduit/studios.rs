@@ -0,0 +1,99 @@
+use crate::connection::Repo;
+use photo_core::helpers::uuid::Uuid;
+use photo_core::models::{Member, ModelError, Role, Studio, StudioMembership, User};
+use snafu::{Backtrace, ResultExt};
+
+pub async fn create(repo: Repo, user: &User, name: String) -> Result<Studio> {
+    let user = user.clone();
+    repo.run(move |conn| {
+        let studio = Studio::new(name).insert(&conn, &user).context(Model)?;
+
+        Ok(studio)
+    })
+    .await
+}
+
+pub async fn find_all(repo: Repo, user: &User) -> Result<Vec<StudioMembership>> {
+    let user = user.clone();
+    repo.run(move |conn| {
+        let list = Studio::find_by_member(&conn, &user).context(Model)?;
+
+        Ok(list)
+    })
+    .await
+}
+
+/// Finds a studio of the user, failing with `StudioNotFound` for other studios and with
+/// `Forbidden` when the user has a lower role than `role`.
+pub async fn find_for(repo: Repo, user: &User, id: String, role: Role) -> Result<Studio> {
+    let user = user.clone();
+    repo.run(move |conn| {
+        let studio = Studio::find_for_member(&conn, &user, &id, role).context(Model)?;
+
+        Ok(studio)
+    })
+    .await
+}
+
+pub async fn rename(repo: Repo, studio: &Studio, name: String) -> Result<Studio> {
+    let studio = studio.clone();
+    repo.run(move |conn| {
+        let studio = studio.rename(&conn, name).context(Model)?;
+
+        Ok(studio)
+    })
+    .await
+}
+
+pub async fn members(repo: Repo, studio: &Studio) -> Result<Vec<Member>> {
+    let studio = studio.clone();
+    repo.run(move |conn| {
+        let list = studio.members(&conn).context(Model)?;
+
+        Ok(list)
+    })
+    .await
+}
+
+/// Gives a role in the studio to the user with this email. Only existing users can be added.
+pub async fn set_member(
+    repo: Repo,
+    studio: &Studio,
+    email: String,
+    role: Role,
+) -> Result<Vec<Member>> {
+    let studio = studio.clone();
+    repo.run(move |conn| {
+        let member = User::find_by_email(&conn, &email).context(Model)?;
+        studio.set_member(&conn, &member, role).context(Model)?;
+        let list = studio.members(&conn).context(Model)?;
+
+        Ok(list)
+    })
+    .await
+}
+
+pub async fn remove_member(repo: Repo, studio: &Studio, user_id: String) -> Result<()> {
+    let studio = studio.clone();
+    repo.run(move |conn| {
+        let member_id = Uuid::parse_str(&user_id)
+            .map_err(|_| ModelError::MemberNotFound)
+            .context(Model)?;
+        studio.remove_member(&conn, &member_id).context(Model)?;
+
+        Ok(())
+    })
+    .await
+}
+
+pub type Result<T, E = StudioError> = std::result::Result<T, E>;
+
+#[derive(Debug, Snafu)]
+pub enum StudioError {
+    #[snafu(display("Problem with model: {}", cause))]
+    Model {
+        #[snafu(source)]
+        cause: ModelError,
+        backtrace: Backtrace,
+    },
+}
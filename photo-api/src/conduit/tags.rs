@@ -0,0 +1,73 @@
+use crate::connection::Repo;
+use photo_core::models::{ModelError, Photo, PhotoTags, Role, Tag, TagCount, User};
+use snafu::{Backtrace, ResultExt};
+
+pub async fn find_all(repo: Repo, user: &User) -> Result<Vec<TagCount>> {
+    let user = user.clone();
+    repo.run(move |conn| {
+        let tags = Tag::find_all(&conn, &user).context(Model)?;
+
+        Ok(tags)
+    })
+    .await
+}
+
+pub async fn find_by_photo(repo: Repo, user: &User, photo_id: String) -> Result<PhotoTags> {
+    let user = user.clone();
+    repo.run(move |conn| {
+        let photos =
+            Photo::find_all_for_member(&conn, &user, &[photo_id], Role::Viewer).context(Model)?;
+        let mut list = Tag::find_by_photos(&conn, &photos).context(Model)?;
+
+        Ok(list.remove(0))
+    })
+    .await
+}
+
+pub async fn add(
+    repo: Repo,
+    user: &User,
+    photo_ids: Vec<String>,
+    names: Vec<String>,
+) -> Result<Vec<PhotoTags>> {
+    let user = user.clone();
+    repo.run(move |conn| {
+        let photos =
+            Photo::find_all_for_member(&conn, &user, &photo_ids, Role::Editor).context(Model)?;
+        Tag::add_to_photos(&conn, &user, &names, &photos).context(Model)?;
+        let list = Tag::find_by_photos(&conn, &photos).context(Model)?;
+
+        Ok(list)
+    })
+    .await
+}
+
+pub async fn remove(
+    repo: Repo,
+    user: &User,
+    photo_ids: Vec<String>,
+    names: Vec<String>,
+) -> Result<Vec<PhotoTags>> {
+    let user = user.clone();
+    repo.run(move |conn| {
+        let photos =
+            Photo::find_all_for_member(&conn, &user, &photo_ids, Role::Editor).context(Model)?;
+        Tag::remove_from_photos(&conn, &names, &photos).context(Model)?;
+        let list = Tag::find_by_photos(&conn, &photos).context(Model)?;
+
+        Ok(list)
+    })
+    .await
+}
+
+pub type Result<T, E = TagError> = std::result::Result<T, E>;
+
+#[derive(Debug, Snafu)]
+pub enum TagError {
+    #[snafu(display("Problem with model: {}", cause))]
+    Model {
+        #[snafu(source)]
+        cause: ModelError,
+        backtrace: Backtrace,
+    },
+}
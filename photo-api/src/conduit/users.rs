@@ -1,32 +1,60 @@
 use crate::auth::Profile;
 use crate::connection::Repo;
-use crate::utils::get_allowed_emails;
-use photo_core::models::{Album, ModelError, User};
+use photo_core::models::{
+    transaction, Album, AuditAction, AuditContext, Invitation, ModelError, Studio, User,
+};
 use snafu::{Backtrace, ResultExt};
 
-pub async fn find_or_create<T: Profile>(repo: Repo, profile: T) -> Result<User> {
-    let new_user = profile.new_user();
+/// Finds the user of a profile, or creates it when the email is invited. A new user gets a personal
+/// studio with a default album, and its creation is recorded in the audit log.
+pub async fn find_or_create<T: Profile>(
+    repo: Repo,
+    audit: &AuditContext,
+    profile: T,
+) -> Result<User> {
+    let mut new_user = profile.new_user();
+    let audit = audit.clone();
 
     repo.run(
         move |conn| match User::find_by_email(&conn, &new_user.email).context(Model) {
             Ok(u) => Ok(u),
             Err(_) => {
-                let allowed = get_allowed_emails();
-                let index = allowed.iter().position(|x| x == &new_user.email);
-                if index.is_none() {
+                let invited = Invitation::is_invited(&conn, &new_user.email).context(Model)?;
+                if !invited {
                     return Err(UserError::UserNotAllowed);
                 }
 
-                debug!("New User, creating {}", &new_user.email);
-                let user = new_user.insert(&conn).unwrap();
+                let user = transaction(&conn, || {
+                    debug!("New User, creating {}", &new_user.email);
+                    new_user.is_admin = !User::has_admin(&conn)?;
+                    let user = new_user.insert(&conn)?;
+                    Invitation::accept(&conn, &user.email)?;
 
-                let default_album = Album::new(
-                    &user,
-                    String::from("weddings"),
-                    Some(String::from("Wedding pictures")),
-                );
-                debug!("Creating default album");
-                default_album.insert(&conn).unwrap();
+                    debug!("Creating personal studio");
+                    let studio = Studio::new(user.email.clone()).insert_personal(&conn, &user)?;
+                    let user = User {
+                        personal_studio_id: Some(studio.id),
+                        ..user
+                    };
+
+                    let default_album = Album::new(
+                        &user,
+                        &studio,
+                        String::from("weddings"),
+                        Some(String::from("Wedding pictures")),
+                    );
+                    debug!("Creating default album");
+                    default_album.insert(&conn)?;
+
+                    let audit = AuditContext {
+                        actor_id: Some(user.id),
+                        ..audit
+                    };
+                    audit.record(&conn, AuditAction::Create, None, Some(&user))?;
+
+                    Ok(user)
+                })
+                .context(Model)?;
 
                 Ok(user)
             }
@@ -1,48 +1,114 @@
-use super::utils::{extract_json, HandlerUtilsError};
-use crate::auth::AuthUser;
+use super::utils::{
+    audit_context, client_ip, create_archive_response, create_tagged_response, double_option,
+    extract_json, has_album_access, photo_pagination, precondition, HandlerUtilsError,
+};
+use crate::archive::{album_archive, DownloadSize};
+use crate::auth::throttle::{ALBUM_UNLOCK_THROTTLE, UNLOCK_THROTTLE};
+use crate::auth::{encode_album_token, ALBUM_TOKEN_EXPIRY};
 use crate::conduit::{albums, users};
 use crate::connection::Repo;
+use crate::middlewares::current_user::CurrentUser;
+use crate::utils::{get_url, location_with_query};
 use gotham::handler::HandlerResult;
-use gotham::helpers::http::response::{create_empty_response, create_response};
+use gotham::helpers::http::response::{
+    create_empty_response, create_permanent_redirect, create_response,
+};
 use gotham::state::{FromState, State};
-use gotham_middleware_jwt::AuthorizationToken;
-use hyper::StatusCode;
-use photo_core::models::{Album, AlbumWithPhotos, Photo};
+use hyper::{StatusCode, Uri};
+use photo_core::models::{
+    Album, AlbumChanges, AlbumNode, AlbumSummary, AlbumWithPhotos, ModelError, PhotoPagination,
+    PhotoSort, Role,
+};
 use serde::{Deserialize, Serialize};
 use snafu::{Backtrace, ResultExt};
 
 #[derive(Deserialize, Serialize, StateData, StaticResponseExtender)]
 pub struct WithIdExtractor {
     id: String,
+    /// Only return the photos labeled with this tag.
+    tag: Option<String>,
+    sort: Option<PhotoSort>,
+    limit: Option<usize>,
+    cursor: Option<String>,
+}
+
+impl WithIdExtractor {
+    fn pagination(&self) -> PhotoPagination {
+        photo_pagination(
+            self.sort.unwrap_or_default(),
+            self.limit,
+            self.cursor.clone(),
+        )
+    }
 }
 
 #[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct PublicAlbumResponse {
     album: AlbumWithPhotos,
+    /// Number of photos across all the pages.
+    total: usize,
+    next_cursor: Option<String>,
+}
+
+impl PublicAlbumResponse {
+    /// Loads the requested page of the photos of the album.
+    async fn load(
+        repo: Repo,
+        album: Album,
+        tag: Option<String>,
+        pagination: PhotoPagination,
+    ) -> Result<Self, AlbumHandlersError> {
+        let page = albums::photos(repo, &album, tag, pagination)
+            .await
+            .context(AlbumIssue)?;
+
+        Ok(PublicAlbumResponse {
+            album: (album, page.list),
+            total: page.total,
+            next_cursor: page.next_cursor,
+        })
+    }
 }
 
 pub async fn get_main_public(mut state: State) -> HandlerResult {
     let repo = Repo::borrow_from(&state).clone();
     let query_param = WithIdExtractor::take_from(&mut state);
+    let pagination = query_param.pagination();
 
     let user = match users::find_by_id(repo.clone(), query_param.id)
         .await
         .context(UserIssue)
     {
         Ok(u) => u,
+        Err(e) if is_unknown_user(&e) => {
+            let res = create_empty_response(&state, StatusCode::NOT_FOUND);
+            return Ok((state, res));
+        }
         Err(e) => return Err((state, e.into())),
     };
 
-    let response = match albums::find_main_public(repo, &user)
+    let album = match albums::find_main_public(repo.clone(), &user)
         .await
         .context(AlbumIssue)
     {
-        Ok(album) => {
-            let response = PublicAlbumResponse { album };
+        Ok(album) => album,
+        Err(e) => return Err((state, e.into())),
+    };
+
+    if !has_album_access(&state, &album) {
+        let res = create_empty_response(&state, StatusCode::UNAUTHORIZED);
+
+        return Ok((state, res));
+    }
+
+    let response = match PublicAlbumResponse::load(repo, album, query_param.tag, pagination).await {
+        Ok(response) => {
             let body = serde_json::to_string(&response).expect("Failed to serialize album");
 
             create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body)
         }
+        Err(e) if is_invalid_cursor(&e) => create_empty_response(&state, StatusCode::BAD_REQUEST),
         Err(e) => return Err((state, e.into())),
     };
 
@@ -50,56 +116,281 @@ pub async fn get_main_public(mut state: State) -> HandlerResult {
 }
 
 #[derive(Deserialize, Serialize, StateData, StaticResponseExtender)]
-pub struct WithNameExtractor {
-    name: String,
+pub struct WithSlugExtractor {
+    slug: String,
+}
+
+/// Returns a public album by its slug. Old slugs of renamed albums redirect to the current one.
+pub async fn get_album_by_slug(mut state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let query_param = WithIdExtractor::take_from(&mut state);
+    let path_param = WithSlugExtractor::take_from(&mut state);
+    let pagination = query_param.pagination();
+
+    let user = match users::find_by_id(repo.clone(), query_param.id)
+        .await
+        .context(UserIssue)
+    {
+        Ok(u) => u,
+        Err(e) if is_unknown_user(&e) => {
+            let res = create_empty_response(&state, StatusCode::NOT_FOUND);
+            return Ok((state, res));
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    let album = match albums::find_by_slug(repo.clone(), &user, path_param.slug.clone())
+        .await
+        .context(AlbumIssue)
+    {
+        Ok(album) => album,
+        Err(e) if is_not_found(&e) => {
+            let res = create_empty_response(&state, StatusCode::NOT_FOUND);
+            return Ok((state, res));
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    if album.slug != path_param.slug {
+        let path = format!("/api/public/album/{}", album.slug);
+        let location = location_with_query(&get_url(), Uri::borrow_from(&state), &path);
+        let res = create_permanent_redirect(&state, location);
+
+        return Ok((state, res));
+    }
+
+    if !has_album_access(&state, &album) {
+        let res = create_empty_response(&state, StatusCode::UNAUTHORIZED);
+
+        return Ok((state, res));
+    }
+
+    let response = match PublicAlbumResponse::load(repo, album, query_param.tag, pagination).await {
+        Ok(response) => {
+            let body = serde_json::to_string(&response).expect("Failed to serialize album");
+
+            create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body)
+        }
+        Err(e) if is_invalid_cursor(&e) => create_empty_response(&state, StatusCode::BAD_REQUEST),
+        Err(e) => return Err((state, e.into())),
+    };
+
+    Ok((state, response))
+}
+
+#[derive(Serialize)]
+pub struct PublicCollectionsResponse {
+    list: Vec<AlbumSummary>,
+}
+
+/// Returns the root albums of a user, to be used as the entry point of the public collections.
+pub async fn get_public_collections(mut state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let query_param = WithIdExtractor::take_from(&mut state);
+
+    let user = match users::find_by_id(repo.clone(), query_param.id)
+        .await
+        .context(UserIssue)
+    {
+        Ok(u) => u,
+        Err(e) if is_unknown_user(&e) => {
+            let res = create_empty_response(&state, StatusCode::NOT_FOUND);
+            return Ok((state, res));
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    let response = match albums::find_children(repo, &user, None)
+        .await
+        .context(AlbumIssue)
+    {
+        Ok(list) => {
+            let response = PublicCollectionsResponse {
+                list: hide_protected_covers(list),
+            };
+            let body = serde_json::to_string(&response).expect("Failed to serialize albums");
+
+            create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body)
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    Ok((state, response))
 }
 
-pub async fn get_album_by_name(mut state: State) -> HandlerResult {
+#[derive(Serialize)]
+pub struct PublicCollectionResponse {
+    #[serde(flatten)]
+    album: PublicAlbumResponse,
+    children: Vec<AlbumSummary>,
+}
+
+/// Returns a public album by its slug along with the albums nested inside of it and their covers.
+pub async fn get_public_collection_by_slug(mut state: State) -> HandlerResult {
     let repo = Repo::borrow_from(&state).clone();
     let query_param = WithIdExtractor::take_from(&mut state);
-    let path_param = WithNameExtractor::take_from(&mut state);
+    let path_param = WithSlugExtractor::take_from(&mut state);
+    let pagination = query_param.pagination();
 
     let user = match users::find_by_id(repo.clone(), query_param.id)
         .await
         .context(UserIssue)
     {
         Ok(u) => u,
+        Err(e) if is_unknown_user(&e) => {
+            let res = create_empty_response(&state, StatusCode::NOT_FOUND);
+            return Ok((state, res));
+        }
         Err(e) => return Err((state, e.into())),
     };
 
-    let response = match albums::find_by_name(repo, &user, path_param.name)
+    let album = match albums::find_by_slug(repo.clone(), &user, path_param.slug.clone())
         .await
         .context(AlbumIssue)
     {
+        Ok(album) => album,
+        Err(e) if is_not_found(&e) => {
+            let res = create_empty_response(&state, StatusCode::NOT_FOUND);
+            return Ok((state, res));
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    if album.slug != path_param.slug {
+        let path = format!("/api/public/collection/{}", album.slug);
+        let location = location_with_query(&get_url(), Uri::borrow_from(&state), &path);
+        let res = create_permanent_redirect(&state, location);
+
+        return Ok((state, res));
+    }
+
+    if !has_album_access(&state, &album) {
+        let res = create_empty_response(&state, StatusCode::UNAUTHORIZED);
+
+        return Ok((state, res));
+    }
+
+    let children = match albums::find_children(repo.clone(), &user, Some(&album))
+        .await
+        .context(AlbumIssue)
+    {
+        Ok(children) => hide_protected_covers(children),
+        Err(e) => return Err((state, e.into())),
+    };
+
+    let response = match PublicAlbumResponse::load(repo, album, None, pagination).await {
         Ok(album) => {
-            let response = PublicAlbumResponse { album };
+            let response = PublicCollectionResponse { album, children };
             let body = serde_json::to_string(&response).expect("Failed to serialize album");
 
             create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body)
         }
+        Err(e) if is_invalid_cursor(&e) => create_empty_response(&state, StatusCode::BAD_REQUEST),
         Err(e) => return Err((state, e.into())),
     };
 
     Ok((state, response))
 }
 
+/// Covers of password protected albums are not shown in public listings.
+fn hide_protected_covers(list: Vec<AlbumSummary>) -> Vec<AlbumSummary> {
+    list.into_iter()
+        .map(|mut summary| {
+            if summary.album.is_protected() {
+                summary.cover = None;
+            }
+
+            summary
+        })
+        .collect()
+}
+
+#[derive(Deserialize)]
+pub struct UnlockAlbumRequest {
+    pub password: String,
+}
+
 #[derive(Serialize)]
-pub struct AllAlbumsResponse {
-    list: Vec<AlbumWithPhotos>,
+#[serde(rename_all = "camelCase")]
+pub struct UnlockAlbumResponse {
+    token: String,
+    expires_in: u64,
 }
 
-pub async fn all_albums(state: State) -> HandlerResult {
+/// Verifies the password of a protected album and returns a short-lived token that grants access
+/// to it. Failed attempts are throttled per album and IP address, and per album overall (see
+/// `ALBUM_UNLOCK_THROTTLE` for the trade-off).
+pub async fn unlock_album(mut state: State) -> HandlerResult {
     let repo = Repo::borrow_from(&state).clone();
-    let token = AuthorizationToken::<AuthUser>::borrow_from(&state);
-    let email = token.0.claims.email();
+    let query_param = WithIdExtractor::take_from(&mut state);
+    let path_param = WithSlugExtractor::take_from(&mut state);
+    let req_data: UnlockAlbumRequest =
+        match extract_json(&mut state).await.context(HandlerUtilsIssue) {
+            Ok(data) => data,
+            Err(e) => return Err((state, e.into())),
+        };
 
-    let user = match users::find_by_email(repo.clone(), email)
+    let user = match users::find_by_id(repo.clone(), query_param.id)
         .await
         .context(UserIssue)
     {
         Ok(u) => u,
+        Err(e) if is_unknown_user(&e) => {
+            let res = create_empty_response(&state, StatusCode::NOT_FOUND);
+            return Ok((state, res));
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    let album = match albums::find_by_slug(repo, &user, path_param.slug)
+        .await
+        .context(AlbumIssue)
+    {
+        Ok(album) => album,
+        Err(e) if is_not_found(&e) => {
+            let res = create_empty_response(&state, StatusCode::NOT_FOUND);
+            return Ok((state, res));
+        }
         Err(e) => return Err((state, e.into())),
     };
+
+    let album_key = format!("album:{}", album.id);
+    let throttle_key = format!("{}:{}", album_key, client_ip(&state));
+    if UNLOCK_THROTTLE.is_blocked(&throttle_key) || ALBUM_UNLOCK_THROTTLE.is_blocked(&album_key) {
+        let res = create_empty_response(&state, StatusCode::TOO_MANY_REQUESTS);
+
+        return Ok((state, res));
+    }
+
+    if !album.verify_password(&req_data.password) {
+        UNLOCK_THROTTLE.fail(&throttle_key);
+        ALBUM_UNLOCK_THROTTLE.fail(&album_key);
+        let res = create_empty_response(&state, StatusCode::UNAUTHORIZED);
+
+        return Ok((state, res));
+    }
+
+    UNLOCK_THROTTLE.reset(&throttle_key);
+
+    let response = UnlockAlbumResponse {
+        token: encode_album_token(&album, ALBUM_TOKEN_EXPIRY),
+        expires_in: ALBUM_TOKEN_EXPIRY,
+    };
+    let body = serde_json::to_string(&response).expect("Failed to serialize token");
+    let res = create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body);
+
+    Ok((state, res))
+}
+
+#[derive(Serialize)]
+pub struct AllAlbumsResponse {
+    list: Vec<AlbumSummary>,
+}
+
+pub async fn all_albums(state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+
+    let user = CurrentUser::borrow_from(&state).0.clone();
     let response = match albums::find_all(repo, &user).await.context(AlbumIssue) {
         Ok(albums) => {
             let response = AllAlbumsResponse { list: albums };
@@ -115,25 +406,75 @@ pub async fn all_albums(state: State) -> HandlerResult {
 }
 
 #[derive(Serialize)]
-pub struct AlbumPhotosResponse {
-    list: Vec<Photo>,
+pub struct AlbumTreeResponse {
+    list: Vec<AlbumNode>,
+}
+
+pub async fn album_tree(state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+
+    let user = CurrentUser::borrow_from(&state).0.clone();
+
+    let response = match albums::find_tree(repo, &user).await.context(AlbumIssue) {
+        Ok(list) => {
+            let response = AlbumTreeResponse { list };
+            let body = serde_json::to_string(&response).expect("Failed to serialize albums");
+
+            create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body)
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    Ok((state, response))
+}
+
+#[derive(Deserialize, StateData, StaticResponseExtender)]
+pub struct PhotosQueryExtractor {
+    tag: Option<String>,
+    sort: Option<PhotoSort>,
+    limit: Option<usize>,
+    cursor: Option<String>,
 }
 
 pub async fn album_photos(state: State) -> HandlerResult {
     let repo = Repo::borrow_from(&state).clone();
     let path_data = AlbumPathExtractor::borrow_from(&state);
+    let query_data = PhotosQueryExtractor::borrow_from(&state);
+    let pagination = photo_pagination(
+        query_data.sort.unwrap_or_default(),
+        query_data.limit,
+        query_data.cursor.clone(),
+    );
+
+    let user = CurrentUser::borrow_from(&state).0.clone();
 
-    let response = match albums::photos(repo.clone(), path_data.id.clone())
+    let album = match albums::find_for(repo.clone(), &user, path_data.id.clone(), Role::Viewer)
         .await
         .context(AlbumIssue)
     {
-        Ok(photos) => {
-            let response = AlbumPhotosResponse { list: photos };
-            let body = serde_json::to_string(&response).expect("Failed to serialize response");
+        Ok(a) => a,
+        Err(e) if is_not_found(&e) => {
+            let res = create_empty_response(&state, StatusCode::NOT_FOUND);
+            return Ok((state, res));
+        }
+        Err(e) if is_forbidden(&e) => {
+            let res = create_empty_response(&state, StatusCode::FORBIDDEN);
+            return Ok((state, res));
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    let response = match albums::photos(repo, &album, query_data.tag.clone(), pagination)
+        .await
+        .context(AlbumIssue)
+    {
+        Ok(page) => {
+            let body = serde_json::to_string(&page).expect("Failed to serialize response");
             let res = create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body);
 
             res
         }
+        Err(e) if is_invalid_cursor(&e) => create_empty_response(&state, StatusCode::BAD_REQUEST),
         Err(e) => return Err((state, e.into())),
     };
 
@@ -146,9 +487,12 @@ pub struct AlbumPathExtractor {
 }
 
 #[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct NewAlbumRequest {
     pub name: String,
     pub description: Option<String>,
+    /// Studio to create the album in, the personal studio of the user when missing.
+    pub studio_id: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -164,20 +508,22 @@ pub async fn new_album(mut state: State) -> HandlerResult {
         Err(e) => return Err((state, e.into())),
     };
 
-    let token = AuthorizationToken::<AuthUser>::borrow_from(&state);
-    let email = token.0.claims.email();
-
-    let user = match users::find_by_email(repo.clone(), email)
-        .await
-        .context(UserIssue)
-    {
-        Ok(u) => u,
-        Err(e) => return Err((state, e.into())),
-    };
+    let user = CurrentUser::borrow_from(&state).0.clone();
 
+    let audit = audit_context(&state, Some(&user));
     let description = req_data.description.clone();
 
-    let response = match albums::create(repo, &user, req_data.name, description).await {
+    let response = match albums::create(
+        repo,
+        &audit,
+        &user,
+        req_data.studio_id,
+        req_data.name,
+        description,
+    )
+    .await
+    .context(AlbumIssue)
+    {
         Ok(album) => {
             let response = AlbumResponse { album };
             let body = serde_json::to_string(&response).expect("Failed to serialize album");
@@ -185,6 +531,15 @@ pub async fn new_album(mut state: State) -> HandlerResult {
 
             res
         }
+        Err(AlbumHandlersError::AlbumIssue {
+            cause:
+                albums::AlbumError::Model {
+                    cause: ModelError::StudioNotFound,
+                    ..
+                },
+            ..
+        }) => create_empty_response(&state, StatusCode::NOT_FOUND),
+        Err(e) if is_forbidden(&e) => create_empty_response(&state, StatusCode::FORBIDDEN),
         Err(e) => return Err((state, e.into())),
     };
 
@@ -206,15 +561,28 @@ pub async fn update_album(mut state: State) -> HandlerResult {
         };
     let path_data = AlbumPathExtractor::borrow_from(&state);
 
-    let album = match albums::find_by_id(repo.clone(), path_data.id.clone())
+    let user = CurrentUser::borrow_from(&state).0.clone();
+
+    let album = match albums::find_for(repo.clone(), &user, path_data.id.clone(), Role::Editor)
         .await
         .context(AlbumIssue)
     {
         Ok(a) => a,
+        Err(e) if is_not_found(&e) => {
+            let res = create_empty_response(&state, StatusCode::NOT_FOUND);
+            return Ok((state, res));
+        }
+        Err(e) if is_forbidden(&e) => {
+            let res = create_empty_response(&state, StatusCode::FORBIDDEN);
+            return Ok((state, res));
+        }
         Err(e) => return Err((state, e.into())),
     };
 
-    let response = match albums::update(repo, &album, req_data.name, req_data.description).await {
+    let audit = audit_context(&state, Some(&user));
+    let updated = albums::update(repo, &audit, &album, req_data.name, req_data.description).await;
+
+    let response = match updated {
         Ok(album) => {
             let response = AlbumResponse { album };
             let body = serde_json::to_string(&response).expect("Failed to serialize response");
@@ -228,19 +596,269 @@ pub async fn update_album(mut state: State) -> HandlerResult {
     Ok((state, response))
 }
 
+/// Fields left out are not changed, a `null` description removes it.
+#[derive(Deserialize)]
+pub struct PatchAlbumRequest {
+    pub name: Option<String>,
+    #[serde(default, deserialize_with = "double_option")]
+    pub description: Option<Option<String>>,
+}
+
+/// The album along with its entity tag, to be sent back in `If-Match` when changing it.
+pub async fn get_album(state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let path_data = AlbumPathExtractor::borrow_from(&state);
+
+    let user = CurrentUser::borrow_from(&state).0.clone();
+
+    let album = match albums::find_for(repo, &user, path_data.id.clone(), Role::Viewer)
+        .await
+        .context(AlbumIssue)
+    {
+        Ok(a) => a,
+        Err(e) if is_not_found(&e) => {
+            let res = create_empty_response(&state, StatusCode::NOT_FOUND);
+            return Ok((state, res));
+        }
+        Err(e) if is_forbidden(&e) => {
+            let res = create_empty_response(&state, StatusCode::FORBIDDEN);
+            return Ok((state, res));
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    let etag = album.etag();
+    let response = AlbumResponse { album };
+    let body = serde_json::to_string(&response).expect("Failed to serialize response");
+    let res = create_tagged_response(&state, &etag, body);
+
+    Ok((state, res))
+}
+
+/// Updates only the fields sent. With an `If-Match` or `If-Unmodified-Since` header, the album is
+/// only changed if nobody else modified it in between.
+pub async fn patch_album(mut state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let req_data: PatchAlbumRequest =
+        match extract_json(&mut state).await.context(HandlerUtilsIssue) {
+            Ok(data) => data,
+            Err(e) => return Err((state, e.into())),
+        };
+    let path_data = AlbumPathExtractor::borrow_from(&state);
+
+    if req_data
+        .name
+        .as_ref()
+        .map_or(false, |n| n.trim().is_empty())
+    {
+        let res = create_empty_response(&state, StatusCode::BAD_REQUEST);
+        return Ok((state, res));
+    }
+
+    let user = CurrentUser::borrow_from(&state).0.clone();
+
+    let album = match albums::find_for(repo.clone(), &user, path_data.id.clone(), Role::Editor)
+        .await
+        .context(AlbumIssue)
+    {
+        Ok(a) => a,
+        Err(e) if is_not_found(&e) => {
+            let res = create_empty_response(&state, StatusCode::NOT_FOUND);
+            return Ok((state, res));
+        }
+        Err(e) if is_forbidden(&e) => {
+            let res = create_empty_response(&state, StatusCode::FORBIDDEN);
+            return Ok((state, res));
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    let changes = AlbumChanges {
+        name: req_data.name,
+        description: req_data.description,
+    };
+
+    let audit = audit_context(&state, Some(&user));
+
+    let response = match albums::patch(repo, &audit, &album, changes, precondition(&state))
+        .await
+        .context(AlbumIssue)
+    {
+        Ok(album) => {
+            let etag = album.etag();
+            let response = AlbumResponse { album };
+            let body = serde_json::to_string(&response).expect("Failed to serialize response");
+
+            create_tagged_response(&state, &etag, body)
+        }
+        Err(AlbumHandlersError::AlbumIssue {
+            cause:
+                albums::AlbumError::Model {
+                    cause: ModelError::PreconditionFailed,
+                    ..
+                },
+            ..
+        }) => create_empty_response(&state, StatusCode::PRECONDITION_FAILED),
+        Err(e) => return Err((state, e.into())),
+    };
+
+    Ok((state, response))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MoveAlbumRequest {
+    pub parent_id: Option<String>,
+}
+
+pub async fn move_album(mut state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let req_data: MoveAlbumRequest = match extract_json(&mut state).await.context(HandlerUtilsIssue)
+    {
+        Ok(data) => data,
+        Err(e) => return Err((state, e.into())),
+    };
+    let path_data = AlbumPathExtractor::borrow_from(&state);
+
+    let user = CurrentUser::borrow_from(&state).0.clone();
+
+    let album = match albums::find_for(repo.clone(), &user, path_data.id.clone(), Role::Editor)
+        .await
+        .context(AlbumIssue)
+    {
+        Ok(a) => a,
+        Err(e) if is_not_found(&e) => {
+            let res = create_empty_response(&state, StatusCode::NOT_FOUND);
+            return Ok((state, res));
+        }
+        Err(e) if is_forbidden(&e) => {
+            let res = create_empty_response(&state, StatusCode::FORBIDDEN);
+            return Ok((state, res));
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    let audit = audit_context(&state, Some(&user));
+
+    let response = match albums::move_to(repo, &audit, &user, &album, req_data.parent_id)
+        .await
+        .context(AlbumIssue)
+    {
+        Ok(album) => {
+            let response = AlbumResponse { album };
+            let body = serde_json::to_string(&response).expect("Failed to serialize response");
+
+            create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body)
+        }
+        Err(AlbumHandlersError::AlbumIssue {
+            cause:
+                albums::AlbumError::Model {
+                    cause: ModelError::AlbumCycle,
+                    ..
+                },
+            ..
+        })
+        | Err(AlbumHandlersError::AlbumIssue {
+            cause:
+                albums::AlbumError::Model {
+                    cause: ModelError::InvalidParent,
+                    ..
+                },
+            ..
+        }) => create_empty_response(&state, StatusCode::BAD_REQUEST),
+        Err(e) if is_forbidden(&e) => create_empty_response(&state, StatusCode::FORBIDDEN),
+        Err(e) if is_not_found(&e) => create_empty_response(&state, StatusCode::BAD_REQUEST),
+        Err(e) => return Err((state, e.into())),
+    };
+
+    Ok((state, response))
+}
+
+#[derive(Deserialize)]
+pub struct AlbumPasswordRequest {
+    /// New password, or `null` to make the album public again.
+    pub password: Option<String>,
+}
+
+pub async fn set_album_password(mut state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let req_data: AlbumPasswordRequest =
+        match extract_json(&mut state).await.context(HandlerUtilsIssue) {
+            Ok(data) => data,
+            Err(e) => return Err((state, e.into())),
+        };
+
+    if req_data
+        .password
+        .as_deref()
+        .map_or(false, |p| p.trim().is_empty())
+    {
+        let res = create_empty_response(&state, StatusCode::BAD_REQUEST);
+        return Ok((state, res));
+    }
+    let path_data = AlbumPathExtractor::borrow_from(&state);
+
+    let user = CurrentUser::borrow_from(&state).0.clone();
+
+    let album = match albums::find_for(repo.clone(), &user, path_data.id.clone(), Role::Editor)
+        .await
+        .context(AlbumIssue)
+    {
+        Ok(a) => a,
+        Err(e) if is_not_found(&e) => {
+            let res = create_empty_response(&state, StatusCode::NOT_FOUND);
+            return Ok((state, res));
+        }
+        Err(e) if is_forbidden(&e) => {
+            let res = create_empty_response(&state, StatusCode::FORBIDDEN);
+            return Ok((state, res));
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    let audit = audit_context(&state, Some(&user));
+
+    let response = match albums::set_password(repo, &audit, &album, req_data.password)
+        .await
+        .context(AlbumIssue)
+    {
+        Ok(album) => {
+            let response = AlbumResponse { album };
+            let body = serde_json::to_string(&response).expect("Failed to serialize response");
+
+            create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body)
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    Ok((state, response))
+}
+
 pub async fn delete_album(state: State) -> HandlerResult {
     let repo = Repo::borrow_from(&state).clone();
     let path_data = AlbumPathExtractor::borrow_from(&state);
 
-    let album = match albums::find_by_id(repo.clone(), path_data.id.clone())
+    let user = CurrentUser::borrow_from(&state).0.clone();
+
+    let album = match albums::find_for(repo.clone(), &user, path_data.id.clone(), Role::Owner)
         .await
         .context(AlbumIssue)
     {
         Ok(a) => a,
+        Err(e) if is_not_found(&e) => {
+            let res = create_empty_response(&state, StatusCode::NOT_FOUND);
+            return Ok((state, res));
+        }
+        Err(e) if is_forbidden(&e) => {
+            let res = create_empty_response(&state, StatusCode::FORBIDDEN);
+            return Ok((state, res));
+        }
         Err(e) => return Err((state, e.into())),
     };
 
-    let response = match albums::delete(repo, &album).await {
+    let audit = audit_context(&state, Some(&user));
+
+    let response = match albums::delete(repo, &audit, &album).await {
         Ok(_) => {
             let res = create_empty_response(&state, StatusCode::OK);
 
@@ -252,6 +870,105 @@ pub async fn delete_album(state: State) -> HandlerResult {
     Ok((state, response))
 }
 
+#[derive(Deserialize, StateData, StaticResponseExtender)]
+pub struct DownloadQueryExtractor {
+    size: Option<DownloadSize>,
+}
+
+/// Streams the album photos as a ZIP archive, either the originals or resized with `?size=`.
+pub async fn download_album(state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let path_data = AlbumPathExtractor::borrow_from(&state);
+    let query_data = DownloadQueryExtractor::borrow_from(&state);
+    let size = query_data.size.unwrap_or_default();
+
+    let user = CurrentUser::borrow_from(&state).0.clone();
+
+    let album = match albums::find_for(repo.clone(), &user, path_data.id.clone(), Role::Viewer)
+        .await
+        .context(AlbumIssue)
+    {
+        Ok(a) => a,
+        Err(e) if is_not_found(&e) => {
+            let res = create_empty_response(&state, StatusCode::NOT_FOUND);
+            return Ok((state, res));
+        }
+        Err(e) if is_forbidden(&e) => {
+            let res = create_empty_response(&state, StatusCode::FORBIDDEN);
+            return Ok((state, res));
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    let response = match albums::photos(repo, &album, None, PhotoPagination::default())
+        .await
+        .context(AlbumIssue)
+    {
+        Ok(page) => create_archive_response(&state, &album.slug, album_archive(page.list, size)),
+        Err(e) => return Err((state, e.into())),
+    };
+
+    Ok((state, response))
+}
+
+/// Whether the album does not exist or belongs to a studio the user is not a member of.
+fn is_not_found(e: &AlbumHandlersError) -> bool {
+    match e {
+        AlbumHandlersError::AlbumIssue {
+            cause:
+                albums::AlbumError::Model {
+                    cause: ModelError::AlbumNotFound,
+                    ..
+                },
+            ..
+        } => true,
+        _ => false,
+    }
+}
+
+/// Whether the album belongs to a studio of the user, but the role of the user is too low.
+fn is_forbidden(e: &AlbumHandlersError) -> bool {
+    match e {
+        AlbumHandlersError::AlbumIssue {
+            cause:
+                albums::AlbumError::Model {
+                    cause: ModelError::Forbidden,
+                    ..
+                },
+            ..
+        } => true,
+        _ => false,
+    }
+}
+
+fn is_unknown_user(e: &AlbumHandlersError) -> bool {
+    match e {
+        AlbumHandlersError::UserIssue {
+            cause:
+                users::UserError::Model {
+                    cause: ModelError::UserNotFound,
+                    ..
+                },
+            ..
+        } => true,
+        _ => false,
+    }
+}
+
+fn is_invalid_cursor(e: &AlbumHandlersError) -> bool {
+    match e {
+        AlbumHandlersError::AlbumIssue {
+            cause:
+                albums::AlbumError::Model {
+                    cause: ModelError::InvalidCursor,
+                    ..
+                },
+            ..
+        } => true,
+        _ => false,
+    }
+}
+
 #[derive(Debug, Snafu)]
 pub enum AlbumHandlersError {
     #[snafu(display("Could not get request: {}", cause))]
@@ -0,0 +1,128 @@
+use super::utils::{extract_json, HandlerUtilsError};
+use crate::conduit::api_keys;
+use crate::connection::Repo;
+use crate::middlewares::current_user::CurrentUser;
+use gotham::handler::HandlerResult;
+use gotham::helpers::http::response::{create_empty_response, create_response};
+use gotham::state::{FromState, State};
+use hyper::StatusCode;
+use photo_core::models::{ApiKey, ApiKeyScope, ModelError};
+use serde::{Deserialize, Serialize};
+use snafu::{Backtrace, ResultExt};
+
+#[derive(Deserialize, StateData, StaticResponseExtender)]
+pub struct ApiKeyPathExtractor {
+    id: String,
+}
+
+#[derive(Deserialize)]
+pub struct NewApiKeyRequest {
+    pub name: String,
+    pub scope: ApiKeyScope,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewApiKeyResponse {
+    api_key: ApiKey,
+    /// The key itself, only returned when it is created.
+    key: String,
+}
+
+#[derive(Serialize)]
+pub struct ApiKeysResponse {
+    list: Vec<ApiKey>,
+}
+
+/// Creates an API key to use in place of an access token, in the `Authorization: Bearer` header.
+/// The key is only returned once.
+pub async fn new_api_key(mut state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let req_data: NewApiKeyRequest = match extract_json(&mut state).await.context(HandlerUtilsIssue)
+    {
+        Ok(data) => data,
+        Err(e) => return Err((state, e.into())),
+    };
+    let user = CurrentUser::borrow_from(&state).0.clone();
+
+    let name = req_data.name.trim().to_string();
+    if name.is_empty() {
+        let res = create_empty_response(&state, StatusCode::BAD_REQUEST);
+        return Ok((state, res));
+    }
+
+    let response = match api_keys::create(repo, &user, name, req_data.scope)
+        .await
+        .context(ApiKeyIssue)
+    {
+        Ok((api_key, key)) => {
+            let response = NewApiKeyResponse { api_key, key };
+            let body = serde_json::to_string(&response).expect("Failed to serialize API key");
+
+            create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body)
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    Ok((state, response))
+}
+
+/// Lists the API keys of the user, with when they were last used.
+pub async fn all_api_keys(state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let user = CurrentUser::borrow_from(&state).0.clone();
+
+    let response = match api_keys::find_all(repo, &user).await.context(ApiKeyIssue) {
+        Ok(list) => {
+            let response = ApiKeysResponse { list };
+            let body = serde_json::to_string(&response).expect("Failed to serialize API keys");
+
+            create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body)
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    Ok((state, response))
+}
+
+/// Revokes an API key, requests made with it are rejected from now on.
+pub async fn revoke_api_key(state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let path_data = ApiKeyPathExtractor::borrow_from(&state);
+    let user = CurrentUser::borrow_from(&state).0.clone();
+
+    let response = match api_keys::revoke(repo, &user, path_data.id.clone())
+        .await
+        .context(ApiKeyIssue)
+    {
+        Ok(_) => create_empty_response(&state, StatusCode::OK),
+        Err(ApiKeyHandlersError::ApiKeyIssue {
+            cause:
+                api_keys::ApiKeyError::Model {
+                    cause: ModelError::ApiKeyNotFound,
+                    ..
+                },
+            ..
+        }) => create_empty_response(&state, StatusCode::NOT_FOUND),
+        Err(e) => return Err((state, e.into())),
+    };
+
+    Ok((state, response))
+}
+
+#[derive(Debug, Snafu)]
+pub enum ApiKeyHandlersError {
+    #[snafu(display("Could not get request: {}", cause))]
+    HandlerUtilsIssue {
+        #[snafu(source)]
+        cause: HandlerUtilsError,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Could not get API key: {}", cause))]
+    ApiKeyIssue {
+        #[snafu(source)]
+        cause: api_keys::ApiKeyError,
+        backtrace: Backtrace,
+    },
+}
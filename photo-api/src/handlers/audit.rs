@@ -0,0 +1,113 @@
+use super::utils::timestamp;
+use crate::conduit::audit;
+use crate::connection::Repo;
+use crate::middlewares::current_user::CurrentUser;
+use gotham::handler::HandlerResult;
+use gotham::helpers::http::response::{create_empty_response, create_response};
+use gotham::state::{FromState, State};
+use hyper::StatusCode;
+use photo_core::models::{AuditAction, AuditEvent, AuditFilter, AuditTarget, ModelError};
+use serde::{Deserialize, Serialize};
+use snafu::{Backtrace, ResultExt};
+
+/// Events returned when the query does not ask for a number.
+const DEFAULT_PAGE_SIZE: i64 = 50;
+
+/// Largest number of events returned in a single page.
+const MAX_PAGE_SIZE: i64 = 200;
+
+#[derive(Deserialize, StateData, StaticResponseExtender)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditQueryExtractor {
+    actor_id: Option<String>,
+    action: Option<AuditAction>,
+    target_type: Option<AuditTarget>,
+    target_id: Option<String>,
+    /// Unix timestamps (seconds): events from `since` included, up to `until` excluded.
+    since: Option<i64>,
+    until: Option<i64>,
+    limit: Option<i64>,
+    cursor: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEventsResponse {
+    list: Vec<AuditEvent>,
+    next_cursor: Option<String>,
+}
+
+/// Browses the audit log, most recent events first. Admins see every event, other users the changes
+/// they made and the changes to the albums and photos of the studios they own.
+pub async fn all_audit_events(state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let query_data = AuditQueryExtractor::borrow_from(&state);
+    let user = CurrentUser::borrow_from(&state).0.clone();
+
+    let visible_to = if user.is_admin { None } else { Some(user) };
+    let (since, until) = match (timestamp(query_data.since), timestamp(query_data.until)) {
+        (Ok(since), Ok(until)) => (since, until),
+        _ => {
+            let res = create_empty_response(&state, StatusCode::BAD_REQUEST);
+            return Ok((state, res));
+        }
+    };
+    let filter = AuditFilter {
+        visible_to,
+        actor_id: query_data.actor_id.clone(),
+        action: query_data.action,
+        target: query_data.target_type,
+        target_id: query_data.target_id.clone(),
+        since,
+        until,
+    };
+    let limit = query_data
+        .limit
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .max(1)
+        .min(MAX_PAGE_SIZE);
+    let cursor = query_data.cursor.clone();
+
+    let response = match audit::find(repo, filter, limit, cursor)
+        .await
+        .context(AuditIssue)
+    {
+        Ok(page) => {
+            let response = AuditEventsResponse {
+                list: page.list,
+                next_cursor: page.next_cursor,
+            };
+            let body = serde_json::to_string(&response).expect("Failed to serialize audit events");
+
+            create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body)
+        }
+        Err(e) if is_invalid_cursor(&e) => create_empty_response(&state, StatusCode::BAD_REQUEST),
+        Err(e) => return Err((state, e.into())),
+    };
+
+    Ok((state, response))
+}
+
+fn is_invalid_cursor(e: &AuditHandlersError) -> bool {
+    match e {
+        AuditHandlersError::AuditIssue {
+            cause:
+                audit::AuditError::Model {
+                    cause: ModelError::InvalidCursor,
+                    ..
+                },
+            ..
+        } => true,
+        _ => false,
+    }
+}
+
+#[derive(Debug, Snafu)]
+pub enum AuditHandlersError {
+    #[snafu(display("Could not get audit events: {}", cause))]
+    AuditIssue {
+        #[snafu(source)]
+        cause: audit::AuditError,
+        backtrace: Backtrace,
+    },
+}
@@ -0,0 +1,184 @@
+use super::login::{finish_login, login_error_page};
+use crate::auth::email::{EmailLoginExtractor, EmailProfile};
+use crate::auth::throttle::LOGIN_LINK_THROTTLE;
+use crate::conduit::login_links::{self, LoginLinkError};
+use crate::connection::Repo;
+use crate::handlers::utils::{client_ip, extract_json, get_body_bytes};
+use crate::mail;
+use crate::utils::{encode_url_component, get_url};
+use gotham::handler::HandlerResult;
+use gotham::helpers::http::response::{create_empty_response, create_response};
+use gotham::state::{FromState, State};
+use hyper::StatusCode;
+use photo_core::models::{ModelError, LOGIN_LINK_EXPIRY};
+use serde::Deserialize;
+use url::form_urlencoded;
+
+/// Where a failed login sends the user back to when there is no client to redirect to.
+const RETRY_URL: &str = "/";
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmailLoginRequest {
+    pub email: String,
+}
+
+/// Emails a single use login link, for users without a Google account. The response is the same
+/// whether or not the email can sign in, so it can't be used to find out who has an account.
+pub async fn email_login_request_handler(mut state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let req_data: EmailLoginRequest = match extract_json(&mut state).await {
+        Ok(data) => data,
+        Err(_) => {
+            let res = create_empty_response(&state, StatusCode::BAD_REQUEST);
+            return Ok((state, res));
+        }
+    };
+    let email = req_data.email.trim().to_string();
+
+    // Per client so one can't flood many inboxes, per email so many clients can't flood one.
+    let throttle_keys = [
+        format!("login_link:{}", client_ip(&state)),
+        format!("login_link_email:{}", email.to_lowercase()),
+    ];
+    if throttle_keys
+        .iter()
+        .any(|key| LOGIN_LINK_THROTTLE.is_blocked(key))
+    {
+        let res = create_empty_response(&state, StatusCode::TOO_MANY_REQUESTS);
+        return Ok((state, res));
+    }
+    for key in throttle_keys.iter() {
+        LOGIN_LINK_THROTTLE.fail(key);
+    }
+
+    let token = match login_links::create(repo, email.clone()).await {
+        Ok(Some(t)) => t,
+        Ok(None) => {
+            debug!("Login link requested for unknown email {}", email);
+            let res = create_empty_response(&state, StatusCode::OK);
+            return Ok((state, res));
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    let link = format!(
+        "{}/email/login?token={}",
+        get_url(),
+        encode_url_component(token)
+    );
+    let html = format!(
+        r#"
+                <!DOCTYPE html>
+                <html lang="en">
+                  <head>
+                    <meta charset="utf-8" />
+                    <title>Sign in</title>
+                  </head>
+                  <body>
+                    <p>
+                        <a href="{}">Sign in</a>
+                    </p>
+                    <p>
+                        This link can be used once and expires in {} minutes. If you did not ask
+                        for it, you can ignore this email.
+                    </p>
+                  </body>
+                </html>
+        "#,
+        link, LOGIN_LINK_EXPIRY
+    );
+
+    // A failure is only logged, answering otherwise would tell that the email has an account.
+    match mail::send("Photos", "no-reply", &email, "Sign in", &html).await {
+        Ok(r) if r.status() == StatusCode::OK => {}
+        Ok(r) => error!(
+            "Could not send login link, mail service answered {}",
+            r.status()
+        ),
+        Err(e) => error!("Could not send login link: {}", e),
+    }
+
+    let res = create_empty_response(&state, StatusCode::OK);
+
+    Ok((state, res))
+}
+
+/// Page opened from the login link. It only asks to confirm, the link is used when the form is
+/// posted, so mail scanners opening links don't use it up.
+pub async fn email_login_confirm_handler(mut state: State) -> HandlerResult {
+    let query_param = EmailLoginExtractor::take_from(&mut state);
+
+    // Tokens are alphanumeric, anything else can't be a link we sent and is not put in the page.
+    if query_param.token.is_empty() || !query_param.token.chars().all(char::is_alphanumeric) {
+        let res = login_error_page(
+            &state,
+            StatusCode::BAD_REQUEST,
+            "This link expired or was already used.",
+            RETRY_URL,
+        );
+        return Ok((state, res));
+    }
+
+    let body = format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+  <head>
+    <meta charset="utf-8" />
+    <title>Sign in</title>
+  </head>
+  <body>
+    <form method="post" action="{}/email/login">
+      <input type="hidden" name="token" value="{}" />
+      <button type="submit">Sign in</button>
+    </form>
+  </body>
+</html>
+"#,
+        get_url(),
+        query_param.token
+    );
+    let res = create_response(&state, StatusCode::OK, mime::TEXT_HTML_UTF_8, body);
+
+    Ok((state, res))
+}
+
+/// Signs in with a link sent by email, once the user confirmed. The link can only be used once,
+/// like the Google login it starts a session and hands the tokens to the client.
+pub async fn email_login_handler(mut state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let token = match get_body_bytes(&mut state).await {
+        Ok(body) => form_urlencoded::parse(&body)
+            .find(|(key, _)| key == "token")
+            .map(|(_, value)| value.into_owned()),
+        Err(e) => return Err((state, e.into())),
+    };
+    let token = match token {
+        Some(t) => t,
+        None => {
+            let res = create_empty_response(&state, StatusCode::BAD_REQUEST);
+            return Ok((state, res));
+        }
+    };
+
+    let link = match login_links::take(repo.clone(), token).await {
+        Ok(l) => l,
+        Err(LoginLinkError::Model {
+            cause: ModelError::InvalidLoginLink,
+            ..
+        }) => {
+            let res = login_error_page(
+                &state,
+                StatusCode::BAD_REQUEST,
+                "This link expired or was already used.",
+                RETRY_URL,
+            );
+            return Ok((state, res));
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    let profile = EmailProfile { email: link.email };
+
+    finish_login(state, repo, profile, RETRY_URL).await
+}
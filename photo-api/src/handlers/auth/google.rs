@@ -1,47 +1,120 @@
-use crate::auth::encode_token;
+use super::login::{finish_login, login_error_page};
+use crate::auth::csrf::{state_cookie, state_from_cookies};
 use crate::auth::google::{
     build_client, exchange_token, gen_authorize_url, get_user_profile, GoogleRedirectExtractor,
 };
-use crate::conduit::users::find_or_create;
+use crate::conduit::oauth_states::{self, OAuthStateError};
 use crate::connection::Repo;
-use crate::handlers::utils::error_request;
-use futures::future;
-use futures::prelude::*;
-use gotham::handler::{HandlerFuture, HandlerResult};
-use gotham::helpers::http::response::{create_response, create_temporary_redirect};
+use gotham::handler::HandlerResult;
+use gotham::helpers::http::response::create_temporary_redirect;
 use gotham::state::{FromState, State};
+use hyper::header::{HeaderMap, SET_COOKIE};
 use hyper::StatusCode;
-use photo_core::models::User;
-use serde::Serialize;
-use std::env;
-use std::pin::Pin;
+use oauth2::PkceCodeVerifier;
+use photo_core::models::ModelError;
 
-#[derive(Serialize)]
-struct AuthenticatedUser {
-    user: User,
-    token: String,
-}
+/// Where a failed login sends the user back to when there is no client to redirect to.
+const RETRY_URL: &str = "/google/authorize";
+
+/// Provider saved with the pending logins, OIDC providers are saved as `oidc:<name>`.
+const PROVIDER: &str = "google";
 
-pub fn google_authorize_handler(state: State) -> Pin<Box<HandlerFuture>> {
+pub async fn google_authorize_handler(state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
     let google_client = match build_client() {
         Ok(c) => c,
-        Err(e) => return error_request(state, e.into()),
+        Err(e) => return Err((state, e.into())),
     };
-    let (authorize_url, _) = gen_authorize_url(google_client);
-    let res = create_temporary_redirect(&state, authorize_url.to_string());
-    let f = future::ok((state, res));
+    let (authorize_url, csrf_token, pkce_verifier) = gen_authorize_url(google_client);
 
-    f.boxed()
+    let saved = oauth_states::save(
+        repo,
+        String::from(PROVIDER),
+        csrf_token.secret().clone(),
+        pkce_verifier.secret().clone(),
+    )
+    .await;
+    if let Err(e) = saved {
+        return Err((state, e.into()));
+    }
+
+    let mut res = create_temporary_redirect(&state, authorize_url.to_string());
+    res.headers_mut()
+        .insert(SET_COOKIE, state_cookie(csrf_token.secret()));
+
+    Ok((state, res))
 }
 
+/// Finishes the login once Google redirects back. The state must match the one saved for this
+/// browser when the login started, and can only be used once.
 pub async fn google_redirect_handler(mut state: State) -> HandlerResult {
     let query_param = GoogleRedirectExtractor::take_from(&mut state);
+    let repo = Repo::borrow_from(&state).clone();
+    let cookie_state = state_from_cookies(HeaderMap::borrow_from(&state));
+
+    if query_param.error.is_some() {
+        let res = login_error_page(
+            &state,
+            StatusCode::FORBIDDEN,
+            "The login was cancelled.",
+            RETRY_URL,
+        );
+        return Ok((state, res));
+    }
+
+    if cookie_state.as_deref() != Some(query_param.state.as_str()) {
+        let res = login_error_page(
+            &state,
+            StatusCode::BAD_REQUEST,
+            "This login was not started from this browser.",
+            RETRY_URL,
+        );
+        return Ok((state, res));
+    }
+
+    let pending = match oauth_states::take(
+        repo.clone(),
+        String::from(PROVIDER),
+        query_param.state.clone(),
+    )
+    .await
+    {
+        Ok(p) => p,
+        Err(OAuthStateError::Model {
+            cause: ModelError::InvalidOAuthState,
+            ..
+        }) => {
+            let res = login_error_page(
+                &state,
+                StatusCode::BAD_REQUEST,
+                "This login expired or was already used.",
+                RETRY_URL,
+            );
+            return Ok((state, res));
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    let code = match query_param.code {
+        Some(c) => c,
+        None => {
+            let res = login_error_page(
+                &state,
+                StatusCode::BAD_REQUEST,
+                "Google did not send an authorization code.",
+                RETRY_URL,
+            );
+            return Ok((state, res));
+        }
+    };
+
     let google_client = match build_client() {
         Ok(c) => c,
         Err(e) => return Err((state, e.into())),
     };
 
-    let token = match exchange_token(&query_param, &google_client).await {
+    let pkce_verifier = PkceCodeVerifier::new(pending.pkce_verifier);
+    let token = match exchange_token(&code, &google_client, pkce_verifier).await {
         Ok(t) => t,
         Err(e) => return Err((state, e.into())),
     };
@@ -51,20 +124,5 @@ pub async fn google_redirect_handler(mut state: State) -> HandlerResult {
         Err(e) => return Err((state, e.into())),
     };
 
-    let repo = Repo::borrow_from(&state).clone();
-    let user = find_or_create(repo, profile).await.unwrap();
-
-    let token = encode_token(&user, 3600);
-    let redirect_url = env::var("REDIRECT_CLIENT_URL");
-
-    let res = match redirect_url {
-        Ok(u) => create_temporary_redirect(&state, format!("{}?token={}", u, token)),
-        _ => {
-            let response = AuthenticatedUser { user, token };
-            let body = serde_json::to_string(&response).expect("Failed to serialize user.");
-            create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body)
-        }
-    };
-
-    Ok((state, res))
+    finish_login(state, repo, profile, RETRY_URL).await
 }
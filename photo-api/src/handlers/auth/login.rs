@@ -0,0 +1,105 @@
+use crate::auth::csrf::expired_state_cookie;
+use crate::auth::{encode_token, Profile, ACCESS_TOKEN_EXPIRY};
+use crate::conduit::users::{find_or_create, UserError};
+use crate::conduit::{login_codes, sessions};
+use crate::connection::Repo;
+use crate::handlers::utils::{audit_context, client_ip, user_agent};
+use gotham::handler::HandlerResult;
+use gotham::helpers::http::response::{create_response, create_temporary_redirect};
+use gotham::state::State;
+use hyper::header::SET_COOKIE;
+use hyper::{Body, Response, StatusCode};
+use photo_core::models::User;
+use serde::Serialize;
+use std::env;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AuthenticatedUser {
+    user: User,
+    token: String,
+    refresh_token: String,
+}
+
+/// Signs in the user of a profile returned by a provider: the user is created if allowed. Redirects
+/// to the client with a single use code it exchanges for tokens, so tokens never end up in URLs
+/// or browser history. When there is no client to redirect to, a session is started and the
+/// tokens returned.
+pub async fn finish_login<T: Profile>(
+    state: State,
+    repo: Repo,
+    profile: T,
+    retry_url: &str,
+) -> HandlerResult {
+    let audit = audit_context(&state, None);
+    let user = match find_or_create(repo.clone(), &audit, profile).await {
+        Ok(u) => u,
+        Err(UserError::UserNotAllowed) => {
+            let res = login_error_page(
+                &state,
+                StatusCode::FORBIDDEN,
+                "This account is not allowed to sign in.",
+                retry_url,
+            );
+            return Ok((state, res));
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    let mut res = match env::var("REDIRECT_CLIENT_URL") {
+        Ok(u) => {
+            let code = match login_codes::create(repo, &user).await {
+                Ok(c) => c,
+                Err(e) => return Err((state, e.into())),
+            };
+
+            create_temporary_redirect(&state, format!("{}?code={}", u, code))
+        }
+        _ => {
+            let started =
+                sessions::start(repo, &user, user_agent(&state), Some(client_ip(&state))).await;
+            let (session, refresh_token) = match started {
+                Ok(s) => s,
+                Err(e) => return Err((state, e.into())),
+            };
+
+            let response = AuthenticatedUser {
+                token: encode_token(&user, &session, ACCESS_TOKEN_EXPIRY),
+                user,
+                refresh_token,
+            };
+            let body = serde_json::to_string(&response).expect("Failed to serialize user.");
+            create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body)
+        }
+    };
+    res.headers_mut().insert(SET_COOKIE, expired_state_cookie());
+
+    Ok((state, res))
+}
+
+/// A page telling the user the login failed, with a way to start again.
+pub fn login_error_page(
+    state: &State,
+    status: StatusCode,
+    message: &str,
+    retry_url: &str,
+) -> Response<Body> {
+    let retry_url = env::var("REDIRECT_CLIENT_URL").unwrap_or(String::from(retry_url));
+    let body = format!(
+        "<!DOCTYPE html>\n\
+         <html>\n\
+         <head><meta charset=\"utf-8\"><title>Login failed</title></head>\n\
+         <body>\n\
+         <h1>Login failed</h1>\n\
+         <p>{}</p>\n\
+         <p><a href=\"{}\">Try again</a></p>\n\
+         </body>\n\
+         </html>\n",
+        message, retry_url
+    );
+
+    let mut res = create_response(state, status, mime::TEXT_HTML_UTF_8, body);
+    res.headers_mut().insert(SET_COOKIE, expired_state_cookie());
+
+    res
+}
@@ -1,2 +1,10 @@
+mod email;
+pub use self::email::*;
+
 mod google;
 pub use self::google::*;
+
+mod login;
+
+mod oidc;
+pub use self::oidc::*;
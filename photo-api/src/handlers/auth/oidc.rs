@@ -0,0 +1,170 @@
+use super::login::{finish_login, login_error_page};
+use crate::auth::csrf::{state_cookie, state_from_cookies};
+use crate::auth::oidc::{
+    build_client, discover, exchange_token, find_provider, gen_authorize_url, get_user_profile,
+    OidcError, OidcPathExtractor, OidcRedirectExtractor,
+};
+use crate::conduit::oauth_states::{self, OAuthStateError};
+use crate::connection::Repo;
+use gotham::handler::HandlerResult;
+use gotham::helpers::http::response::{create_empty_response, create_temporary_redirect};
+use gotham::state::{request_id, FromState, State};
+use hyper::header::{HeaderMap, SET_COOKIE};
+use hyper::StatusCode;
+use oauth2::PkceCodeVerifier;
+use photo_core::models::ModelError;
+
+pub async fn oidc_authorize_handler(state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let path_data = OidcPathExtractor::borrow_from(&state);
+
+    let provider = match find_provider(&path_data.provider) {
+        Some(p) => p,
+        None => {
+            let res = create_empty_response(&state, StatusCode::NOT_FOUND);
+            return Ok((state, res));
+        }
+    };
+
+    let discovery = match discover(provider).await {
+        Ok(d) => d,
+        Err(e) => return Err((state, e.into())),
+    };
+    let client = match build_client(provider, &discovery) {
+        Ok(c) => c,
+        Err(e) => return Err((state, e.into())),
+    };
+    let (authorize_url, csrf_token, pkce_verifier) = gen_authorize_url(provider, &client);
+
+    let saved = oauth_states::save(
+        repo,
+        provider.state_key(),
+        csrf_token.secret().clone(),
+        pkce_verifier.secret().clone(),
+    )
+    .await;
+    if let Err(e) = saved {
+        return Err((state, e.into()));
+    }
+
+    let mut res = create_temporary_redirect(&state, authorize_url.to_string());
+    res.headers_mut()
+        .insert(SET_COOKIE, state_cookie(csrf_token.secret()));
+
+    Ok((state, res))
+}
+
+/// Finishes the login once the provider redirects back. Like the Google login, the state must
+/// match the one saved for this browser and can only be used once; the ID token must also be
+/// signed by the provider and carry that state as its nonce.
+pub async fn oidc_redirect_handler(mut state: State) -> HandlerResult {
+    let query_param = OidcRedirectExtractor::take_from(&mut state);
+    let path_data = OidcPathExtractor::borrow_from(&state);
+    let repo = Repo::borrow_from(&state).clone();
+    let cookie_state = state_from_cookies(HeaderMap::borrow_from(&state));
+
+    let provider = match find_provider(&path_data.provider) {
+        Some(p) => p,
+        None => {
+            let res = create_empty_response(&state, StatusCode::NOT_FOUND);
+            return Ok((state, res));
+        }
+    };
+    let retry_url = format!("/oidc/{}/authorize", provider.name);
+
+    if query_param.error.is_some() {
+        let res = login_error_page(
+            &state,
+            StatusCode::FORBIDDEN,
+            "The login was cancelled.",
+            &retry_url,
+        );
+        return Ok((state, res));
+    }
+
+    if cookie_state.as_deref() != Some(query_param.state.as_str()) {
+        let res = login_error_page(
+            &state,
+            StatusCode::BAD_REQUEST,
+            "This login was not started from this browser.",
+            &retry_url,
+        );
+        return Ok((state, res));
+    }
+
+    let pending = match oauth_states::take(
+        repo.clone(),
+        provider.state_key(),
+        query_param.state.clone(),
+    )
+    .await
+    {
+        Ok(p) => p,
+        Err(OAuthStateError::Model {
+            cause: ModelError::InvalidOAuthState,
+            ..
+        }) => {
+            let res = login_error_page(
+                &state,
+                StatusCode::BAD_REQUEST,
+                "This login expired or was already used.",
+                &retry_url,
+            );
+            return Ok((state, res));
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    let code = match query_param.code {
+        Some(c) => c,
+        None => {
+            let res = login_error_page(
+                &state,
+                StatusCode::BAD_REQUEST,
+                "The provider did not send an authorization code.",
+                &retry_url,
+            );
+            return Ok((state, res));
+        }
+    };
+
+    let discovery = match discover(provider).await {
+        Ok(d) => d,
+        Err(e) => return Err((state, e.into())),
+    };
+    let client = match build_client(provider, &discovery) {
+        Ok(c) => c,
+        Err(e) => return Err((state, e.into())),
+    };
+
+    let pkce_verifier = PkceCodeVerifier::new(pending.pkce_verifier);
+    let token = match exchange_token(&code, &client, pkce_verifier).await {
+        Ok(t) => t,
+        Err(e) => return Err((state, e.into())),
+    };
+
+    let profile = match get_user_profile(provider, &discovery, &token, &query_param.state).await {
+        Ok(p) => p,
+        Err(OidcError::EmailNotVerified) => {
+            let res = login_error_page(
+                &state,
+                StatusCode::FORBIDDEN,
+                "The email of this account is not verified.",
+                &retry_url,
+            );
+            return Ok((state, res));
+        }
+        Err(e) => {
+            debug!("[{}] invalid ID token: {}", request_id(&state), e);
+            let res = login_error_page(
+                &state,
+                StatusCode::UNAUTHORIZED,
+                "The provider did not send a valid identity.",
+                &retry_url,
+            );
+            return Ok((state, res));
+        }
+    };
+
+    finish_login(state, repo, profile, &retry_url).await
+}
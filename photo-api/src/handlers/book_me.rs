@@ -1,17 +1,15 @@
-use super::utils::{extract_json, HandlerUtilsError};
-use crate::auth::AuthUser;
+use super::utils::{audit_context, extract_json, HandlerUtilsError};
 use crate::conduit::{book_me, users};
 use crate::connection::Repo;
+use crate::mail;
+use crate::middlewares::current_user::CurrentUser;
 use gotham::handler::HandlerResult;
 use gotham::helpers::http::response::{create_empty_response, create_response};
 use gotham::state::{FromState, State};
-use gotham_middleware_jwt::AuthorizationToken;
 use hyper::StatusCode;
-use photo_core::models::BookMe;
-use reqwest;
+use photo_core::models::{BookMe, ModelError};
 use serde::{Deserialize, Serialize};
 use snafu::{Backtrace, ResultExt};
-use std::env;
 
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -37,21 +35,10 @@ pub async fn update(mut state: State) -> HandlerResult {
             }
         };
 
-    let token = AuthorizationToken::<AuthUser>::borrow_from(&state);
-    let email = token.0.claims.email();
+    let user = CurrentUser::borrow_from(&state).0.clone();
+    let audit = audit_context(&state, Some(&user));
 
-    let user = match users::find_by_email(repo.clone(), email)
-        .await
-        .context(UserIssue)
-    {
-        Ok(u) => u,
-        Err(e) => {
-            debug!("{:?}", e);
-            return Err((state, e.into()));
-        }
-    };
-
-    let response = match book_me::update_or_create(repo, &user, req_data.email)
+    let response = match book_me::update_or_create(repo, &audit, &user, req_data.email)
         .await
         .context(BookMeIssue)
     {
@@ -70,19 +57,7 @@ pub async fn update(mut state: State) -> HandlerResult {
 pub async fn find_by_user(state: State) -> HandlerResult {
     let repo = Repo::borrow_from(&state).clone();
 
-    let token = AuthorizationToken::<AuthUser>::borrow_from(&state);
-    let email = token.0.claims.email();
-
-    let user = match users::find_by_email(repo.clone(), email)
-        .await
-        .context(UserIssue)
-    {
-        Ok(u) => u,
-        Err(e) => {
-            debug!("{:?}", e);
-            return Err((state, e.into()));
-        }
-    };
+    let user = CurrentUser::borrow_from(&state).0.clone();
 
     let response = match book_me::find_by_user(repo, &user)
         .await
@@ -94,9 +69,14 @@ pub async fn find_by_user(state: State) -> HandlerResult {
 
             create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body)
         }
-        Err(AlbumHandlersError::BookMeIssue { .. }) => {
-            create_empty_response(&state, StatusCode::NOT_FOUND)
-        }
+        Err(AlbumHandlersError::BookMeIssue {
+            cause:
+                book_me::BookMeError::Model {
+                    cause: ModelError::BookMeNotFound,
+                    ..
+                },
+            ..
+        }) => create_empty_response(&state, StatusCode::NOT_FOUND),
         Err(e) => return Err((state, e.into())),
     };
 
@@ -139,24 +119,15 @@ pub async fn book_me(mut state: State) -> HandlerResult {
         }
     };
 
-    let domain = match env::var("MAILGUN_DOMAIN") {
-        Ok(v) => v,
-        Err(e) => {
-            debug!("{:?}", e);
-            return Err((state, e.into()));
-        }
-    };
-
-    let api_key = match env::var("MAILGUN_API_KEY") {
-        Ok(v) => v,
-        Err(e) => {
-            debug!("{:?}", e);
-            return Err((state, e.into()));
-        }
-    };
-
     let user = match users::find_by_id(repo.clone(), query_param.id).await {
         Ok(u) => u,
+        Err(users::UserError::Model {
+            cause: ModelError::UserNotFound,
+            ..
+        }) => {
+            let res = create_empty_response(&state, StatusCode::NOT_FOUND);
+            return Ok((state, res));
+        }
         Err(e) => {
             debug!("{:?}", e);
             return Err((state, e.into()));
@@ -171,8 +142,6 @@ pub async fn book_me(mut state: State) -> HandlerResult {
         }
     };
 
-    let uri = format!("https://api.mailgun.net/v3/{}/messages", domain);
-
     let phone = match req_data.phone {
         Some(p) => p,
         None => String::from("Not specified."),
@@ -193,14 +162,8 @@ pub async fn book_me(mut state: State) -> HandlerResult {
         None => String::from("Not specified."),
     };
 
-    let params = [
-        ("from", &format!("Website <website@{}>", domain)),
-        ("to", &info.email),
-        ("subject", &String::from("Contact from Website")),
-        (
-            "html",
-            &String::from(format!(
-                r#"
+    let html = format!(
+        r#"
                 <!DOCTYPE html>
                 <html lang="en">
                   <head>
@@ -240,19 +203,17 @@ pub async fn book_me(mut state: State) -> HandlerResult {
                   </body>
                 </html>
         "#,
-                req_data.name, req_data.email, req_data.message, phone, date, venue, city,
-            )),
-        ),
-    ];
-
-    let client = reqwest::Client::new();
-
-    let res = match client
-        .post(&uri)
-        .basic_auth("api", Some(api_key))
-        .form(&params)
-        .send()
-        .await
+        req_data.name, req_data.email, req_data.message, phone, date, venue, city,
+    );
+
+    let res = match mail::send(
+        "Website",
+        "website",
+        &info.email,
+        "Contact from Website",
+        &html,
+    )
+    .await
     {
         Ok(r) => r,
         Err(e) => {
@@ -261,7 +222,6 @@ pub async fn book_me(mut state: State) -> HandlerResult {
         }
     };
 
-    debug!("Response: {:?}", res);
     match res.status() {
         StatusCode::OK => {}
         code => {
@@ -291,13 +251,6 @@ pub enum AlbumHandlersError {
         backtrace: Backtrace,
     },
 
-    #[snafu(display("Could not get user: {}", cause))]
-    UserIssue {
-        #[snafu(source)]
-        cause: users::UserError,
-        backtrace: Backtrace,
-    },
-
     #[snafu(display("Could not complete request: {}", source))]
     ReqwestError {
         source: reqwest::Error,
@@ -0,0 +1,151 @@
+use super::utils::photo_pagination;
+use crate::conduit::{photos, users};
+use crate::connection::Repo;
+use crate::middlewares::current_user::CurrentUser;
+use gotham::handler::HandlerResult;
+use gotham::helpers::http::response::{create_empty_response, create_response};
+use gotham::state::{FromState, State};
+use hyper::StatusCode;
+use photo_core::models::{Album, ModelError, PhotoPage, PhotoSort};
+use serde::{Deserialize, Serialize};
+use snafu::{Backtrace, ResultExt};
+
+/// Favorites are listed with the most recently uploaded first unless asked otherwise.
+const DEFAULT_SORT: PhotoSort = PhotoSort::CreatedAt;
+
+#[derive(Deserialize, StateData, StaticResponseExtender)]
+pub struct FavoritesQueryExtractor {
+    sort: Option<PhotoSort>,
+    limit: Option<usize>,
+    cursor: Option<String>,
+}
+
+#[derive(Deserialize, StateData, StaticResponseExtender)]
+pub struct PublicFavoritesQueryExtractor {
+    id: String,
+    sort: Option<PhotoSort>,
+    limit: Option<usize>,
+    cursor: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct FavoritesResponse {
+    #[serde(flatten)]
+    page: PhotoPage,
+    /// Albums the photos of the page belong to.
+    albums: Vec<Album>,
+}
+
+/// Lists the favorite photos of the user across all of their albums.
+pub async fn all_favorites(state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let query_data = FavoritesQueryExtractor::borrow_from(&state);
+    let pagination = photo_pagination(
+        query_data.sort.unwrap_or(DEFAULT_SORT),
+        query_data.limit,
+        query_data.cursor.clone(),
+    );
+
+    let user = CurrentUser::borrow_from(&state).0.clone();
+
+    let response = match photos::favorites(repo, &user, false, pagination)
+        .await
+        .context(PhotoIssue)
+    {
+        Ok((page, albums)) => {
+            let response = FavoritesResponse { page, albums };
+            let body = serde_json::to_string(&response).expect("Failed to serialize photos");
+
+            create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body)
+        }
+        Err(e) if is_invalid_cursor(&e) => create_empty_response(&state, StatusCode::BAD_REQUEST),
+        Err(e) => return Err((state, e.into())),
+    };
+
+    Ok((state, response))
+}
+
+/// "Best of" feed: the favorite photos of a user, taken from albums that are not password
+/// protected.
+pub async fn get_public_favorites(state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let query_data = PublicFavoritesQueryExtractor::borrow_from(&state);
+    let pagination = photo_pagination(
+        query_data.sort.unwrap_or(DEFAULT_SORT),
+        query_data.limit,
+        query_data.cursor.clone(),
+    );
+
+    let user = match users::find_by_id(repo.clone(), query_data.id.clone())
+        .await
+        .context(UserIssue)
+    {
+        Ok(u) => u,
+        Err(e) if is_unknown_user(&e) => {
+            let res = create_empty_response(&state, StatusCode::NOT_FOUND);
+            return Ok((state, res));
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    let response = match photos::favorites(repo, &user, true, pagination)
+        .await
+        .context(PhotoIssue)
+    {
+        Ok((page, albums)) => {
+            let response = FavoritesResponse { page, albums };
+            let body = serde_json::to_string(&response).expect("Failed to serialize photos");
+
+            create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body)
+        }
+        Err(e) if is_invalid_cursor(&e) => create_empty_response(&state, StatusCode::BAD_REQUEST),
+        Err(e) => return Err((state, e.into())),
+    };
+
+    Ok((state, response))
+}
+
+fn is_invalid_cursor(e: &FavoritesHandlersError) -> bool {
+    match e {
+        FavoritesHandlersError::PhotoIssue {
+            cause:
+                photos::PhotoError::Model {
+                    cause: ModelError::InvalidCursor,
+                    ..
+                },
+            ..
+        } => true,
+        _ => false,
+    }
+}
+
+fn is_unknown_user(e: &FavoritesHandlersError) -> bool {
+    match e {
+        FavoritesHandlersError::UserIssue {
+            cause:
+                users::UserError::Model {
+                    cause: ModelError::UserNotFound,
+                    ..
+                },
+            ..
+        } => true,
+        _ => false,
+    }
+}
+
+#[derive(Debug, Snafu)]
+pub enum FavoritesHandlersError {
+    #[snafu(display("Could not get photos: {}", cause))]
+    PhotoIssue {
+        #[snafu(source)]
+        cause: photos::PhotoError,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Could not get user: {}", cause))]
+    UserIssue {
+        #[snafu(source)]
+        cause: users::UserError,
+        backtrace: Backtrace,
+    },
+}
@@ -0,0 +1,148 @@
+use super::utils::{extract_json, timestamp, HandlerUtilsError};
+use crate::conduit::invitations;
+use crate::connection::Repo;
+use crate::middlewares::current_user::CurrentUser;
+use gotham::handler::HandlerResult;
+use gotham::helpers::http::response::{create_empty_response, create_response};
+use gotham::state::{FromState, State};
+use hyper::StatusCode;
+use photo_core::models::{Invitation, ModelError};
+use serde::{Deserialize, Serialize};
+use snafu::{Backtrace, ResultExt};
+
+#[derive(Deserialize, StateData, StaticResponseExtender)]
+pub struct InvitationPathExtractor {
+    id: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewInvitationRequest {
+    pub email: String,
+    /// Unix timestamp (seconds) after which the invitation can't be used to sign up.
+    pub expires_at: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct InvitationResponse {
+    invitation: Invitation,
+}
+
+#[derive(Serialize)]
+pub struct InvitationsResponse {
+    list: Vec<Invitation>,
+}
+
+/// Allows an email to create an account. Inviting an email again replaces its invitation.
+pub async fn invite(mut state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let req_data: NewInvitationRequest =
+        match extract_json(&mut state).await.context(HandlerUtilsIssue) {
+            Ok(data) => data,
+            Err(e) => return Err((state, e.into())),
+        };
+    let user = CurrentUser::borrow_from(&state).0.clone();
+
+    if !user.is_admin {
+        let res = create_empty_response(&state, StatusCode::FORBIDDEN);
+        return Ok((state, res));
+    }
+
+    let email = req_data.email.trim().to_lowercase();
+    if !email.contains('@') {
+        let res = create_empty_response(&state, StatusCode::BAD_REQUEST);
+        return Ok((state, res));
+    }
+
+    let expires_at = match timestamp(req_data.expires_at) {
+        Ok(date) => date,
+        Err(_) => {
+            let res = create_empty_response(&state, StatusCode::BAD_REQUEST);
+            return Ok((state, res));
+        }
+    };
+
+    let response = match invitations::invite(repo, &user, email, expires_at)
+        .await
+        .context(InvitationIssue)
+    {
+        Ok(invitation) => {
+            let response = InvitationResponse { invitation };
+            let body = serde_json::to_string(&response).expect("Failed to serialize invitation");
+
+            create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body)
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    Ok((state, response))
+}
+
+pub async fn all_invitations(state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let user = CurrentUser::borrow_from(&state).0.clone();
+
+    if !user.is_admin {
+        let res = create_empty_response(&state, StatusCode::FORBIDDEN);
+        return Ok((state, res));
+    }
+
+    let response = match invitations::find_all(repo).await.context(InvitationIssue) {
+        Ok(list) => {
+            let response = InvitationsResponse { list };
+            let body = serde_json::to_string(&response).expect("Failed to serialize invitations");
+
+            create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body)
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    Ok((state, response))
+}
+
+/// Revokes an invitation. Accounts already created with it are kept.
+pub async fn revoke_invitation(state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let path_data = InvitationPathExtractor::borrow_from(&state);
+    let user = CurrentUser::borrow_from(&state).0.clone();
+
+    if !user.is_admin {
+        let res = create_empty_response(&state, StatusCode::FORBIDDEN);
+        return Ok((state, res));
+    }
+
+    let response = match invitations::revoke(repo, path_data.id.clone())
+        .await
+        .context(InvitationIssue)
+    {
+        Ok(_) => create_empty_response(&state, StatusCode::OK),
+        Err(InvitationHandlersError::InvitationIssue {
+            cause:
+                invitations::InvitationError::Model {
+                    cause: ModelError::InvitationNotFound,
+                    ..
+                },
+            ..
+        }) => create_empty_response(&state, StatusCode::NOT_FOUND),
+        Err(e) => return Err((state, e.into())),
+    };
+
+    Ok((state, response))
+}
+
+#[derive(Debug, Snafu)]
+pub enum InvitationHandlersError {
+    #[snafu(display("Could not get request: {}", cause))]
+    HandlerUtilsIssue {
+        #[snafu(source)]
+        cause: HandlerUtilsError,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Could not get invitation: {}", cause))]
+    InvitationIssue {
+        #[snafu(source)]
+        cause: invitations::InvitationError,
+        backtrace: Backtrace,
+    },
+}
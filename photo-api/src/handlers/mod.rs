@@ -1,6 +1,16 @@
 pub mod albums;
+pub mod api_keys;
+pub mod audit;
 pub mod auth;
 pub mod book_me;
+pub mod favorites;
+pub mod invitations;
 pub mod photos;
+pub mod proofing;
+pub mod search;
+pub mod sessions;
+pub mod share_links;
+pub mod studios;
+pub mod tags;
 pub mod users;
 pub mod utils;
@@ -1,17 +1,26 @@
-use super::utils::{extract_json, handle_multipart, HandlerUtilsError};
-use crate::auth::AuthUser;
+use super::utils::{
+    audit_context, create_tagged_response, double_option, extract_json, handle_multipart,
+    precondition, timestamp, HandlerUtilsError, MultiPartData,
+};
 use crate::aws::{delete, get_url, upload, AwsS3Error};
-use crate::conduit::{albums, photos, users};
+use crate::conduit::{albums, photos};
 use crate::connection::Repo;
+use crate::metadata::{file_stem, is_sidecar, ImageInfo, PhotoMetadata};
+use crate::middlewares::current_user::CurrentUser;
+use chrono::NaiveDateTime;
 use gotham::handler::HandlerResult;
 use gotham::helpers::http::response::{create_empty_response, create_response};
 use gotham::hyper::StatusCode;
 use gotham::state::{FromState, State};
-use gotham_middleware_jwt::AuthorizationToken;
+use photo_core::helpers::datetime::ts_seconds_option;
 use photo_core::helpers::uuid::Uuid;
-use photo_core::models::Photo;
+use photo_core::models::{
+    BulkResult, ModelError, Photo, PhotoChanges, PhotoImage, PhotoOperation, PhotoVersion, Role,
+    User,
+};
 use serde::{Deserialize, Serialize};
-use snafu::{Backtrace, OptionExt, ResultExt};
+use snafu::{Backtrace, ResultExt};
+use std::collections::HashMap;
 
 #[derive(Deserialize, StateData, StaticResponseExtender)]
 pub struct AlbumPathExtractor {
@@ -29,6 +38,12 @@ pub struct NewPhotoRequest {
     pub description: Option<String>,
     pub width: i32,
     pub height: i32,
+    pub filename: Option<String>,
+    /// Tag names. When missing, the keywords found in the uploaded file are used.
+    pub tags: Option<Vec<String>>,
+    /// Capture date as a UNIX timestamp.
+    #[serde(default, with = "ts_seconds_option")]
+    pub taken_at: Option<NaiveDateTime>,
 }
 
 #[derive(Serialize)]
@@ -47,43 +62,43 @@ pub async fn new_photo(mut state: State) -> HandlerResult {
         }
     };
     let album_data = AlbumPathExtractor::borrow_from(&state);
-    let token = AuthorizationToken::<AuthUser>::borrow_from(&state);
-    let email = token.0.claims.email();
 
-    let user = match users::find_by_email(repo.clone(), email)
-        .await
-        .context(UserIssue)
-    {
-        Ok(u) => u,
-        Err(e) => {
-            debug!("{:?}", e);
-            return Err((state, e.into()));
-        }
-    };
-    let album = match albums::find_by_id(repo.clone(), album_data.id.clone())
+    let user = CurrentUser::borrow_from(&state).0.clone();
+    let album = match albums::find_for(repo.clone(), &user, album_data.id.clone(), Role::Editor)
         .await
         .context(AlbumIssue)
     {
         Ok(a) => a,
-        Err(e) => {
-            debug!("{:?}", e);
-            return Err((state, e.into()));
+        Err(e) if is_not_found(&e) => {
+            let res = create_empty_response(&state, StatusCode::NOT_FOUND);
+            return Ok((state, res));
+        }
+        Err(e) if is_forbidden(&e) => {
+            let res = create_empty_response(&state, StatusCode::FORBIDDEN);
+            return Ok((state, res));
         }
+        Err(e) => return Err((state, e.into())),
     };
 
+    let audit = audit_context(&state, Some(&user));
+
     let response = match photos::create(
         repo,
+        &audit,
         &album,
         &user,
         req_data.index_in_album,
         req_data.s3_id,
         req_data.src,
         req_data.main_color,
-        req_data.title,
-        req_data.description,
+        not_blank(req_data.title),
+        not_blank(req_data.description),
         req_data.width,
         req_data.height,
         false,
+        req_data.filename,
+        req_data.tags,
+        req_data.taken_at,
     )
     .await
     {
@@ -125,16 +140,29 @@ pub async fn update_photo(mut state: State) -> HandlerResult {
     };
     let path_data = PhotoPathExtractor::borrow_from(&state);
 
-    let photo = match photos::find_by_id(repo.clone(), path_data.id.clone())
+    let user = CurrentUser::borrow_from(&state).0.clone();
+
+    let photo = match photos::find_for(repo.clone(), &user, path_data.id.clone(), Role::Editor)
         .await
         .context(PhotoIssue)
     {
         Ok(p) => p,
+        Err(e) if is_not_found(&e) => {
+            let res = create_empty_response(&state, StatusCode::NOT_FOUND);
+            return Ok((state, res));
+        }
+        Err(e) if is_forbidden(&e) => {
+            let res = create_empty_response(&state, StatusCode::FORBIDDEN);
+            return Ok((state, res));
+        }
         Err(e) => return Err((state, e.into())),
     };
 
+    let audit = audit_context(&state, Some(&user));
+
     let response = match photos::update(
         repo,
+        &audit,
         &photo,
         req_data.index_in_album,
         req_data.is_favorite,
@@ -157,83 +185,651 @@ pub async fn update_photo(mut state: State) -> HandlerResult {
     Ok((state, response))
 }
 
-pub async fn delete_photo(state: State) -> HandlerResult {
+/// Fields left out are not changed, `null` removes the title, description or capture date.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PatchPhotoRequest {
+    pub index_in_album: Option<i32>,
+    pub is_favorite: Option<bool>,
+    #[serde(default, deserialize_with = "double_option")]
+    pub title: Option<Option<String>>,
+    #[serde(default, deserialize_with = "double_option")]
+    pub description: Option<Option<String>>,
+    /// Capture date as a UNIX timestamp.
+    #[serde(default, deserialize_with = "double_option")]
+    pub taken_at: Option<Option<i64>>,
+}
+
+/// The photo along with its entity tag, to be sent back in `If-Match` when changing it.
+pub async fn get_photo(state: State) -> HandlerResult {
     let repo = Repo::borrow_from(&state).clone();
     let path_data = PhotoPathExtractor::borrow_from(&state);
 
-    let photo = match photos::find_by_id(repo.clone(), path_data.id.clone())
+    let user = CurrentUser::borrow_from(&state).0.clone();
+
+    let photo = match photos::find_for(repo, &user, path_data.id.clone(), Role::Viewer)
         .await
         .context(PhotoIssue)
     {
         Ok(p) => p,
+        Err(e) if is_not_found(&e) => {
+            let res = create_empty_response(&state, StatusCode::NOT_FOUND);
+            return Ok((state, res));
+        }
+        Err(e) if is_forbidden(&e) => {
+            let res = create_empty_response(&state, StatusCode::FORBIDDEN);
+            return Ok((state, res));
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    let etag = photo.etag();
+    let response = PhotoResponse { photo };
+    let body = serde_json::to_string(&response).expect("Fail to serialize photo");
+    let res = create_tagged_response(&state, &etag, body);
+
+    Ok((state, res))
+}
+
+/// Updates only the fields sent. With an `If-Match` or `If-Unmodified-Since` header, the photo is
+/// only changed if nobody else modified it in between.
+pub async fn patch_photo(mut state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let req_data: PatchPhotoRequest = match extract_json(&mut state).await.context(ExtractJson) {
+        Ok(data) => data,
         Err(e) => return Err((state, e.into())),
     };
+    let path_data = PhotoPathExtractor::borrow_from(&state);
+
+    let user = CurrentUser::borrow_from(&state).0.clone();
 
-    match delete(photo.s3_id.to_string().clone())
+    let photo = match photos::find_for(repo.clone(), &user, path_data.id.clone(), Role::Editor)
         .await
-        .context(AwsS3Issue)
+        .context(PhotoIssue)
     {
-        Ok(_) => (),
+        Ok(p) => p,
+        Err(e) if is_not_found(&e) => {
+            let res = create_empty_response(&state, StatusCode::NOT_FOUND);
+            return Ok((state, res));
+        }
+        Err(e) if is_forbidden(&e) => {
+            let res = create_empty_response(&state, StatusCode::FORBIDDEN);
+            return Ok((state, res));
+        }
         Err(e) => return Err((state, e.into())),
     };
 
-    let response = match photos::delete(repo, &photo).await.context(PhotoIssue) {
-        Ok(_) => {
-            let res = create_empty_response(&state, StatusCode::OK);
+    let taken_at = match req_data.taken_at.map(timestamp).transpose() {
+        Ok(date) => date,
+        Err(_) => {
+            let res = create_empty_response(&state, StatusCode::BAD_REQUEST);
+            return Ok((state, res));
+        }
+    };
+
+    let changes = PhotoChanges {
+        index_in_album: req_data.index_in_album,
+        is_favorite: req_data.is_favorite,
+        title: req_data.title.map(not_blank),
+        description: req_data.description.map(not_blank),
+        taken_at,
+    };
+
+    let audit = audit_context(&state, Some(&user));
 
-            res
+    let response = match photos::patch(repo, &audit, &photo, changes, precondition(&state))
+        .await
+        .context(PhotoIssue)
+    {
+        Ok(photo) => {
+            let etag = photo.etag();
+            let response = PhotoResponse { photo };
+            let body = serde_json::to_string(&response).expect("Fail to serialize photo");
+
+            create_tagged_response(&state, &etag, body)
         }
+        Err(PhotoHandlersError::PhotoIssue {
+            cause:
+                photos::PhotoError::Model {
+                    cause: ModelError::PreconditionFailed,
+                    ..
+                },
+            ..
+        }) => create_empty_response(&state, StatusCode::PRECONDITION_FAILED),
         Err(e) => return Err((state, e.into())),
     };
 
     Ok((state, response))
 }
 
-pub async fn upload_photo(state: State) -> HandlerResult {
-    let (state, data) = match handle_multipart(state).await {
+pub async fn delete_photo(state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let path_data = PhotoPathExtractor::borrow_from(&state);
+
+    let user = CurrentUser::borrow_from(&state).0.clone();
+
+    let photo = match photos::find_for(repo.clone(), &user, path_data.id.clone(), Role::Editor)
+        .await
+        .context(PhotoIssue)
+    {
+        Ok(p) => p,
+        Err(e) if is_not_found(&e) => {
+            let res = create_empty_response(&state, StatusCode::NOT_FOUND);
+            return Ok((state, res));
+        }
+        Err(e) if is_forbidden(&e) => {
+            let res = create_empty_response(&state, StatusCode::FORBIDDEN);
+            return Ok((state, res));
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    let versions = match photos::versions(repo.clone(), &photo)
+        .await
+        .context(PhotoIssue)
+    {
+        Ok(v) => v,
+        Err(e) => return Err((state, e.into())),
+    };
+
+    let audit = audit_context(&state, Some(&user));
+
+    let response = match photos::delete(repo, &audit, &photo)
+        .await
+        .context(PhotoIssue)
+    {
+        Ok(_) => create_empty_response(&state, StatusCode::OK),
+        Err(e) => return Err((state, e.into())),
+    };
+
+    // The photo is already gone at this point, a file that could not be removed is only logged.
+    let keys = std::iter::once(photo.s3_id).chain(versions.into_iter().map(|v| v.s3_id));
+    for key in keys {
+        if let Err(e) = delete(key.clone()).await {
+            error!("Could not delete {} from S3: {}", key, e);
+        }
+    }
+
+    Ok((state, response))
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum BulkOperationRequest {
+    Delete,
+    Favorite,
+    Unfavorite,
+    #[serde(rename_all = "camelCase")]
+    Move {
+        album_id: String,
+    },
+    SetTag {
+        tag: String,
+    },
+    SetDescription {
+        description: Option<String>,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkPhotosRequest {
+    pub photo_ids: Vec<String>,
+    pub operation: BulkOperationRequest,
+}
+
+#[derive(Serialize)]
+pub struct BulkPhotosResponse {
+    list: Vec<BulkResult>,
+}
+
+/// Applies the same operation to many photos at once. Every photo must belong to the user, the
+/// ones that do not are reported as not found. Requests above `MAX_BULK_PHOTOS` photos get a 400.
+pub async fn bulk_photos(mut state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let req_data: BulkPhotosRequest = match extract_json(&mut state).await.context(ExtractJson) {
+        Ok(data) => data,
+        Err(e) => return Err((state, e.into())),
+    };
+
+    let user = CurrentUser::borrow_from(&state).0.clone();
+
+    let operation = match req_data.operation {
+        BulkOperationRequest::Delete => PhotoOperation::Delete,
+        BulkOperationRequest::Favorite => PhotoOperation::SetFavorite(true),
+        BulkOperationRequest::Unfavorite => PhotoOperation::SetFavorite(false),
+        BulkOperationRequest::Move { album_id } => {
+            match albums::find_for(repo.clone(), &user, album_id, Role::Editor)
+                .await
+                .context(AlbumIssue)
+            {
+                Ok(album) => PhotoOperation::MoveTo(album),
+                Err(e) if is_not_found(&e) => {
+                    let res = create_empty_response(&state, StatusCode::NOT_FOUND);
+                    return Ok((state, res));
+                }
+                Err(e) if is_forbidden(&e) => {
+                    let res = create_empty_response(&state, StatusCode::FORBIDDEN);
+                    return Ok((state, res));
+                }
+                Err(e) => return Err((state, e.into())),
+            }
+        }
+        BulkOperationRequest::SetTag { tag } if tag.trim().is_empty() => {
+            let res = create_empty_response(&state, StatusCode::BAD_REQUEST);
+            return Ok((state, res));
+        }
+        BulkOperationRequest::SetTag { tag } => PhotoOperation::AddTag(tag),
+        BulkOperationRequest::SetDescription { description } => {
+            PhotoOperation::SetDescription(not_blank(description))
+        }
+    };
+
+    let audit = audit_context(&state, Some(&user));
+
+    let outcome = match photos::bulk(repo, &audit, &user, req_data.photo_ids, operation)
+        .await
+        .context(PhotoIssue)
+    {
+        Ok(outcome) => outcome,
+        Err(PhotoHandlersError::PhotoIssue {
+            cause:
+                photos::PhotoError::Model {
+                    cause: ModelError::TooManyPhotos,
+                    ..
+                },
+            ..
+        }) => {
+            let res = create_empty_response(&state, StatusCode::BAD_REQUEST);
+            return Ok((state, res));
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    // Photos are already gone at this point, a file that could not be removed is only logged.
+    for key in outcome.unused_objects {
+        if let Err(e) = delete(key.clone()).await {
+            error!("Could not delete {} from S3: {}", key, e);
+        }
+    }
+
+    let response = BulkPhotosResponse {
+        list: outcome.results,
+    };
+    let body = serde_json::to_string(&response).expect("Fail to serialize results");
+    let res = create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body);
+
+    Ok((state, res))
+}
+
+/// Replaces the image of a photo with the uploaded file. Title, description, tags, favorite and
+/// position are kept, while the previous image goes to the history of the photo.
+pub async fn replace_photo(state: State) -> HandlerResult {
+    let (state, entries) = match handle_multipart(state).await {
         Ok(d) => d,
         Err((state, e)) => return Err((state, e.into())),
     };
-    let multipart = match data.context(NoMultipartData) {
-        Ok(d) => d,
+    let repo = Repo::borrow_from(&state).clone();
+    let path_data = PhotoPathExtractor::borrow_from(&state);
+
+    let user = CurrentUser::borrow_from(&state).0.clone();
+
+    let photo = match photos::find_for(repo.clone(), &user, path_data.id.clone(), Role::Editor)
+        .await
+        .context(PhotoIssue)
+    {
+        Ok(p) => p,
+        Err(e) if is_not_found(&e) => {
+            let res = create_empty_response(&state, StatusCode::NOT_FOUND);
+            return Ok((state, res));
+        }
+        Err(e) if is_forbidden(&e) => {
+            let res = create_empty_response(&state, StatusCode::FORBIDDEN);
+            return Ok((state, res));
+        }
         Err(e) => return Err((state, e.into())),
     };
 
-    let content_type = match multipart.content_type {
-        Some(c) => Some(c.to_string()),
-        None => None,
+    let file = match entries
+        .into_iter()
+        .find(|entry| !entry.filename.as_deref().map_or(false, is_sidecar))
+    {
+        Some(f) => f,
+        None => return Err((state, PhotoHandlersError::NoMultipartData.into())),
     };
 
+    let data = file.data.clone();
+    let info = match tokio::task::spawn_blocking(move || ImageInfo::read(&data))
+        .await
+        .context(ImageTaskIssue)
+    {
+        Ok(Some(info)) => info,
+        Ok(None) => {
+            let res = create_empty_response(&state, StatusCode::BAD_REQUEST);
+            return Ok((state, res));
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    let content_type = file.content_type.map(|c| c.to_string());
     let key = Uuid::new_v4().to_string();
 
-    match upload(key.clone(), content_type, multipart.data)
+    match upload(key.clone(), content_type, file.data)
         .await
         .context(AwsS3Issue)
     {
-        Ok(s3) => s3,
+        Ok(_) => (),
         Err(e) => return Err((state, e.into())),
     };
 
-    let photo_url = match get_url(key.clone()).context(AwsS3Issue) {
-        Ok(u) => u,
+    let src = match get_url(key.clone()).context(AwsS3Issue) {
+        Ok(url) => url,
         Err(e) => return Err((state, e.into())),
     };
 
-    let response = UploadedPhotoResponse {
-        photo_url,
+    let image = PhotoImage {
         s3_id: key,
+        src,
+        main_color: info.main_color,
+        width: info.width,
+        height: info.height,
+        filename: file.filename,
+    };
+
+    let audit = audit_context(&state, Some(&user));
+
+    let response = match photos::replace_image(repo, &audit, &photo, image)
+        .await
+        .context(PhotoIssue)
+    {
+        Ok(photo) => {
+            let response = PhotoResponse { photo };
+            let body = serde_json::to_string(&response).expect("Fail to serialize photo");
+
+            create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body)
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    Ok((state, response))
+}
+
+#[derive(Serialize)]
+pub struct PhotoVersionsResponse {
+    list: Vec<PhotoVersion>,
+}
+
+/// Lists the images a photo used before, the most recently replaced first.
+pub async fn photo_versions(state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let path_data = PhotoPathExtractor::borrow_from(&state);
+
+    let user = CurrentUser::borrow_from(&state).0.clone();
+
+    let photo = match photos::find_for(repo.clone(), &user, path_data.id.clone(), Role::Viewer)
+        .await
+        .context(PhotoIssue)
+    {
+        Ok(p) => p,
+        Err(e) if is_not_found(&e) => {
+            let res = create_empty_response(&state, StatusCode::NOT_FOUND);
+            return Ok((state, res));
+        }
+        Err(e) if is_forbidden(&e) => {
+            let res = create_empty_response(&state, StatusCode::FORBIDDEN);
+            return Ok((state, res));
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    let response = match photos::versions(repo, &photo).await.context(PhotoIssue) {
+        Ok(list) => {
+            let response = PhotoVersionsResponse { list };
+            let body = serde_json::to_string(&response).expect("Fail to serialize versions");
+
+            create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body)
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    Ok((state, response))
+}
+
+#[derive(Deserialize, StateData, StaticResponseExtender)]
+pub struct PhotoVersionPathExtractor {
+    id: String,
+    version_id: String,
+}
+
+/// Brings back a previous image of a photo.
+pub async fn revert_photo(state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let path_data = PhotoVersionPathExtractor::borrow_from(&state);
+
+    let user = CurrentUser::borrow_from(&state).0.clone();
+
+    let photo = match photos::find_for(repo.clone(), &user, path_data.id.clone(), Role::Editor)
+        .await
+        .context(PhotoIssue)
+    {
+        Ok(p) => p,
+        Err(e) if is_not_found(&e) => {
+            let res = create_empty_response(&state, StatusCode::NOT_FOUND);
+            return Ok((state, res));
+        }
+        Err(e) if is_forbidden(&e) => {
+            let res = create_empty_response(&state, StatusCode::FORBIDDEN);
+            return Ok((state, res));
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    let audit = audit_context(&state, Some(&user));
+
+    let response = match photos::revert(repo, &audit, &photo, path_data.version_id.clone())
+        .await
+        .context(PhotoIssue)
+    {
+        Ok(photo) => {
+            let response = PhotoResponse { photo };
+            let body = serde_json::to_string(&response).expect("Fail to serialize photo");
+
+            create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body)
+        }
+        Err(PhotoHandlersError::PhotoIssue {
+            cause:
+                photos::PhotoError::Model {
+                    cause: ModelError::PhotoVersionNotFound,
+                    ..
+                },
+            ..
+        }) => create_empty_response(&state, StatusCode::NOT_FOUND),
+        Err(e) => return Err((state, e.into())),
+    };
+
+    Ok((state, response))
+}
+
+/// Whether the album or photo does not exist or belongs to a studio the user is not a member of.
+fn is_not_found(e: &PhotoHandlersError) -> bool {
+    match e {
+        PhotoHandlersError::AlbumIssue {
+            cause:
+                albums::AlbumError::Model {
+                    cause: ModelError::AlbumNotFound,
+                    ..
+                },
+            ..
+        }
+        | PhotoHandlersError::PhotoIssue {
+            cause:
+                photos::PhotoError::Model {
+                    cause: ModelError::PhotoNotFound,
+                    ..
+                },
+            ..
+        } => true,
+        _ => false,
+    }
+}
+
+/// Whether the album or photo belongs to a studio of the user, but the role of the user is too low.
+fn is_forbidden(e: &PhotoHandlersError) -> bool {
+    match e {
+        PhotoHandlersError::AlbumIssue {
+            cause:
+                albums::AlbumError::Model {
+                    cause: ModelError::Forbidden,
+                    ..
+                },
+            ..
+        }
+        | PhotoHandlersError::PhotoIssue {
+            cause:
+                photos::PhotoError::Model {
+                    cause: ModelError::Forbidden,
+                    ..
+                },
+            ..
+        } => true,
+        _ => false,
+    }
+}
+
+/// Uploads a photo to S3. Title, caption and keywords found in the file, or in an `.xmp` sidecar
+/// sent along with it, are returned and later used to prefill the photo when it is created. Only
+/// one image can be sent, `upload_photos` takes many.
+pub async fn upload_photo(state: State) -> HandlerResult {
+    let (state, entries) = match handle_multipart(state).await {
+        Ok(d) => d,
+        Err((state, e)) => return Err((state, e.into())),
+    };
+    let repo = Repo::borrow_from(&state).clone();
+
+    let images = entries
+        .iter()
+        .filter(|entry| !entry.filename.as_deref().map_or(false, is_sidecar))
+        .count();
+    if images > 1 {
+        let res = create_empty_response(&state, StatusCode::BAD_REQUEST);
+        return Ok((state, res));
+    }
+
+    let user = CurrentUser::borrow_from(&state).0.clone();
+
+    let mut uploaded = match upload_files(repo, &user, entries).await {
+        Ok(list) if !list.is_empty() => list,
+        Ok(_) => return Err((state, PhotoHandlersError::NoMultipartData.into())),
+        Err(e) => return Err((state, e.into())),
     };
+
+    let response = uploaded.remove(0);
     let body = serde_json::to_string(&response).expect("Fail to serialize response");
     let res = create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body);
 
     Ok((state, res))
 }
 
+#[derive(Serialize)]
+pub struct UploadedPhotosResponse {
+    list: Vec<UploadedPhotoResponse>,
+}
+
+/// Uploads many photos at once, each one can come with an `.xmp` sidecar of the same name.
+pub async fn upload_photos(state: State) -> HandlerResult {
+    let (state, entries) = match handle_multipart(state).await {
+        Ok(d) => d,
+        Err((state, e)) => return Err((state, e.into())),
+    };
+    let repo = Repo::borrow_from(&state).clone();
+
+    let user = CurrentUser::borrow_from(&state).0.clone();
+
+    let response = match upload_files(repo, &user, entries).await {
+        Ok(list) => UploadedPhotosResponse { list },
+        Err(e) => return Err((state, e.into())),
+    };
+    let body = serde_json::to_string(&response).expect("Fail to serialize response");
+    let res = create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body);
+
+    Ok((state, res))
+}
+
+async fn upload_files(
+    repo: Repo,
+    user: &User,
+    entries: Vec<MultiPartData>,
+) -> Result<Vec<UploadedPhotoResponse>, PhotoHandlersError> {
+    let (sidecar_entries, files): (Vec<MultiPartData>, Vec<MultiPartData>) = entries
+        .into_iter()
+        .partition(|entry| entry.filename.as_deref().map_or(false, is_sidecar));
+
+    let sidecars: HashMap<String, PhotoMetadata> = sidecar_entries
+        .iter()
+        .filter_map(|entry| {
+            let filename = entry.filename.as_deref()?;
+            let metadata = PhotoMetadata::from_sidecar(&entry.data);
+
+            Some((file_stem(filename).to_string(), metadata))
+        })
+        .collect();
+
+    let mut uploaded = Vec::new();
+    for file in files {
+        let embedded = PhotoMetadata::from_image(&file.data);
+        let sidecar = file
+            .filename
+            .as_deref()
+            .and_then(|filename| sidecars.get(file_stem(filename)));
+        let metadata = match sidecar {
+            Some(sidecar) => sidecar.clone().or(embedded),
+            None => embedded,
+        };
+
+        let content_type = file.content_type.map(|c| c.to_string());
+        let key = Uuid::new_v4().to_string();
+
+        upload(key.clone(), content_type, file.data)
+            .await
+            .context(AwsS3Issue)?;
+        let photo_url = get_url(key.clone()).context(AwsS3Issue)?;
+
+        if metadata != PhotoMetadata::default() {
+            photos::save_upload(
+                repo.clone(),
+                user,
+                key.clone(),
+                metadata.title.clone(),
+                metadata.description.clone(),
+                metadata.keywords.clone(),
+            )
+            .await
+            .context(PhotoIssue)?;
+        }
+
+        uploaded.push(UploadedPhotoResponse {
+            photo_url,
+            s3_id: key,
+            filename: file.filename,
+            metadata,
+        });
+    }
+
+    Ok(uploaded)
+}
+
+/// Empty values sent by the client count as missing, so they do not hide the file metadata.
+fn not_blank(value: Option<String>) -> Option<String> {
+    value.filter(|v| !v.trim().is_empty())
+}
+
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UploadedPhotoResponse {
     photo_url: String,
     s3_id: String,
+    filename: Option<String>,
+    metadata: PhotoMetadata,
 }
 
 #[derive(Debug, Snafu)]
@@ -245,13 +841,6 @@ pub enum PhotoHandlersError {
         backtrace: Backtrace,
     },
 
-    #[snafu(display("Could not get user: {}", cause))]
-    UserIssue {
-        #[snafu(source)]
-        cause: users::UserError,
-        backtrace: Backtrace,
-    },
-
     #[snafu(display("Could not get album: {}", cause))]
     AlbumIssue {
         #[snafu(source)]
@@ -273,6 +862,12 @@ pub enum PhotoHandlersError {
         backtrace: Backtrace,
     },
 
+    #[snafu(display("Could not read image: {}", source))]
+    ImageTaskIssue {
+        source: tokio::task::JoinError,
+        backtrace: Backtrace,
+    },
+
     NoMultipartData,
 
     MultipartNoFilename,
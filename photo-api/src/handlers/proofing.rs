@@ -0,0 +1,534 @@
+use super::utils::{extract_json, has_share_link_access, HandlerUtilsError};
+use crate::conduit::{proofing, share_links};
+use crate::connection::Repo;
+use crate::middlewares::current_user::CurrentUser;
+use gotham::handler::HandlerResult;
+use gotham::helpers::http::response::{create_empty_response, create_response};
+use gotham::state::{FromState, State};
+use hyper::header::{HeaderValue, CONTENT_DISPOSITION};
+use hyper::StatusCode;
+use photo_core::models::{Album, ModelError, ProofingComment, ProofingSummary, ShareLink};
+use serde::{Deserialize, Serialize};
+use snafu::{Backtrace, ResultExt};
+use std::borrow::Cow;
+
+#[derive(Deserialize, StateData, StaticResponseExtender)]
+pub struct TokenPathExtractor {
+    token: String,
+}
+
+#[derive(Deserialize, StateData, StaticResponseExtender)]
+pub struct TokenPhotoPathExtractor {
+    token: String,
+    photo_id: String,
+}
+
+#[derive(Deserialize, StateData, StaticResponseExtender)]
+pub struct ShareLinkPathExtractor {
+    id: String,
+}
+
+#[derive(Deserialize, StateData, StaticResponseExtender)]
+pub struct ExportQueryExtractor {
+    /// Only export the selections of this client.
+    email: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ProofingClientRequest {
+    pub name: String,
+    pub email: String,
+}
+
+#[derive(Deserialize)]
+pub struct SelectionRequest {
+    /// Secret the client got when registering.
+    pub secret: String,
+    pub selected: bool,
+}
+
+#[derive(Deserialize)]
+pub struct CommentRequest {
+    /// Secret the client got when registering.
+    pub secret: String,
+    pub body: String,
+}
+
+#[derive(Serialize)]
+pub struct ProofingSummaryResponse {
+    summary: ProofingSummary,
+}
+
+#[derive(Serialize)]
+pub struct ProofingClientResponse {
+    summary: ProofingSummary,
+    /// Only sent once, the client needs it to pick and comment photos.
+    secret: String,
+}
+
+#[derive(Serialize)]
+pub struct CommentResponse {
+    comment: ProofingComment,
+}
+
+/// Registers a client by name and email on a proofing link, returning the secret they pick and
+/// comment photos with. Emails already registered get a 409, so the link alone doesn't let anyone
+/// act as another client.
+pub async fn register_client(mut state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let path_data = TokenPathExtractor::take_from(&mut state);
+    let req_data: ProofingClientRequest =
+        match extract_json(&mut state).await.context(HandlerUtilsIssue) {
+            Ok(data) => data,
+            Err(e) => return Err((state, e.into())),
+        };
+
+    if !is_valid_client(&req_data.name, &req_data.email) {
+        let res = create_empty_response(&state, StatusCode::BAD_REQUEST);
+        return Ok((state, res));
+    }
+
+    let (link, album) = match find_proofing_link(&state, repo.clone(), path_data.token).await {
+        Ok(Ok(found)) => found,
+        Ok(Err(status)) => {
+            let res = create_empty_response(&state, status);
+            return Ok((state, res));
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    let response = match proofing::register(repo, &link, req_data.name, req_data.email)
+        .await
+        .context(ProofingIssue)
+    {
+        Ok((summary, secret)) => {
+            let response = ProofingClientResponse { summary, secret };
+            let body = serde_json::to_string(&response).expect("Failed to serialize proofing");
+
+            create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body)
+        }
+        Err(e) if is_client_taken(&e) => create_empty_response(&state, StatusCode::CONFLICT),
+        Err(e) => return Err((state, e.into())),
+    };
+
+    Ok((state, response))
+}
+
+/// Picks or unpicks a photo of the shared album for the client.
+pub async fn select_photo(mut state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let path_data = TokenPhotoPathExtractor::take_from(&mut state);
+    let req_data: SelectionRequest = match extract_json(&mut state).await.context(HandlerUtilsIssue)
+    {
+        Ok(data) => data,
+        Err(e) => return Err((state, e.into())),
+    };
+
+    let (link, album) = match find_proofing_link(&state, repo.clone(), path_data.token).await {
+        Ok(Ok(found)) => found,
+        Ok(Err(status)) => {
+            let res = create_empty_response(&state, status);
+            return Ok((state, res));
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    let response = match proofing::select(
+        repo,
+        &link,
+        &album,
+        req_data.secret,
+        path_data.photo_id,
+        req_data.selected,
+    )
+    .await
+    .context(ProofingIssue)
+    {
+        Ok(summary) => {
+            let response = ProofingSummaryResponse { summary };
+            let body = serde_json::to_string(&response).expect("Failed to serialize proofing");
+
+            create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body)
+        }
+        Err(e) if is_not_found(&e) => create_empty_response(&state, StatusCode::NOT_FOUND),
+        Err(e) if is_invalid_secret(&e) => create_empty_response(&state, StatusCode::UNAUTHORIZED),
+        Err(e) => return Err((state, e.into())),
+    };
+
+    Ok((state, response))
+}
+
+pub async fn comment_photo(mut state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let path_data = TokenPhotoPathExtractor::take_from(&mut state);
+    let req_data: CommentRequest = match extract_json(&mut state).await.context(HandlerUtilsIssue) {
+        Ok(data) => data,
+        Err(e) => return Err((state, e.into())),
+    };
+
+    if req_data.body.trim().is_empty() {
+        let res = create_empty_response(&state, StatusCode::BAD_REQUEST);
+        return Ok((state, res));
+    }
+
+    let (link, album) = match find_proofing_link(&state, repo.clone(), path_data.token).await {
+        Ok(Ok(found)) => found,
+        Ok(Err(status)) => {
+            let res = create_empty_response(&state, status);
+            return Ok((state, res));
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    let response = match proofing::comment(
+        repo,
+        &link,
+        &album,
+        req_data.secret,
+        path_data.photo_id,
+        req_data.body,
+    )
+    .await
+    .context(ProofingIssue)
+    {
+        Ok(comment) => {
+            let response = CommentResponse { comment };
+            let body = serde_json::to_string(&response).expect("Failed to serialize comment");
+
+            create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body)
+        }
+        Err(e) if is_not_found(&e) => create_empty_response(&state, StatusCode::NOT_FOUND),
+        Err(e) if is_invalid_secret(&e) => create_empty_response(&state, StatusCode::UNAUTHORIZED),
+        Err(e) => return Err((state, e.into())),
+    };
+
+    Ok((state, response))
+}
+
+#[derive(Deserialize)]
+pub struct ProofingSettingsRequest {
+    pub enabled: bool,
+}
+
+#[derive(Serialize)]
+pub struct ShareLinkResponse {
+    link: ShareLink,
+}
+
+pub async fn set_proofing(mut state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let req_data: ProofingSettingsRequest =
+        match extract_json(&mut state).await.context(HandlerUtilsIssue) {
+            Ok(data) => data,
+            Err(e) => return Err((state, e.into())),
+        };
+    let path_data = ShareLinkPathExtractor::borrow_from(&state);
+
+    let user = CurrentUser::borrow_from(&state).0.clone();
+
+    let response = match proofing::set_enabled(repo, &user, path_data.id.clone(), req_data.enabled)
+        .await
+        .context(ProofingIssue)
+    {
+        Ok(link) => {
+            let response = ShareLinkResponse { link };
+            let body = serde_json::to_string(&response).expect("Failed to serialize share link");
+
+            create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body)
+        }
+        Err(e) if is_forbidden(&e) => create_empty_response(&state, StatusCode::FORBIDDEN),
+        Err(e) => return Err((state, e.into())),
+    };
+
+    Ok((state, response))
+}
+
+#[derive(Serialize)]
+pub struct ProofingSummariesResponse {
+    list: Vec<ProofingSummary>,
+}
+
+/// Lists every client of a share link with the photos they picked and their comments.
+pub async fn proofing_summary(state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let path_data = ShareLinkPathExtractor::borrow_from(&state);
+
+    let user = CurrentUser::borrow_from(&state).0.clone();
+
+    let response = match proofing::summaries(repo, &user, path_data.id.clone())
+        .await
+        .context(ProofingIssue)
+    {
+        Ok(list) => {
+            let response = ProofingSummariesResponse { list };
+            let body = serde_json::to_string(&response).expect("Failed to serialize proofing");
+
+            create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body)
+        }
+        Err(e) if is_forbidden(&e) => create_empty_response(&state, StatusCode::FORBIDDEN),
+        Err(e) => return Err((state, e.into())),
+    };
+
+    Ok((state, response))
+}
+
+#[derive(Serialize)]
+struct SelectionRow<'a> {
+    filename: Cow<'a, str>,
+    title: Cow<'a, str>,
+    client: Cow<'a, str>,
+    email: Cow<'a, str>,
+}
+
+impl<'a> SelectionRow<'a> {
+    fn new(filename: &'a str, title: &'a str, client: &'a str, email: &'a str) -> Self {
+        Self {
+            filename: escape_formula(filename),
+            title: escape_formula(title),
+            client: escape_formula(client),
+            email: escape_formula(email),
+        }
+    }
+}
+
+/// Quotes values a spreadsheet would run as a formula. Names and titles come from clients, so
+/// opening the export must not evaluate them.
+fn escape_formula(value: &str) -> Cow<'_, str> {
+    if value.starts_with(&['=', '+', '-', '@', '\t', '\r'][..]) {
+        Cow::Owned(format!("'{}", value))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+/// Exports the selected photos as CSV, one row per client selection, so the filenames can be
+/// pasted into the Lightroom library filter.
+pub async fn export_selections(state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let path_data = ShareLinkPathExtractor::borrow_from(&state);
+    let query_data = ExportQueryExtractor::borrow_from(&state);
+
+    let user = CurrentUser::borrow_from(&state).0.clone();
+
+    let summaries = match proofing::summaries(repo, &user, path_data.id.clone())
+        .await
+        .context(ProofingIssue)
+    {
+        Ok(list) => list,
+        Err(e) if is_forbidden(&e) => {
+            let res = create_empty_response(&state, StatusCode::FORBIDDEN);
+            return Ok((state, res));
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    let client_email = query_data.email.as_ref().map(|e| e.trim().to_lowercase());
+    let body = match selections_csv(&summaries, client_email.as_deref()).context(CsvIssue) {
+        Ok(body) => body,
+        Err(e) => return Err((state, e.into())),
+    };
+
+    let mut res = create_response(&state, StatusCode::OK, mime::TEXT_CSV, body);
+    res.headers_mut().insert(
+        CONTENT_DISPOSITION,
+        HeaderValue::from_static("attachment; filename=\"selections.csv\""),
+    );
+
+    Ok((state, res))
+}
+
+fn selections_csv(summaries: &[ProofingSummary], email: Option<&str>) -> csv::Result<Vec<u8>> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+
+    for summary in summaries {
+        if email.map_or(false, |e| e != summary.client.email) {
+            continue;
+        }
+
+        for photo in &summary.selections {
+            let filename = photo
+                .filename
+                .as_deref()
+                .or_else(|| photo.title.as_deref())
+                .unwrap_or(&photo.s3_id);
+
+            writer.serialize(SelectionRow::new(
+                filename,
+                photo.title.as_deref().unwrap_or(""),
+                &summary.client.name,
+                &summary.client.email,
+            ))?;
+        }
+    }
+
+    writer
+        .into_inner()
+        .map_err(|e| csv::Error::from(e.into_error()))
+}
+
+/// Finds the share link of the token along with its album and checks the client can use it for
+/// proofing. When they cannot, returns the status to reply with instead: 404 for unknown, expired
+/// or revoked links, 403 when proofing is disabled and 401 when the link was not unlocked.
+async fn find_proofing_link(
+    state: &State,
+    repo: Repo,
+    token: String,
+) -> Result<Result<(ShareLink, Album), StatusCode>, ProofingHandlersError> {
+    let (link, album) = match share_links::find_valid(repo, token)
+        .await
+        .context(ShareLinkIssue)
+    {
+        Ok(found) => found,
+        Err(e) if is_not_found(&e) => return Ok(Err(StatusCode::NOT_FOUND)),
+        Err(e) => return Err(e),
+    };
+
+    if !link.proofing {
+        return Ok(Err(StatusCode::FORBIDDEN));
+    }
+
+    if !has_share_link_access(state, &link, &album) {
+        return Ok(Err(StatusCode::UNAUTHORIZED));
+    }
+
+    Ok(Ok((link, album)))
+}
+
+fn is_valid_client(name: &str, email: &str) -> bool {
+    !name.trim().is_empty() && email.contains('@')
+}
+
+fn is_not_found(e: &ProofingHandlersError) -> bool {
+    match e {
+        ProofingHandlersError::ShareLinkIssue {
+            cause:
+                share_links::ShareLinkError::Model {
+                    cause: ModelError::InvalidShareLink,
+                    ..
+                },
+            ..
+        } => true,
+        ProofingHandlersError::ProofingIssue {
+            cause:
+                proofing::ProofingError::Model {
+                    cause: ModelError::PhotoNotInAlbum,
+                    ..
+                },
+            ..
+        } => true,
+        _ => false,
+    }
+}
+
+fn is_client_taken(e: &ProofingHandlersError) -> bool {
+    match e {
+        ProofingHandlersError::ProofingIssue {
+            cause:
+                proofing::ProofingError::Model {
+                    cause: ModelError::ProofingClientTaken,
+                    ..
+                },
+            ..
+        } => true,
+        _ => false,
+    }
+}
+
+fn is_invalid_secret(e: &ProofingHandlersError) -> bool {
+    match e {
+        ProofingHandlersError::ProofingIssue {
+            cause:
+                proofing::ProofingError::Model {
+                    cause: ModelError::InvalidProofingSecret,
+                    ..
+                },
+            ..
+        } => true,
+        _ => false,
+    }
+}
+
+fn is_forbidden(e: &ProofingHandlersError) -> bool {
+    match e {
+        ProofingHandlersError::ProofingIssue {
+            cause:
+                proofing::ProofingError::Model {
+                    cause: ModelError::Forbidden,
+                    ..
+                },
+            ..
+        } => true,
+        _ => false,
+    }
+}
+
+#[derive(Debug, Snafu)]
+pub enum ProofingHandlersError {
+    #[snafu(display("Could not get request: {}", cause))]
+    HandlerUtilsIssue {
+        #[snafu(source)]
+        cause: HandlerUtilsError,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Could not get proofing: {}", cause))]
+    ProofingIssue {
+        #[snafu(source)]
+        cause: proofing::ProofingError,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Could not get share link: {}", cause))]
+    ShareLinkIssue {
+        #[snafu(source)]
+        cause: share_links::ShareLinkError,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Could not write CSV: {}", source))]
+    CsvIssue {
+        source: csv::Error,
+        backtrace: Backtrace,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formulas_are_escaped() {
+        assert_eq!(escape_formula("=HYPERLINK(\"x\")"), "'=HYPERLINK(\"x\")");
+        assert_eq!(escape_formula("+1"), "'+1");
+        assert_eq!(escape_formula("-1"), "'-1");
+        assert_eq!(escape_formula("@SUM(A1)"), "'@SUM(A1)");
+        assert_eq!(escape_formula("\tcmd"), "'\tcmd");
+        assert_eq!(escape_formula("\rcmd"), "'\rcmd");
+    }
+
+    #[test]
+    fn plain_values_are_kept() {
+        assert!(matches!(escape_formula("IMG_0042.jpg"), Cow::Borrowed(_)));
+        assert_eq!(escape_formula("Jane = bride"), "Jane = bride");
+        assert_eq!(escape_formula(""), "");
+    }
+
+    #[test]
+    fn rows_escape_every_cell() {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        writer
+            .serialize(SelectionRow::new(
+                "IMG_0042.jpg",
+                "=1+1",
+                "@evil",
+                "jane@example.com",
+            ))
+            .unwrap();
+        let csv = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+
+        assert_eq!(
+            csv,
+            "filename,title,client,email\nIMG_0042.jpg,'=1+1,'@evil,jane@example.com\n"
+        );
+    }
+}
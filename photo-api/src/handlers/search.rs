@@ -0,0 +1,62 @@
+use crate::conduit::search;
+use crate::connection::Repo;
+use crate::middlewares::current_user::CurrentUser;
+use gotham::handler::HandlerResult;
+use gotham::helpers::http::response::create_response;
+use gotham::state::{FromState, State};
+use hyper::StatusCode;
+use photo_core::models::SearchResults;
+use serde::{Deserialize, Serialize};
+use snafu::{Backtrace, ResultExt};
+
+const DEFAULT_LIMIT: i64 = 20;
+const MAX_LIMIT: i64 = 100;
+
+#[derive(Deserialize, StateData, StaticResponseExtender)]
+pub struct SearchQueryExtractor {
+    q: String,
+    /// Maximum number of albums, and of photos, to return.
+    limit: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct SearchResponse {
+    results: SearchResults,
+}
+
+pub async fn search(state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let query_data = SearchQueryExtractor::borrow_from(&state);
+    let limit = query_data
+        .limit
+        .unwrap_or(DEFAULT_LIMIT)
+        .max(1)
+        .min(MAX_LIMIT);
+
+    let user = CurrentUser::borrow_from(&state).0.clone();
+
+    let response = match search::find(repo, &user, query_data.q.clone(), limit)
+        .await
+        .context(SearchIssue)
+    {
+        Ok(results) => {
+            let response = SearchResponse { results };
+            let body = serde_json::to_string(&response).expect("Failed to serialize results");
+
+            create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body)
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    Ok((state, response))
+}
+
+#[derive(Debug, Snafu)]
+pub enum SearchHandlersError {
+    #[snafu(display("Could not search: {}", cause))]
+    SearchIssue {
+        #[snafu(source)]
+        cause: search::SearchError,
+        backtrace: Backtrace,
+    },
+}
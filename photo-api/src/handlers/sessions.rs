@@ -0,0 +1,285 @@
+use super::utils::{client_ip, extract_json, user_agent, HandlerUtilsError};
+use crate::auth::{encode_token, AuthUser, ACCESS_TOKEN_EXPIRY};
+use crate::conduit::{login_codes, sessions};
+use crate::connection::Repo;
+use crate::middlewares::current_user::CurrentUser;
+use chrono::NaiveDateTime;
+use gotham::handler::HandlerResult;
+use gotham::helpers::http::response::{create_empty_response, create_response};
+use gotham::state::{FromState, State};
+use gotham_middleware_jwt::AuthorizationToken;
+use hyper::StatusCode;
+use photo_core::models::{ModelError, Session, User};
+use serde::{Deserialize, Serialize};
+use snafu::{Backtrace, ResultExt};
+
+#[derive(Deserialize, StateData, StaticResponseExtender)]
+pub struct SessionPathExtractor {
+    id: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RefreshTokenRequest {
+    pub refresh_token: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RefreshTokenResponse {
+    token: String,
+    refresh_token: String,
+    expires_in: u64,
+}
+
+/// Exchanges a refresh token for a new access token. The refresh token is rotated, the one sent
+/// can't be used again.
+pub async fn refresh_token(mut state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let req_data: RefreshTokenRequest =
+        match extract_json(&mut state).await.context(HandlerUtilsIssue) {
+            Ok(data) => data,
+            Err(e) => return Err((state, e.into())),
+        };
+
+    let rotated = sessions::rotate(
+        repo,
+        req_data.refresh_token,
+        user_agent(&state),
+        Some(client_ip(&state)),
+    )
+    .await
+    .context(SessionIssue);
+
+    let response = match rotated {
+        Ok((session, user, refresh_token)) => {
+            let response = RefreshTokenResponse {
+                token: encode_token(&user, &session, ACCESS_TOKEN_EXPIRY),
+                refresh_token,
+                expires_in: ACCESS_TOKEN_EXPIRY,
+            };
+            let body = serde_json::to_string(&response).expect("Failed to serialize token");
+
+            create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body)
+        }
+        Err(SessionHandlersError::SessionIssue {
+            cause:
+                sessions::SessionError::Model {
+                    cause: ModelError::InvalidRefreshToken,
+                    ..
+                },
+            ..
+        }) => create_empty_response(&state, StatusCode::UNAUTHORIZED),
+        Err(e) => return Err((state, e.into())),
+    };
+
+    Ok((state, response))
+}
+
+#[derive(Deserialize)]
+pub struct LoginCodeRequest {
+    pub code: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoginCodeResponse {
+    user: User,
+    token: String,
+    refresh_token: String,
+    expires_in: u64,
+}
+
+/// Exchanges the code the client got redirected with after a login for tokens, starting a
+/// session. The code can only be used once.
+pub async fn exchange_login_code(mut state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let req_data: LoginCodeRequest = match extract_json(&mut state).await.context(HandlerUtilsIssue)
+    {
+        Ok(data) => data,
+        Err(e) => return Err((state, e.into())),
+    };
+
+    let user = match login_codes::take(repo.clone(), req_data.code)
+        .await
+        .context(LoginCodeIssue)
+    {
+        Ok(u) => u,
+        Err(SessionHandlersError::LoginCodeIssue {
+            cause:
+                login_codes::LoginCodeError::Model {
+                    cause: ModelError::InvalidLoginCode,
+                    ..
+                },
+            ..
+        }) => {
+            let res = create_empty_response(&state, StatusCode::UNAUTHORIZED);
+            return Ok((state, res));
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    let started = sessions::start(repo, &user, user_agent(&state), Some(client_ip(&state)))
+        .await
+        .context(SessionIssue);
+    let (session, refresh_token) = match started {
+        Ok(s) => s,
+        Err(e) => return Err((state, e.into())),
+    };
+
+    let response = LoginCodeResponse {
+        token: encode_token(&user, &session, ACCESS_TOKEN_EXPIRY),
+        user,
+        refresh_token,
+        expires_in: ACCESS_TOKEN_EXPIRY,
+    };
+    let body = serde_json::to_string(&response).expect("Failed to serialize token");
+    let res = create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body);
+
+    Ok((state, res))
+}
+
+#[derive(Serialize)]
+pub struct SessionResponse {
+    #[serde(flatten)]
+    session: Session,
+    /// Whether this is the session the request was made with.
+    current: bool,
+}
+
+#[derive(Serialize)]
+pub struct SessionsResponse {
+    list: Vec<SessionResponse>,
+}
+
+/// Lists the sessions of the user, with the device they were last used from.
+pub async fn all_sessions(state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let user = CurrentUser::borrow_from(&state).0.clone();
+    let current_id = AuthorizationToken::<AuthUser>::try_borrow_from(&state)
+        .map(|token| token.0.claims.session_id());
+
+    let response = match sessions::find_by_user(repo, &user)
+        .await
+        .context(SessionIssue)
+    {
+        Ok(list) => {
+            let list = list
+                .into_iter()
+                .map(|session| SessionResponse {
+                    current: current_id.as_deref() == Some(session.id.to_string().as_str()),
+                    session,
+                })
+                .collect();
+            let response = SessionsResponse { list };
+            let body = serde_json::to_string(&response).expect("Failed to serialize sessions");
+
+            create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body)
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    Ok((state, response))
+}
+
+/// Ends a session, its refresh token and access tokens stop working.
+pub async fn revoke_session(state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let path_data = SessionPathExtractor::borrow_from(&state);
+    let user = CurrentUser::borrow_from(&state).0.clone();
+
+    let response = match sessions::revoke(repo, &user, path_data.id.clone())
+        .await
+        .context(SessionIssue)
+    {
+        Ok(_) => create_empty_response(&state, StatusCode::OK),
+        Err(SessionHandlersError::SessionIssue {
+            cause:
+                sessions::SessionError::Model {
+                    cause: ModelError::SessionNotFound,
+                    ..
+                },
+            ..
+        }) => create_empty_response(&state, StatusCode::NOT_FOUND),
+        Err(e) => return Err((state, e.into())),
+    };
+
+    Ok((state, response))
+}
+
+/// Ends every session of the user, including the current one.
+pub async fn revoke_all_sessions(state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let user = CurrentUser::borrow_from(&state).0.clone();
+
+    let response = match sessions::revoke_all(repo, &user)
+        .await
+        .context(SessionIssue)
+    {
+        Ok(_) => create_empty_response(&state, StatusCode::OK),
+        Err(e) => return Err((state, e.into())),
+    };
+
+    Ok((state, response))
+}
+
+/// Logs out: the access token of the request is denied until it expires, and its session ends so
+/// the refresh token can't be used either. API keys are revoked on their own and get a 400.
+pub async fn revoke_token(state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let user = CurrentUser::borrow_from(&state).0.clone();
+    let claims = match AuthorizationToken::<AuthUser>::try_borrow_from(&state) {
+        Some(token) => token.0.claims.clone(),
+        None => {
+            let res = create_empty_response(&state, StatusCode::BAD_REQUEST);
+            return Ok((state, res));
+        }
+    };
+    let expires_at = NaiveDateTime::from_timestamp(claims.exp() as i64, 0);
+
+    if let Err(e) = sessions::revoke_token(repo.clone(), claims.jti(), expires_at).await {
+        return Err((state, e.into()));
+    }
+
+    let response = match sessions::revoke(repo, &user, claims.session_id())
+        .await
+        .context(SessionIssue)
+    {
+        Ok(_)
+        | Err(SessionHandlersError::SessionIssue {
+            cause:
+                sessions::SessionError::Model {
+                    cause: ModelError::SessionNotFound,
+                    ..
+                },
+            ..
+        }) => create_empty_response(&state, StatusCode::OK),
+        Err(e) => return Err((state, e.into())),
+    };
+
+    Ok((state, response))
+}
+
+#[derive(Debug, Snafu)]
+pub enum SessionHandlersError {
+    #[snafu(display("Could not get request: {}", cause))]
+    HandlerUtilsIssue {
+        #[snafu(source)]
+        cause: HandlerUtilsError,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Could not get session: {}", cause))]
+    SessionIssue {
+        #[snafu(source)]
+        cause: sessions::SessionError,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Could not use login code: {}", cause))]
+    LoginCodeIssue {
+        #[snafu(source)]
+        cause: login_codes::LoginCodeError,
+        backtrace: Backtrace,
+    },
+}
@@ -0,0 +1,554 @@
+use super::utils::{
+    client_ip, create_archive_response, extract_json, has_share_link_access, photo_pagination,
+    timestamp, HandlerUtilsError,
+};
+use crate::archive::{album_archive, DownloadSize};
+use crate::auth::throttle::{ALBUM_UNLOCK_THROTTLE, UNLOCK_THROTTLE};
+use crate::auth::{encode_share_link_token, ALBUM_TOKEN_EXPIRY};
+use crate::conduit::{albums, share_links};
+use crate::connection::Repo;
+use crate::middlewares::current_user::CurrentUser;
+use chrono::NaiveDateTime;
+use gotham::handler::HandlerResult;
+use gotham::helpers::http::response::{create_empty_response, create_response};
+use gotham::state::{FromState, State};
+use hyper::StatusCode;
+use photo_core::models::{
+    Album, AlbumWithPhotos, ModelError, PhotoPagination, PhotoSort, Role, ShareLink,
+};
+use serde::{Deserialize, Serialize};
+use snafu::{Backtrace, ResultExt};
+
+#[derive(Deserialize, StateData, StaticResponseExtender)]
+pub struct AlbumPathExtractor {
+    id: String,
+}
+
+#[derive(Deserialize, StateData, StaticResponseExtender)]
+pub struct ShareLinkPathExtractor {
+    id: String,
+}
+
+#[derive(Deserialize, StateData, StaticResponseExtender)]
+pub struct TokenPathExtractor {
+    token: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewShareLinkRequest {
+    /// Unix timestamp (seconds) after which the link stops working.
+    pub expires_at: Option<i64>,
+    pub max_views: Option<i32>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShareLinkResponse {
+    link: ShareLink,
+}
+
+pub async fn new_share_link(mut state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let req_data: NewShareLinkRequest =
+        match extract_json(&mut state).await.context(HandlerUtilsIssue) {
+            Ok(data) => data,
+            Err(e) => return Err((state, e.into())),
+        };
+    let path_data = AlbumPathExtractor::borrow_from(&state);
+
+    let user = CurrentUser::borrow_from(&state).0.clone();
+
+    let album = match albums::find_for(repo.clone(), &user, path_data.id.clone(), Role::Editor)
+        .await
+        .context(AlbumIssue)
+    {
+        Ok(a) => a,
+        Err(e) if is_not_found(&e) => {
+            let res = create_empty_response(&state, StatusCode::NOT_FOUND);
+            return Ok((state, res));
+        }
+        Err(e) if is_forbidden(&e) => {
+            let res = create_empty_response(&state, StatusCode::FORBIDDEN);
+            return Ok((state, res));
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    let expires_at = match timestamp(req_data.expires_at) {
+        Ok(date) => date,
+        Err(_) => {
+            let res = create_empty_response(&state, StatusCode::BAD_REQUEST);
+            return Ok((state, res));
+        }
+    };
+
+    let response = match share_links::create(repo, &album, expires_at, req_data.max_views)
+        .await
+        .context(ShareLinkIssue)
+    {
+        Ok(link) => {
+            let response = ShareLinkResponse { link };
+            let body = serde_json::to_string(&response).expect("Failed to serialize share link");
+
+            create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body)
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    Ok((state, response))
+}
+
+#[derive(Serialize)]
+pub struct ShareLinksResponse {
+    list: Vec<ShareLink>,
+}
+
+pub async fn album_share_links(state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let path_data = AlbumPathExtractor::borrow_from(&state);
+
+    let user = CurrentUser::borrow_from(&state).0.clone();
+
+    let album = match albums::find_for(repo.clone(), &user, path_data.id.clone(), Role::Viewer)
+        .await
+        .context(AlbumIssue)
+    {
+        Ok(a) => a,
+        Err(e) if is_not_found(&e) => {
+            let res = create_empty_response(&state, StatusCode::NOT_FOUND);
+            return Ok((state, res));
+        }
+        Err(e) if is_forbidden(&e) => {
+            let res = create_empty_response(&state, StatusCode::FORBIDDEN);
+            return Ok((state, res));
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    let response = match share_links::find_by_album(repo, &album)
+        .await
+        .context(ShareLinkIssue)
+    {
+        Ok(list) => {
+            let response = ShareLinksResponse { list };
+            let body = serde_json::to_string(&response).expect("Failed to serialize share links");
+
+            create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body)
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    Ok((state, response))
+}
+
+pub async fn revoke_share_link(state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let path_data = ShareLinkPathExtractor::borrow_from(&state);
+
+    let user = CurrentUser::borrow_from(&state).0.clone();
+
+    let response = match share_links::revoke(repo, &user, path_data.id.clone())
+        .await
+        .context(ShareLinkIssue)
+    {
+        Ok(link) => {
+            let response = ShareLinkResponse { link };
+            let body = serde_json::to_string(&response).expect("Failed to serialize share link");
+
+            create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body)
+        }
+        Err(e) if is_not_found(&e) => create_empty_response(&state, StatusCode::NOT_FOUND),
+        Err(e) if is_forbidden(&e) => create_empty_response(&state, StatusCode::FORBIDDEN),
+        Err(e) => return Err((state, e.into())),
+    };
+
+    Ok((state, response))
+}
+
+#[derive(Deserialize, StateData, StaticResponseExtender)]
+pub struct SharedAlbumQueryExtractor {
+    /// Only return the photos labeled with this tag.
+    tag: Option<String>,
+    sort: Option<PhotoSort>,
+    limit: Option<usize>,
+    cursor: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SharedAlbumResponse {
+    album: AlbumWithPhotos,
+    /// Number of photos across all the pages.
+    total: usize,
+    next_cursor: Option<String>,
+    #[serde(with = "photo_core::helpers::datetime::ts_seconds_option")]
+    expires_at: Option<NaiveDateTime>,
+}
+
+/// Returns the album behind a share link with a page of its photos, as long as the link is still
+/// valid. Links protected with a password, or links to protected albums, also need an album token.
+pub async fn get_shared_album(state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let path_data = TokenPathExtractor::borrow_from(&state);
+    let query_data = SharedAlbumQueryExtractor::borrow_from(&state);
+    let tag = query_data.tag.clone();
+    let pagination = photo_pagination(
+        query_data.sort.unwrap_or_default(),
+        query_data.limit,
+        query_data.cursor.clone(),
+    );
+
+    let (link, album) = match find_shared(repo.clone(), path_data.token.clone()).await {
+        Ok(Ok(found)) => found,
+        Ok(Err(status)) => {
+            let res = create_empty_response(&state, status);
+            return Ok((state, res));
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    if !has_share_link_access(&state, &link, &album) {
+        let res = create_empty_response(&state, StatusCode::UNAUTHORIZED);
+        return Ok((state, res));
+    }
+
+    let response = match share_links::open(repo, &link, &album, tag, pagination)
+        .await
+        .context(ShareLinkIssue)
+    {
+        Ok(page) => {
+            let response = SharedAlbumResponse {
+                album: (album, page.list),
+                total: page.total,
+                next_cursor: page.next_cursor,
+                expires_at: link.expires_at,
+            };
+            let body = serde_json::to_string(&response).expect("Failed to serialize album");
+
+            create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body)
+        }
+        Err(e) if is_invalid_link(&e) => create_empty_response(&state, StatusCode::NOT_FOUND),
+        Err(e) if is_invalid_cursor(&e) => create_empty_response(&state, StatusCode::BAD_REQUEST),
+        Err(e) => return Err((state, e.into())),
+    };
+
+    Ok((state, response))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnlockSharedAlbumRequest {
+    /// Password of the link, or of the album when only the album is protected.
+    pub password: String,
+    /// Password of the album, needed as well when both the link and the album are protected.
+    pub album_password: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnlockSharedAlbumResponse {
+    token: String,
+    expires_in: u64,
+}
+
+/// Verifies the password of a share link (or of its album when the link has none) and returns a
+/// short-lived token only valid along with that link. Failed attempts are throttled per album and
+/// IP address, and per album overall (see `ALBUM_UNLOCK_THROTTLE` for the trade-off).
+pub async fn unlock_shared_album(mut state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let path_data = TokenPathExtractor::take_from(&mut state);
+    let req_data: UnlockSharedAlbumRequest =
+        match extract_json(&mut state).await.context(HandlerUtilsIssue) {
+            Ok(data) => data,
+            Err(e) => return Err((state, e.into())),
+        };
+
+    let (link, album) = match find_shared(repo, path_data.token).await {
+        Ok(Ok(found)) => found,
+        Ok(Err(status)) => {
+            let res = create_empty_response(&state, status);
+            return Ok((state, res));
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    // Same key as unlocking the album directly, so every link to the album shares the attempts.
+    let album_key = format!("album:{}", album.id);
+    let throttle_key = format!("{}:{}", album_key, client_ip(&state));
+    if UNLOCK_THROTTLE.is_blocked(&throttle_key) || ALBUM_UNLOCK_THROTTLE.is_blocked(&album_key) {
+        let res = create_empty_response(&state, StatusCode::TOO_MANY_REQUESTS);
+        return Ok((state, res));
+    }
+
+    let verified = match (link.is_protected(), album.is_protected()) {
+        (true, true) => {
+            link.verify_password(&req_data.password)
+                && req_data
+                    .album_password
+                    .map(|password| album.verify_password(&password))
+                    .unwrap_or(false)
+        }
+        (true, false) => link.verify_password(&req_data.password),
+        (false, _) => album.verify_password(&req_data.password),
+    };
+
+    if !verified {
+        UNLOCK_THROTTLE.fail(&throttle_key);
+        ALBUM_UNLOCK_THROTTLE.fail(&album_key);
+        let res = create_empty_response(&state, StatusCode::UNAUTHORIZED);
+        return Ok((state, res));
+    }
+
+    UNLOCK_THROTTLE.reset(&throttle_key);
+
+    let response = UnlockSharedAlbumResponse {
+        token: encode_share_link_token(&link, ALBUM_TOKEN_EXPIRY),
+        expires_in: ALBUM_TOKEN_EXPIRY,
+    };
+    let body = serde_json::to_string(&response).expect("Failed to serialize token");
+    let res = create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body);
+
+    Ok((state, res))
+}
+
+#[derive(Deserialize)]
+pub struct ShareLinkPasswordRequest {
+    /// New password, or `null` to remove it.
+    pub password: Option<String>,
+}
+
+pub async fn set_share_link_password(mut state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let req_data: ShareLinkPasswordRequest =
+        match extract_json(&mut state).await.context(HandlerUtilsIssue) {
+            Ok(data) => data,
+            Err(e) => return Err((state, e.into())),
+        };
+
+    if req_data
+        .password
+        .as_deref()
+        .map_or(false, |p| p.trim().is_empty())
+    {
+        let res = create_empty_response(&state, StatusCode::BAD_REQUEST);
+        return Ok((state, res));
+    }
+    let path_data = ShareLinkPathExtractor::borrow_from(&state);
+
+    let user = CurrentUser::borrow_from(&state).0.clone();
+
+    let response =
+        match share_links::set_password(repo, &user, path_data.id.clone(), req_data.password)
+            .await
+            .context(ShareLinkIssue)
+        {
+            Ok(link) => {
+                let response = ShareLinkResponse { link };
+                let body =
+                    serde_json::to_string(&response).expect("Failed to serialize share link");
+
+                create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body)
+            }
+            Err(e) if is_not_found(&e) => create_empty_response(&state, StatusCode::NOT_FOUND),
+            Err(e) if is_forbidden(&e) => create_empty_response(&state, StatusCode::FORBIDDEN),
+            Err(e) => return Err((state, e.into())),
+        };
+
+    Ok((state, response))
+}
+
+#[derive(Deserialize)]
+pub struct ShareLinkDownloadsRequest {
+    pub enabled: bool,
+}
+
+pub async fn set_share_link_downloads(mut state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let req_data: ShareLinkDownloadsRequest =
+        match extract_json(&mut state).await.context(HandlerUtilsIssue) {
+            Ok(data) => data,
+            Err(e) => return Err((state, e.into())),
+        };
+    let path_data = ShareLinkPathExtractor::borrow_from(&state);
+
+    let user = CurrentUser::borrow_from(&state).0.clone();
+
+    let response =
+        match share_links::set_allow_downloads(repo, &user, path_data.id.clone(), req_data.enabled)
+            .await
+            .context(ShareLinkIssue)
+        {
+            Ok(link) => {
+                let response = ShareLinkResponse { link };
+                let body =
+                    serde_json::to_string(&response).expect("Failed to serialize share link");
+
+                create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body)
+            }
+            Err(e) if is_not_found(&e) => create_empty_response(&state, StatusCode::NOT_FOUND),
+            Err(e) if is_forbidden(&e) => create_empty_response(&state, StatusCode::FORBIDDEN),
+            Err(e) => return Err((state, e.into())),
+        };
+
+    Ok((state, response))
+}
+
+#[derive(Deserialize, StateData, StaticResponseExtender)]
+pub struct DownloadQueryExtractor {
+    size: Option<DownloadSize>,
+}
+
+/// Streams the album behind a share link as a ZIP archive, when the link allows downloads.
+pub async fn download_shared_album(mut state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let path_data = TokenPathExtractor::take_from(&mut state);
+    let query_data = DownloadQueryExtractor::take_from(&mut state);
+
+    let (link, album) = match find_shared(repo.clone(), path_data.token).await {
+        Ok(Ok(found)) => found,
+        Ok(Err(status)) => {
+            let res = create_empty_response(&state, status);
+            return Ok((state, res));
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    if !link.allow_downloads {
+        let res = create_empty_response(&state, StatusCode::FORBIDDEN);
+        return Ok((state, res));
+    }
+
+    if !has_share_link_access(&state, &link, &album) {
+        let res = create_empty_response(&state, StatusCode::UNAUTHORIZED);
+        return Ok((state, res));
+    }
+
+    let response = match albums::photos(repo, &album, None, PhotoPagination::default())
+        .await
+        .context(AlbumIssue)
+    {
+        Ok(page) => {
+            let archive = album_archive(page.list, query_data.size.unwrap_or_default());
+
+            create_archive_response(&state, &album.slug, archive)
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    Ok((state, response))
+}
+
+/// Finds the share link of the token along with its album, or returns the status to reply with
+/// when the link is unknown, expired or revoked.
+async fn find_shared(
+    repo: Repo,
+    token: String,
+) -> Result<Result<(ShareLink, Album), StatusCode>, ShareLinkHandlersError> {
+    match share_links::find_valid(repo, token)
+        .await
+        .context(ShareLinkIssue)
+    {
+        Ok(found) => Ok(Ok(found)),
+        Err(e) if is_invalid_link(&e) => Ok(Err(StatusCode::NOT_FOUND)),
+        Err(e) => Err(e),
+    }
+}
+
+fn is_invalid_cursor(e: &ShareLinkHandlersError) -> bool {
+    match e {
+        ShareLinkHandlersError::ShareLinkIssue {
+            cause:
+                share_links::ShareLinkError::Model {
+                    cause: ModelError::InvalidCursor,
+                    ..
+                },
+            ..
+        } => true,
+        _ => false,
+    }
+}
+
+/// Whether the album or link does not exist or belongs to a studio the user is not a member of.
+fn is_not_found(e: &ShareLinkHandlersError) -> bool {
+    match e {
+        ShareLinkHandlersError::AlbumIssue {
+            cause:
+                albums::AlbumError::Model {
+                    cause: ModelError::AlbumNotFound,
+                    ..
+                },
+            ..
+        }
+        | ShareLinkHandlersError::ShareLinkIssue {
+            cause:
+                share_links::ShareLinkError::Model {
+                    cause: ModelError::ShareLinkNotFound,
+                    ..
+                },
+            ..
+        } => true,
+        _ => false,
+    }
+}
+
+/// Whether the album or link belongs to a studio of the user, but the role of the user is too low.
+fn is_forbidden(e: &ShareLinkHandlersError) -> bool {
+    match e {
+        ShareLinkHandlersError::AlbumIssue {
+            cause:
+                albums::AlbumError::Model {
+                    cause: ModelError::Forbidden,
+                    ..
+                },
+            ..
+        }
+        | ShareLinkHandlersError::ShareLinkIssue {
+            cause:
+                share_links::ShareLinkError::Model {
+                    cause: ModelError::Forbidden,
+                    ..
+                },
+            ..
+        } => true,
+        _ => false,
+    }
+}
+
+fn is_invalid_link(e: &ShareLinkHandlersError) -> bool {
+    match e {
+        ShareLinkHandlersError::ShareLinkIssue {
+            cause:
+                share_links::ShareLinkError::Model {
+                    cause: ModelError::InvalidShareLink,
+                    ..
+                },
+            ..
+        } => true,
+        _ => false,
+    }
+}
+
+#[derive(Debug, Snafu)]
+pub enum ShareLinkHandlersError {
+    #[snafu(display("Could not get request: {}", cause))]
+    HandlerUtilsIssue {
+        #[snafu(source)]
+        cause: HandlerUtilsError,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Could not get share link: {}", cause))]
+    ShareLinkIssue {
+        #[snafu(source)]
+        cause: share_links::ShareLinkError,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Could not get album: {}", cause))]
+    AlbumIssue {
+        #[snafu(source)]
+        cause: albums::AlbumError,
+        backtrace: Backtrace,
+    },
+}
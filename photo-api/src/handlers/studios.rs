@@ -0,0 +1,289 @@
+use super::utils::{extract_json, HandlerUtilsError};
+use crate::conduit::studios;
+use crate::connection::Repo;
+use crate::middlewares::current_user::CurrentUser;
+use gotham::handler::HandlerResult;
+use gotham::helpers::http::response::{create_empty_response, create_response};
+use gotham::state::{FromState, State};
+use hyper::StatusCode;
+use photo_core::models::{Member, ModelError, Role, Studio, StudioMembership};
+use serde::{Deserialize, Serialize};
+use snafu::{Backtrace, ResultExt};
+
+#[derive(Deserialize, StateData, StaticResponseExtender)]
+pub struct StudioPathExtractor {
+    id: String,
+}
+
+#[derive(Deserialize, StateData, StaticResponseExtender)]
+pub struct StudioMemberPathExtractor {
+    id: String,
+    user_id: String,
+}
+
+#[derive(Deserialize)]
+pub struct StudioRequest {
+    pub name: String,
+}
+
+#[derive(Deserialize)]
+pub struct MemberRequest {
+    pub email: String,
+    pub role: Role,
+}
+
+#[derive(Serialize)]
+pub struct StudioResponse {
+    studio: Studio,
+}
+
+#[derive(Serialize)]
+pub struct StudiosResponse {
+    list: Vec<StudioMembership>,
+}
+
+#[derive(Serialize)]
+pub struct MembersResponse {
+    list: Vec<Member>,
+}
+
+/// Studios the user is a member of, with their role in each one.
+pub async fn all_studios(state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let user = CurrentUser::borrow_from(&state).0.clone();
+
+    let response = match studios::find_all(repo, &user).await.context(StudioIssue) {
+        Ok(list) => {
+            let response = StudiosResponse { list };
+            let body = serde_json::to_string(&response).expect("Failed to serialize studios");
+
+            create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body)
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    Ok((state, response))
+}
+
+/// Creates a studio owned by the user.
+pub async fn new_studio(mut state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let req_data: StudioRequest = match extract_json(&mut state).await.context(HandlerUtilsIssue) {
+        Ok(data) => data,
+        Err(e) => return Err((state, e.into())),
+    };
+    let user = CurrentUser::borrow_from(&state).0.clone();
+
+    let name = req_data.name.trim().to_string();
+    if name.is_empty() {
+        let res = create_empty_response(&state, StatusCode::BAD_REQUEST);
+        return Ok((state, res));
+    }
+
+    let response = match studios::create(repo, &user, name)
+        .await
+        .context(StudioIssue)
+    {
+        Ok(studio) => {
+            let response = StudioResponse { studio };
+            let body = serde_json::to_string(&response).expect("Failed to serialize studio");
+
+            create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body)
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    Ok((state, response))
+}
+
+/// Renames a studio. Only its owners can.
+pub async fn rename_studio(mut state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let req_data: StudioRequest = match extract_json(&mut state).await.context(HandlerUtilsIssue) {
+        Ok(data) => data,
+        Err(e) => return Err((state, e.into())),
+    };
+    let path_data = StudioPathExtractor::borrow_from(&state);
+    let user = CurrentUser::borrow_from(&state).0.clone();
+
+    let name = req_data.name.trim().to_string();
+    if name.is_empty() {
+        let res = create_empty_response(&state, StatusCode::BAD_REQUEST);
+        return Ok((state, res));
+    }
+
+    let studio = match studios::find_for(repo.clone(), &user, path_data.id.clone(), Role::Owner)
+        .await
+        .context(StudioIssue)
+    {
+        Ok(s) => s,
+        Err(e) => match error_status(&e) {
+            Some(status) => {
+                let res = create_empty_response(&state, status);
+                return Ok((state, res));
+            }
+            None => return Err((state, e.into())),
+        },
+    };
+
+    let response = match studios::rename(repo, &studio, name)
+        .await
+        .context(StudioIssue)
+    {
+        Ok(studio) => {
+            let response = StudioResponse { studio };
+            let body = serde_json::to_string(&response).expect("Failed to serialize studio");
+
+            create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body)
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    Ok((state, response))
+}
+
+pub async fn studio_members(state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let path_data = StudioPathExtractor::borrow_from(&state);
+    let user = CurrentUser::borrow_from(&state).0.clone();
+
+    let studio = match studios::find_for(repo.clone(), &user, path_data.id.clone(), Role::Viewer)
+        .await
+        .context(StudioIssue)
+    {
+        Ok(s) => s,
+        Err(e) => match error_status(&e) {
+            Some(status) => {
+                let res = create_empty_response(&state, status);
+                return Ok((state, res));
+            }
+            None => return Err((state, e.into())),
+        },
+    };
+
+    let response = match studios::members(repo, &studio).await.context(StudioIssue) {
+        Ok(list) => {
+            let response = MembersResponse { list };
+            let body = serde_json::to_string(&response).expect("Failed to serialize members");
+
+            create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body)
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    Ok((state, response))
+}
+
+/// Adds a user to the studio or changes their role. The user must already have an account, and
+/// the studio must keep at least one owner.
+pub async fn set_studio_member(mut state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let req_data: MemberRequest = match extract_json(&mut state).await.context(HandlerUtilsIssue) {
+        Ok(data) => data,
+        Err(e) => return Err((state, e.into())),
+    };
+    let path_data = StudioPathExtractor::borrow_from(&state);
+    let user = CurrentUser::borrow_from(&state).0.clone();
+
+    let studio = match studios::find_for(repo.clone(), &user, path_data.id.clone(), Role::Owner)
+        .await
+        .context(StudioIssue)
+    {
+        Ok(s) => s,
+        Err(e) => match error_status(&e) {
+            Some(status) => {
+                let res = create_empty_response(&state, status);
+                return Ok((state, res));
+            }
+            None => return Err((state, e.into())),
+        },
+    };
+
+    let email = req_data.email.trim().to_string();
+    let response = match studios::set_member(repo, &studio, email, req_data.role)
+        .await
+        .context(StudioIssue)
+    {
+        Ok(list) => {
+            let response = MembersResponse { list };
+            let body = serde_json::to_string(&response).expect("Failed to serialize members");
+
+            create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body)
+        }
+        Err(e) => match error_status(&e) {
+            Some(status) => create_empty_response(&state, status),
+            None => return Err((state, e.into())),
+        },
+    };
+
+    Ok((state, response))
+}
+
+/// Removes a member from the studio. The last owner can't be removed.
+pub async fn remove_studio_member(state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let path_data = StudioMemberPathExtractor::borrow_from(&state);
+    let user = CurrentUser::borrow_from(&state).0.clone();
+
+    let studio = match studios::find_for(repo.clone(), &user, path_data.id.clone(), Role::Owner)
+        .await
+        .context(StudioIssue)
+    {
+        Ok(s) => s,
+        Err(e) => match error_status(&e) {
+            Some(status) => {
+                let res = create_empty_response(&state, status);
+                return Ok((state, res));
+            }
+            None => return Err((state, e.into())),
+        },
+    };
+
+    let response = match studios::remove_member(repo, &studio, path_data.user_id.clone())
+        .await
+        .context(StudioIssue)
+    {
+        Ok(_) => create_empty_response(&state, StatusCode::OK),
+        Err(e) => match error_status(&e) {
+            Some(status) => create_empty_response(&state, status),
+            None => return Err((state, e.into())),
+        },
+    };
+
+    Ok((state, response))
+}
+
+/// Status for the errors caused by the request rather than by the server.
+fn error_status(e: &StudioHandlersError) -> Option<StatusCode> {
+    match e {
+        StudioHandlersError::StudioIssue {
+            cause: studios::StudioError::Model { cause, .. },
+            ..
+        } => match cause {
+            ModelError::StudioNotFound | ModelError::MemberNotFound | ModelError::UserNotFound => {
+                Some(StatusCode::NOT_FOUND)
+            }
+            ModelError::Forbidden => Some(StatusCode::FORBIDDEN),
+            ModelError::LastOwner => Some(StatusCode::CONFLICT),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+#[derive(Debug, Snafu)]
+pub enum StudioHandlersError {
+    #[snafu(display("Could not get request: {}", cause))]
+    HandlerUtilsIssue {
+        #[snafu(source)]
+        cause: HandlerUtilsError,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Could not get studio: {}", cause))]
+    StudioIssue {
+        #[snafu(source)]
+        cause: studios::StudioError,
+        backtrace: Backtrace,
+    },
+}
@@ -0,0 +1,185 @@
+use super::utils::{extract_json, HandlerUtilsError};
+use crate::conduit::tags;
+use crate::connection::Repo;
+use crate::middlewares::current_user::CurrentUser;
+use gotham::handler::HandlerResult;
+use gotham::helpers::http::response::{create_empty_response, create_response};
+use gotham::state::{FromState, State};
+use hyper::StatusCode;
+use photo_core::models::{ModelError, PhotoTags, TagCount};
+use serde::{Deserialize, Serialize};
+use snafu::{Backtrace, ResultExt};
+
+#[derive(Deserialize, StateData, StaticResponseExtender)]
+pub struct PhotoPathExtractor {
+    id: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PhotoTagsRequest {
+    pub photo_ids: Vec<String>,
+    /// Tag names, tags that do not exist yet are created.
+    pub tags: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct TagsResponse {
+    list: Vec<TagCount>,
+}
+
+#[derive(Serialize)]
+pub struct PhotoTagsResponse {
+    list: Vec<PhotoTags>,
+}
+
+#[derive(Serialize)]
+pub struct SinglePhotoTagsResponse {
+    photo: PhotoTags,
+}
+
+/// Lists the user's tags with the number of photos labeled with each of them.
+pub async fn all_tags(state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+
+    let user = CurrentUser::borrow_from(&state).0.clone();
+
+    let response = match tags::find_all(repo, &user).await.context(TagIssue) {
+        Ok(list) => {
+            let response = TagsResponse { list };
+            let body = serde_json::to_string(&response).expect("Failed to serialize tags");
+
+            create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body)
+        }
+        Err(e) => return Err((state, e.into())),
+    };
+
+    Ok((state, response))
+}
+
+pub async fn photo_tags(state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let path_data = PhotoPathExtractor::borrow_from(&state);
+
+    let user = CurrentUser::borrow_from(&state).0.clone();
+
+    let response = match tags::find_by_photo(repo, &user, path_data.id.clone())
+        .await
+        .context(TagIssue)
+    {
+        Ok(photo) => {
+            let response = SinglePhotoTagsResponse { photo };
+            let body = serde_json::to_string(&response).expect("Failed to serialize tags");
+
+            create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body)
+        }
+        Err(e) if is_photo_not_found(&e) => create_empty_response(&state, StatusCode::NOT_FOUND),
+        Err(e) if is_forbidden(&e) => create_empty_response(&state, StatusCode::FORBIDDEN),
+        Err(e) => return Err((state, e.into())),
+    };
+
+    Ok((state, response))
+}
+
+/// Labels one or many photos with one or many tags.
+pub async fn add_tags(mut state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let req_data: PhotoTagsRequest = match extract_json(&mut state).await.context(HandlerUtilsIssue)
+    {
+        Ok(data) => data,
+        Err(e) => return Err((state, e.into())),
+    };
+
+    let user = CurrentUser::borrow_from(&state).0.clone();
+
+    let response = match tags::add(repo, &user, req_data.photo_ids, req_data.tags)
+        .await
+        .context(TagIssue)
+    {
+        Ok(list) => {
+            let response = PhotoTagsResponse { list };
+            let body = serde_json::to_string(&response).expect("Failed to serialize tags");
+
+            create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body)
+        }
+        Err(e) if is_photo_not_found(&e) => create_empty_response(&state, StatusCode::NOT_FOUND),
+        Err(e) if is_forbidden(&e) => create_empty_response(&state, StatusCode::FORBIDDEN),
+        Err(e) => return Err((state, e.into())),
+    };
+
+    Ok((state, response))
+}
+
+/// Removes one or many tags from one or many photos. The tags themselves are kept.
+pub async fn remove_tags(mut state: State) -> HandlerResult {
+    let repo = Repo::borrow_from(&state).clone();
+    let req_data: PhotoTagsRequest = match extract_json(&mut state).await.context(HandlerUtilsIssue)
+    {
+        Ok(data) => data,
+        Err(e) => return Err((state, e.into())),
+    };
+
+    let user = CurrentUser::borrow_from(&state).0.clone();
+
+    let response = match tags::remove(repo, &user, req_data.photo_ids, req_data.tags)
+        .await
+        .context(TagIssue)
+    {
+        Ok(list) => {
+            let response = PhotoTagsResponse { list };
+            let body = serde_json::to_string(&response).expect("Failed to serialize tags");
+
+            create_response(&state, StatusCode::OK, mime::APPLICATION_JSON, body)
+        }
+        Err(e) if is_photo_not_found(&e) => create_empty_response(&state, StatusCode::NOT_FOUND),
+        Err(e) if is_forbidden(&e) => create_empty_response(&state, StatusCode::FORBIDDEN),
+        Err(e) => return Err((state, e.into())),
+    };
+
+    Ok((state, response))
+}
+
+fn is_photo_not_found(e: &TagHandlersError) -> bool {
+    match e {
+        TagHandlersError::TagIssue {
+            cause:
+                tags::TagError::Model {
+                    cause: ModelError::PhotoNotFound,
+                    ..
+                },
+            ..
+        } => true,
+        _ => false,
+    }
+}
+
+fn is_forbidden(e: &TagHandlersError) -> bool {
+    match e {
+        TagHandlersError::TagIssue {
+            cause:
+                tags::TagError::Model {
+                    cause: ModelError::Forbidden,
+                    ..
+                },
+            ..
+        } => true,
+        _ => false,
+    }
+}
+
+#[derive(Debug, Snafu)]
+pub enum TagHandlersError {
+    #[snafu(display("Could not get request: {}", cause))]
+    HandlerUtilsIssue {
+        #[snafu(source)]
+        cause: HandlerUtilsError,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Could not get tags: {}", cause))]
+    TagIssue {
+        #[snafu(source)]
+        cause: tags::TagError,
+        backtrace: Backtrace,
+    },
+}
@@ -1,18 +1,45 @@
+use crate::archive::sanitize;
+use crate::auth::{decode_album_token, AlbumAccess};
+use crate::utils::encode_url_component;
 use bytes::Bytes;
+use chrono::{DateTime, NaiveDateTime};
 use futures::future;
 use futures::prelude::*;
-use gotham::anyhow::Error;
-use gotham::handler::{HandlerError, HandlerFuture};
-use gotham::helpers::http::response::create_empty_response;
+use gotham::helpers::http::response::{create_empty_response, create_response};
 use gotham::hyper::{
-    body, header::CONTENT_TYPE, Body, Error as HyperError, HeaderMap, Response, StatusCode,
+    body,
+    header::{
+        HeaderValue, AUTHORIZATION, CONTENT_DISPOSITION, CONTENT_TYPE, ETAG, IF_MATCH,
+        IF_UNMODIFIED_SINCE, USER_AGENT,
+    },
+    Body, Error as HyperError, HeaderMap, Response, StatusCode,
 };
-use gotham::state::{FromState, State};
+use gotham::state::{client_addr, request_id, FromState, State};
 use multipart::server::Multipart;
-use snafu::{Backtrace, ResultExt};
+use photo_core::helpers::precondition::Precondition;
+use photo_core::models::{Album, AuditContext, PhotoPagination, PhotoSort, ShareLink, User};
+use serde::{Deserialize, Deserializer};
+use snafu::{Backtrace, OptionExt, ResultExt};
+use std::env;
 use std::io::{Cursor, Read};
 use std::pin::Pin;
 
+/// Largest number of photos returned in a single page.
+const MAX_PAGE_SIZE: usize = 500;
+
+/// Pagination of a photo listing, as requested in the query string.
+pub fn photo_pagination(
+    sort: PhotoSort,
+    limit: Option<usize>,
+    cursor: Option<String>,
+) -> PhotoPagination {
+    PhotoPagination {
+        sort,
+        limit: limit.map(|l| l.max(1).min(MAX_PAGE_SIZE)),
+        cursor,
+    }
+}
+
 pub async fn get_body_bytes(state: &mut State) -> HandlerUtilsResult<Bytes> {
     let body_from_state = Body::take_from(state);
     let body_bytes = body::to_bytes(body_from_state).await.context(BodyParse)?;
@@ -30,18 +57,162 @@ where
     Ok(json)
 }
 
+/// Date of an optional Unix timestamp sent by the client. Fails for dates out of the supported
+/// range, so the request can be rejected.
+pub fn timestamp(secs: Option<i64>) -> HandlerUtilsResult<Option<NaiveDateTime>> {
+    match secs {
+        Some(secs) => NaiveDateTime::from_timestamp_opt(secs, 0)
+            .map(Some)
+            .context(InvalidTimestamp { secs }),
+        None => Ok(None),
+    }
+}
+
+/// Deserializes a field that can be missing, `null` or have a value into `None`, `Some(None)` or
+/// `Some(Some(value))`. Needs `#[serde(default)]` on the field as well.
+pub fn double_option<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// Version of the resource the client expects to be changing, taken from the `If-Match` or
+/// `If-Unmodified-Since` headers.
+pub fn precondition(state: &State) -> Option<Precondition> {
+    let headers = HeaderMap::borrow_from(state);
+
+    if let Some(tags) = headers.get(IF_MATCH).and_then(|v| v.to_str().ok()) {
+        return Some(Precondition::Matches(tags.to_string()));
+    }
+
+    headers
+        .get(IF_UNMODIFIED_SINCE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| DateTime::parse_from_rfc2822(v).ok())
+        .map(|date| Precondition::UnmodifiedSince(date.naive_utc()))
+}
+
+/// JSON response along with the entity tag of the resource, to be sent back in `If-Match`.
+pub fn create_tagged_response(state: &State, etag: &str, body: String) -> Response<Body> {
+    let mut res = create_response(state, StatusCode::OK, mime::APPLICATION_JSON, body);
+
+    if let Ok(value) = HeaderValue::from_str(etag) {
+        res.headers_mut().insert(ETAG, value);
+    }
+
+    res
+}
+
+/// Valid album token the request carries (`Authorization: Bearer <token>`), if any.
+fn album_token(state: &State) -> Option<AlbumAccess> {
+    HeaderMap::borrow_from(state)
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .and_then(decode_album_token)
+}
+
+/// Public albums are always accessible, password protected ones need an album token.
+pub fn has_album_access(state: &State, album: &Album) -> bool {
+    !album.is_protected()
+        || album_token(state)
+            .map(|access| access.grants(album))
+            .unwrap_or(false)
+}
+
+/// Links protected with a password, or links to protected albums, need a token given by
+/// unlocking that same link.
+pub fn has_share_link_access(state: &State, link: &ShareLink, album: &Album) -> bool {
+    !(link.is_protected() || album.is_protected())
+        || album_token(state)
+            .map(|access| access.grants_link(link))
+            .unwrap_or(false)
+}
+
+/// Client IP address, used to throttle requests. Behind a reverse proxy (`TRUST_PROXY` set) the
+/// peer is the proxy, so the client is the address it appended to `X-Forwarded-For` instead.
+pub fn client_ip(state: &State) -> String {
+    let forwarded = env::var("TRUST_PROXY")
+        .ok()
+        .and_then(|_| HeaderMap::borrow_from(state).get("x-forwarded-for"))
+        .and_then(|value| value.to_str().ok())
+        .and_then(last_forwarded_for);
+
+    forwarded.unwrap_or_else(|| {
+        client_addr(state)
+            .map(|addr| addr.ip().to_string())
+            .unwrap_or_default()
+    })
+}
+
+/// Last address of an `X-Forwarded-For` header. The ones before it are sent by the client and
+/// can't be trusted.
+fn last_forwarded_for(header: &str) -> Option<String> {
+    header
+        .rsplit(',')
+        .next()
+        .map(|ip| ip.trim().to_string())
+        .filter(|ip| !ip.is_empty())
+}
+
+/// Who makes the request and from where, for the audit log.
+pub fn audit_context(state: &State, user: Option<&User>) -> AuditContext {
+    let ip = client_ip(state);
+
+    AuditContext {
+        actor_id: user.map(|u| u.id),
+        ip: Some(ip).filter(|ip| !ip.is_empty()),
+        request_id: Some(request_id(state).to_string()),
+    }
+}
+
+/// The `User-Agent` the request was sent with, if any.
+pub fn user_agent(state: &State) -> Option<String> {
+    HeaderMap::borrow_from(state)
+        .get(USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+        .map(String::from)
+}
+
+/// Response that downloads the body as a ZIP file with the given name.
+pub fn create_archive_response(state: &State, filename: &str, body: Body) -> Response<Body> {
+    let zip_mime: mime::Mime = "application/zip".parse().expect("Invalid ZIP mime type");
+    let mut res = create_response(state, StatusCode::OK, zip_mime, body);
+
+    if let Ok(value) = HeaderValue::from_str(&archive_disposition(filename)) {
+        res.headers_mut().insert(CONTENT_DISPOSITION, value);
+    }
+
+    res
+}
+
+/// `Content-Disposition` of an archive named after `filename`. The name is sanitized, with an
+/// ASCII fallback for old clients and the UTF-8 name in `filename*` (RFC 5987).
+fn archive_disposition(filename: &str) -> String {
+    let name = match sanitize(filename) {
+        name if name.is_empty() => String::from("album"),
+        name => name,
+    };
+    let fallback: String = name
+        .chars()
+        .map(|c| if c.is_ascii() { c } else { '_' })
+        .collect();
+
+    format!(
+        "attachment; filename=\"{}.zip\"; filename*=UTF-8''{}.zip",
+        fallback,
+        encode_url_component(name)
+    )
+}
+
 pub fn empty_handler(state: State) -> (State, Response<Body>) {
     let res = create_empty_response(&state, StatusCode::NO_CONTENT);
 
     (state, res)
 }
 
-pub fn error_request(state: State, e: Error) -> Pin<Box<HandlerFuture>> {
-    let err = HandlerError::from(e);
-    let f = future::err((state, err.into()));
-    return f.boxed();
-}
-
 pub type HandlerUtilsResult<T> = std::result::Result<T, HandlerUtilsError>;
 
 #[derive(Debug, Snafu)]
@@ -57,39 +228,50 @@ pub enum HandlerUtilsError {
         source: serde_json::error::Error,
         backtrace: Backtrace,
     },
+
+    #[snafu(display("Timestamp out of range: {}", secs))]
+    InvalidTimestamp { secs: i64, backtrace: Backtrace },
 }
 
+/// Reads every entry of a multipart body, files are kept in memory.
 pub fn handle_multipart(mut state: State) -> Pin<Box<HandlerMultipart>> {
     const BOUNDARY: &str = "boundary=";
     let header_map = HeaderMap::borrow_from(&state);
-    let boundary = header_map
-        .get(CONTENT_TYPE)
-        .and_then(|ct| {
-            let ct = ct.to_str().ok()?;
-            let idx = ct.find(BOUNDARY)?;
-            Some(ct[idx + BOUNDARY.len()..].to_string())
-        })
-        .unwrap();
+    let boundary = header_map.get(CONTENT_TYPE).and_then(|ct| {
+        let ct = ct.to_str().ok()?;
+        let idx = ct.find(BOUNDARY)?;
+        Some(ct[idx + BOUNDARY.len()..].to_string())
+    });
+    let boundary = match boundary {
+        Some(b) => b,
+        None => return future::err((state, MultiPartError::NoBoundary)).boxed(),
+    };
 
     let f = body::to_bytes(Body::take_from(&mut state)).then(|full_body| match full_body {
         Ok(valid_body) => {
             let mut m = Multipart::with_body(Cursor::new(valid_body), boundary);
-            match m.read_entry() {
-                Ok(Some(mut field)) => {
-                    let mut data: Vec<u8> = Vec::new();
-                    field.data.read_to_end(&mut data).expect("can't read");
-
-                    let multipart_data = MultiPartData {
-                        data,
-                        filename: field.headers.filename.clone(),
-                        content_type: field.headers.content_type.clone(),
-                    };
-
-                    future::ok((state, Some(multipart_data)))
+            let mut entries = Vec::new();
+
+            loop {
+                match m.read_entry() {
+                    Ok(Some(mut field)) => {
+                        let mut data: Vec<u8> = Vec::new();
+                        if let Err(e) = field.data.read_to_end(&mut data) {
+                            return future::err((state, MultiPartError::ReadEntry { source: e }));
+                        }
+
+                        entries.push(MultiPartData {
+                            data,
+                            filename: field.headers.filename.clone(),
+                            content_type: field.headers.content_type.clone(),
+                        });
+                    }
+                    Ok(None) => break,
+                    Err(e) => return future::err((state, MultiPartError::ReadEntry { source: e })),
                 }
-                Ok(None) => future::ok((state, None)),
-                Err(e) => future::err((state, MultiPartError::ReadEntry { source: e })),
             }
+
+            future::ok((state, entries))
         }
         Err(e) => future::err((state, MultiPartError::BodyParseIssue { source: e })),
     });
@@ -100,7 +282,7 @@ pub fn handle_multipart(mut state: State) -> Pin<Box<HandlerMultipart>> {
 pub type HandlerMultipart = dyn Future<Output = MultipartResult> + Send;
 
 pub type MultipartResult =
-    std::result::Result<(State, Option<MultiPartData>), (State, MultiPartError)>;
+    std::result::Result<(State, Vec<MultiPartData>), (State, MultiPartError)>;
 
 pub struct MultiPartData {
     pub data: Vec<u8>,
@@ -115,4 +297,54 @@ pub enum MultiPartError {
 
     #[snafu(display("Could not get body: {}", source))]
     BodyParseIssue { source: HyperError },
+
+    #[snafu(display("Multipart boundary is missing from the content type"))]
+    NoBoundary,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forwarded_for_uses_the_address_added_by_the_proxy() {
+        let ip = last_forwarded_for("1.1.1.1, 10.0.0.1,203.0.113.7");
+
+        assert_eq!(ip, Some(String::from("203.0.113.7")));
+    }
+
+    #[test]
+    fn forwarded_for_with_a_single_address() {
+        assert_eq!(
+            last_forwarded_for("203.0.113.7"),
+            Some(String::from("203.0.113.7"))
+        );
+        assert_eq!(last_forwarded_for("1.1.1.1, "), None);
+    }
+
+    #[test]
+    fn archive_names_are_sanitized() {
+        let disposition = archive_disposition("a\"; filename=evil.exe");
+
+        assert_eq!(
+            disposition,
+            "attachment; filename=\"a_; filename=evil.exe.zip\"; \
+             filename*=UTF-8''a%5F%3B%20filename%3Devil%2Eexe.zip"
+        );
+    }
+
+    #[test]
+    fn archive_names_keep_unicode_in_extended_filename() {
+        let disposition = archive_disposition("Été");
+
+        assert_eq!(
+            disposition,
+            "attachment; filename=\"_t_.zip\"; filename*=UTF-8''%C3%89t%C3%A9.zip"
+        );
+        assert!(HeaderValue::from_str(&disposition).is_ok());
+        assert_eq!(
+            archive_disposition(" .. "),
+            "attachment; filename=\"album.zip\"; filename*=UTF-8''album.zip"
+        );
+    }
 }
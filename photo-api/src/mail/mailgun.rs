@@ -0,0 +1,62 @@
+use reqwest::{Error as ReqwestError, Response};
+use snafu::{Backtrace, ResultExt};
+use std::env;
+use std::env::VarError;
+
+/// Sends an HTML email through Mailgun, from `<from_user>@MAILGUN_DOMAIN`. The response is
+/// returned as is, callers decide what to do when Mailgun refuses the message.
+pub async fn send(
+    from_name: &str,
+    from_user: &str,
+    to: &str,
+    subject: &str,
+    html: &str,
+) -> Result<Response> {
+    let domain = env::var("MAILGUN_DOMAIN").context(NoDomain)?;
+    let api_key = env::var("MAILGUN_API_KEY").context(NoApiKey)?;
+
+    let uri = format!("https://api.mailgun.net/v3/{}/messages", domain);
+    let from = format!("{} <{}@{}>", from_name, from_user, domain);
+    let params = [
+        ("from", from.as_str()),
+        ("to", to),
+        ("subject", subject),
+        ("html", html),
+    ];
+
+    let client = reqwest::Client::new();
+    let res = client
+        .post(&uri)
+        .basic_auth("api", Some(api_key))
+        .form(&params)
+        .send()
+        .await
+        .context(ReqwestIssue)?;
+
+    debug!("Response: {:?}", res);
+
+    Ok(res)
+}
+
+pub type Result<T, E = MailError> = std::result::Result<T, E>;
+
+#[derive(Debug, Snafu)]
+pub enum MailError {
+    #[snafu(display("Missing MAILGUN_DOMAIN environment variable: {}", source))]
+    NoDomain {
+        source: VarError,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Missing MAILGUN_API_KEY environment variable: {}", source))]
+    NoApiKey {
+        source: VarError,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Could not complete request: {}", source))]
+    ReqwestIssue {
+        source: ReqwestError,
+        backtrace: Backtrace,
+    },
+}
@@ -0,0 +1,2 @@
+mod mailgun;
+pub use self::mailgun::*;
@@ -16,19 +16,30 @@ extern crate lazy_static;
 #[macro_use]
 extern crate snafu_derive;
 
+mod archive;
 mod auth;
 mod aws;
 mod conduit;
 mod connection;
 mod handlers;
+mod mail;
+mod metadata;
 mod middlewares;
 mod utils;
 
+use crate::auth::email::EmailLoginExtractor;
 use crate::auth::google::GoogleRedirectExtractor;
-use crate::auth::{get_secret, AuthUser};
-use crate::handlers::auth::{google_authorize_handler, google_redirect_handler};
+use crate::auth::oidc::{OidcPathExtractor, OidcRedirectExtractor};
+use crate::connection::Repo;
+use crate::handlers::auth::{
+    email_login_confirm_handler, email_login_handler, email_login_request_handler,
+    google_authorize_handler, google_redirect_handler, oidc_authorize_handler,
+    oidc_redirect_handler,
+};
 use crate::handlers::utils::empty_handler;
 use crate::middlewares::cors::CorsMiddleware;
+use crate::middlewares::current_user::CurrentUserMiddleware;
+use crate::middlewares::token::{SessionOnlyMiddleware, TokenMiddleware};
 use dotenv::dotenv;
 use gotham::middleware::logger::RequestLogger;
 use gotham::pipeline::new_pipeline;
@@ -36,7 +47,6 @@ use gotham::pipeline::set::{finalize_pipeline_set, new_pipeline_set};
 use gotham::router::builder::*;
 use gotham::router::Router;
 use gotham_middleware_diesel::{self, DieselMiddleware};
-use gotham_middleware_jwt::JWTMiddleware;
 use hyper::Method;
 use photo_core::connection::{connect, db_migrate, get_database_url};
 use photo_core::custom_migrations::apply_custom_migrations;
@@ -72,12 +82,10 @@ fn main() {
 
     info!("Listening for requests at http://{}", addr);
 
-    gotham::start(addr, router());
+    gotham::start(addr, router(connection::repo()));
 }
 
-fn router() -> Router {
-    let repo = connection::repo();
-
+fn router(repo: Repo) -> Router {
     let pipelines = new_pipeline_set();
     let (pipelines, default) = pipelines.add(
         new_pipeline()
@@ -89,7 +97,15 @@ fn router() -> Router {
 
     let (pipelines, authenticated) = pipelines.add(
         new_pipeline()
-            .add(JWTMiddleware::<AuthUser>::new(get_secret()).scheme("Bearer"))
+            .add(TokenMiddleware)
+            .add(CurrentUserMiddleware)
+            .build(),
+    );
+    let (pipelines, session_only) = pipelines.add(
+        new_pipeline()
+            .add(TokenMiddleware)
+            .add(SessionOnlyMiddleware)
+            .add(CurrentUserMiddleware)
             .build(),
     );
     let (pipelines, cors) = pipelines.add(
@@ -103,16 +119,34 @@ fn router() -> Router {
     let default_chain = (default, ());
     let cors_preflight_chain = (cors, ());
     let auth_chain = (authenticated, default_chain);
+    let session_only_chain = (session_only, default_chain);
 
     build_router(default_chain, pipeline_set, |route| {
         route.get_or_head("/").to(empty_handler);
 
-        route.get("/google/authorize").to(google_authorize_handler);
+        route
+            .get("/google/authorize")
+            .to_async(google_authorize_handler);
         route
             .get("/google/redirect")
             .with_query_string_extractor::<GoogleRedirectExtractor>()
             .to_async(google_redirect_handler);
 
+        route
+            .get("/oidc/:provider/authorize")
+            .with_path_extractor::<OidcPathExtractor>()
+            .to_async(oidc_authorize_handler);
+        route
+            .get("/oidc/:provider/redirect")
+            .with_path_extractor::<OidcPathExtractor>()
+            .with_query_string_extractor::<OidcRedirectExtractor>()
+            .to_async(oidc_redirect_handler);
+        route
+            .get("/email/login")
+            .with_query_string_extractor::<EmailLoginExtractor>()
+            .to_async(email_login_confirm_handler);
+        route.post("/email/login").to_async(email_login_handler);
+
         route.scope("/api", |route| {
             route
                 .get("/public/album")
@@ -120,34 +154,142 @@ fn router() -> Router {
                 .to_async(handlers::albums::get_main_public);
 
             route
-                .get("/public/album/:name")
+                .get("/public/album/:slug")
+                .with_query_string_extractor::<handlers::albums::WithIdExtractor>()
+                .with_path_extractor::<handlers::albums::WithSlugExtractor>()
+                .to_async(handlers::albums::get_album_by_slug);
+
+            route
+                .post("/public/album/:slug/unlock")
                 .with_query_string_extractor::<handlers::albums::WithIdExtractor>()
-                .with_path_extractor::<handlers::albums::WithNameExtractor>()
-                .to_async(handlers::albums::get_album_by_name);
+                .with_path_extractor::<handlers::albums::WithSlugExtractor>()
+                .to_async(handlers::albums::unlock_album);
+
+            route
+                .get("/public/collections")
+                .with_query_string_extractor::<handlers::albums::WithIdExtractor>()
+                .to_async(handlers::albums::get_public_collections);
+
+            route
+                .get("/public/collection/:slug")
+                .with_query_string_extractor::<handlers::albums::WithIdExtractor>()
+                .with_path_extractor::<handlers::albums::WithSlugExtractor>()
+                .to_async(handlers::albums::get_public_collection_by_slug);
+
+            route
+                .get("/public/favorites")
+                .with_query_string_extractor::<handlers::favorites::PublicFavoritesQueryExtractor>()
+                .to_async(handlers::favorites::get_public_favorites);
+
+            route
+                .get("/public/shared/:token")
+                .with_path_extractor::<handlers::share_links::TokenPathExtractor>()
+                .with_query_string_extractor::<handlers::share_links::SharedAlbumQueryExtractor>()
+                .to_async(handlers::share_links::get_shared_album);
+
+            route
+                .post("/public/shared/:token/unlock")
+                .with_path_extractor::<handlers::share_links::TokenPathExtractor>()
+                .to_async(handlers::share_links::unlock_shared_album);
+
+            route
+                .get("/public/shared/:token/download")
+                .with_path_extractor::<handlers::share_links::TokenPathExtractor>()
+                .with_query_string_extractor::<handlers::share_links::DownloadQueryExtractor>()
+                .to_async(handlers::share_links::download_shared_album);
+
+            route
+                .post("/public/shared/:token/proofing")
+                .with_path_extractor::<handlers::proofing::TokenPathExtractor>()
+                .to_async(handlers::proofing::register_client);
+
+            route
+                .put("/public/shared/:token/proofing/photo/:photo_id")
+                .with_path_extractor::<handlers::proofing::TokenPhotoPathExtractor>()
+                .to_async(handlers::proofing::select_photo);
+
+            route
+                .post("/public/shared/:token/proofing/photo/:photo_id/comments")
+                .with_path_extractor::<handlers::proofing::TokenPhotoPathExtractor>()
+                .to_async(handlers::proofing::comment_photo);
 
             route
                 .post("/public/book_me")
                 .with_query_string_extractor::<handlers::book_me::WithIdExtractor>()
                 .to_async(handlers::book_me::book_me);
 
+            route
+                .post("/token/refresh")
+                .to_async(handlers::sessions::refresh_token);
+
+            route
+                .post("/token/exchange")
+                .to_async(handlers::sessions::exchange_login_code);
+
+            route
+                .post("/login/email")
+                .to_async(email_login_request_handler);
+
             route.with_pipeline_chain(auth_chain, |route| {
                 route.get("/me").to_async(handlers::users::me);
 
                 route.get("/albums").to_async(handlers::albums::all_albums);
 
+                route
+                    .get("/albums/tree")
+                    .to_async(handlers::albums::album_tree);
+
+                route.scope("/tags", |route| {
+                    route.get("/").to_async(handlers::tags::all_tags);
+
+                    route.post("/add").to_async(handlers::tags::add_tags);
+
+                    route.post("/remove").to_async(handlers::tags::remove_tags);
+                });
+
+                route
+                    .get("/search")
+                    .with_query_string_extractor::<handlers::search::SearchQueryExtractor>()
+                    .to_async(handlers::search::search);
+
+                route
+                    .get("/favorites")
+                    .with_query_string_extractor::<handlers::favorites::FavoritesQueryExtractor>()
+                    .to_async(handlers::favorites::all_favorites);
+
                 route.scope("/album", |route| {
                     route.post("/").to_async(handlers::albums::new_album);
 
+                    route
+                        .get("/:id")
+                        .with_path_extractor::<handlers::albums::AlbumPathExtractor>()
+                        .to_async(handlers::albums::get_album);
+
                     route
                         .put("/:id")
                         .with_path_extractor::<handlers::albums::AlbumPathExtractor>()
                         .to_async(handlers::albums::update_album);
 
+                    route
+                        .patch("/:id")
+                        .with_path_extractor::<handlers::albums::AlbumPathExtractor>()
+                        .to_async(handlers::albums::patch_album);
+
                     route
                         .delete("/:id")
                         .with_path_extractor::<handlers::albums::AlbumPathExtractor>()
                         .to_async(handlers::albums::delete_album);
 
+                    route
+                        .put("/:id/parent")
+                        .with_path_extractor::<handlers::albums::AlbumPathExtractor>()
+                        .to_async(handlers::albums::move_album);
+
+                    route
+                        .put("/:id/password")
+                        .with_path_extractor::<handlers::albums::AlbumPathExtractor>()
+                        .to_async(handlers::albums::set_album_password);
+
                     route
                         .post("/:id/photo")
                         .with_path_extractor::<handlers::photos::AlbumPathExtractor>()
@@ -156,23 +298,129 @@ fn router() -> Router {
                     route
                         .get("/:id/photos")
                         .with_path_extractor::<handlers::albums::AlbumPathExtractor>()
+                        .with_query_string_extractor::<handlers::albums::PhotosQueryExtractor>()
                         .to_async(handlers::albums::album_photos);
+
+                    route
+                        .get("/:id/download")
+                        .with_path_extractor::<handlers::albums::AlbumPathExtractor>()
+                        .with_query_string_extractor::<handlers::albums::DownloadQueryExtractor>()
+                        .to_async(handlers::albums::download_album);
+
+                    route
+                        .post("/:id/share_links")
+                        .with_path_extractor::<handlers::share_links::AlbumPathExtractor>()
+                        .to_async(handlers::share_links::new_share_link);
+
+                    route
+                        .get("/:id/share_links")
+                        .with_path_extractor::<handlers::share_links::AlbumPathExtractor>()
+                        .to_async(handlers::share_links::album_share_links);
+                });
+
+                route.scope("/share_link", |route| {
+                    route
+                        .delete("/:id")
+                        .with_path_extractor::<handlers::share_links::ShareLinkPathExtractor>()
+                        .to_async(handlers::share_links::revoke_share_link);
+
+                    route
+                        .put("/:id/password")
+                        .with_path_extractor::<handlers::share_links::ShareLinkPathExtractor>()
+                        .to_async(handlers::share_links::set_share_link_password);
+
+                    route
+                        .put("/:id/downloads")
+                        .with_path_extractor::<handlers::share_links::ShareLinkPathExtractor>()
+                        .to_async(handlers::share_links::set_share_link_downloads);
+
+                    route
+                        .put("/:id/proofing")
+                        .with_path_extractor::<handlers::proofing::ShareLinkPathExtractor>()
+                        .to_async(handlers::proofing::set_proofing);
+
+                    route
+                        .get("/:id/proofing")
+                        .with_path_extractor::<handlers::proofing::ShareLinkPathExtractor>()
+                        .to_async(handlers::proofing::proofing_summary);
+
+                    route
+                        .get("/:id/proofing/export")
+                        .with_path_extractor::<handlers::proofing::ShareLinkPathExtractor>()
+                        .with_query_string_extractor::<handlers::proofing::ExportQueryExtractor>()
+                        .to_async(handlers::proofing::export_selections);
                 });
 
                 route.scope("/photo", |route| {
+                    route
+                        .get("/:id")
+                        .with_path_extractor::<handlers::photos::PhotoPathExtractor>()
+                        .to_async(handlers::photos::get_photo);
+
                     route
                         .put("/:id")
                         .with_path_extractor::<handlers::photos::PhotoPathExtractor>()
                         .to_async(handlers::photos::update_photo);
 
+                    route
+                        .patch("/:id")
+                        .with_path_extractor::<handlers::photos::PhotoPathExtractor>()
+                        .to_async(handlers::photos::patch_photo);
+
                     route
                         .delete("/:id")
                         .with_path_extractor::<handlers::photos::PhotoPathExtractor>()
                         .to_async(handlers::photos::delete_photo);
 
+                    route
+                        .get("/:id/tags")
+                        .with_path_extractor::<handlers::tags::PhotoPathExtractor>()
+                        .to_async(handlers::tags::photo_tags);
+
+                    route
+                        .post("/:id/image")
+                        .with_path_extractor::<handlers::photos::PhotoPathExtractor>()
+                        .to_async(handlers::photos::replace_photo);
+
+                    route
+                        .get("/:id/versions")
+                        .with_path_extractor::<handlers::photos::PhotoPathExtractor>()
+                        .to_async(handlers::photos::photo_versions);
+
+                    route
+                        .post("/:id/versions/:version_id/revert")
+                        .with_path_extractor::<handlers::photos::PhotoVersionPathExtractor>()
+                        .to_async(handlers::photos::revert_photo);
+
                     route
                         .post("/upload")
                         .to_async(handlers::photos::upload_photo);
+
+                    route.post("/bulk").to_async(handlers::photos::bulk_photos);
+
+                    route
+                        .post("/upload/batch")
+                        .to_async(handlers::photos::upload_photos);
+                });
+
+                route
+                    .post("/token/revoke")
+                    .to_async(handlers::sessions::revoke_token);
+
+                route
+                    .get("/audit")
+                    .with_query_string_extractor::<handlers::audit::AuditQueryExtractor>()
+                    .to_async(handlers::audit::all_audit_events);
+
+                route.scope("/studios", |route| {
+                    route.get("/").to_async(handlers::studios::all_studios);
+
+                    route.post("/").to_async(handlers::studios::new_studio);
+
+                    route
+                        .put("/:id")
+                        .with_path_extractor::<handlers::studios::StudioPathExtractor>()
+                        .to_async(handlers::studios::rename_studio);
                 });
 
                 route.scope("/book_me", |route| {
@@ -182,6 +430,63 @@ fn router() -> Router {
                 });
             });
 
+            // Managing the account needs a signed in session, API keys are rejected.
+            route.with_pipeline_chain(session_only_chain, |route| {
+                route.scope("/keys", |route| {
+                    route.get("/").to_async(handlers::api_keys::all_api_keys);
+
+                    route.post("/").to_async(handlers::api_keys::new_api_key);
+
+                    route
+                        .delete("/:id")
+                        .with_path_extractor::<handlers::api_keys::ApiKeyPathExtractor>()
+                        .to_async(handlers::api_keys::revoke_api_key);
+                });
+
+                route.scope("/studios", |route| {
+                    route
+                        .get("/:id/members")
+                        .with_path_extractor::<handlers::studios::StudioPathExtractor>()
+                        .to_async(handlers::studios::studio_members);
+
+                    route
+                        .put("/:id/members")
+                        .with_path_extractor::<handlers::studios::StudioPathExtractor>()
+                        .to_async(handlers::studios::set_studio_member);
+
+                    route
+                        .delete("/:id/members/:user_id")
+                        .with_path_extractor::<handlers::studios::StudioMemberPathExtractor>()
+                        .to_async(handlers::studios::remove_studio_member);
+                });
+
+                route.scope("/invitations", |route| {
+                    route
+                        .get("/")
+                        .to_async(handlers::invitations::all_invitations);
+
+                    route.post("/").to_async(handlers::invitations::invite);
+
+                    route
+                        .delete("/:id")
+                        .with_path_extractor::<handlers::invitations::InvitationPathExtractor>()
+                        .to_async(handlers::invitations::revoke_invitation);
+                });
+
+                route.scope("/sessions", |route| {
+                    route.get("/").to_async(handlers::sessions::all_sessions);
+
+                    route
+                        .delete("/")
+                        .to_async(handlers::sessions::revoke_all_sessions);
+
+                    route
+                        .delete("/:id")
+                        .with_path_extractor::<handlers::sessions::SessionPathExtractor>()
+                        .to_async(handlers::sessions::revoke_session);
+                });
+            });
+
             // CORS, need to investigate a better way to do this without repeating routes.
             route.with_pipeline_chain(cors_preflight_chain, |route| {
                 route
@@ -193,17 +498,105 @@ fn router() -> Router {
                     .to(empty_handler);
 
                 route
-                    .request(OPTIONS_OR_HEAD.clone(), "/public/album/:name")
+                    .request(OPTIONS_OR_HEAD.clone(), "/public/album/:slug")
+                    .to(empty_handler);
+
+                route
+                    .request(OPTIONS_OR_HEAD.clone(), "/public/album/:slug/unlock")
+                    .to(empty_handler);
+
+                route
+                    .request(OPTIONS_OR_HEAD.clone(), "/public/collections")
+                    .to(empty_handler);
+
+                route
+                    .request(OPTIONS_OR_HEAD.clone(), "/public/collection/:slug")
+                    .to(empty_handler);
+
+                route
+                    .request(OPTIONS_OR_HEAD.clone(), "/public/favorites")
+                    .to(empty_handler);
+
+                route
+                    .request(OPTIONS_OR_HEAD.clone(), "/public/shared/:token")
+                    .to(empty_handler);
+
+                route
+                    .request(OPTIONS_OR_HEAD.clone(), "/public/shared/:token/unlock")
+                    .to(empty_handler);
+
+                route
+                    .request(OPTIONS_OR_HEAD.clone(), "/public/shared/:token/download")
+                    .to(empty_handler);
+
+                route
+                    .request(OPTIONS_OR_HEAD.clone(), "/public/shared/:token/proofing")
+                    .to(empty_handler);
+
+                route
+                    .request(
+                        OPTIONS_OR_HEAD.clone(),
+                        "/public/shared/:token/proofing/photo/:photo_id",
+                    )
+                    .to(empty_handler);
+
+                route
+                    .request(
+                        OPTIONS_OR_HEAD.clone(),
+                        "/public/shared/:token/proofing/photo/:photo_id/comments",
+                    )
                     .to(empty_handler);
 
                 route
                     .request(OPTIONS_OR_HEAD.clone(), "/public/book_me")
                     .to(empty_handler);
 
+                route
+                    .request(OPTIONS_OR_HEAD.clone(), "/token/refresh")
+                    .to(empty_handler);
+
+                route
+                    .request(OPTIONS_OR_HEAD.clone(), "/token/exchange")
+                    .to(empty_handler);
+
+                route
+                    .request(OPTIONS_OR_HEAD.clone(), "/login/email")
+                    .to(empty_handler);
+
+                route
+                    .request(OPTIONS_OR_HEAD.clone(), "/token/revoke")
+                    .to(empty_handler);
+
                 route
                     .request(OPTIONS_OR_HEAD.clone(), "/albums")
                     .to(empty_handler);
 
+                route
+                    .request(OPTIONS_OR_HEAD.clone(), "/albums/tree")
+                    .to(empty_handler);
+
+                route
+                    .request(OPTIONS_OR_HEAD.clone(), "/search")
+                    .to(empty_handler);
+
+                route
+                    .request(OPTIONS_OR_HEAD.clone(), "/favorites")
+                    .to(empty_handler);
+
+                route.scope("/tags", |route| {
+                    route
+                        .request(OPTIONS_OR_HEAD.clone(), "/")
+                        .to(empty_handler);
+
+                    route
+                        .request(OPTIONS_OR_HEAD.clone(), "/add")
+                        .to(empty_handler);
+
+                    route
+                        .request(OPTIONS_OR_HEAD.clone(), "/remove")
+                        .to(empty_handler);
+                });
+
                 route.scope("/album", |route| {
                     route
                         .request(OPTIONS_OR_HEAD.clone(), "/")
@@ -217,9 +610,47 @@ fn router() -> Router {
                         .request(OPTIONS_OR_HEAD.clone(), "/:id/photos")
                         .to(empty_handler);
 
+                    route
+                        .request(OPTIONS_OR_HEAD.clone(), "/:id/download")
+                        .to(empty_handler);
+
+                    route
+                        .request(OPTIONS_OR_HEAD.clone(), "/:id/parent")
+                        .to(empty_handler);
+
                     route
                         .request(OPTIONS_OR_HEAD.clone(), "/:id/photo")
                         .to(empty_handler);
+
+                    route
+                        .request(OPTIONS_OR_HEAD.clone(), "/:id/password")
+                        .to(empty_handler);
+
+                    route
+                        .request(OPTIONS_OR_HEAD.clone(), "/:id/share_links")
+                        .to(empty_handler);
+                });
+
+                route.scope("/share_link", |route| {
+                    route
+                        .request(OPTIONS_OR_HEAD.clone(), "/:id")
+                        .to(empty_handler);
+
+                    route
+                        .request(OPTIONS_OR_HEAD.clone(), "/:id/password")
+                        .to(empty_handler);
+
+                    route
+                        .request(OPTIONS_OR_HEAD.clone(), "/:id/downloads")
+                        .to(empty_handler);
+
+                    route
+                        .request(OPTIONS_OR_HEAD.clone(), "/:id/proofing")
+                        .to(empty_handler);
+
+                    route
+                        .request(OPTIONS_OR_HEAD.clone(), "/:id/proofing/export")
+                        .to(empty_handler);
                 });
 
                 route.scope("/photo", |route| {
@@ -227,9 +658,85 @@ fn router() -> Router {
                         .request(OPTIONS_OR_HEAD.clone(), "/:id")
                         .to(empty_handler);
 
+                    route
+                        .request(OPTIONS_OR_HEAD.clone(), "/:id/tags")
+                        .to(empty_handler);
+
+                    route
+                        .request(OPTIONS_OR_HEAD.clone(), "/:id/image")
+                        .to(empty_handler);
+
+                    route
+                        .request(OPTIONS_OR_HEAD.clone(), "/:id/versions")
+                        .to(empty_handler);
+
+                    route
+                        .request(OPTIONS_OR_HEAD.clone(), "/:id/versions/:version_id/revert")
+                        .to(empty_handler);
+
                     route
                         .request(OPTIONS_OR_HEAD.clone(), "/upload")
                         .to(empty_handler);
+
+                    route
+                        .request(OPTIONS_OR_HEAD.clone(), "/upload/batch")
+                        .to(empty_handler);
+
+                    route
+                        .request(OPTIONS_OR_HEAD.clone(), "/bulk")
+                        .to(empty_handler);
+                });
+
+                route
+                    .request(OPTIONS_OR_HEAD.clone(), "/audit")
+                    .to(empty_handler);
+
+                route.scope("/keys", |route| {
+                    route
+                        .request(OPTIONS_OR_HEAD.clone(), "/")
+                        .to(empty_handler);
+
+                    route
+                        .request(OPTIONS_OR_HEAD.clone(), "/:id")
+                        .to(empty_handler);
+                });
+
+                route.scope("/studios", |route| {
+                    route
+                        .request(OPTIONS_OR_HEAD.clone(), "/")
+                        .to(empty_handler);
+
+                    route
+                        .request(OPTIONS_OR_HEAD.clone(), "/:id")
+                        .to(empty_handler);
+
+                    route
+                        .request(OPTIONS_OR_HEAD.clone(), "/:id/members")
+                        .to(empty_handler);
+
+                    route
+                        .request(OPTIONS_OR_HEAD.clone(), "/:id/members/:user_id")
+                        .to(empty_handler);
+                });
+
+                route.scope("/invitations", |route| {
+                    route
+                        .request(OPTIONS_OR_HEAD.clone(), "/")
+                        .to(empty_handler);
+
+                    route
+                        .request(OPTIONS_OR_HEAD.clone(), "/:id")
+                        .to(empty_handler);
+                });
+
+                route.scope("/sessions", |route| {
+                    route
+                        .request(OPTIONS_OR_HEAD.clone(), "/")
+                        .to(empty_handler);
+
+                    route
+                        .request(OPTIONS_OR_HEAD.clone(), "/:id")
+                        .to(empty_handler);
                 });
 
                 route.scope("/book_me", |route| {
@@ -241,3 +748,803 @@ fn router() -> Router {
         })
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::encode_token;
+    use crate::utils::set_test_env;
+    use gotham::hyper::header::{HeaderValue, AUTHORIZATION};
+    use gotham::hyper::StatusCode;
+    use gotham::test::TestServer;
+    use photo_core::connection::Conn;
+    use photo_core::models::{
+        Album, ApiKey, ApiKeyScope, Invitation, LoginLink, Photo, Role, Session, ShareLink, Studio,
+        User, MAX_BULK_PHOTOS,
+    };
+    use serde_json::{json, Value};
+    use std::fs;
+    use uuid::Uuid;
+
+    /// An album with a photo and a share link, owned by one user, and the tokens of that user and
+    /// of another one to request them with.
+    struct Fixture {
+        server: TestServer,
+        database: String,
+        owner: User,
+        owner_token: String,
+        intruder_token: String,
+        studio: Studio,
+        album: Album,
+        photo: Photo,
+        link: ShareLink,
+    }
+
+    /// Access token of a new session of the user.
+    fn token(conn: &Conn, user: &User) -> String {
+        let (session, _) = Session::new(user, None, None);
+        let session = session.insert(conn).unwrap();
+
+        encode_token(user, &session, 3600)
+    }
+
+    impl Fixture {
+        fn new() -> Fixture {
+            set_test_env();
+            let database = std::env::temp_dir()
+                .join(format!("photo-api-{}.db", Uuid::new_v4()))
+                .to_string_lossy()
+                .into_owned();
+            let conn = connect(Some(database.clone())).unwrap();
+            db_migrate(&conn).unwrap();
+            apply_custom_migrations(Some(database.clone())).unwrap();
+
+            let owner = User::new(String::from("owner@example.com"), None)
+                .insert(&conn)
+                .unwrap();
+            let intruder = User::new(String::from("intruder@example.com"), None)
+                .insert(&conn)
+                .unwrap();
+
+            let studio = Studio::new(String::from("Owner"))
+                .insert_personal(&conn, &owner)
+                .unwrap();
+            let album = Album::new(&owner, &studio, String::from("Private"), None)
+                .insert(&conn)
+                .unwrap();
+            let photo = Photo::new(
+                &album,
+                &owner,
+                0,
+                String::from("key"),
+                String::from("src"),
+                String::from("#fff"),
+                None,
+                None,
+                1,
+                1,
+                false,
+                None,
+                None,
+            )
+            .insert(&conn)
+            .unwrap();
+            let link = ShareLink::new(&album, None, None).insert(&conn).unwrap();
+
+            Fixture {
+                server: TestServer::new(router(Repo::new(&database))).unwrap(),
+                database,
+                owner_token: token(&conn, &owner),
+                intruder_token: token(&conn, &intruder),
+                owner,
+                studio,
+                album,
+                photo,
+                link,
+            }
+        }
+
+        /// Status of a request made with `token`, `body` is only sent for `POST` and `PUT`.
+        fn status(&self, token: &str, method: Method, path: &str, body: Value) -> StatusCode {
+            let client = self.server.client();
+            let uri = format!("http://localhost/api{}", path);
+            let body = body.to_string();
+            let request = match method {
+                Method::GET => client.get(uri),
+                Method::DELETE => client.delete(uri),
+                Method::POST => client.post(uri, body, mime::APPLICATION_JSON),
+                Method::PUT => client.put(uri, body, mime::APPLICATION_JSON),
+                _ => unreachable!("Unsupported method {}", method),
+            };
+            let auth = HeaderValue::from_str(&format!("Bearer {}", token)).unwrap();
+
+            request
+                .with_header(AUTHORIZATION, auth)
+                .perform()
+                .unwrap()
+                .status()
+        }
+
+        fn intruder_status(&self, method: Method, path: &str, body: Value) -> StatusCode {
+            self.status(&self.intruder_token, method, path, body)
+        }
+
+        /// Status of a multipart `POST` made with `token`, sending `data` as a single file.
+        fn upload_status(&self, token: &str, path: &str, data: &[u8]) -> StatusCode {
+            const BOUNDARY: &str = "photo-api-test";
+            let uri = format!("http://localhost/api{}", path);
+            let mut body = format!(
+                "--{}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"photo.jpg\"\r\n\
+                 Content-Type: image/jpeg\r\n\r\n",
+                BOUNDARY
+            )
+            .into_bytes();
+            body.extend_from_slice(data);
+            body.extend_from_slice(format!("\r\n--{}--\r\n", BOUNDARY).as_bytes());
+            let content_type = format!("multipart/form-data; boundary={}", BOUNDARY)
+                .parse()
+                .unwrap();
+            let auth = HeaderValue::from_str(&format!("Bearer {}", token)).unwrap();
+
+            self.server
+                .client()
+                .post(uri, body, content_type)
+                .with_header(AUTHORIZATION, auth)
+                .perform()
+                .unwrap()
+                .status()
+        }
+    }
+
+    impl Drop for Fixture {
+        fn drop(&mut self) {
+            fs::remove_file(&self.database).ok();
+        }
+    }
+
+    #[test]
+    fn owner_gets_own_album() {
+        let fixture = Fixture::new();
+        let path = format!("/album/{}", fixture.album.id);
+
+        let status = fixture.status(&fixture.owner_token, Method::GET, &path, Value::Null);
+
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[test]
+    fn revoked_session_token_is_rejected() {
+        let fixture = Fixture::new();
+        let conn = connect(Some(fixture.database.clone())).unwrap();
+        Session::revoke_all(&conn, &fixture.owner).unwrap();
+        let path = format!("/album/{}", fixture.album.id);
+
+        let status = fixture.status(&fixture.owner_token, Method::GET, &path, Value::Null);
+
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn revoked_token_is_rejected() {
+        let fixture = Fixture::new();
+        let path = format!("/album/{}", fixture.album.id);
+
+        let revoked = fixture.status(
+            &fixture.owner_token,
+            Method::POST,
+            "/token/revoke",
+            Value::Null,
+        );
+        let status = fixture.status(&fixture.owner_token, Method::GET, &path, Value::Null);
+
+        assert_eq!(revoked, StatusCode::OK);
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn login_links_can_only_be_used_once() {
+        let fixture = Fixture::new();
+        let conn = connect(Some(fixture.database.clone())).unwrap();
+        let (link, token) = LoginLink::new(fixture.owner.email.clone());
+        link.insert(&conn).unwrap();
+        let login = || {
+            fixture
+                .server
+                .client()
+                .post(
+                    "http://localhost/email/login",
+                    format!("token={}", token),
+                    mime::APPLICATION_WWW_FORM_URLENCODED,
+                )
+                .perform()
+                .unwrap()
+                .status()
+        };
+
+        let first = login();
+        let second = login();
+
+        assert!(first.is_success() || first.is_redirection(), "{}", first);
+        assert_eq!(second, StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn login_link_page_rejects_malformed_tokens() {
+        let fixture = Fixture::new();
+
+        let status = fixture
+            .server
+            .client()
+            .get("http://localhost/email/login?token=%22%3E%3Cscript%3E")
+            .perform()
+            .unwrap()
+            .status();
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn only_admins_manage_invitations() {
+        let fixture = Fixture::new();
+        let conn = connect(Some(fixture.database.clone())).unwrap();
+        let mut admin = User::new(String::from("admin@example.com"), None);
+        admin.is_admin = true;
+        let admin = admin.insert(&conn).unwrap();
+        let admin_token = token(&conn, &admin);
+        let invitation = json!({ "email": " Guest@Example.com " });
+        let unknown = format!("/invitations/{}", Uuid::new_v4());
+
+        let requests = vec![
+            (Method::GET, String::from("/invitations"), Value::Null),
+            (
+                Method::POST,
+                String::from("/invitations"),
+                invitation.clone(),
+            ),
+            (Method::DELETE, unknown.clone(), Value::Null),
+        ];
+        for (method, path, body) in requests {
+            let status = fixture.status(&fixture.owner_token, method.clone(), &path, body);
+            assert_eq!(status, StatusCode::FORBIDDEN, "{} {}", method, path);
+        }
+
+        let invited = fixture.status(&admin_token, Method::POST, "/invitations", invitation);
+        let revoked = fixture.status(&admin_token, Method::DELETE, &unknown, Value::Null);
+
+        assert_eq!(invited, StatusCode::OK);
+        assert_eq!(revoked, StatusCode::NOT_FOUND);
+        assert_eq!(
+            Invitation::find_all(&conn).unwrap()[0].email,
+            "guest@example.com"
+        );
+    }
+
+    #[test]
+    fn api_keys_are_limited_to_their_scope() {
+        let fixture = Fixture::new();
+        let conn = connect(Some(fixture.database.clone())).unwrap();
+        let (read, read_key) = ApiKey::new(&fixture.owner, String::from("read"), ApiKeyScope::Read);
+        read.insert(&conn).unwrap();
+        let (write, write_key) =
+            ApiKey::new(&fixture.owner, String::from("write"), ApiKeyScope::Write);
+        write.insert(&conn).unwrap();
+        let album_path = format!("/album/{}", fixture.album.id);
+        let album = json!({ "name": "Renamed", "description": null });
+        let api_key = json!({ "name": "another", "scope": "write" });
+
+        let requests = vec![
+            (
+                read_key.as_str(),
+                Method::GET,
+                album_path.clone(),
+                Value::Null,
+                StatusCode::OK,
+            ),
+            (
+                read_key.as_str(),
+                Method::PUT,
+                album_path.clone(),
+                album.clone(),
+                StatusCode::FORBIDDEN,
+            ),
+            (
+                write_key.as_str(),
+                Method::PUT,
+                album_path,
+                album,
+                StatusCode::OK,
+            ),
+            (
+                write_key.as_str(),
+                Method::GET,
+                String::from("/keys"),
+                Value::Null,
+                StatusCode::FORBIDDEN,
+            ),
+            (
+                write_key.as_str(),
+                Method::POST,
+                String::from("/keys"),
+                api_key,
+                StatusCode::FORBIDDEN,
+            ),
+            (
+                "pk_unknown",
+                Method::GET,
+                String::from("/albums"),
+                Value::Null,
+                StatusCode::UNAUTHORIZED,
+            ),
+        ];
+        for (key, method, path, body, expected) in requests {
+            let status = fixture.status(key, method.clone(), &path, body);
+            assert_eq!(status, expected, "{} {}", method, path);
+        }
+    }
+
+    #[test]
+    fn api_keys_cannot_manage_the_account() {
+        let fixture = Fixture::new();
+        let conn = connect(Some(fixture.database.clone())).unwrap();
+        let (write, write_key) =
+            ApiKey::new(&fixture.owner, String::from("write"), ApiKeyScope::Write);
+        let write = write.insert(&conn).unwrap();
+        User::new(String::from("member@example.com"), None)
+            .insert(&conn)
+            .unwrap();
+        let members = format!("/studios/{}/members", fixture.studio.id);
+        let member = json!({ "email": "member@example.com", "role": "editor" });
+
+        let requests = vec![
+            (Method::DELETE, format!("/keys/{}", write.id), Value::Null),
+            (
+                Method::POST,
+                String::from("/invitations"),
+                json!({ "email": "guest@example.com" }),
+            ),
+            (Method::GET, String::from("/invitations"), Value::Null),
+            (Method::GET, members.clone(), Value::Null),
+            (Method::PUT, members.clone(), member.clone()),
+            (
+                Method::DELETE,
+                format!("{}/{}", members, fixture.owner.id),
+                Value::Null,
+            ),
+            (Method::GET, String::from("/sessions"), Value::Null),
+            (Method::DELETE, String::from("/sessions"), Value::Null),
+        ];
+        for (method, path, body) in requests {
+            let status = fixture.status(&write_key, method.clone(), &path, body);
+            assert_eq!(status, StatusCode::FORBIDDEN, "{} {}", method, path);
+        }
+
+        let studios = fixture.status(&write_key, Method::GET, "/studios", Value::Null);
+        let added = fixture.status(&fixture.owner_token, Method::PUT, &members, member);
+
+        assert_eq!(studios, StatusCode::OK);
+        assert_eq!(added, StatusCode::OK);
+    }
+
+    #[test]
+    fn members_act_according_to_their_role() {
+        let fixture = Fixture::new();
+        let conn = connect(Some(fixture.database.clone())).unwrap();
+        let member = User::new(String::from("member@example.com"), None)
+            .insert(&conn)
+            .unwrap();
+        let member_token = token(&conn, &member);
+        let album_path = format!("/album/{}", fixture.album.id);
+        let photo_path = format!("/photo/{}", fixture.photo.id);
+        let album = json!({ "name": "Renamed", "description": null });
+
+        fixture
+            .studio
+            .set_member(&conn, &member, Role::Viewer)
+            .unwrap();
+        let requests = vec![
+            (Method::GET, album_path.clone(), Value::Null, StatusCode::OK),
+            (Method::GET, photo_path.clone(), Value::Null, StatusCode::OK),
+            (
+                Method::PUT,
+                album_path.clone(),
+                album.clone(),
+                StatusCode::FORBIDDEN,
+            ),
+            (
+                Method::DELETE,
+                photo_path.clone(),
+                Value::Null,
+                StatusCode::FORBIDDEN,
+            ),
+        ];
+        for (method, path, body, expected) in requests {
+            let status = fixture.status(&member_token, method.clone(), &path, body);
+            assert_eq!(status, expected, "viewer {} {}", method, path);
+        }
+
+        fixture
+            .studio
+            .set_member(&conn, &member, Role::Editor)
+            .unwrap();
+        let requests = vec![
+            (Method::PUT, album_path.clone(), album, StatusCode::OK),
+            (
+                Method::DELETE,
+                album_path.clone(),
+                Value::Null,
+                StatusCode::FORBIDDEN,
+            ),
+        ];
+        for (method, path, body, expected) in requests {
+            let status = fixture.status(&member_token, method.clone(), &path, body);
+            assert_eq!(status, expected, "editor {} {}", method, path);
+        }
+    }
+
+    #[test]
+    fn album_changes_are_visible_to_their_studio_owner_only() {
+        let fixture = Fixture::new();
+        let path = format!("/album/{}", fixture.album.id);
+        let album = json!({ "name": "Renamed", "description": null });
+        let events = |token: &str| -> Value {
+            let auth = HeaderValue::from_str(&format!("Bearer {}", token)).unwrap();
+            let res = fixture
+                .server
+                .client()
+                .get("http://localhost/api/audit?targetType=album")
+                .with_header(AUTHORIZATION, auth)
+                .perform()
+                .unwrap();
+            assert_eq!(res.status(), StatusCode::OK);
+
+            serde_json::from_slice(&res.read_body().unwrap()).unwrap()
+        };
+
+        let status = fixture.status(&fixture.owner_token, Method::PUT, &path, album);
+        let seen_by_owner = events(&fixture.owner_token);
+        let seen_by_intruder = events(&fixture.intruder_token);
+        let invalid_cursor = fixture.status(
+            &fixture.owner_token,
+            Method::GET,
+            "/audit?cursor=invalid",
+            Value::Null,
+        );
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(invalid_cursor, StatusCode::BAD_REQUEST);
+        assert_eq!(seen_by_owner["list"][0]["action"], "update");
+        assert_eq!(
+            seen_by_owner["list"][0]["targetId"],
+            json!(fixture.album.id)
+        );
+        assert_eq!(
+            seen_by_owner["list"][0]["changes"]["name"]["after"],
+            "Renamed"
+        );
+        assert_eq!(seen_by_intruder["list"], json!([]));
+    }
+
+    #[test]
+    fn proofing_clients_act_with_their_secret() {
+        let fixture = Fixture::new();
+        let conn = connect(Some(fixture.database.clone())).unwrap();
+        fixture.link.set_proofing(&conn, true).unwrap();
+        let register_path = format!(
+            "http://localhost/api/public/shared/{}/proofing",
+            fixture.link.token
+        );
+        let select_path = format!(
+            "/public/shared/{}/proofing/photo/{}",
+            fixture.link.token, fixture.photo.id
+        );
+        let register = |email: &str| {
+            fixture
+                .server
+                .client()
+                .post(
+                    register_path.clone(),
+                    json!({ "name": "Jane", "email": email }).to_string(),
+                    mime::APPLICATION_JSON,
+                )
+                .perform()
+                .unwrap()
+        };
+
+        let registered = register("jane@example.com");
+        assert_eq!(registered.status(), StatusCode::OK);
+        let registered: Value = serde_json::from_slice(&registered.read_body().unwrap()).unwrap();
+        let secret = registered["secret"].as_str().unwrap();
+        let taken = register("jane@example.com").status();
+        let guessed = fixture.status(
+            "",
+            Method::PUT,
+            &select_path,
+            json!({ "secret": "guess", "selected": true }),
+        );
+        let selected = fixture.status(
+            "",
+            Method::PUT,
+            &select_path,
+            json!({ "secret": secret, "selected": true }),
+        );
+
+        assert_eq!(taken, StatusCode::CONFLICT);
+        assert_eq!(guessed, StatusCode::UNAUTHORIZED);
+        assert_eq!(selected, StatusCode::OK);
+    }
+
+    #[test]
+    fn public_pages_of_unknown_users_are_not_found() {
+        let fixture = Fixture::new();
+        let id = Uuid::new_v4();
+
+        let requests = vec![
+            (Method::GET, format!("/public/album?id={}", id), Value::Null),
+            (
+                Method::GET,
+                format!("/public/album/private?id={}", id),
+                Value::Null,
+            ),
+            (
+                Method::POST,
+                format!("/public/album/private/unlock?id={}", id),
+                json!({ "password": "secret" }),
+            ),
+            (
+                Method::GET,
+                format!("/public/collections?id={}", id),
+                Value::Null,
+            ),
+            (
+                Method::GET,
+                format!("/public/collection/private?id={}", id),
+                Value::Null,
+            ),
+            (
+                Method::GET,
+                format!("/public/favorites?id={}", id),
+                Value::Null,
+            ),
+        ];
+
+        for (method, path, body) in requests {
+            let status = fixture.status("", method.clone(), &path, body);
+            assert_eq!(status, StatusCode::NOT_FOUND, "{} {}", method, path);
+        }
+    }
+
+    #[test]
+    fn shared_albums_are_paginated() {
+        let fixture = Fixture::new();
+        let path = format!("/public/shared/{}", fixture.link.token);
+        let res = fixture
+            .server
+            .client()
+            .get(format!(
+                "http://localhost/api{}?limit=1&sort=createdAt",
+                path
+            ))
+            .perform()
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let page: Value = serde_json::from_slice(&res.read_body().unwrap()).unwrap();
+
+        let invalid_cursor = fixture.status(
+            "",
+            Method::GET,
+            &format!("{}?cursor=invalid", path),
+            Value::Null,
+        );
+
+        assert_eq!(page["total"], 1);
+        assert_eq!(page["album"][1][0]["id"], json!(fixture.photo.id));
+        assert_eq!(page["nextCursor"], Value::Null);
+        assert_eq!(invalid_cursor, StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn other_user_album_is_not_found() {
+        let fixture = Fixture::new();
+        let id = fixture.album.id;
+        let album = json!({ "name": "Mine", "description": null });
+
+        let requests = vec![
+            (Method::GET, format!("/album/{}", id), Value::Null),
+            (Method::PUT, format!("/album/{}", id), album),
+            (Method::DELETE, format!("/album/{}", id), Value::Null),
+            (Method::GET, format!("/album/{}/photos", id), Value::Null),
+            (
+                Method::GET,
+                format!("/album/{}/share_links", id),
+                Value::Null,
+            ),
+            (
+                Method::POST,
+                format!("/album/{}/share_links", id),
+                json!({}),
+            ),
+        ];
+
+        for (method, path, body) in requests {
+            let status = fixture.intruder_status(method.clone(), &path, body);
+            assert_eq!(status, StatusCode::NOT_FOUND, "{} {}", method, path);
+        }
+    }
+
+    #[test]
+    fn albums_are_moved_by_their_owner_only() {
+        let fixture = Fixture::new();
+        let path = format!("/album/{}/parent", fixture.album.id);
+        let body = json!({ "parentId": null });
+
+        let anonymous = fixture.status("invalid", Method::PUT, &path, body.clone());
+        let intruder = fixture.intruder_status(Method::PUT, &path, body.clone());
+        let owner = fixture.status(&fixture.owner_token, Method::PUT, &path, body);
+
+        assert_eq!(anonymous, StatusCode::UNAUTHORIZED);
+        assert_eq!(intruder, StatusCode::NOT_FOUND);
+        assert_eq!(owner, StatusCode::OK);
+    }
+
+    #[test]
+    fn album_passwords_are_set_by_their_owner_only() {
+        let fixture = Fixture::new();
+        let path = format!("/album/{}/password", fixture.album.id);
+        let body = json!({ "password": "secret" });
+
+        let anonymous = fixture.status("invalid", Method::PUT, &path, body.clone());
+        let intruder = fixture.intruder_status(Method::PUT, &path, body.clone());
+        let cleared = fixture.intruder_status(Method::PUT, &path, json!({ "password": null }));
+        let owner = fixture.status(&fixture.owner_token, Method::PUT, &path, body);
+
+        assert_eq!(anonymous, StatusCode::UNAUTHORIZED);
+        assert_eq!(intruder, StatusCode::NOT_FOUND);
+        assert_eq!(cleared, StatusCode::NOT_FOUND);
+        assert_eq!(owner, StatusCode::OK);
+    }
+
+    #[test]
+    fn other_user_photo_is_not_found() {
+        let fixture = Fixture::new();
+        let id = fixture.photo.id;
+        let photo = json!({
+            "indexInAlbum": 0,
+            "isFavorite": true,
+            "title": null,
+            "description": null,
+        });
+
+        let requests = vec![
+            (Method::GET, format!("/photo/{}", id), Value::Null),
+            (Method::PUT, format!("/photo/{}", id), photo),
+            (Method::DELETE, format!("/photo/{}", id), Value::Null),
+        ];
+
+        for (method, path, body) in requests {
+            let status = fixture.intruder_status(method.clone(), &path, body);
+            assert_eq!(status, StatusCode::NOT_FOUND, "{} {}", method, path);
+        }
+    }
+
+    #[test]
+    fn photo_images_are_replaced_by_their_owner_only() {
+        let fixture = Fixture::new();
+        let path = format!("/photo/{}/image", fixture.photo.id);
+
+        let anonymous = fixture.upload_status("invalid", &path, b"not an image");
+        let intruder = fixture.upload_status(&fixture.intruder_token, &path, b"not an image");
+        let owner = fixture.upload_status(&fixture.owner_token, &path, b"not an image");
+
+        assert_eq!(anonymous, StatusCode::UNAUTHORIZED);
+        assert_eq!(intruder, StatusCode::NOT_FOUND);
+        // The owner gets past the ownership check, the data is then refused as not an image.
+        assert_eq!(owner, StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn photo_versions_are_managed_by_their_owner_only() {
+        let fixture = Fixture::new();
+        let versions = format!("/photo/{}/versions", fixture.photo.id);
+        let revert = format!(
+            "/photo/{}/versions/{}/revert",
+            fixture.photo.id,
+            Uuid::new_v4()
+        );
+
+        let requests = vec![
+            (Method::GET, versions.clone(), StatusCode::OK),
+            (Method::POST, revert, StatusCode::NOT_FOUND),
+        ];
+
+        for (method, path, owner_status) in requests {
+            let anonymous = fixture.status("invalid", method.clone(), &path, json!({}));
+            let intruder = fixture.intruder_status(method.clone(), &path, json!({}));
+            let owner = fixture.status(&fixture.owner_token, method.clone(), &path, json!({}));
+
+            assert_eq!(anonymous, StatusCode::UNAUTHORIZED, "{} {}", method, path);
+            assert_eq!(intruder, StatusCode::NOT_FOUND, "{} {}", method, path);
+            assert_eq!(owner, owner_status, "{} {}", method, path);
+        }
+    }
+
+    #[test]
+    fn bulk_photo_changes_are_checked() {
+        let fixture = Fixture::new();
+        let photo_ids = vec![fixture.photo.id.to_string()];
+        let favorite = json!({ "photoIds": photo_ids, "operation": { "type": "favorite" } });
+        let move_to = |album_id: String| {
+            json!({
+                "photoIds": photo_ids,
+                "operation": { "type": "move", "albumId": album_id },
+            })
+        };
+        let too_many = json!({
+            "photoIds": vec![fixture.photo.id.to_string(); MAX_BULK_PHOTOS + 1],
+            "operation": { "type": "favorite" },
+        });
+
+        let anonymous = fixture.status("invalid", Method::POST, "/photo/bulk", favorite.clone());
+        let intruder = fixture.intruder_status(
+            Method::POST,
+            "/photo/bulk",
+            move_to(fixture.album.id.to_string()),
+        );
+        let unknown = fixture.status(
+            &fixture.owner_token,
+            Method::POST,
+            "/photo/bulk",
+            move_to(Uuid::new_v4().to_string()),
+        );
+        let limit = fixture.status(&fixture.owner_token, Method::POST, "/photo/bulk", too_many);
+        let owner = fixture.status(&fixture.owner_token, Method::POST, "/photo/bulk", favorite);
+
+        assert_eq!(anonymous, StatusCode::UNAUTHORIZED);
+        assert_eq!(intruder, StatusCode::NOT_FOUND);
+        assert_eq!(unknown, StatusCode::NOT_FOUND);
+        assert_eq!(limit, StatusCode::BAD_REQUEST);
+        assert_eq!(owner, StatusCode::OK);
+    }
+
+    #[test]
+    fn other_user_share_link_is_not_found() {
+        let fixture = Fixture::new();
+        let id = fixture.link.id;
+
+        let requests = vec![
+            (
+                Method::PUT,
+                format!("/share_link/{}/password", id),
+                json!({ "password": "secret" }),
+            ),
+            (
+                Method::PUT,
+                format!("/share_link/{}/downloads", id),
+                json!({ "enabled": true }),
+            ),
+            (Method::DELETE, format!("/share_link/{}", id), Value::Null),
+        ];
+
+        for (method, path, body) in requests {
+            let status = fixture.intruder_status(method.clone(), &path, body);
+            assert_eq!(status, StatusCode::NOT_FOUND, "{} {}", method, path);
+        }
+    }
+
+    #[test]
+    fn other_user_photo_tags_are_not_found() {
+        let fixture = Fixture::new();
+        let id = fixture.photo.id;
+        let tags = json!({ "photoIds": [id.to_string()], "tags": ["sunset"] });
+
+        let requests = vec![
+            (Method::GET, format!("/photo/{}/tags", id), Value::Null),
+            (Method::POST, String::from("/tags/add"), tags.clone()),
+            (Method::POST, String::from("/tags/remove"), tags),
+        ];
+
+        for (method, path, body) in requests {
+            let status = fixture.intruder_status(method.clone(), &path, body);
+            assert_eq!(status, StatusCode::NOT_FOUND, "{} {}", method, path);
+        }
+    }
+}
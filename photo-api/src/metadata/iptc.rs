@@ -0,0 +1,143 @@
+use super::PhotoMetadata;
+
+const PHOTOSHOP_HEADER: &[u8] = b"Photoshop 3.0\0";
+const RESOURCE_SIGNATURE: &[u8] = b"8BIM";
+const IPTC_RESOURCE: u16 = 0x0404;
+
+const APP13: u8 = 0xED;
+const START_OF_SCAN: u8 = 0xDA;
+
+const RECORD_APPLICATION: u8 = 2;
+const OBJECT_NAME: u8 = 5;
+const KEYWORDS: u8 = 25;
+const HEADLINE: u8 = 105;
+const CAPTION: u8 = 120;
+
+/// Reads the IPTC-IIM record Photoshop stores in the APP13 segment of JPEG files.
+pub fn parse_jpeg(data: &[u8]) -> Option<PhotoMetadata> {
+    let iptc = find_jpeg_segment(data, APP13, PHOTOSHOP_HEADER).and_then(find_iptc_resource)?;
+
+    let mut title = None;
+    let mut headline = None;
+    let mut description = None;
+    let mut keywords = Vec::new();
+
+    for (record, dataset, value) in datasets(iptc) {
+        if record != RECORD_APPLICATION {
+            continue;
+        }
+
+        let value = decode(value);
+        match dataset {
+            OBJECT_NAME => title = Some(value),
+            HEADLINE => headline = Some(value),
+            CAPTION => description = Some(value),
+            KEYWORDS => keywords.push(value),
+            _ => (),
+        }
+    }
+
+    Some(PhotoMetadata::from_values(
+        title.or(headline),
+        description,
+        keywords,
+    ))
+}
+
+/// Payload (after `header`) of the first segment with the given marker.
+fn find_jpeg_segment<'a>(data: &'a [u8], marker: u8, header: &[u8]) -> Option<&'a [u8]> {
+    if !data.starts_with(&[0xFF, 0xD8]) {
+        return None;
+    }
+
+    let mut pos = 2;
+    while pos + 4 <= data.len() {
+        if data[pos] != 0xFF {
+            return None;
+        }
+
+        let segment = data[pos + 1];
+        if segment == START_OF_SCAN {
+            return None;
+        }
+
+        let length = u16::from_be_bytes([data[pos + 2], data[pos + 3]]) as usize;
+        let end = (pos + 2 + length).min(data.len());
+        let payload = &data[(pos + 4).min(end)..end];
+
+        if segment == marker && payload.starts_with(header) {
+            return Some(&payload[header.len()..]);
+        }
+
+        pos = end;
+    }
+
+    None
+}
+
+/// Finds the IPTC block among the Photoshop image resources.
+fn find_iptc_resource(resources: &[u8]) -> Option<&[u8]> {
+    let mut pos = 0;
+    while pos + 8 <= resources.len() && &resources[pos..pos + 4] == RESOURCE_SIGNATURE {
+        let id = u16::from_be_bytes([resources[pos + 4], resources[pos + 5]]);
+
+        // Pascal string name, padded so length byte and name take an even number of bytes.
+        let name_length = resources[pos + 6] as usize;
+        let mut cursor = pos + 6 + name_length + 1;
+        cursor += cursor % 2;
+
+        if cursor + 4 > resources.len() {
+            return None;
+        }
+
+        let size = u32::from_be_bytes([
+            resources[cursor],
+            resources[cursor + 1],
+            resources[cursor + 2],
+            resources[cursor + 3],
+        ]) as usize;
+        let start = cursor + 4;
+        let end = start.checked_add(size)?.min(resources.len());
+
+        if id == IPTC_RESOURCE {
+            return Some(&resources[start..end]);
+        }
+
+        pos = end + (end % 2);
+    }
+
+    None
+}
+
+/// Iterates over the `(record, dataset, value)` entries of an IPTC-IIM block.
+fn datasets(data: &[u8]) -> Vec<(u8, u8, &[u8])> {
+    let mut entries = Vec::new();
+    let mut pos = 0;
+
+    while pos + 5 <= data.len() && data[pos] == 0x1C {
+        let record = data[pos + 1];
+        let dataset = data[pos + 2];
+        let size = u16::from_be_bytes([data[pos + 3], data[pos + 4]]);
+
+        // Extended datasets (size with the high bit set) are never used for text values.
+        if size & 0x8000 != 0 {
+            break;
+        }
+
+        let start = pos + 5;
+        let end = (start + size as usize).min(data.len());
+        entries.push((record, dataset, &data[start..end]));
+
+        pos = end;
+    }
+
+    entries
+}
+
+/// Lightroom writes UTF-8, older tools Latin-1.
+fn decode(value: &[u8]) -> String {
+    match std::str::from_utf8(value) {
+        Ok(text) => text.to_string(),
+        Err(_) => value.iter().map(|&b| b as char).collect(),
+    }
+}
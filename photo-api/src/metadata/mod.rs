@@ -0,0 +1,205 @@
+mod iptc;
+mod pixels;
+mod xmp;
+
+pub use self::pixels::ImageInfo;
+
+use serde::Serialize;
+
+/// Title, caption and keywords written by editors like Lightroom into the photo (as XMP or IPTC)
+/// or into an `.xmp` sidecar file.
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PhotoMetadata {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub keywords: Vec<String>,
+}
+
+impl PhotoMetadata {
+    /// Reads the metadata embedded in an image. XMP is preferred over IPTC since it is the one
+    /// Lightroom keeps up to date, IPTC fills whatever XMP is missing.
+    pub fn from_image(data: &[u8]) -> Self {
+        let from_xmp = xmp::find_packet(data)
+            .and_then(xmp::parse)
+            .unwrap_or_default();
+        let from_iptc = iptc::parse_jpeg(data).unwrap_or_default();
+
+        from_xmp.or(from_iptc)
+    }
+
+    /// Reads an `.xmp` sidecar file.
+    pub fn from_sidecar(data: &[u8]) -> Self {
+        xmp::find_packet(data)
+            .and_then(xmp::parse)
+            .unwrap_or_default()
+    }
+
+    /// Fills the missing values with the ones of `other`.
+    pub fn or(self, other: PhotoMetadata) -> Self {
+        PhotoMetadata {
+            title: self.title.or(other.title),
+            description: self.description.or(other.description),
+            keywords: if self.keywords.is_empty() {
+                other.keywords
+            } else {
+                self.keywords
+            },
+        }
+    }
+
+    fn from_values(
+        title: Option<String>,
+        description: Option<String>,
+        keywords: Vec<String>,
+    ) -> Self {
+        let clean = |value: String| {
+            let value = value.trim().to_string();
+            if value.is_empty() {
+                None
+            } else {
+                Some(value)
+            }
+        };
+
+        let mut unique: Vec<String> = Vec::new();
+        for keyword in keywords.into_iter().filter_map(clean) {
+            if !unique.contains(&keyword) {
+                unique.push(keyword);
+            }
+        }
+
+        PhotoMetadata {
+            title: title.and_then(clean),
+            description: description.and_then(clean),
+            keywords: unique,
+        }
+    }
+}
+
+/// Whether the file is an XMP sidecar, judging by its name.
+pub fn is_sidecar(filename: &str) -> bool {
+    filename.to_lowercase().ends_with(".xmp")
+}
+
+/// File name without its extension, used to match photos with their sidecars.
+pub fn file_stem(filename: &str) -> &str {
+    match filename.rfind('.') {
+        Some(idx) => &filename[..idx],
+        None => filename,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIDECAR: &str = r#"<?xpacket begin="" id="W5M0MpCehiHzreSzNTczkc9d"?>
+<x:xmpmeta xmlns:x="adobe:ns:meta/">
+  <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+    <rdf:Description xmlns:dc="http://purl.org/dc/elements/1.1/">
+      <dc:title>
+        <rdf:Alt>
+          <rdf:li xml:lang="fr">Première danse</rdf:li>
+          <rdf:li xml:lang="x-default">First dance</rdf:li>
+        </rdf:Alt>
+      </dc:title>
+      <dc:description>
+        <rdf:Alt><rdf:li xml:lang="x-default">  </rdf:li></rdf:Alt>
+      </dc:description>
+      <dc:subject>
+        <rdf:Bag>
+          <rdf:li>wedding</rdf:li>
+          <rdf:li> dance </rdf:li>
+          <rdf:li>wedding</rdf:li>
+        </rdf:Bag>
+      </dc:subject>
+    </rdf:Description>
+  </rdf:RDF>
+</x:xmpmeta>
+<?xpacket end="w"?>"#;
+
+    /// JPEG with an APP13 segment holding the given IPTC datasets, followed by `extra`.
+    fn jpeg_with_iptc(datasets: &[(u8, &str)], extra: &[u8]) -> Vec<u8> {
+        let mut iptc = Vec::new();
+        for (dataset, value) in datasets {
+            iptc.extend_from_slice(&[0x1C, 2, *dataset]);
+            iptc.extend_from_slice(&(value.len() as u16).to_be_bytes());
+            iptc.extend_from_slice(value.as_bytes());
+        }
+
+        let mut resources = b"Photoshop 3.0\0".to_vec();
+        resources.extend_from_slice(b"8BIM");
+        resources.extend_from_slice(&0x0404u16.to_be_bytes());
+        resources.extend_from_slice(&[0, 0]);
+        resources.extend_from_slice(&(iptc.len() as u32).to_be_bytes());
+        resources.extend_from_slice(&iptc);
+
+        let mut jpeg = vec![0xFF, 0xD8, 0xFF, 0xED];
+        jpeg.extend_from_slice(&((resources.len() + 2) as u16).to_be_bytes());
+        jpeg.extend_from_slice(&resources);
+        jpeg.extend_from_slice(extra);
+        jpeg.extend_from_slice(&[0xFF, 0xDA, 0x00, 0x02]);
+
+        jpeg
+    }
+
+    #[test]
+    fn reads_sidecar_files() {
+        let metadata = PhotoMetadata::from_sidecar(SIDECAR.as_bytes());
+
+        assert_eq!(metadata.title.as_deref(), Some("First dance"));
+        assert_eq!(metadata.description, None);
+        assert_eq!(metadata.keywords, vec!["wedding", "dance"]);
+    }
+
+    #[test]
+    fn reads_iptc_from_jpeg() {
+        let jpeg = jpeg_with_iptc(
+            &[
+                (105, "Headline"),
+                (120, "Caption"),
+                (25, "cake"),
+                (25, "cake"),
+            ],
+            &[],
+        );
+
+        let metadata = PhotoMetadata::from_image(&jpeg);
+
+        assert_eq!(metadata.title.as_deref(), Some("Headline"));
+        assert_eq!(metadata.description.as_deref(), Some("Caption"));
+        assert_eq!(metadata.keywords, vec!["cake"]);
+    }
+
+    #[test]
+    fn xmp_is_preferred_over_iptc() {
+        let jpeg = jpeg_with_iptc(&[(5, "Old title"), (120, "Caption")], SIDECAR.as_bytes());
+
+        let metadata = PhotoMetadata::from_image(&jpeg);
+
+        assert_eq!(metadata.title.as_deref(), Some("First dance"));
+        assert_eq!(metadata.description.as_deref(), Some("Caption"));
+        assert_eq!(metadata.keywords, vec!["wedding", "dance"]);
+    }
+
+    #[test]
+    fn files_without_metadata_are_empty() {
+        assert_eq!(
+            PhotoMetadata::from_image(b"\xFF\xD8\xFF\xDA"),
+            PhotoMetadata::default()
+        );
+        assert_eq!(
+            PhotoMetadata::from_sidecar(b"<x:xmpmeta"),
+            PhotoMetadata::default()
+        );
+    }
+
+    #[test]
+    fn sidecars_are_matched_by_name() {
+        assert!(is_sidecar("IMG_0042.XMP"));
+        assert!(!is_sidecar("IMG_0042.jpg"));
+        assert_eq!(file_stem("IMG_0042.CR2.xmp"), "IMG_0042.CR2");
+        assert_eq!(file_stem("IMG_0042"), "IMG_0042");
+    }
+}
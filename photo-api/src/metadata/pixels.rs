@@ -0,0 +1,87 @@
+use image::imageops::FilterType;
+use image::{GenericImageView, RgbImage};
+use std::collections::HashMap;
+
+/// Side of the thumbnail sampled to find the main color.
+const SAMPLE_SIZE: u32 = 64;
+
+/// Values derived from the pixels of an image, the same ones the management app computes before
+/// creating a photo.
+#[derive(Debug, Clone)]
+pub struct ImageInfo {
+    pub width: i32,
+    pub height: i32,
+    pub main_color: String,
+}
+
+impl ImageInfo {
+    /// Decodes the image. Returns `None` when the data is not an image in a supported format.
+    pub fn read(data: &[u8]) -> Option<Self> {
+        let img = image::load_from_memory(data).ok()?;
+        let (width, height) = img.dimensions();
+        let sample = img
+            .resize(SAMPLE_SIZE, SAMPLE_SIZE, FilterType::Triangle)
+            .to_rgb();
+
+        Some(ImageInfo {
+            width: width as i32,
+            height: height as i32,
+            main_color: main_color(&sample),
+        })
+    }
+}
+
+/// Most common color of the image, leaving out the ones close to black or white. Similar colors
+/// are counted together and averaged.
+fn main_color(img: &RgbImage) -> String {
+    let mut groups: HashMap<[u8; 3], (u32, [u32; 3])> = HashMap::new();
+
+    for pixel in img.pixels() {
+        let [r, g, b] = pixel.0;
+        if !is_not_white_or_black(r, g, b) {
+            continue;
+        }
+
+        let group = groups
+            .entry([r / 16, g / 16, b / 16])
+            .or_insert((0, [0; 3]));
+        group.0 += 1;
+        group.1[0] += u32::from(r);
+        group.1[1] += u32::from(g);
+        group.1[2] += u32::from(b);
+    }
+
+    let popular = groups
+        .into_iter()
+        .max_by(|(a_key, a), (b_key, b)| a.0.cmp(&b.0).then(b_key.cmp(a_key)));
+
+    match popular {
+        Some((_, (count, [r, g, b]))) => {
+            format!("#{:02x}{:02x}{:02x}", r / count, g / count, b / count)
+        }
+        None => average_color(img),
+    }
+}
+
+/// Used when every pixel is close to black or white.
+fn average_color(img: &RgbImage) -> String {
+    let count = u64::from(img.width() * img.height()).max(1);
+    let mut sums = [0u64; 3];
+
+    for pixel in img.pixels() {
+        for (sum, value) in sums.iter_mut().zip(pixel.0.iter()) {
+            *sum += u64::from(*value);
+        }
+    }
+
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        sums[0] / count,
+        sums[1] / count,
+        sums[2] / count
+    )
+}
+
+fn is_not_white_or_black(r: u8, g: u8, b: u8) -> bool {
+    [r, g, b].iter().all(|c| *c > 10 && *c < 245)
+}
@@ -0,0 +1,72 @@
+use super::PhotoMetadata;
+use roxmltree::{Document, Node};
+
+const DC: &str = "http://purl.org/dc/elements/1.1/";
+const RDF: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+const XML: &str = "http://www.w3.org/XML/1998/namespace";
+
+/// Finds the XMP packet inside a file. Images keep it as plain text (in the APP1 segment for
+/// JPEG), so there is no need to understand the image format to find it.
+pub fn find_packet(data: &[u8]) -> Option<&str> {
+    let (start, end) = find_between(data, b"<x:xmpmeta", b"</x:xmpmeta>")
+        .or_else(|| find_between(data, b"<rdf:RDF", b"</rdf:RDF>"))?;
+
+    std::str::from_utf8(&data[start..end]).ok()
+}
+
+pub fn parse(packet: &str) -> Option<PhotoMetadata> {
+    let doc = Document::parse(packet).ok()?;
+
+    let title = find_property(&doc, "title").and_then(|node| alt_text(&node));
+    let description = find_property(&doc, "description").and_then(|node| alt_text(&node));
+    let keywords = find_property(&doc, "subject")
+        .map(|node| list_items(&node))
+        .unwrap_or_default();
+
+    Some(PhotoMetadata::from_values(title, description, keywords))
+}
+
+fn find_property<'a, 'input>(doc: &'a Document<'input>, name: &str) -> Option<Node<'a, 'input>> {
+    doc.root().descendants().find(|node| {
+        let tag = node.tag_name();
+        tag.namespace() == Some(DC) && tag.name() == name
+    })
+}
+
+/// Text of a language alternative, preferring the default language.
+fn alt_text(node: &Node) -> Option<String> {
+    let items: Vec<Node> = node
+        .descendants()
+        .filter(|n| n.has_tag_name((RDF, "li")))
+        .collect();
+
+    items
+        .iter()
+        .find(|n| n.attribute((XML, "lang")) == Some("x-default"))
+        .or_else(|| items.first())
+        .and_then(|n| n.text())
+        .or_else(|| node.text())
+        .map(String::from)
+}
+
+fn list_items(node: &Node) -> Vec<String> {
+    node.descendants()
+        .filter(|n| n.has_tag_name((RDF, "li")))
+        .filter_map(|n| n.text())
+        .map(String::from)
+        .collect()
+}
+
+fn find_between(data: &[u8], start: &[u8], end: &[u8]) -> Option<(usize, usize)> {
+    let from = find(data, start, 0)?;
+    let to = find(data, end, from)? + end.len();
+
+    Some((from, to))
+}
+
+fn find(data: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    data[from..]
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|idx| idx + from)
+}
@@ -4,8 +4,9 @@ use gotham::middleware::Middleware;
 use gotham::state::{request_id, FromState, State};
 use hyper::header::{
     HeaderMap, HeaderValue, ACCESS_CONTROL_ALLOW_CREDENTIALS, ACCESS_CONTROL_ALLOW_HEADERS,
-    ACCESS_CONTROL_ALLOW_METHODS, ACCESS_CONTROL_ALLOW_ORIGIN, ACCESS_CONTROL_MAX_AGE,
-    AUTHORIZATION, CONTENT_TYPE, ORIGIN,
+    ACCESS_CONTROL_ALLOW_METHODS, ACCESS_CONTROL_ALLOW_ORIGIN, ACCESS_CONTROL_EXPOSE_HEADERS,
+    ACCESS_CONTROL_MAX_AGE, AUTHORIZATION, CONTENT_TYPE, ETAG, IF_MATCH, IF_UNMODIFIED_SINCE,
+    ORIGIN,
 };
 use hyper::Method;
 use std::option::Option;
@@ -75,7 +76,7 @@ impl Middleware for CorsMiddleware {
                 .collect::<Vec<String>>()
                 .join(", ");
 
-            let headers = vec![AUTHORIZATION, CONTENT_TYPE]
+            let headers = vec![AUTHORIZATION, CONTENT_TYPE, IF_MATCH, IF_UNMODIFIED_SINCE]
                 .iter()
                 .map(|m| String::from(m.as_str()))
                 .collect::<Vec<String>>()
@@ -101,6 +102,11 @@ impl Middleware for CorsMiddleware {
                 HeaderValue::from_str(&methods).unwrap(),
             );
 
+            response.headers_mut().insert(
+                ACCESS_CONTROL_EXPOSE_HEADERS,
+                HeaderValue::from_str(ETAG.as_str()).unwrap(),
+            );
+
             response
                 .headers_mut()
                 .insert(ACCESS_CONTROL_MAX_AGE, HeaderValue::from(settings.max_age));
@@ -0,0 +1,85 @@
+use super::token::ApiKeyAuth;
+use crate::auth::AuthUser;
+use crate::conduit::{sessions, users};
+use crate::connection::Repo;
+use futures::prelude::*;
+use gotham::handler::HandlerFuture;
+use gotham::helpers::http::response::create_empty_response;
+use gotham::hyper::StatusCode;
+use gotham::middleware::Middleware;
+use gotham::state::{request_id, FromState, State};
+use gotham_middleware_jwt::AuthorizationToken;
+use photo_core::models::{ModelError, User};
+use std::pin::Pin;
+
+/// The user the request was authenticated as, loaded once by `CurrentUserMiddleware`.
+#[derive(Clone, StateData)]
+pub struct CurrentUser(pub User);
+
+/// Resolves the user of the bearer token, or of the API key, before the handler runs. Must come
+/// after the token middleware; requests whose user no longer exists or whose session was revoked
+/// are rejected with 401.
+#[derive(Clone, NewMiddleware, Debug, Default)]
+pub struct CurrentUserMiddleware;
+
+impl Middleware for CurrentUserMiddleware {
+    fn call<Chain>(self, mut state: State, chain: Chain) -> Pin<Box<HandlerFuture>>
+    where
+        Chain: FnOnce(State) -> Pin<Box<HandlerFuture>> + Send + 'static,
+        Self: Sized,
+    {
+        let repo = Repo::borrow_from(&state).clone();
+        let key_user_id =
+            ApiKeyAuth::try_borrow_from(&state).map(|api_key| api_key.0.user_id.to_string());
+        let claims = AuthorizationToken::<AuthUser>::try_borrow_from(&state)
+            .map(|token| (token.0.claims.email(), token.0.claims.session_id()));
+
+        async move {
+            // Keys are deleted along with their user, so the user of a valid key always exists.
+            if let Some(user_id) = key_user_id {
+                return match users::find_by_id(repo, user_id).await {
+                    Ok(user) => {
+                        state.put(CurrentUser(user));
+                        chain(state).await
+                    }
+                    Err(e) => Err((state, e.into())),
+                };
+            }
+
+            let (email, session_id) = match claims {
+                Some(claims) => claims,
+                None => {
+                    let res = create_empty_response(&state, StatusCode::UNAUTHORIZED);
+                    return Ok((state, res));
+                }
+            };
+
+            match sessions::is_active(repo.clone(), session_id).await {
+                Ok(true) => (),
+                Ok(false) => {
+                    debug!("[{}] token session was revoked", request_id(&state));
+                    let res = create_empty_response(&state, StatusCode::UNAUTHORIZED);
+                    return Ok((state, res));
+                }
+                Err(e) => return Err((state, e.into())),
+            }
+
+            match users::find_by_email(repo, email).await {
+                Ok(user) => {
+                    state.put(CurrentUser(user));
+                    chain(state).await
+                }
+                Err(users::UserError::Model {
+                    cause: ModelError::UserNotFound,
+                    ..
+                }) => {
+                    debug!("[{}] token user does not exist", request_id(&state));
+                    let res = create_empty_response(&state, StatusCode::UNAUTHORIZED);
+                    Ok((state, res))
+                }
+                Err(e) => Err((state, e.into())),
+            }
+        }
+        .boxed()
+    }
+}
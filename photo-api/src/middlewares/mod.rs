@@ -1 +1,3 @@
 pub mod cors;
+pub mod current_user;
+pub mod token;
@@ -0,0 +1,127 @@
+use crate::auth::decode_token;
+use crate::conduit::{api_keys, sessions};
+use crate::connection::Repo;
+use futures::prelude::*;
+use gotham::handler::HandlerFuture;
+use gotham::helpers::http::response::create_empty_response;
+use gotham::hyper::header::{HeaderMap, AUTHORIZATION};
+use gotham::hyper::{Method, StatusCode};
+use gotham::middleware::Middleware;
+use gotham::state::{request_id, FromState, State};
+use gotham_middleware_jwt::AuthorizationToken;
+use photo_core::models::{ApiKey, ModelError, API_KEY_PREFIX};
+use std::pin::Pin;
+
+/// The API key the request was made with, instead of an access token.
+#[derive(Clone, StateData)]
+pub struct ApiKeyAuth(pub ApiKey);
+
+/// Validates the `Bearer` token of the request: its signature against the key named by its
+/// `kid`, its issuer and audience, and that its `jti` was not revoked. The claims are put in the
+/// state as an `AuthorizationToken<AuthUser>`, like `gotham_middleware_jwt` does, which only
+/// supports a single secret.
+///
+/// The token can also be an API key, recognized by its prefix, which is put in the state as
+/// `ApiKeyAuth`. Keys with the read scope can only be used for `GET` and `HEAD` requests.
+///
+/// Requests without a token get a 400, invalid or revoked tokens a 401.
+#[derive(Clone, NewMiddleware, Debug, Default)]
+pub struct TokenMiddleware;
+
+impl Middleware for TokenMiddleware {
+    fn call<Chain>(self, mut state: State, chain: Chain) -> Pin<Box<HandlerFuture>>
+    where
+        Chain: FnOnce(State) -> Pin<Box<HandlerFuture>> + Send + 'static,
+        Self: Sized,
+    {
+        let repo = Repo::borrow_from(&state).clone();
+        let is_read = match *Method::borrow_from(&state) {
+            Method::GET | Method::HEAD => true,
+            _ => false,
+        };
+        let token = HeaderMap::borrow_from(&state)
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(|token| token.trim().to_string());
+
+        async move {
+            let token = match token {
+                Some(token) => token,
+                None => {
+                    let res = create_empty_response(&state, StatusCode::BAD_REQUEST);
+                    return Ok((state, res));
+                }
+            };
+
+            if token.starts_with(API_KEY_PREFIX) {
+                let api_key = match api_keys::authenticate(repo, token).await {
+                    Ok(k) => k,
+                    Err(api_keys::ApiKeyError::Model {
+                        cause: ModelError::InvalidApiKey,
+                        ..
+                    }) => {
+                        debug!("[{}] invalid API key", request_id(&state));
+                        let res = create_empty_response(&state, StatusCode::UNAUTHORIZED);
+                        return Ok((state, res));
+                    }
+                    Err(e) => return Err((state, e.into())),
+                };
+
+                if !is_read && !api_key.can_write() {
+                    debug!("[{}] API key can only read", request_id(&state));
+                    let res = create_empty_response(&state, StatusCode::FORBIDDEN);
+                    return Ok((state, res));
+                }
+
+                state.put(ApiKeyAuth(api_key));
+                return chain(state).await;
+            }
+
+            let token_data = match decode_token(&token) {
+                Ok(data) => data,
+                Err(e) => {
+                    debug!("[{}] invalid token: {}", request_id(&state), e);
+                    let res = create_empty_response(&state, StatusCode::UNAUTHORIZED);
+                    return Ok((state, res));
+                }
+            };
+
+            match sessions::is_token_revoked(repo, token_data.claims.jti()).await {
+                Ok(false) => (),
+                Ok(true) => {
+                    debug!("[{}] token was revoked", request_id(&state));
+                    let res = create_empty_response(&state, StatusCode::UNAUTHORIZED);
+                    return Ok((state, res));
+                }
+                Err(e) => return Err((state, e.into())),
+            }
+
+            state.put(AuthorizationToken(token_data));
+            chain(state).await
+        }
+        .boxed()
+    }
+}
+
+/// Rejects requests made with an API key with a 403, so they need a signed in session. Goes after
+/// `TokenMiddleware` on the routes that manage the account: its keys, sessions, invitations and
+/// studio members, where a leaked key could otherwise keep or widen its access.
+#[derive(Clone, NewMiddleware, Debug, Default)]
+pub struct SessionOnlyMiddleware;
+
+impl Middleware for SessionOnlyMiddleware {
+    fn call<Chain>(self, state: State, chain: Chain) -> Pin<Box<HandlerFuture>>
+    where
+        Chain: FnOnce(State) -> Pin<Box<HandlerFuture>> + Send + 'static,
+        Self: Sized,
+    {
+        if ApiKeyAuth::try_borrow_from(&state).is_none() {
+            return chain(state);
+        }
+
+        debug!("[{}] API key needs a session", request_id(&state));
+        let res = create_empty_response(&state, StatusCode::FORBIDDEN);
+        future::ok((state, res)).boxed()
+    }
+}
@@ -1,9 +1,31 @@
+use hyper::Uri;
 use std::env;
 
 pub fn get_url() -> String {
     env::var("PUBLIC_API_URL").expect("Missing PUBLIC_API_URL environment variable.")
 }
 
+/// URL of `path` under `base_url` with the query of the request, used to redirect without losing
+/// filters or pagination.
+pub fn location_with_query(base_url: &str, uri: &Uri, path: &str) -> String {
+    match uri.query() {
+        Some(query) => format!("{}{}?{}", base_url, path, query),
+        None => format!("{}{}", base_url, path),
+    }
+}
+
+/// Sets the environment variables the tests need. They are shared by the whole process, so they
+/// are set only once instead of by each test while others may be reading them.
+#[cfg(test)]
+pub fn set_test_env() {
+    static INIT: std::sync::Once = std::sync::Once::new();
+
+    INIT.call_once(|| {
+        env::set_var("TOKEN_SECRET", "test-secret");
+        env::set_var("PUBLIC_API_URL", "https://api.example.com");
+    });
+}
+
 const MAX_CHAR_VAL: u32 = std::char::MAX as u32;
 
 // Function from https://rosettacode.org/wiki/URL_encoding#Rust
@@ -24,13 +46,33 @@ pub fn encode_url_component(key: String) -> String {
         .collect::<String>()
 }
 
-pub fn get_allowed_emails() -> Vec<String> {
-    let allowed_emails =
-        env::var("ALLOWED_EMAILS").expect("Missing ALLOWED_EMAILS environment variable.");
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const API_URL: &str = "https://api.example.com";
+
+    #[test]
+    fn location_keeps_the_query_of_the_request() {
+        let uri: Uri = "/api/public/album/old-name?id=42&page=2".parse().unwrap();
+
+        let location = location_with_query(API_URL, &uri, "/api/public/album/new-name");
+
+        assert_eq!(
+            location,
+            "https://api.example.com/api/public/album/new-name?id=42&page=2"
+        );
+    }
+
+    #[test]
+    fn location_without_query() {
+        let uri: Uri = "/api/public/album/old-name".parse().unwrap();
+
+        let location = location_with_query(API_URL, &uri, "/api/public/album/new-name");
 
-    allowed_emails
-        .split(",")
-        .into_iter()
-        .map(|e| String::from(e))
-        .collect()
+        assert_eq!(
+            location,
+            "https://api.example.com/api/public/album/new-name"
+        );
+    }
 }
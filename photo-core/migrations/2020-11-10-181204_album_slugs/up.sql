@@ -0,0 +1,24 @@
+ALTER TABLE albums ADD COLUMN slug TEXT NOT NULL DEFAULT '';
+
+-- Placeholder until the `album_slugs` custom migration generates the real slugs.
+UPDATE albums SET slug = id;
+
+CREATE UNIQUE INDEX albums_user_id_slug ON albums (user_id, slug);
+
+CREATE TABLE album_slugs (
+  id TEXT PRIMARY KEY NOT NULL,
+  album_id TEXT NOT NULL,
+  user_id TEXT NOT NULL,
+  slug TEXT NOT NULL,
+  created_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  FOREIGN KEY (album_id)
+    REFERENCES albums (id)
+      ON DELETE CASCADE
+      ON UPDATE CASCADE,
+  FOREIGN KEY (user_id)
+    REFERENCES users (id)
+      ON DELETE CASCADE
+      ON UPDATE CASCADE
+);
+
+CREATE UNIQUE INDEX album_slugs_user_id_slug ON album_slugs (user_id, slug);
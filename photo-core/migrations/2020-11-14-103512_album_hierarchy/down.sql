@@ -0,0 +1,28 @@
+DROP INDEX albums_parent_id;
+
+CREATE TABLE albums_bkp (
+  id TEXT PRIMARY KEY NOT NULL,
+  user_id TEXT NOT NULL,
+  name TEXT NOT NULL,
+  description TEXT NULL,
+  created_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  updated_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  deleted BOOLEAN NOT NULL DEFAULT false,
+  slug TEXT NOT NULL DEFAULT '',
+  FOREIGN KEY (user_id)
+    REFERENCES users (id)
+        ON DELETE CASCADE
+        ON UPDATE CASCADE
+);
+
+INSERT INTO albums_bkp
+  SELECT id, user_id, name, description, created_at, updated_at, deleted, slug
+  FROM albums;
+
+DROP INDEX albums_user_id_slug;
+
+DROP TABLE albums;
+
+ALTER TABLE albums_bkp RENAME TO albums;
+
+CREATE UNIQUE INDEX albums_user_id_slug ON albums (user_id, slug);
@@ -0,0 +1,6 @@
+ALTER TABLE albums ADD COLUMN parent_id TEXT NULL
+  REFERENCES albums (id)
+    ON DELETE SET NULL
+    ON UPDATE CASCADE;
+
+CREATE INDEX albums_parent_id ON albums (parent_id);
@@ -0,0 +1,20 @@
+CREATE TABLE share_links (
+  id TEXT PRIMARY KEY NOT NULL,
+  album_id TEXT NOT NULL,
+  user_id TEXT NOT NULL,
+  token TEXT UNIQUE NOT NULL,
+  expires_at TIMESTAMP,
+  max_views INTEGER,
+  views INTEGER NOT NULL DEFAULT 0,
+  revoked BOOLEAN NOT NULL DEFAULT false,
+  created_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  updated_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  FOREIGN KEY (album_id)
+    REFERENCES albums (id)
+      ON DELETE CASCADE
+      ON UPDATE CASCADE,
+  FOREIGN KEY (user_id)
+    REFERENCES users (id)
+      ON DELETE CASCADE
+      ON UPDATE CASCADE
+);
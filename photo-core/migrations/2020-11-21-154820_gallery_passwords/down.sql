@@ -0,0 +1,64 @@
+CREATE TABLE share_links_bkp (
+  id TEXT PRIMARY KEY NOT NULL,
+  album_id TEXT NOT NULL,
+  user_id TEXT NOT NULL,
+  token TEXT UNIQUE NOT NULL,
+  expires_at TIMESTAMP,
+  max_views INTEGER,
+  views INTEGER NOT NULL DEFAULT 0,
+  revoked BOOLEAN NOT NULL DEFAULT false,
+  created_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  updated_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  FOREIGN KEY (album_id)
+    REFERENCES albums (id)
+      ON DELETE CASCADE
+      ON UPDATE CASCADE,
+  FOREIGN KEY (user_id)
+    REFERENCES users (id)
+      ON DELETE CASCADE
+      ON UPDATE CASCADE
+);
+
+INSERT INTO share_links_bkp
+  SELECT id, album_id, user_id, token, expires_at, max_views, views, revoked, created_at, updated_at
+  FROM share_links;
+
+DROP TABLE share_links;
+
+ALTER TABLE share_links_bkp RENAME TO share_links;
+
+CREATE TABLE albums_bkp (
+  id TEXT PRIMARY KEY NOT NULL,
+  user_id TEXT NOT NULL,
+  name TEXT NOT NULL,
+  description TEXT NULL,
+  created_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  updated_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  deleted BOOLEAN NOT NULL DEFAULT false,
+  slug TEXT NOT NULL DEFAULT '',
+  parent_id TEXT NULL,
+  FOREIGN KEY (user_id)
+    REFERENCES users (id)
+        ON DELETE CASCADE
+        ON UPDATE CASCADE,
+  FOREIGN KEY (parent_id)
+    REFERENCES albums (id)
+        ON DELETE SET NULL
+        ON UPDATE CASCADE
+);
+
+INSERT INTO albums_bkp
+  SELECT id, user_id, name, description, created_at, updated_at, deleted, slug, parent_id
+  FROM albums;
+
+DROP INDEX albums_user_id_slug;
+
+DROP INDEX albums_parent_id;
+
+DROP TABLE albums;
+
+ALTER TABLE albums_bkp RENAME TO albums;
+
+CREATE UNIQUE INDEX albums_user_id_slug ON albums (user_id, slug);
+
+CREATE INDEX albums_parent_id ON albums (parent_id);
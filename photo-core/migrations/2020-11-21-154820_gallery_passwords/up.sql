@@ -0,0 +1,3 @@
+ALTER TABLE albums ADD COLUMN password_hash TEXT NULL;
+
+ALTER TABLE share_links ADD COLUMN password_hash TEXT NULL;
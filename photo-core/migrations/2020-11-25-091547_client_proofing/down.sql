@@ -0,0 +1,69 @@
+DROP TABLE proofing_comments;
+
+DROP TABLE proofing_selections;
+
+DROP TABLE proofing_clients;
+
+CREATE TABLE share_links_bkp (
+  id TEXT PRIMARY KEY NOT NULL,
+  album_id TEXT NOT NULL,
+  user_id TEXT NOT NULL,
+  token TEXT UNIQUE NOT NULL,
+  expires_at TIMESTAMP,
+  max_views INTEGER,
+  views INTEGER NOT NULL DEFAULT 0,
+  revoked BOOLEAN NOT NULL DEFAULT false,
+  created_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  updated_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  password_hash TEXT NULL,
+  FOREIGN KEY (album_id)
+    REFERENCES albums (id)
+      ON DELETE CASCADE
+      ON UPDATE CASCADE,
+  FOREIGN KEY (user_id)
+    REFERENCES users (id)
+      ON DELETE CASCADE
+      ON UPDATE CASCADE
+);
+
+INSERT INTO share_links_bkp
+  SELECT id, album_id, user_id, token, expires_at, max_views, views, revoked, created_at, updated_at, password_hash
+  FROM share_links;
+
+DROP TABLE share_links;
+
+ALTER TABLE share_links_bkp RENAME TO share_links;
+
+CREATE TABLE photos_bkp (
+  id TEXT PRIMARY KEY NOT NULL,
+  album_id TEXT NOT NULL,
+  user_id TEXT NOT NULL,
+  index_in_album INTEGER NOT NULL DEFAULT 0,
+  s3_id TEXT NOT NULL,
+  src TEXT NOT NULL,
+  main_color TEXT NOT NULL,
+  title TEXT,
+  description TEXT,
+  width INT NOT NULL,
+  height INT NOT NULL,
+  is_favorite BOOLEAN NOT NULL DEFAULT false,
+  created_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  updated_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  deleted BOOLEAN NOT NULL DEFAULT false,
+  FOREIGN KEY (album_id)
+    REFERENCES albums (id)
+      ON DELETE CASCADE
+      ON UPDATE CASCADE,
+  FOREIGN KEY (user_id)
+    REFERENCES users (id)
+      ON DELETE CASCADE
+      ON UPDATE CASCADE
+);
+
+INSERT INTO photos_bkp
+  SELECT id, album_id, user_id, index_in_album, s3_id, src, main_color, title, description, width, height, is_favorite, created_at, updated_at, deleted
+  FROM photos;
+
+DROP TABLE photos;
+
+ALTER TABLE photos_bkp RENAME TO photos;
@@ -0,0 +1,51 @@
+ALTER TABLE share_links ADD COLUMN proofing BOOLEAN NOT NULL DEFAULT false;
+
+ALTER TABLE photos ADD COLUMN filename TEXT NULL;
+
+CREATE TABLE proofing_clients (
+  id TEXT PRIMARY KEY NOT NULL,
+  share_link_id TEXT NOT NULL,
+  name TEXT NOT NULL,
+  email TEXT NOT NULL,
+  created_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  updated_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  FOREIGN KEY (share_link_id)
+    REFERENCES share_links (id)
+      ON DELETE CASCADE
+      ON UPDATE CASCADE
+);
+
+CREATE UNIQUE INDEX proofing_clients_share_link_id_email ON proofing_clients (share_link_id, email);
+
+CREATE TABLE proofing_selections (
+  id TEXT PRIMARY KEY NOT NULL,
+  client_id TEXT NOT NULL,
+  photo_id TEXT NOT NULL,
+  created_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  FOREIGN KEY (client_id)
+    REFERENCES proofing_clients (id)
+      ON DELETE CASCADE
+      ON UPDATE CASCADE,
+  FOREIGN KEY (photo_id)
+    REFERENCES photos (id)
+      ON DELETE CASCADE
+      ON UPDATE CASCADE
+);
+
+CREATE UNIQUE INDEX proofing_selections_client_id_photo_id ON proofing_selections (client_id, photo_id);
+
+CREATE TABLE proofing_comments (
+  id TEXT PRIMARY KEY NOT NULL,
+  client_id TEXT NOT NULL,
+  photo_id TEXT NOT NULL,
+  body TEXT NOT NULL,
+  created_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  FOREIGN KEY (client_id)
+    REFERENCES proofing_clients (id)
+      ON DELETE CASCADE
+      ON UPDATE CASCADE,
+  FOREIGN KEY (photo_id)
+    REFERENCES photos (id)
+      ON DELETE CASCADE
+      ON UPDATE CASCADE
+);
@@ -0,0 +1,30 @@
+CREATE TABLE share_links_bkp (
+  id TEXT PRIMARY KEY NOT NULL,
+  album_id TEXT NOT NULL,
+  user_id TEXT NOT NULL,
+  token TEXT UNIQUE NOT NULL,
+  expires_at TIMESTAMP,
+  max_views INTEGER,
+  views INTEGER NOT NULL DEFAULT 0,
+  revoked BOOLEAN NOT NULL DEFAULT false,
+  created_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  updated_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  password_hash TEXT NULL,
+  proofing BOOLEAN NOT NULL DEFAULT false,
+  FOREIGN KEY (album_id)
+    REFERENCES albums (id)
+      ON DELETE CASCADE
+      ON UPDATE CASCADE,
+  FOREIGN KEY (user_id)
+    REFERENCES users (id)
+      ON DELETE CASCADE
+      ON UPDATE CASCADE
+);
+
+INSERT INTO share_links_bkp
+  SELECT id, album_id, user_id, token, expires_at, max_views, views, revoked, created_at, updated_at, password_hash, proofing
+  FROM share_links;
+
+DROP TABLE share_links;
+
+ALTER TABLE share_links_bkp RENAME TO share_links;
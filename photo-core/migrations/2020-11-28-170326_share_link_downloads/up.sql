@@ -0,0 +1 @@
+ALTER TABLE share_links ADD COLUMN allow_downloads BOOLEAN NOT NULL DEFAULT false;
@@ -0,0 +1,9 @@
+DROP TRIGGER photos_search_delete;
+DROP TRIGGER photos_search_update;
+DROP TRIGGER photos_search_insert;
+DROP TRIGGER albums_search_delete;
+DROP TRIGGER albums_search_update;
+DROP TRIGGER albums_search_insert;
+
+DROP TABLE photos_search;
+DROP TABLE albums_search;
@@ -0,0 +1,53 @@
+-- Standalone FTS5 tables: albums and photos use text ids, so their rowids are not stable enough
+-- to back an external content table.
+CREATE VIRTUAL TABLE albums_search USING fts5(
+  id UNINDEXED,
+  user_id UNINDEXED,
+  name,
+  description,
+  tokenize = 'unicode61 remove_diacritics 2'
+);
+
+CREATE VIRTUAL TABLE photos_search USING fts5(
+  id UNINDEXED,
+  user_id UNINDEXED,
+  title,
+  description,
+  tokenize = 'unicode61 remove_diacritics 2'
+);
+
+INSERT INTO albums_search (id, user_id, name, description)
+  SELECT id, user_id, name, COALESCE(description, '') FROM albums;
+
+INSERT INTO photos_search (id, user_id, title, description)
+  SELECT id, user_id, COALESCE(title, ''), COALESCE(description, '') FROM photos;
+
+CREATE TRIGGER albums_search_insert AFTER INSERT ON albums BEGIN
+  INSERT INTO albums_search (id, user_id, name, description)
+    VALUES (new.id, new.user_id, new.name, COALESCE(new.description, ''));
+END;
+
+CREATE TRIGGER albums_search_update AFTER UPDATE OF name, description ON albums BEGIN
+  DELETE FROM albums_search WHERE id = old.id;
+  INSERT INTO albums_search (id, user_id, name, description)
+    VALUES (new.id, new.user_id, new.name, COALESCE(new.description, ''));
+END;
+
+CREATE TRIGGER albums_search_delete AFTER DELETE ON albums BEGIN
+  DELETE FROM albums_search WHERE id = old.id;
+END;
+
+CREATE TRIGGER photos_search_insert AFTER INSERT ON photos BEGIN
+  INSERT INTO photos_search (id, user_id, title, description)
+    VALUES (new.id, new.user_id, COALESCE(new.title, ''), COALESCE(new.description, ''));
+END;
+
+CREATE TRIGGER photos_search_update AFTER UPDATE OF title, description ON photos BEGIN
+  DELETE FROM photos_search WHERE id = old.id;
+  INSERT INTO photos_search (id, user_id, title, description)
+    VALUES (new.id, new.user_id, COALESCE(new.title, ''), COALESCE(new.description, ''));
+END;
+
+CREATE TRIGGER photos_search_delete AFTER DELETE ON photos BEGIN
+  DELETE FROM photos_search WHERE id = old.id;
+END;
@@ -0,0 +1,3 @@
+DROP TABLE photo_tags;
+
+DROP TABLE tags;
@@ -0,0 +1,32 @@
+CREATE TABLE tags (
+  id TEXT PRIMARY KEY NOT NULL,
+  user_id TEXT NOT NULL,
+  name TEXT NOT NULL,
+  slug TEXT NOT NULL,
+  created_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  FOREIGN KEY (user_id)
+    REFERENCES users (id)
+      ON DELETE CASCADE
+      ON UPDATE CASCADE
+);
+
+CREATE UNIQUE INDEX tags_user_id_slug ON tags (user_id, slug);
+
+CREATE TABLE photo_tags (
+  id TEXT PRIMARY KEY NOT NULL,
+  photo_id TEXT NOT NULL,
+  tag_id TEXT NOT NULL,
+  created_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  FOREIGN KEY (photo_id)
+    REFERENCES photos (id)
+      ON DELETE CASCADE
+      ON UPDATE CASCADE,
+  FOREIGN KEY (tag_id)
+    REFERENCES tags (id)
+      ON DELETE CASCADE
+      ON UPDATE CASCADE
+);
+
+CREATE UNIQUE INDEX photo_tags_photo_id_tag_id ON photo_tags (photo_id, tag_id);
+
+CREATE INDEX photo_tags_tag_id ON photo_tags (tag_id);
@@ -0,0 +1,14 @@
+-- Metadata read from uploaded files, used to prefill photos once they are created.
+CREATE TABLE photo_uploads (
+  id TEXT PRIMARY KEY NOT NULL,
+  user_id TEXT NOT NULL,
+  s3_id TEXT UNIQUE NOT NULL,
+  title TEXT,
+  description TEXT,
+  keywords TEXT NOT NULL DEFAULT '[]',
+  created_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  FOREIGN KEY (user_id)
+    REFERENCES users (id)
+      ON DELETE CASCADE
+      ON UPDATE CASCADE
+);
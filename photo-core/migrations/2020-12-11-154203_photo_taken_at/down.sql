@@ -0,0 +1,51 @@
+DROP INDEX photos_album_id_index_in_album;
+
+CREATE TABLE photos_bkp (
+  id TEXT PRIMARY KEY NOT NULL,
+  album_id TEXT NOT NULL,
+  user_id TEXT NOT NULL,
+  index_in_album INTEGER NOT NULL DEFAULT 0,
+  s3_id TEXT NOT NULL,
+  src TEXT NOT NULL,
+  main_color TEXT NOT NULL,
+  title TEXT,
+  description TEXT,
+  width INT NOT NULL,
+  height INT NOT NULL,
+  is_favorite BOOLEAN NOT NULL DEFAULT false,
+  created_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  updated_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  deleted BOOLEAN NOT NULL DEFAULT false,
+  filename TEXT NULL,
+  FOREIGN KEY (album_id)
+    REFERENCES albums (id)
+      ON DELETE CASCADE
+      ON UPDATE CASCADE,
+  FOREIGN KEY (user_id)
+    REFERENCES users (id)
+      ON DELETE CASCADE
+      ON UPDATE CASCADE
+);
+
+INSERT INTO photos_bkp
+  SELECT id, album_id, user_id, index_in_album, s3_id, src, main_color, title, description, width, height, is_favorite, created_at, updated_at, deleted, filename
+  FROM photos;
+
+DROP TABLE photos;
+
+ALTER TABLE photos_bkp RENAME TO photos;
+
+CREATE TRIGGER photos_search_insert AFTER INSERT ON photos BEGIN
+  INSERT INTO photos_search (id, user_id, title, description)
+    VALUES (new.id, new.user_id, COALESCE(new.title, ''), COALESCE(new.description, ''));
+END;
+
+CREATE TRIGGER photos_search_update AFTER UPDATE OF title, description ON photos BEGIN
+  DELETE FROM photos_search WHERE id = old.id;
+  INSERT INTO photos_search (id, user_id, title, description)
+    VALUES (new.id, new.user_id, COALESCE(new.title, ''), COALESCE(new.description, ''));
+END;
+
+CREATE TRIGGER photos_search_delete AFTER DELETE ON photos BEGIN
+  DELETE FROM photos_search WHERE id = old.id;
+END;
@@ -0,0 +1,3 @@
+ALTER TABLE photos ADD COLUMN taken_at TIMESTAMP NULL;
+
+CREATE INDEX photos_album_id_index_in_album ON photos (album_id, index_in_album);
@@ -0,0 +1,18 @@
+-- Images a photo used before being replaced, so they can be brought back.
+CREATE TABLE photo_versions (
+  id TEXT PRIMARY KEY NOT NULL,
+  photo_id TEXT NOT NULL,
+  s3_id TEXT NOT NULL,
+  src TEXT NOT NULL,
+  main_color TEXT NOT NULL,
+  width INT NOT NULL,
+  height INT NOT NULL,
+  filename TEXT,
+  created_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  FOREIGN KEY (photo_id)
+    REFERENCES photos (id)
+      ON DELETE CASCADE
+      ON UPDATE CASCADE
+);
+
+CREATE INDEX photo_versions_photo_id ON photo_versions (photo_id);
@@ -0,0 +1,6 @@
+-- Pending OAuth2 logins: the CSRF state sent to the provider and the PKCE verifier that goes with it.
+CREATE TABLE oauth_states (
+  state TEXT PRIMARY KEY NOT NULL,
+  pkce_verifier TEXT NOT NULL,
+  created_at TIMESTAMP DEFAULT current_timestamp NOT NULL
+);
@@ -0,0 +1,19 @@
+-- Login sessions, renewed with rotating refresh tokens. Only the hash of the tokens is stored.
+CREATE TABLE sessions (
+  id TEXT PRIMARY KEY NOT NULL,
+  user_id TEXT NOT NULL,
+  token_hash TEXT UNIQUE NOT NULL,
+  previous_token_hash TEXT,
+  user_agent TEXT,
+  ip TEXT,
+  created_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  last_used_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  expires_at TIMESTAMP NOT NULL,
+  FOREIGN KEY (user_id)
+    REFERENCES users (id)
+      ON DELETE CASCADE
+      ON UPDATE CASCADE
+);
+
+CREATE INDEX sessions_user_id ON sessions (user_id);
+CREATE INDEX sessions_previous_token_hash ON sessions (previous_token_hash);
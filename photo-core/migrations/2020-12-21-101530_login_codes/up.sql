@@ -0,0 +1,8 @@
+-- Single use codes the client exchanges for tokens after a login, so tokens are never put in
+-- redirect URLs. Only the hash of the code is kept.
+CREATE TABLE login_codes (
+  code_hash TEXT PRIMARY KEY NOT NULL,
+  user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+  created_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  expires_at TIMESTAMP NOT NULL
+);
@@ -0,0 +1,6 @@
+-- Access tokens denied before they expire, by their `jti` claim.
+CREATE TABLE revoked_tokens (
+  jti TEXT PRIMARY KEY NOT NULL,
+  expires_at TIMESTAMP NOT NULL,
+  created_at TIMESTAMP DEFAULT current_timestamp NOT NULL
+);
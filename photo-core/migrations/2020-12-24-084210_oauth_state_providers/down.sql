@@ -0,0 +1,11 @@
+CREATE TABLE oauth_states_bkp (
+  state TEXT PRIMARY KEY NOT NULL,
+  pkce_verifier TEXT NOT NULL,
+  created_at TIMESTAMP DEFAULT current_timestamp NOT NULL
+);
+
+INSERT INTO oauth_states_bkp (state, pkce_verifier, created_at)
+SELECT state, pkce_verifier, created_at FROM oauth_states;
+
+DROP TABLE oauth_states;
+ALTER TABLE oauth_states_bkp RENAME TO oauth_states;
@@ -0,0 +1,3 @@
+-- Provider a login was started with, so a state can't be used to finish a login with another one.
+-- Logins pending during the migration have none and have to be started again.
+ALTER TABLE oauth_states ADD COLUMN provider TEXT NOT NULL DEFAULT '';
@@ -0,0 +1,7 @@
+-- Single use links emailed to sign in without a Google account. Only the hash of the token is kept.
+CREATE TABLE login_links (
+  token_hash TEXT PRIMARY KEY NOT NULL,
+  email TEXT NOT NULL,
+  created_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  expires_at TIMESTAMP NOT NULL
+);
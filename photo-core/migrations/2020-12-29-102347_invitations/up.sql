@@ -0,0 +1,20 @@
+-- Admins manage the invitations. The first user to sign up, who set up the instance, keeps
+-- managing who can sign in.
+ALTER TABLE users ADD COLUMN is_admin BOOLEAN NOT NULL DEFAULT false;
+
+UPDATE users SET is_admin = true
+WHERE id = (SELECT id FROM users ORDER BY created_at ASC, rowid ASC LIMIT 1);
+
+-- Emails allowed to create an account.
+CREATE TABLE invitations (
+  id TEXT PRIMARY KEY NOT NULL,
+  email TEXT UNIQUE NOT NULL,
+  invited_by TEXT NULL,
+  created_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  expires_at TIMESTAMP NULL,
+  accepted_at TIMESTAMP NULL,
+  FOREIGN KEY (invited_by)
+    REFERENCES users (id)
+      ON DELETE SET NULL
+      ON UPDATE CASCADE
+);
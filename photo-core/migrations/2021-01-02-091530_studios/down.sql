@@ -0,0 +1,50 @@
+CREATE TABLE albums_bkp (
+  id TEXT PRIMARY KEY NOT NULL,
+  user_id TEXT NOT NULL,
+  name TEXT NOT NULL,
+  description TEXT NULL,
+  created_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  updated_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  deleted BOOLEAN NOT NULL DEFAULT false,
+  slug TEXT NOT NULL DEFAULT '',
+  parent_id TEXT NULL,
+  password_hash TEXT NULL,
+  FOREIGN KEY (user_id)
+    REFERENCES users (id)
+      ON DELETE CASCADE
+      ON UPDATE CASCADE,
+  FOREIGN KEY (parent_id)
+    REFERENCES albums (id)
+      ON DELETE SET NULL
+      ON UPDATE CASCADE
+);
+
+INSERT INTO albums_bkp
+  SELECT id, user_id, name, description, created_at, updated_at, deleted, slug, parent_id, password_hash
+  FROM albums;
+
+DROP TABLE albums;
+
+ALTER TABLE albums_bkp RENAME TO albums;
+
+CREATE UNIQUE INDEX albums_user_id_slug ON albums (user_id, slug);
+CREATE INDEX albums_parent_id ON albums (parent_id);
+
+CREATE TRIGGER albums_search_insert AFTER INSERT ON albums BEGIN
+  INSERT INTO albums_search (id, user_id, name, description)
+    VALUES (new.id, new.user_id, new.name, COALESCE(new.description, ''));
+END;
+
+CREATE TRIGGER albums_search_update AFTER UPDATE OF name, description ON albums BEGIN
+  DELETE FROM albums_search WHERE id = old.id;
+  INSERT INTO albums_search (id, user_id, name, description)
+    VALUES (new.id, new.user_id, new.name, COALESCE(new.description, ''));
+END;
+
+CREATE TRIGGER albums_search_delete AFTER DELETE ON albums BEGIN
+  DELETE FROM albums_search WHERE id = old.id;
+END;
+
+DROP TABLE studio_members;
+
+DROP TABLE studios;
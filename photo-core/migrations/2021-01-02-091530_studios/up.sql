@@ -0,0 +1,88 @@
+-- Workspaces shared by a team. Albums belong to a studio, its members access them according to
+-- their role: `owner`, `editor` or `viewer`.
+CREATE TABLE studios (
+  id TEXT PRIMARY KEY NOT NULL,
+  name TEXT NOT NULL,
+  created_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  updated_at TIMESTAMP DEFAULT current_timestamp NOT NULL
+);
+
+CREATE TABLE studio_members (
+  studio_id TEXT NOT NULL,
+  user_id TEXT NOT NULL,
+  role TEXT NOT NULL,
+  created_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  PRIMARY KEY (studio_id, user_id),
+  FOREIGN KEY (studio_id)
+    REFERENCES studios (id)
+      ON DELETE CASCADE
+      ON UPDATE CASCADE,
+  FOREIGN KEY (user_id)
+    REFERENCES users (id)
+      ON DELETE CASCADE
+      ON UPDATE CASCADE
+);
+
+CREATE INDEX studio_members_user_id ON studio_members (user_id);
+
+-- Every user gets a studio of their own, with the same id, owning the albums they had.
+INSERT INTO studios (id, name, created_at, updated_at)
+  SELECT id, email, created_at, updated_at FROM users;
+
+INSERT INTO studio_members (studio_id, user_id, role, created_at)
+  SELECT id, id, 'owner', created_at FROM users;
+
+-- Rebuilt to add the studio of the albums, SQLite only allows adding nullable columns referencing
+-- another table.
+CREATE TABLE albums_bkp (
+  id TEXT PRIMARY KEY NOT NULL,
+  user_id TEXT NOT NULL,
+  name TEXT NOT NULL,
+  description TEXT NULL,
+  created_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  updated_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  deleted BOOLEAN NOT NULL DEFAULT false,
+  slug TEXT NOT NULL DEFAULT '',
+  parent_id TEXT NULL,
+  password_hash TEXT NULL,
+  studio_id TEXT NOT NULL,
+  FOREIGN KEY (user_id)
+    REFERENCES users (id)
+      ON DELETE CASCADE
+      ON UPDATE CASCADE,
+  FOREIGN KEY (parent_id)
+    REFERENCES albums (id)
+      ON DELETE SET NULL
+      ON UPDATE CASCADE,
+  FOREIGN KEY (studio_id)
+    REFERENCES studios (id)
+      ON DELETE CASCADE
+      ON UPDATE CASCADE
+);
+
+INSERT INTO albums_bkp
+  SELECT id, user_id, name, description, created_at, updated_at, deleted, slug, parent_id, password_hash, user_id AS studio_id
+  FROM albums;
+
+DROP TABLE albums;
+
+ALTER TABLE albums_bkp RENAME TO albums;
+
+CREATE UNIQUE INDEX albums_user_id_slug ON albums (user_id, slug);
+CREATE INDEX albums_parent_id ON albums (parent_id);
+CREATE INDEX albums_studio_id ON albums (studio_id);
+
+CREATE TRIGGER albums_search_insert AFTER INSERT ON albums BEGIN
+  INSERT INTO albums_search (id, user_id, name, description)
+    VALUES (new.id, new.user_id, new.name, COALESCE(new.description, ''));
+END;
+
+CREATE TRIGGER albums_search_update AFTER UPDATE OF name, description ON albums BEGIN
+  DELETE FROM albums_search WHERE id = old.id;
+  INSERT INTO albums_search (id, user_id, name, description)
+    VALUES (new.id, new.user_id, new.name, COALESCE(new.description, ''));
+END;
+
+CREATE TRIGGER albums_search_delete AFTER DELETE ON albums BEGIN
+  DELETE FROM albums_search WHERE id = old.id;
+END;
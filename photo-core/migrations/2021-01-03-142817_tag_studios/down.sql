@@ -0,0 +1,25 @@
+-- A user can only have one tag per slug again, the first one they created is kept.
+DELETE FROM tags WHERE rowid NOT IN (SELECT MIN(rowid) FROM tags GROUP BY user_id, slug);
+DELETE FROM photo_tags WHERE tag_id NOT IN (SELECT id FROM tags);
+
+CREATE TABLE tags_bkp (
+  id TEXT PRIMARY KEY NOT NULL,
+  user_id TEXT NOT NULL,
+  name TEXT NOT NULL,
+  slug TEXT NOT NULL,
+  created_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  FOREIGN KEY (user_id)
+    REFERENCES users (id)
+      ON DELETE CASCADE
+      ON UPDATE CASCADE
+);
+
+INSERT INTO tags_bkp
+  SELECT id, user_id, name, slug, created_at
+  FROM tags;
+
+DROP TABLE tags;
+
+ALTER TABLE tags_bkp RENAME TO tags;
+
+CREATE UNIQUE INDEX tags_user_id_slug ON tags (user_id, slug);
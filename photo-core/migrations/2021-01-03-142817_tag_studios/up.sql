@@ -0,0 +1,28 @@
+-- Tags are shared by the members of a studio, like its albums. The tags a user had go to the studio
+-- created for them, which has the same id.
+CREATE TABLE tags_bkp (
+  id TEXT PRIMARY KEY NOT NULL,
+  user_id TEXT NOT NULL,
+  name TEXT NOT NULL,
+  slug TEXT NOT NULL,
+  created_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  studio_id TEXT NOT NULL,
+  FOREIGN KEY (user_id)
+    REFERENCES users (id)
+      ON DELETE CASCADE
+      ON UPDATE CASCADE,
+  FOREIGN KEY (studio_id)
+    REFERENCES studios (id)
+      ON DELETE CASCADE
+      ON UPDATE CASCADE
+);
+
+INSERT INTO tags_bkp
+  SELECT id, user_id, name, slug, created_at, user_id AS studio_id
+  FROM tags;
+
+DROP TABLE tags;
+
+ALTER TABLE tags_bkp RENAME TO tags;
+
+CREATE UNIQUE INDEX tags_studio_id_slug ON tags (studio_id, slug);
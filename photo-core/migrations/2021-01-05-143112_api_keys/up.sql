@@ -0,0 +1,14 @@
+-- Keys used by scripts instead of a token obtained by signing in. Only the hash of a key is kept,
+-- along with its first characters so users can tell their keys apart.
+CREATE TABLE api_keys (
+  id TEXT PRIMARY KEY NOT NULL,
+  user_id TEXT NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+  name TEXT NOT NULL,
+  key_hash TEXT NOT NULL UNIQUE,
+  prefix TEXT NOT NULL,
+  scope TEXT NOT NULL,
+  last_used_at TIMESTAMP NULL,
+  created_at TIMESTAMP DEFAULT current_timestamp NOT NULL
+);
+
+CREATE INDEX api_keys_user_id ON api_keys (user_id);
@@ -0,0 +1,5 @@
+DROP INDEX audit_events_studio_id;
+DROP INDEX audit_events_actor_id;
+DROP INDEX audit_events_target;
+DROP INDEX audit_events_created_at;
+DROP TABLE audit_events;
@@ -0,0 +1,19 @@
+-- Who created, changed or deleted what, kept when the actor or the target is gone. Changes to
+-- albums and photos keep their studio, so its owners can see them even after the record is deleted.
+CREATE TABLE audit_events (
+  id TEXT PRIMARY KEY NOT NULL,
+  actor_id TEXT NULL REFERENCES users (id) ON DELETE SET NULL,
+  action TEXT NOT NULL,
+  target_type TEXT NOT NULL,
+  target_id TEXT NOT NULL,
+  changes TEXT NOT NULL,
+  ip TEXT NULL,
+  request_id TEXT NULL,
+  created_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  studio_id TEXT NULL
+);
+
+CREATE INDEX audit_events_created_at ON audit_events (created_at);
+CREATE INDEX audit_events_target ON audit_events (target_type, target_id);
+CREATE INDEX audit_events_actor_id ON audit_events (actor_id);
+CREATE INDEX audit_events_studio_id ON audit_events (studio_id);
@@ -0,0 +1,24 @@
+DROP INDEX proofing_clients_secret_hash;
+
+CREATE TABLE proofing_clients_bkp (
+  id TEXT PRIMARY KEY NOT NULL,
+  share_link_id TEXT NOT NULL,
+  name TEXT NOT NULL,
+  email TEXT NOT NULL,
+  created_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  updated_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  FOREIGN KEY (share_link_id)
+    REFERENCES share_links (id)
+      ON DELETE CASCADE
+      ON UPDATE CASCADE
+);
+
+INSERT INTO proofing_clients_bkp
+  SELECT id, share_link_id, name, email, created_at, updated_at
+  FROM proofing_clients;
+
+DROP TABLE proofing_clients;
+
+ALTER TABLE proofing_clients_bkp RENAME TO proofing_clients;
+
+CREATE UNIQUE INDEX proofing_clients_share_link_id_email ON proofing_clients (share_link_id, email);
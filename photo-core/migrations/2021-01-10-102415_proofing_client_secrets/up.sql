@@ -0,0 +1,5 @@
+-- Hash of the secret a client gets when registering, required to pick and comment photos. Clients
+-- registered before have none and get one the next time they register.
+ALTER TABLE proofing_clients ADD COLUMN secret_hash TEXT NULL;
+
+CREATE UNIQUE INDEX proofing_clients_secret_hash ON proofing_clients (secret_hash);
@@ -0,0 +1,14 @@
+CREATE TABLE users_bkp (
+  id TEXT PRIMARY KEY NOT NULL,
+  email TEXT UNIQUE NOT NULL,
+  picture TEXT,
+  created_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  updated_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  is_admin BOOLEAN NOT NULL DEFAULT false
+);
+
+INSERT INTO users_bkp (id, email, picture, created_at, updated_at, is_admin)
+SELECT id, email, picture, created_at, updated_at, is_admin FROM users;
+
+DROP TABLE users;
+ALTER TABLE users_bkp RENAME TO users;
@@ -0,0 +1,17 @@
+-- Studio the albums of the user go to when they don't pick one. It is set when the user signs up,
+-- owning other studios later doesn't change it.
+ALTER TABLE users ADD COLUMN personal_studio_id TEXT NULL REFERENCES studios (id) ON DELETE SET NULL;
+
+-- Users that existed before studios got one with the same id, the ones created since then own
+-- the studio created along with them first.
+UPDATE users SET personal_studio_id = id WHERE id IN (SELECT id FROM studios);
+
+UPDATE users SET personal_studio_id = (
+  SELECT studio_members.studio_id
+  FROM studio_members
+  INNER JOIN studios ON studios.id = studio_members.studio_id
+  WHERE studio_members.user_id = users.id AND studio_members.role = 'owner'
+  ORDER BY studios.created_at ASC
+  LIMIT 1
+)
+WHERE personal_studio_id IS NULL;
@@ -0,0 +1,39 @@
+-- Public pages show the albums of the personal studio of a user, so slugs are unique per studio
+-- instead of per creator. Albums of members that collide get a suffix.
+UPDATE albums SET slug = slug || '-' || substr(id, 1, 8)
+WHERE rowid NOT IN (SELECT MIN(rowid) FROM albums GROUP BY studio_id, slug);
+
+DROP INDEX albums_user_id_slug;
+CREATE UNIQUE INDEX albums_studio_id_slug ON albums (studio_id, slug);
+
+CREATE TABLE album_slugs_bkp (
+  id TEXT PRIMARY KEY NOT NULL,
+  album_id TEXT NOT NULL,
+  studio_id TEXT NOT NULL,
+  slug TEXT NOT NULL,
+  created_at TIMESTAMP DEFAULT current_timestamp NOT NULL,
+  FOREIGN KEY (album_id)
+    REFERENCES albums (id)
+      ON DELETE CASCADE
+      ON UPDATE CASCADE,
+  FOREIGN KEY (studio_id)
+    REFERENCES studios (id)
+      ON DELETE CASCADE
+      ON UPDATE CASCADE
+);
+
+INSERT INTO album_slugs_bkp (id, album_id, studio_id, slug, created_at)
+SELECT album_slugs.id, album_slugs.album_id, albums.studio_id, album_slugs.slug, album_slugs.created_at
+FROM album_slugs
+INNER JOIN albums ON albums.id = album_slugs.album_id
+WHERE album_slugs.rowid IN (
+  SELECT MAX(s.rowid)
+  FROM album_slugs s
+  INNER JOIN albums a ON a.id = s.album_id
+  GROUP BY a.studio_id, s.slug
+);
+
+DROP TABLE album_slugs;
+ALTER TABLE album_slugs_bkp RENAME TO album_slugs;
+
+CREATE UNIQUE INDEX album_slugs_studio_id_slug ON album_slugs (studio_id, slug);
@@ -69,7 +69,11 @@ pub fn get_database_url(url: Option<String>) -> String {
     }
 }
 
+/// Runs the pending migrations. Foreign keys are turned off first: migrations change columns by
+/// rebuilding tables, and dropping the old table would otherwise delete the rows referencing it.
 pub fn db_migrate(conn: &Conn) -> Result<(), DbError> {
+    conn.batch_execute("PRAGMA foreign_keys = OFF;")
+        .context(DisableForeignKeys)?;
     embedded_migrations::run_with_output(conn, &mut std::io::stdout()).context(Migration)?;
 
     Ok(())
@@ -84,6 +88,8 @@ pub enum DbError {
     Migration {
         source: diesel_migrations::RunMigrationsError,
     },
+    #[snafu(display("Could not turn off foreign keys: {}", source))]
+    DisableForeignKeys { source: diesel::result::Error },
     #[snafu(display("Could not build pool connection: {}", source))]
     BuildPool { source: diesel::r2d2::PoolError },
 }
@@ -1,6 +1,7 @@
 use crate::connection::{connect, Conn};
 use crate::helpers::uuid::Uuid;
-use crate::models::{Album, ModelError, User};
+use crate::models::{Album, Invitation, ModelError, Studio, User};
+use crate::schema::albums;
 use crate::schema::custom_migrations;
 use crate::schema::photos;
 use chrono::naive::serde::ts_seconds;
@@ -9,11 +10,14 @@ use chrono::Utc;
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 use snafu::{Backtrace, ResultExt};
+use std::env;
 
 lazy_static! {
     static ref MIGRATIONS: Vec<String> = [
         String::from("lifestyle_album"),
-        String::from("image_metadata")
+        String::from("image_metadata"),
+        String::from("album_slugs"),
+        String::from("allowed_emails")
     ]
     .to_vec();
 }
@@ -42,6 +46,8 @@ pub fn apply_custom_migrations(url: Option<String>) -> Result<()> {
         match &name[..] {
             "lifestyle_album" => migrate_lifestyle_album(&conn).unwrap(),
             "image_metadata" => migrate_image_metadata(&conn).unwrap(),
+            "album_slugs" => migrate_album_slugs(&conn).unwrap(),
+            "allowed_emails" => migrate_allowed_emails(&conn).unwrap(),
             _ => {}
         };
     });
@@ -55,8 +61,10 @@ fn migrate_lifestyle_album(conn: &Conn) -> Result<()> {
     let users = User::find_all(&conn).context(Model)?;
 
     users.iter().for_each(|user| {
+        let studio = Studio::find_personal(&conn, &user).unwrap();
         let album = Album::new(
             &user,
+            &studio,
             "lifestyle".to_string(),
             Some("Family & Lifestyle".to_string()),
         );
@@ -84,8 +92,8 @@ fn migrate_image_metadata(conn: &Conn) -> Result<()> {
 
     users.iter().for_each(|user| {
         let albums = Album::find_all(conn, &user).unwrap();
-        albums.iter().for_each(|(album, _)| {
-            let photos = album.photos(conn).unwrap();
+        albums.iter().for_each(|summary| {
+            let photos = summary.album.photos(conn).unwrap();
 
             photos.iter().for_each(|photo| {
                 let img_bytes = reqwest::blocking::get(&photo.src).unwrap().bytes().unwrap();
@@ -119,6 +127,55 @@ fn migrate_image_metadata(conn: &Conn) -> Result<()> {
     Ok(())
 }
 
+fn migrate_album_slugs(conn: &Conn) -> Result<()> {
+    debug!("Migrating album_slugs");
+
+    let all_albums: Vec<Album> = albums::table.load(conn).context(Query)?;
+
+    for album in all_albums.iter() {
+        use crate::schema::albums::dsl::*;
+
+        let new_slug = Album::available_slug(conn, album, &album.name).context(Query)?;
+        debug!("Setting slug {} to album {}", new_slug, album.id);
+
+        diesel::update(albums)
+            .filter(id.eq(album.id))
+            .set(slug.eq(new_slug))
+            .execute(conn)
+            .context(Query)?;
+    }
+
+    let migration = CustomMigration::new("album_slugs".to_string());
+    migration.insert(&conn)?;
+
+    Ok(())
+}
+
+/// Invites the emails of the `ALLOWED_EMAILS` variable, which used to be the only way to allow
+/// users to sign in. Without the variable, nothing is recorded so it is imported once it is set.
+fn migrate_allowed_emails(conn: &Conn) -> Result<()> {
+    debug!("Migrating allowed_emails");
+
+    let allowed_emails = match env::var("ALLOWED_EMAILS") {
+        Ok(v) => v,
+        Err(_) => return Ok(()),
+    };
+
+    let emails: Vec<String> = allowed_emails
+        .split(",")
+        .map(|e| e.trim().trim_matches('"').to_string())
+        .filter(|e| !e.is_empty())
+        .collect();
+
+    let imported = Invitation::import(conn, &emails).context(Model)?;
+    debug!("Invited {} allowed emails", imported);
+
+    let migration = CustomMigration::new("allowed_emails".to_string());
+    migration.insert(&conn)?;
+
+    Ok(())
+}
+
 #[derive(
     Debug,
     PartialEq,
@@ -0,0 +1,62 @@
+/// Same as `chrono::naive::serde::ts_seconds` but for optional dates.
+pub mod ts_seconds_option {
+    use chrono::NaiveDateTime;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(date: &Option<NaiveDateTime>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match date {
+            Some(d) => serializer.serialize_some(&d.timestamp()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<NaiveDateTime>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let secs: Option<i64> = Option::deserialize(deserializer)?;
+
+        match secs {
+            Some(s) => NaiveDateTime::from_timestamp_opt(s, 0)
+                .map(Some)
+                .ok_or_else(|| D::Error::custom(format!("timestamp out of range: {}", s))),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ts_seconds_option;
+    use chrono::NaiveDateTime;
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    struct Expiry {
+        #[serde(with = "ts_seconds_option")]
+        expires_at: Option<NaiveDateTime>,
+    }
+
+    #[test]
+    fn reads_timestamps_and_nulls() {
+        let date: Expiry = serde_json::from_str(r#"{ "expires_at": 1600000000 }"#).unwrap();
+        let none: Expiry = serde_json::from_str(r#"{ "expires_at": null }"#).unwrap();
+
+        assert_eq!(
+            date.expires_at,
+            Some(NaiveDateTime::from_timestamp(1600000000, 0))
+        );
+        assert_eq!(none.expires_at, None);
+    }
+
+    #[test]
+    fn rejects_timestamps_out_of_range() {
+        let result = serde_json::from_str::<Expiry>(r#"{ "expires_at": 9223372036854775807 }"#);
+
+        assert!(result.is_err());
+    }
+}
@@ -1 +1,5 @@
+pub mod datetime;
+pub mod password;
+pub mod precondition;
+pub mod token;
 pub mod uuid;
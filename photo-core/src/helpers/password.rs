@@ -0,0 +1,17 @@
+use argon2::Config;
+use rand::{thread_rng, RngCore};
+
+pub type PasswordError = argon2::Error;
+
+/// Hashes a password with Argon2 and a random salt. The returned string is in the PHC encoded
+/// format, so it carries the parameters and salt needed to verify it later.
+pub fn hash_password(password: &str) -> Result<String, PasswordError> {
+    let mut salt = [0u8; 16];
+    thread_rng().fill_bytes(&mut salt);
+
+    argon2::hash_encoded(password.as_bytes(), &salt, &Config::default())
+}
+
+pub fn verify_password(hash: &str, password: &str) -> bool {
+    argon2::verify_encoded(hash, password.as_bytes()).unwrap_or(false)
+}
@@ -0,0 +1,65 @@
+use chrono::NaiveDateTime;
+
+/// Entity tag of a row, it changes every time the row is updated.
+pub fn etag(updated_at: &NaiveDateTime) -> String {
+    format!("\"{}\"", updated_at.timestamp_nanos())
+}
+
+/// Version of a row the client expects to be changing, so edits made in between are not
+/// overwritten.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Precondition {
+    /// Value of an `If-Match` header.
+    Matches(String),
+    /// Value of an `If-Unmodified-Since` header, dates only have a precision of seconds.
+    UnmodifiedSince(NaiveDateTime),
+}
+
+impl Precondition {
+    pub fn holds(&self, updated_at: &NaiveDateTime) -> bool {
+        match self {
+            // `If-Match` uses the strong comparison, so weak tags (`W/"..."`) never match.
+            Precondition::Matches(tags) => tags.split(',').any(|tag| {
+                let tag = tag.trim();
+                tag == "*" || tag == etag(updated_at)
+            }),
+            // Both sides are truncated to seconds, `updated_at` has a precision of nanoseconds.
+            Precondition::UnmodifiedSince(since) => updated_at.timestamp() <= since.timestamp(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{etag, Precondition};
+    use chrono::NaiveDateTime;
+
+    fn date(nanos: u32) -> NaiveDateTime {
+        NaiveDateTime::from_timestamp(1600000000, nanos)
+    }
+
+    #[test]
+    fn matches_any_listed_tag() {
+        let updated_at = date(500);
+        let listed = Precondition::Matches(format!("\"other\", {}", etag(&updated_at)));
+
+        assert!(listed.holds(&updated_at));
+        assert!(Precondition::Matches(String::from("*")).holds(&updated_at));
+        assert!(!Precondition::Matches(etag(&date(600))).holds(&updated_at));
+    }
+
+    #[test]
+    fn weak_tags_never_match() {
+        let updated_at = date(500);
+
+        assert!(!Precondition::Matches(format!("W/{}", etag(&updated_at))).holds(&updated_at));
+    }
+
+    #[test]
+    fn unmodified_since_ignores_fractions_of_seconds() {
+        let since = Precondition::UnmodifiedSince(date(0));
+
+        assert!(since.holds(&date(999_999)));
+        assert!(!since.holds(&NaiveDateTime::from_timestamp(1600000001, 0)));
+    }
+}
@@ -0,0 +1,17 @@
+use rand::distributions::Alphanumeric;
+use rand::{thread_rng, Rng};
+use sha2::{Digest, Sha256};
+
+/// Generates a random alphanumeric token, suitable for URLs.
+pub fn random_token(length: usize) -> String {
+    thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(length)
+        .collect()
+}
+
+/// Hex encoded SHA-256 of a token, to store it without keeping the token itself. Tokens are random
+/// and long, so unlike passwords they don't need a slow, salted hash and can be looked up.
+pub fn hash_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
@@ -1,12 +1,26 @@
 use crate::connection::Conn;
+use crate::helpers::datetime::ts_seconds_option;
+use crate::helpers::password::{hash_password, verify_password, PasswordError};
+use crate::helpers::precondition::{etag, Precondition};
+use crate::helpers::token::{hash_token, random_token};
 use crate::helpers::uuid::Uuid;
-use crate::schema::{albums, book_me, photos, users};
+use crate::schema::{
+    album_slugs, albums, api_keys, audit_events, book_me, invitations, login_codes, login_links,
+    oauth_states, photo_tags, photo_uploads, photo_versions, photos, proofing_clients,
+    proofing_comments, proofing_selections, revoked_tokens, sessions, share_links, studio_members,
+    studios, tags, users,
+};
 use chrono::naive::serde::ts_seconds;
 use chrono::NaiveDateTime;
 use chrono::Utc;
+use diesel::dsl::sql;
 use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Bool, Integer, Nullable, Text, Timestamp};
+use diesel::sqlite::Sqlite;
 use serde::{Deserialize, Serialize};
-use snafu::ResultExt;
+use slug::slugify;
+use snafu::{OptionExt, ResultExt};
+use std::collections::{HashMap, HashSet};
 
 #[derive(
     Serialize,
@@ -29,6 +43,10 @@ pub struct User {
     pub created_at: NaiveDateTime,
     #[serde(with = "ts_seconds")]
     pub updated_at: NaiveDateTime,
+    /// Admins manage who is invited to sign in.
+    pub is_admin: bool,
+    /// Studio created along with the user, where their albums go unless they pick another one.
+    pub personal_studio_id: Option<Uuid>,
 }
 
 impl User {
@@ -41,6 +59,8 @@ impl User {
             picture,
             created_at: now,
             updated_at: now,
+            is_admin: false,
+            personal_studio_id: None,
         }
     }
 
@@ -62,7 +82,12 @@ impl User {
     pub fn find_by_email(conn: &Conn, u_email: &str) -> Result<User> {
         use crate::schema::users::dsl::*;
 
-        let user = users.filter(email.eq(u_email)).first(conn).context(Query)?;
+        let user = users
+            .filter(email.eq(u_email))
+            .first(conn)
+            .optional()
+            .context(Query)?
+            .context(UserNotFound)?;
 
         Ok(user)
     }
@@ -70,7 +95,12 @@ impl User {
     pub fn find_by_id(conn: &Conn, u_id: &str) -> Result<User> {
         use crate::schema::users::dsl::*;
 
-        let user = users.filter(id.eq(u_id)).first(conn).context(Query)?;
+        let user = users
+            .filter(id.eq(u_id))
+            .first(conn)
+            .optional()
+            .context(Query)?
+            .context(UserNotFound)?;
 
         Ok(user)
     }
@@ -80,437 +110,4132 @@ impl User {
 
         Ok(users)
     }
+
+    /// Whether some user can manage invitations. Until then, the first user to sign in becomes
+    /// an admin.
+    pub fn has_admin(conn: &Conn) -> Result<bool> {
+        use crate::schema::users::dsl::*;
+
+        let count: i64 = users
+            .filter(is_admin.eq(true))
+            .count()
+            .get_result(conn)
+            .context(Query)?;
+
+        Ok(count > 0)
+    }
 }
 
-#[derive(
-    Serialize,
-    Deserialize,
-    Debug,
-    PartialEq,
-    Clone,
-    Insertable,
-    Identifiable,
-    Associations,
-    Queryable,
-)]
-#[table_name = "albums"]
+#[derive(Debug, Clone, Serialize, Insertable, Queryable)]
+#[table_name = "invitations"]
 #[serde(rename_all = "camelCase")]
-pub struct Album {
+pub struct Invitation {
     pub id: Uuid,
-    pub user_id: Uuid,
-    pub name: String,
-    pub description: Option<String>,
+    pub email: String,
+    /// Admin who sent the invitation, `None` for the ones imported from `ALLOWED_EMAILS`.
+    pub invited_by: Option<Uuid>,
     #[serde(with = "ts_seconds")]
     pub created_at: NaiveDateTime,
-    #[serde(with = "ts_seconds")]
-    pub updated_at: NaiveDateTime,
-    pub deleted: bool,
-}
-
-#[derive(Serialize, Deserialize, Debug, Clone, AsChangeset)]
-#[table_name = "albums"]
-#[serde(rename_all = "camelCase")]
-struct UpdateAlbum {
-    pub name: String,
-    pub description: Option<String>,
-    #[serde(with = "ts_seconds")]
-    pub updated_at: NaiveDateTime,
+    #[serde(with = "ts_seconds_option")]
+    pub expires_at: Option<NaiveDateTime>,
+    /// When the invited user created their account.
+    #[serde(with = "ts_seconds_option")]
+    pub accepted_at: Option<NaiveDateTime>,
 }
 
-impl Album {
-    pub fn new(user: &User, name: String, description: Option<String>) -> Self {
-        let now = Utc::now().naive_utc();
-
+impl Invitation {
+    pub fn new(
+        email: String,
+        invited_by: Option<&User>,
+        expires_at: Option<NaiveDateTime>,
+    ) -> Self {
         Self {
             id: Uuid::new_v4(),
-            user_id: user.id.clone(),
-            name,
-            description,
-            created_at: now,
-            updated_at: now,
-            deleted: false,
+            email,
+            invited_by: invited_by.map(|user| user.id),
+            created_at: Utc::now().naive_utc(),
+            expires_at,
+            accepted_at: None,
         }
     }
 
-    pub fn insert(&self, conn: &Conn) -> Result<Album> {
-        let album: Album = {
-            use crate::schema::albums::dsl::*;
+    /// Saves the invitation, replacing the previous one sent to the same email.
+    pub fn insert(&self, conn: &Conn) -> Result<Invitation> {
+        let saved: Invitation = conn
+            .transaction(|| {
+                use crate::schema::invitations::dsl::*;
 
-            diesel::insert_into(albums)
-                .values(self)
-                .execute(conn)
-                .context(Query)?;
+                diesel::delete(invitations.filter(email.eq(&self.email))).execute(conn)?;
+                diesel::insert_into(invitations)
+                    .values(self)
+                    .execute(conn)?;
 
-            albums.order(created_at.desc()).first(conn).context(Query)?
-        };
+                invitations.filter(id.eq(self.id)).first(conn)
+            })
+            .context(Query)?;
 
-        Ok(album)
+        Ok(saved)
     }
 
-    pub fn update(&self, conn: &Conn, name: String, description: Option<String>) -> Result<Album> {
-        let updated = self.prepare_update(name, description);
-
-        let album: Album = {
-            use crate::schema::albums::dsl::*;
+    /// Invites emails that were not invited yet, without expiry. Returns how many were added.
+    pub fn import(conn: &Conn, emails: &[String]) -> Result<usize> {
+        use crate::schema::invitations::dsl::*;
 
-            diesel::update(albums)
-                .filter(id.eq(self.id))
-                .set(updated)
+        let mut imported = 0;
+        for address in emails {
+            imported += diesel::insert_or_ignore_into(invitations)
+                .values(Invitation::new(address.to_lowercase(), None, None))
                 .execute(conn)
                 .context(Query)?;
+        }
 
-            albums.order(updated_at.desc()).first(conn).context(Query)?
-        };
-
-        Ok(album)
+        Ok(imported)
     }
 
-    pub fn delete(&self, conn: &Conn) -> Result<()> {
-        use crate::schema::albums::dsl::*;
-
-        conn.execute("PRAGMA foreign_keys = ON").context(Query)?;
+    pub fn find_all(conn: &Conn) -> Result<Vec<Invitation>> {
+        use crate::schema::invitations::dsl::*;
 
-        diesel::delete(albums.filter(id.eq(self.id)))
-            .execute(conn)
+        let list = invitations
+            .order(created_at.desc())
+            .load(conn)
             .context(Query)?;
 
-        Ok(())
-    }
-
-    pub fn find_by_id(conn: &Conn, a_id: &str) -> Result<Album> {
-        let album: Album = {
-            use crate::schema::albums::dsl::*;
-
-            albums.filter(id.eq(a_id)).first(conn).context(Query)?
-        };
-
-        Ok(album)
+        Ok(list)
     }
 
-    pub fn find_by_name(conn: &Conn, user: &User, a_name: &str) -> Result<AlbumWithPhotos> {
-        let album: Album = {
-            use crate::schema::albums::dsl::*;
-
-            albums
-                .filter(user_id.eq(user.id))
-                .filter(name.eq(a_name))
-                .first(conn)
-                .context(Query)?
-        };
+    /// Whether the email has an invitation that did not expire. Invitations are saved lowercase.
+    pub fn is_invited(conn: &Conn, i_email: &str) -> Result<bool> {
+        use crate::schema::invitations::dsl::*;
 
-        let photos = Photo::belonging_to(&album)
-            .load::<Photo>(conn)
+        let now = Utc::now().naive_utc();
+        let count: i64 = invitations
+            .filter(email.eq(i_email.to_lowercase()))
+            .filter(expires_at.is_null().or(expires_at.gt(now)))
+            .count()
+            .get_result(conn)
             .context(Query)?;
 
-        Ok((album, photos))
+        Ok(count > 0)
     }
 
-    /// Find all albums including the first picture of each album.
-    pub fn find_all(conn: &Conn, user: &User) -> Result<Vec<AlbumWithPhotos>> {
-        let albums: Vec<Album> = {
-            use crate::schema::albums::dsl::*;
-
-            albums
-                .filter(deleted.eq(false))
-                .filter(user_id.eq(user.id))
-                .load::<Album>(conn)
-                .context(Query)?
-        };
-
-        let photos: Vec<Vec<Photo>> = albums
-            .iter()
-            .map(|album| {
-                let photos = Photo::belonging_to(album)
-                    .limit(1)
-                    .load::<Photo>(conn)
-                    .unwrap();
-
-                photos
-            })
-            .collect();
+    /// Marks the invitation of the email as accepted, once its account is created.
+    pub fn accept(conn: &Conn, i_email: &str) -> Result<()> {
+        use crate::schema::invitations::dsl::*;
 
-        let data = albums.into_iter().zip(photos).collect::<Vec<_>>();
+        diesel::update(invitations.filter(email.eq(i_email.to_lowercase())))
+            .set(accepted_at.eq(Utc::now().naive_utc()))
+            .execute(conn)
+            .context(Query)?;
 
-        Ok(data)
+        Ok(())
     }
 
-    pub fn find_main_public(conn: &Conn, user: &User) -> Result<AlbumWithPhotos> {
-        // TODO: Implement public & main album functionality. For now it'll return the first album.
-        let album: Album = {
-            use crate::schema::albums::dsl::*;
-
-            albums
-                .filter(deleted.eq(false))
-                .filter(user_id.eq(user.id))
-                .first(conn)
-                .context(Query)?
-        };
+    /// Deletes the invitation, so the email can't create an account anymore. Existing accounts
+    /// are kept. Fails with `InvitationNotFound` when it does not exist.
+    pub fn revoke(conn: &Conn, i_id: &str) -> Result<()> {
+        use crate::schema::invitations::dsl::*;
 
-        let photos = Photo::belonging_to(&album)
-            .load::<Photo>(conn)
+        let deleted = diesel::delete(invitations.filter(id.eq(i_id)))
+            .execute(conn)
             .context(Query)?;
 
-        Ok((album, photos))
-    }
+        if deleted == 0 {
+            return InvitationNotFound.fail();
+        }
 
-    pub fn photos(&self, conn: &Conn) -> Result<Vec<Photo>> {
-        use crate::schema::photos::dsl::*;
+        Ok(())
+    }
+}
 
-        let results: Vec<Photo> = photos
-            .filter(deleted.eq(false))
-            .filter(album_id.eq(self.id))
-            .load::<Photo>(conn)
-            .context(Query)?;
+/// What a member of a studio can do with its albums. Each role can do everything the previous
+/// ones can.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "camelCase")]
+pub enum Role {
+    /// Browses the albums and photos.
+    Viewer,
+    /// Creates and edits albums, uploads and edits photos.
+    Editor,
+    /// Deletes albums and manages the members and settings of the studio.
+    Owner,
+}
 
-        Ok(results)
+impl Role {
+    fn name(self) -> &'static str {
+        match self {
+            Role::Viewer => "viewer",
+            Role::Editor => "editor",
+            Role::Owner => "owner",
+        }
     }
 
-    fn prepare_update(&self, name: String, description: Option<String>) -> UpdateAlbum {
-        let now = Utc::now().naive_utc();
-
-        UpdateAlbum {
-            name,
-            description,
-            updated_at: now,
+    fn from_name(name: &str) -> Role {
+        match name {
+            "owner" => Role::Owner,
+            "editor" => Role::Editor,
+            _ => Role::Viewer,
         }
     }
 }
 
-#[derive(
-    Serialize,
-    Deserialize,
-    Debug,
-    PartialEq,
-    Clone,
-    Insertable,
-    Identifiable,
-    Associations,
-    Queryable,
-)]
-#[table_name = "photos"]
-#[belongs_to(Album)]
-#[belongs_to(User)]
+/// A workspace shared by a team, owning albums.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Insertable, Identifiable, Queryable)]
+#[table_name = "studios"]
 #[serde(rename_all = "camelCase")]
-pub struct Photo {
+pub struct Studio {
     pub id: Uuid,
-    pub album_id: Uuid,
-    pub user_id: Uuid,
-    pub index_in_album: i32,
-    pub s3_id: String,
-    pub src: String,
-    pub main_color: String,
-    pub title: Option<String>,
-    pub description: Option<String>,
-    pub width: i32,
-    pub height: i32,
-    pub is_favorite: bool,
+    pub name: String,
     #[serde(with = "ts_seconds")]
     pub created_at: NaiveDateTime,
     #[serde(with = "ts_seconds")]
     pub updated_at: NaiveDateTime,
-    pub deleted: bool,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, AsChangeset)]
-#[table_name = "photos"]
-struct UpdatePhoto {
-    pub index_in_album: i32,
-    pub is_favorite: bool,
-    pub title: Option<String>,
-    pub description: Option<String>,
+#[derive(Debug, Clone, Insertable, Queryable)]
+#[table_name = "studio_members"]
+struct StudioMember {
+    studio_id: Uuid,
+    user_id: Uuid,
+    role: String,
+    created_at: NaiveDateTime,
+}
+
+impl StudioMember {
+    fn new(studio: &Studio, user: &User, role: Role) -> Self {
+        Self {
+            studio_id: studio.id,
+            user_id: user.id,
+            role: String::from(role.name()),
+            created_at: Utc::now().naive_utc(),
+        }
+    }
+
+    fn role(&self) -> Role {
+        Role::from_name(&self.role)
+    }
+}
+
+/// A studio along with the role the user has in it.
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StudioMembership {
+    #[serde(flatten)]
+    pub studio: Studio,
+    pub role: Role,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Member {
+    pub user: User,
+    pub role: Role,
     #[serde(with = "ts_seconds")]
-    pub updated_at: NaiveDateTime,
+    pub joined_at: NaiveDateTime,
 }
 
-impl Photo {
-    pub fn new(
-        album: &Album,
-        user: &User,
-        index_in_album: i32,
-        s3_id: String,
-        src: String,
-        main_color: String,
-        title: Option<String>,
-        description: Option<String>,
-        width: i32,
-        height: i32,
-        is_favorite: bool,
-    ) -> Self {
+impl Studio {
+    pub fn new(name: String) -> Self {
         let now = Utc::now().naive_utc();
 
         Self {
             id: Uuid::new_v4(),
-            album_id: album.id.clone(),
-            user_id: user.id.clone(),
-            index_in_album,
-            s3_id,
-            src: src.clone(),
-            main_color,
-            title,
-            description,
-            width,
-            height,
-            is_favorite,
+            name,
             created_at: now,
             updated_at: now,
-            deleted: false,
         }
     }
 
-    pub fn insert(&self, conn: &Conn) -> Result<Photo> {
-        let photo: Photo = {
-            use crate::schema::photos::dsl::*;
+    /// Inserts the studio with the user as its owner.
+    pub fn insert(&self, conn: &Conn, owner: &User) -> Result<Studio> {
+        let studio: Studio = conn
+            .transaction(|| {
+                use crate::schema::studios::dsl::*;
 
-            diesel::insert_into(photos)
-                .values(self)
-                .execute(conn)
-                .context(Query)?;
+                diesel::insert_into(studios).values(self).execute(conn)?;
+                diesel::insert_into(studio_members::table)
+                    .values(StudioMember::new(self, owner, Role::Owner))
+                    .execute(conn)?;
 
-            photos.order(created_at.desc()).first(conn).context(Query)?
-        };
+                studios.filter(id.eq(self.id)).first(conn)
+            })
+            .context(Query)?;
 
-        Ok(photo)
+        Ok(studio)
     }
 
-    pub fn update(
-        &self,
-        conn: &Conn,
-        index_in_album: i32,
-        is_favorite: bool,
-        title: Option<String>,
-        description: Option<String>,
-    ) -> Result<Photo> {
-        let updated = self.prepare_update(index_in_album, is_favorite, title, description);
-        let photo: Photo = {
-            use crate::schema::photos::dsl::*;
+    pub fn rename(&self, conn: &Conn, s_name: String) -> Result<Studio> {
+        use crate::schema::studios::dsl::*;
 
-            diesel::update(photos)
-                .filter(id.eq(self.id))
-                .set(updated)
-                .execute(conn)
-                .context(Query)?;
+        diesel::update(studios.filter(id.eq(self.id)))
+            .set((name.eq(s_name), updated_at.eq(Utc::now().naive_utc())))
+            .execute(conn)
+            .context(Query)?;
 
-            photos.order(updated_at.desc()).first(conn).context(Query)?
-        };
+        let studio = studios.filter(id.eq(self.id)).first(conn).context(Query)?;
 
-        Ok(photo)
+        Ok(studio)
     }
 
+    /// Finds a studio the user is a member of. Fails with `StudioNotFound` when it does not exist
+    /// or the user is not a member, and with `Forbidden` when their role is lower than `role`.
+    pub fn find_for_member(conn: &Conn, user: &User, s_id: &str, role: Role) -> Result<Studio> {
+        use crate::schema::studios::dsl::*;
+
+        let studio: Studio = studios
+            .filter(id.eq(s_id))
+            .first(conn)
+            .optional()
+            .context(Query)?
+            .context(StudioNotFound)?;
+
+        match Studio::role_of(conn, &studio.id, user)? {
+            None => StudioNotFound.fail(),
+            Some(r) if r < role => Forbidden.fail(),
+            Some(_) => Ok(studio),
+        }
+    }
+
+    /// Inserts the studio with the user as its owner, and makes it the personal studio of the user.
+    pub fn insert_personal(&self, conn: &Conn, owner: &User) -> Result<Studio> {
+        transaction(conn, || {
+            let studio = self.insert(conn, owner)?;
+
+            diesel::update(users::table.filter(users::id.eq(owner.id)))
+                .set(users::personal_studio_id.eq(studio.id))
+                .execute(conn)
+                .context(Query)?;
+
+            Ok(studio)
+        })
+    }
+
+    /// The studio created along with the user, where their albums go unless they pick another one.
+    /// Fails with `StudioNotFound` when it was deleted or the user can no longer edit it.
+    pub fn find_personal(conn: &Conn, user: &User) -> Result<Studio> {
+        let studio: Studio = users::table
+            .inner_join(studios::table.on(users::personal_studio_id.eq(studios::id.nullable())))
+            .filter(users::id.eq(user.id))
+            .select(studios::all_columns)
+            .first(conn)
+            .optional()
+            .context(Query)?
+            .context(StudioNotFound)?;
+
+        match Studio::role_of(conn, &studio.id, user)? {
+            Some(r) if r >= Role::Editor => Ok(studio),
+            _ => StudioNotFound.fail(),
+        }
+    }
+
+    /// Id of the personal studio of the user, read from the database so users loaded before it was
+    /// set find it too.
+    fn personal_id(conn: &Conn, user: &User) -> Result<Option<Uuid>> {
+        let s_id: Option<Option<Uuid>> = users::table
+            .filter(users::id.eq(user.id))
+            .select(users::personal_studio_id)
+            .first(conn)
+            .optional()
+            .context(Query)?;
+
+        Ok(s_id.flatten())
+    }
+
+    /// Studios the user is a member of, with their role.
+    pub fn find_by_member(conn: &Conn, user: &User) -> Result<Vec<StudioMembership>> {
+        let rows: Vec<(Studio, StudioMember)> = studios::table
+            .inner_join(studio_members::table)
+            .filter(studio_members::user_id.eq(user.id))
+            .order(studios::created_at.asc())
+            .load(conn)
+            .context(Query)?;
+
+        let list = rows
+            .into_iter()
+            .map(|(studio, member)| StudioMembership {
+                role: member.role(),
+                studio,
+            })
+            .collect();
+
+        Ok(list)
+    }
+
+    /// Role of the user in the studio, `None` when they are not a member.
+    pub fn role_of(conn: &Conn, s_id: &Uuid, user: &User) -> Result<Option<Role>> {
+        use crate::schema::studio_members::dsl::*;
+
+        let member: Option<StudioMember> = studio_members
+            .filter(studio_id.eq(s_id))
+            .filter(user_id.eq(user.id))
+            .first(conn)
+            .optional()
+            .context(Query)?;
+
+        Ok(member.map(|m| m.role()))
+    }
+
+    /// Ids of the studios where the user has at least the given role.
+    pub fn ids_for_member(conn: &Conn, user: &User, min_role: Role) -> Result<Vec<Uuid>> {
+        use crate::schema::studio_members::dsl::*;
+
+        let members: Vec<StudioMember> = studio_members
+            .filter(user_id.eq(user.id))
+            .load(conn)
+            .context(Query)?;
+
+        let ids = members
+            .into_iter()
+            .filter(|m| m.role() >= min_role)
+            .map(|m| m.studio_id)
+            .collect();
+
+        Ok(ids)
+    }
+
+    pub fn members(&self, conn: &Conn) -> Result<Vec<Member>> {
+        let rows: Vec<(StudioMember, User)> = studio_members::table
+            .inner_join(users::table)
+            .filter(studio_members::studio_id.eq(self.id))
+            .order(studio_members::created_at.asc())
+            .load(conn)
+            .context(Query)?;
+
+        let list = rows
+            .into_iter()
+            .map(|(member, user)| Member {
+                role: member.role(),
+                joined_at: member.created_at,
+                user,
+            })
+            .collect();
+
+        Ok(list)
+    }
+
+    /// Adds the user to the studio, or changes their role if they already are a member. Fails with
+    /// `LastOwner` when it would leave the studio without an owner.
+    pub fn set_member(&self, conn: &Conn, member: &User, m_role: Role) -> Result<()> {
+        use crate::schema::studio_members::dsl::*;
+
+        if m_role != Role::Owner && self.is_last_owner(conn, &member.id)? {
+            return LastOwner.fail();
+        }
+
+        diesel::replace_into(studio_members)
+            .values(StudioMember::new(self, member, m_role))
+            .execute(conn)
+            .context(Query)?;
+
+        Ok(())
+    }
+
+    /// Removes a member from the studio. Fails with `MemberNotFound` when the user is not a member,
+    /// and with `LastOwner` when they are the only owner left.
+    pub fn remove_member(&self, conn: &Conn, m_id: &Uuid) -> Result<()> {
+        use crate::schema::studio_members::dsl::*;
+
+        if self.is_last_owner(conn, m_id)? {
+            return LastOwner.fail();
+        }
+
+        let deleted = diesel::delete(
+            studio_members
+                .filter(studio_id.eq(self.id))
+                .filter(user_id.eq(m_id)),
+        )
+        .execute(conn)
+        .context(Query)?;
+
+        if deleted == 0 {
+            return MemberNotFound.fail();
+        }
+
+        Ok(())
+    }
+
+    fn is_last_owner(&self, conn: &Conn, m_id: &Uuid) -> Result<bool> {
+        use crate::schema::studio_members::dsl::*;
+
+        let owners: Vec<Uuid> = studio_members
+            .filter(studio_id.eq(self.id))
+            .filter(role.eq(Role::Owner.name()))
+            .select(user_id)
+            .load(conn)
+            .context(Query)?;
+
+        Ok(owners == vec![*m_id])
+    }
+}
+
+/// Seconds a login started with an OAuth2 provider can take before its state is rejected.
+pub const OAUTH_STATE_EXPIRY: i64 = 600;
+
+#[derive(Debug, Clone, Insertable, Queryable)]
+#[table_name = "oauth_states"]
+pub struct OAuthState {
+    pub state: String,
+    pub pkce_verifier: String,
+    pub created_at: NaiveDateTime,
+    /// Name of the provider the login was started with.
+    pub provider: String,
+}
+
+impl OAuthState {
+    pub fn new(provider: String, state: String, pkce_verifier: String) -> Self {
+        Self {
+            state,
+            pkce_verifier,
+            created_at: Utc::now().naive_utc(),
+            provider,
+        }
+    }
+
+    /// Saves the state, forgetting the logins that were abandoned.
+    pub fn insert(&self, conn: &Conn) -> Result<OAuthState> {
+        use crate::schema::oauth_states::dsl::*;
+
+        let expired = Utc::now().naive_utc() - chrono::Duration::seconds(OAUTH_STATE_EXPIRY);
+        diesel::delete(oauth_states.filter(created_at.lt(expired)))
+            .execute(conn)
+            .context(Query)?;
+
+        diesel::insert_into(oauth_states)
+            .values(self)
+            .execute(conn)
+            .context(Query)?;
+
+        let saved = oauth_states
+            .filter(state.eq(&self.state))
+            .first(conn)
+            .context(Query)?;
+
+        Ok(saved)
+    }
+
+    /// Finds the state the provider sent back and forgets about it, so it can only be used once.
+    /// Fails with `InvalidOAuthState` when it is unknown, expired or was created for a login with
+    /// another provider.
+    pub fn take(conn: &Conn, o_provider: &str, o_state: &str) -> Result<OAuthState> {
+        use crate::schema::oauth_states::dsl::*;
+
+        let found: OAuthState = conn.transaction(|| {
+            let found: OAuthState = oauth_states
+                .filter(state.eq(o_state))
+                .filter(provider.eq(o_provider))
+                .first(conn)
+                .optional()
+                .context(Query)?
+                .context(InvalidOAuthState)?;
+
+            let deleted = diesel::delete(
+                oauth_states
+                    .filter(state.eq(o_state))
+                    .filter(provider.eq(o_provider)),
+            )
+            .execute(conn)
+            .context(Query)?;
+            if deleted != 1 {
+                return Err(ModelError::InvalidOAuthState);
+            }
+
+            Ok(found)
+        })?;
+
+        let expired = Utc::now().naive_utc() - chrono::Duration::seconds(OAUTH_STATE_EXPIRY);
+        if found.created_at < expired {
+            return Err(ModelError::InvalidOAuthState);
+        }
+
+        Ok(found)
+    }
+}
+
+/// Minutes an emailed login link can be used for.
+pub const LOGIN_LINK_EXPIRY: i64 = 15;
+
+/// Length of the tokens sent in login links.
+const LOGIN_LINK_TOKEN_LENGTH: usize = 48;
+
+#[derive(Debug, Clone, Insertable, Queryable)]
+#[table_name = "login_links"]
+pub struct LoginLink {
+    pub token_hash: String,
+    pub email: String,
+    pub created_at: NaiveDateTime,
+    pub expires_at: NaiveDateTime,
+}
+
+impl LoginLink {
+    /// Creates a link to sign in as the given email. Returns it along with its token, which is only
+    /// sent to that email.
+    pub fn new(email: String) -> (Self, String) {
+        let now = Utc::now().naive_utc();
+        let token = random_token(LOGIN_LINK_TOKEN_LENGTH);
+
+        let link = Self {
+            token_hash: hash_token(&token),
+            email,
+            created_at: now,
+            expires_at: now + chrono::Duration::minutes(LOGIN_LINK_EXPIRY),
+        };
+
+        (link, token)
+    }
+
+    /// Saves the link, forgetting the ones that expired without being used.
+    pub fn insert(&self, conn: &Conn) -> Result<()> {
+        use crate::schema::login_links::dsl::*;
+
+        let now = Utc::now().naive_utc();
+        diesel::delete(login_links.filter(expires_at.lt(now)))
+            .execute(conn)
+            .context(Query)?;
+
+        diesel::insert_into(login_links)
+            .values(self)
+            .execute(conn)
+            .context(Query)?;
+
+        Ok(())
+    }
+
+    /// Finds the link of a token and forgets about it, so it can only be used once. Fails with
+    /// `InvalidLoginLink` when it is unknown or expired. The link is only returned when this call
+    /// is the one that deleted it, so concurrent requests can't both use it.
+    pub fn take(conn: &Conn, token: &str) -> Result<LoginLink> {
+        use crate::schema::login_links::dsl::*;
+
+        let hash = hash_token(token);
+        let found: LoginLink = conn.transaction(|| {
+            let found: LoginLink = login_links
+                .filter(token_hash.eq(&hash))
+                .first(conn)
+                .optional()
+                .context(Query)?
+                .context(InvalidLoginLink)?;
+
+            let deleted = diesel::delete(login_links.filter(token_hash.eq(&hash)))
+                .execute(conn)
+                .context(Query)?;
+            if deleted != 1 {
+                return Err(ModelError::InvalidLoginLink);
+            }
+
+            Ok(found)
+        })?;
+
+        let now = Utc::now().naive_utc();
+        if found.expires_at < now {
+            return Err(ModelError::InvalidLoginLink);
+        }
+
+        Ok(found)
+    }
+}
+
+/// Seconds the client has to exchange a login code for tokens.
+pub const LOGIN_CODE_EXPIRY: i64 = 60;
+
+/// Length of the codes handed to the client after a login.
+const LOGIN_CODE_LENGTH: usize = 48;
+
+#[derive(Debug, Clone, Insertable, Queryable)]
+#[table_name = "login_codes"]
+pub struct LoginCode {
+    pub code_hash: String,
+    pub user_id: Uuid,
+    pub created_at: NaiveDateTime,
+    pub expires_at: NaiveDateTime,
+}
+
+impl LoginCode {
+    /// Creates a code the client exchanges to sign in as the user. Returns it along with the code,
+    /// which is only sent to the client.
+    pub fn new(user: &User) -> (Self, String) {
+        let now = Utc::now().naive_utc();
+        let code = random_token(LOGIN_CODE_LENGTH);
+
+        let login_code = Self {
+            code_hash: hash_token(&code),
+            user_id: user.id,
+            created_at: now,
+            expires_at: now + chrono::Duration::seconds(LOGIN_CODE_EXPIRY),
+        };
+
+        (login_code, code)
+    }
+
+    /// Saves the code, forgetting the ones that expired without being used.
+    pub fn insert(&self, conn: &Conn) -> Result<()> {
+        use crate::schema::login_codes::dsl::*;
+
+        let now = Utc::now().naive_utc();
+        diesel::delete(login_codes.filter(expires_at.lt(now)))
+            .execute(conn)
+            .context(Query)?;
+
+        diesel::insert_into(login_codes)
+            .values(self)
+            .execute(conn)
+            .context(Query)?;
+
+        Ok(())
+    }
+
+    /// Finds the user of a code and forgets about the code, so it can only be used once. Fails
+    /// with `InvalidLoginCode` when it is unknown or expired.
+    pub fn take(conn: &Conn, code: &str) -> Result<User> {
+        use crate::schema::login_codes::dsl::*;
+
+        let hash = hash_token(code);
+        let found: LoginCode = conn.transaction(|| {
+            let found: LoginCode = login_codes
+                .filter(code_hash.eq(&hash))
+                .first(conn)
+                .optional()
+                .context(Query)?
+                .context(InvalidLoginCode)?;
+
+            let deleted = diesel::delete(login_codes.filter(code_hash.eq(&hash)))
+                .execute(conn)
+                .context(Query)?;
+            if deleted != 1 {
+                return Err(ModelError::InvalidLoginCode);
+            }
+
+            Ok(found)
+        })?;
+
+        let now = Utc::now().naive_utc();
+        if found.expires_at < now {
+            return Err(ModelError::InvalidLoginCode);
+        }
+
+        User::find_by_id(conn, &found.user_id.to_string())
+    }
+}
+
+/// Days a session stays alive without being refreshed.
+pub const SESSION_EXPIRY_DAYS: i64 = 30;
+
+/// Length of the refresh tokens handed to clients.
+const REFRESH_TOKEN_LENGTH: usize = 48;
+
+#[derive(
+    Serialize,
+    Deserialize,
+    Debug,
+    PartialEq,
+    Clone,
+    Insertable,
+    Identifiable,
+    Associations,
+    Queryable,
+)]
+#[table_name = "sessions"]
+#[belongs_to(User)]
+#[serde(rename_all = "camelCase")]
+pub struct Session {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    #[serde(skip)]
+    token_hash: String,
+    #[serde(skip)]
+    previous_token_hash: Option<String>,
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    #[serde(with = "ts_seconds")]
+    pub created_at: NaiveDateTime,
+    #[serde(with = "ts_seconds")]
+    pub last_used_at: NaiveDateTime,
+    #[serde(with = "ts_seconds")]
+    pub expires_at: NaiveDateTime,
+}
+
+impl Session {
+    /// Starts a session for the user. Returns it along with its refresh token, which is not stored
+    /// and can't be recovered later.
+    pub fn new(user: &User, user_agent: Option<String>, ip: Option<String>) -> (Self, String) {
+        let now = Utc::now().naive_utc();
+        let token = random_token(REFRESH_TOKEN_LENGTH);
+
+        let session = Self {
+            id: Uuid::new_v4(),
+            user_id: user.id,
+            token_hash: hash_token(&token),
+            previous_token_hash: None,
+            user_agent,
+            ip,
+            created_at: now,
+            last_used_at: now,
+            expires_at: now + chrono::Duration::days(SESSION_EXPIRY_DAYS),
+        };
+
+        (session, token)
+    }
+
+    pub fn insert(&self, conn: &Conn) -> Result<Session> {
+        use crate::schema::sessions::dsl::*;
+
+        diesel::insert_into(sessions)
+            .values(self)
+            .execute(conn)
+            .context(Query)?;
+
+        let session = sessions.filter(id.eq(self.id)).first(conn).context(Query)?;
+
+        Ok(session)
+    }
+
+    /// Exchanges a refresh token for a new one, extending the session. A token that was already
+    /// rotated means it leaked, so the whole session is revoked. Fails with `InvalidRefreshToken`
+    /// when the token is unknown, reused or the session expired.
+    pub fn rotate(
+        conn: &Conn,
+        token: &str,
+        s_user_agent: Option<String>,
+        s_ip: Option<String>,
+    ) -> Result<(Session, User, String)> {
+        use crate::schema::sessions::dsl::*;
+
+        let hash = hash_token(token);
+        let now = Utc::now().naive_utc();
+        let new_token = random_token(REFRESH_TOKEN_LENGTH);
+
+        let rotated: Option<(Session, User)> = conn
+            .transaction(|| {
+                let reused: Option<Session> = sessions
+                    .filter(previous_token_hash.eq(&hash))
+                    .first(conn)
+                    .optional()?;
+                if let Some(session) = reused {
+                    diesel::delete(sessions.filter(id.eq(session.id))).execute(conn)?;
+                    return Ok(None);
+                }
+
+                let found: Option<Session> = sessions
+                    .filter(token_hash.eq(&hash))
+                    .filter(expires_at.gt(now))
+                    .first(conn)
+                    .optional()?;
+                let session = match found {
+                    Some(session) => session,
+                    None => return Ok(None),
+                };
+
+                diesel::update(sessions.filter(id.eq(session.id)))
+                    .set((
+                        token_hash.eq(hash_token(&new_token)),
+                        previous_token_hash.eq(&hash),
+                        user_agent.eq(s_user_agent.or(session.user_agent)),
+                        ip.eq(s_ip.or(session.ip)),
+                        last_used_at.eq(now),
+                        expires_at.eq(now + chrono::Duration::days(SESSION_EXPIRY_DAYS)),
+                    ))
+                    .execute(conn)?;
+
+                let session: Session = sessions.filter(id.eq(session.id)).first(conn)?;
+                let user: User = users::table
+                    .filter(users::id.eq(session.user_id))
+                    .first(conn)?;
+
+                Ok(Some((session, user)))
+            })
+            .context(Query)?;
+
+        let (session, user) = rotated.context(InvalidRefreshToken)?;
+
+        Ok((session, user, new_token))
+    }
+
+    /// Sessions of the user that did not expire, most recently used first.
+    pub fn find_by_user(conn: &Conn, user: &User) -> Result<Vec<Session>> {
+        use crate::schema::sessions::dsl::*;
+
+        let now = Utc::now().naive_utc();
+        let list = Session::belonging_to(user)
+            .filter(expires_at.gt(now))
+            .order(last_used_at.desc())
+            .load::<Session>(conn)
+            .context(Query)?;
+
+        Ok(list)
+    }
+
+    /// Whether the session still exists and did not expire.
+    pub fn is_active(conn: &Conn, s_id: &str) -> Result<bool> {
+        use crate::schema::sessions::dsl::*;
+
+        let now = Utc::now().naive_utc();
+        let count: i64 = sessions
+            .filter(id.eq(s_id))
+            .filter(expires_at.gt(now))
+            .count()
+            .get_result(conn)
+            .context(Query)?;
+
+        Ok(count > 0)
+    }
+
+    /// Ends a session of the user. Fails with `SessionNotFound` when it belongs to someone else.
+    pub fn revoke(conn: &Conn, user: &User, s_id: &str) -> Result<()> {
+        use crate::schema::sessions::dsl::*;
+
+        let deleted = diesel::delete(sessions.filter(user_id.eq(user.id)).filter(id.eq(s_id)))
+            .execute(conn)
+            .context(Query)?;
+
+        if deleted == 0 {
+            return Err(ModelError::SessionNotFound);
+        }
+
+        Ok(())
+    }
+
+    /// Ends every session of the user.
+    pub fn revoke_all(conn: &Conn, user: &User) -> Result<()> {
+        use crate::schema::sessions::dsl::*;
+
+        diesel::delete(sessions.filter(user_id.eq(user.id)))
+            .execute(conn)
+            .context(Query)?;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Insertable, Queryable)]
+#[table_name = "revoked_tokens"]
+pub struct RevokedToken {
+    pub jti: String,
+    pub expires_at: NaiveDateTime,
+    pub created_at: NaiveDateTime,
+}
+
+impl RevokedToken {
+    /// Denies the token with the given id until it expires.
+    pub fn new(jti: String, expires_at: NaiveDateTime) -> Self {
+        Self {
+            jti,
+            expires_at,
+            created_at: Utc::now().naive_utc(),
+        }
+    }
+
+    /// Saves the token, forgetting the ones that already expired and would be rejected anyway.
+    pub fn insert(&self, conn: &Conn) -> Result<()> {
+        use crate::schema::revoked_tokens::dsl::*;
+
+        let now = Utc::now().naive_utc();
+        diesel::delete(revoked_tokens.filter(expires_at.lt(now)))
+            .execute(conn)
+            .context(Query)?;
+
+        diesel::insert_or_ignore_into(revoked_tokens)
+            .values(self)
+            .execute(conn)
+            .context(Query)?;
+
+        Ok(())
+    }
+
+    pub fn is_revoked(conn: &Conn, t_jti: &str) -> Result<bool> {
+        use crate::schema::revoked_tokens::dsl::*;
+
+        let count: i64 = revoked_tokens
+            .filter(jti.eq(t_jti))
+            .count()
+            .get_result(conn)
+            .context(Query)?;
+
+        Ok(count > 0)
+    }
+}
+
+/// Every API key starts with this, so it can be told apart from an access token.
+pub const API_KEY_PREFIX: &str = "pk_";
+
+/// Random characters of an API key, after its prefix.
+const API_KEY_TOKEN_LENGTH: usize = 40;
+
+/// Characters of a key kept in clear, enough for users to recognize it in a list.
+const API_KEY_HINT_LENGTH: usize = 8;
+
+/// Seconds between two updates of the last use of a key, so scripts making many requests don't
+/// write to the database for each one.
+const API_KEY_USE_PRECISION: i64 = 60;
+
+/// What requests made with an API key are allowed to do.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum ApiKeyScope {
+    /// Only reads, like downloading albums.
+    Read,
+    /// Reads and changes, like uploading photos.
+    Write,
+}
+
+impl ApiKeyScope {
+    fn name(self) -> &'static str {
+        match self {
+            ApiKeyScope::Read => "read",
+            ApiKeyScope::Write => "write",
+        }
+    }
+}
+
+#[derive(
+    Serialize,
+    Deserialize,
+    Debug,
+    PartialEq,
+    Clone,
+    Insertable,
+    Identifiable,
+    Associations,
+    Queryable,
+)]
+#[table_name = "api_keys"]
+#[belongs_to(User)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiKey {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    #[serde(skip)]
+    key_hash: String,
+    /// First characters of the key.
+    pub prefix: String,
+    pub scope: String,
+    #[serde(with = "ts_seconds_option")]
+    pub last_used_at: Option<NaiveDateTime>,
+    #[serde(with = "ts_seconds")]
+    pub created_at: NaiveDateTime,
+}
+
+impl ApiKey {
+    /// Creates a key for the user. Returns it along with the key itself, which is not stored and
+    /// can't be recovered later.
+    pub fn new(user: &User, name: String, scope: ApiKeyScope) -> (Self, String) {
+        let key = format!("{}{}", API_KEY_PREFIX, random_token(API_KEY_TOKEN_LENGTH));
+
+        let api_key = Self {
+            id: Uuid::new_v4(),
+            user_id: user.id,
+            name,
+            key_hash: hash_token(&key),
+            prefix: key.chars().take(API_KEY_HINT_LENGTH).collect(),
+            scope: String::from(scope.name()),
+            last_used_at: None,
+            created_at: Utc::now().naive_utc(),
+        };
+
+        (api_key, key)
+    }
+
+    pub fn insert(&self, conn: &Conn) -> Result<ApiKey> {
+        use crate::schema::api_keys::dsl::*;
+
+        diesel::insert_into(api_keys)
+            .values(self)
+            .execute(conn)
+            .context(Query)?;
+
+        let api_key = api_keys.filter(id.eq(self.id)).first(conn).context(Query)?;
+
+        Ok(api_key)
+    }
+
+    pub fn find_by_user(conn: &Conn, user: &User) -> Result<Vec<ApiKey>> {
+        use crate::schema::api_keys::dsl::*;
+
+        let list = api_keys
+            .filter(user_id.eq(user.id))
+            .order(created_at.desc())
+            .load::<ApiKey>(conn)
+            .context(Query)?;
+
+        Ok(list)
+    }
+
+    /// Finds the API key a request was made with and records that it was used. Fails with
+    /// `InvalidApiKey` when it is unknown or was revoked.
+    pub fn authenticate(conn: &Conn, key: &str) -> Result<ApiKey> {
+        use crate::schema::api_keys::dsl::*;
+
+        let mut found: ApiKey = api_keys
+            .filter(key_hash.eq(hash_token(key)))
+            .first(conn)
+            .optional()
+            .context(Query)?
+            .context(InvalidApiKey)?;
+
+        let now = Utc::now().naive_utc();
+        let is_stale = found.last_used_at.map_or(true, |used| {
+            now - used > chrono::Duration::seconds(API_KEY_USE_PRECISION)
+        });
+
+        if is_stale {
+            diesel::update(api_keys.filter(id.eq(found.id)))
+                .set(last_used_at.eq(now))
+                .execute(conn)
+                .context(Query)?;
+            found.last_used_at = Some(now);
+        }
+
+        Ok(found)
+    }
+
+    /// Whether requests made with this key can change anything.
+    pub fn can_write(&self) -> bool {
+        self.scope == ApiKeyScope::Write.name()
+    }
+
+    /// Revokes a key of the user, it stops working right away. Fails with `ApiKeyNotFound` when
+    /// the user has no such key.
+    pub fn revoke(conn: &Conn, user: &User, k_id: &str) -> Result<()> {
+        use crate::schema::api_keys::dsl::*;
+
+        let deleted = diesel::delete(api_keys.filter(user_id.eq(user.id)).filter(id.eq(k_id)))
+            .execute(conn)
+            .context(Query)?;
+
+        if deleted == 0 {
+            return ApiKeyNotFound.fail();
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(
+    Serialize,
+    Deserialize,
+    Debug,
+    PartialEq,
+    Clone,
+    Insertable,
+    Identifiable,
+    Associations,
+    Queryable,
+    QueryableByName,
+)]
+#[table_name = "albums"]
+#[serde(rename_all = "camelCase")]
+pub struct Album {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(with = "ts_seconds")]
+    pub created_at: NaiveDateTime,
+    #[serde(with = "ts_seconds")]
+    pub updated_at: NaiveDateTime,
+    pub deleted: bool,
+    pub slug: String,
+    pub parent_id: Option<Uuid>,
+    #[serde(
+        rename = "protected",
+        serialize_with = "serialize_is_some",
+        skip_deserializing
+    )]
+    pub password_hash: Option<String>,
+    pub studio_id: Uuid,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AlbumSummary {
+    pub album: Album,
+    pub cover: Option<Photo>,
+    pub photos_count: i64,
+    pub favorites_count: i64,
+    #[serde(with = "ts_seconds")]
+    pub last_updated_at: NaiveDateTime,
+}
+
+/// Album summary along with the albums nested inside of it.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AlbumNode {
+    #[serde(flatten)]
+    pub summary: AlbumSummary,
+    pub children: Vec<AlbumNode>,
+}
+
+#[derive(Debug, QueryableByName)]
+struct AlbumSummaryRow {
+    #[diesel(embed)]
+    album: Album,
+    #[sql_type = "BigInt"]
+    photos_count: i64,
+    #[sql_type = "BigInt"]
+    favorites_count: i64,
+    #[sql_type = "Timestamp"]
+    last_updated_at: NaiveDateTime,
+    /// First photo of the album, loaded afterwards along with the covers of the other albums.
+    #[sql_type = "Nullable<Text>"]
+    cover_id: Option<Uuid>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, AsChangeset)]
+#[table_name = "albums"]
+#[serde(rename_all = "camelCase")]
+struct UpdateAlbum {
+    pub name: String,
+    pub description: Option<String>,
+    pub slug: String,
+    #[serde(with = "ts_seconds")]
+    pub updated_at: NaiveDateTime,
+}
+
+/// Fields of an album to change, the ones left as `None` are kept as they are.
+#[derive(Debug, Clone, Default, AsChangeset)]
+#[table_name = "albums"]
+pub struct AlbumChanges {
+    pub name: Option<String>,
+    /// `Some(None)` removes the description.
+    pub description: Option<Option<String>>,
+}
+
+impl Album {
+    pub fn new(user: &User, studio: &Studio, name: String, description: Option<String>) -> Self {
+        let now = Utc::now().naive_utc();
+
+        Self {
+            id: Uuid::new_v4(),
+            user_id: user.id.clone(),
+            studio_id: studio.id,
+            slug: slugify(&name),
+            name,
+            description,
+            created_at: now,
+            updated_at: now,
+            deleted: false,
+            parent_id: None,
+            password_hash: None,
+        }
+    }
+
+    /// Inserts the album, making sure its slug is not taken by other album of the same studio.
+    pub fn insert(&self, conn: &Conn) -> Result<Album> {
+        let album: Album = conn
+            .transaction(|| {
+                use crate::schema::albums::dsl::*;
+
+                let mut new_album = self.clone();
+                new_album.slug = Album::available_slug(conn, &new_album, &new_album.name)?;
+                AlbumSlug::release(conn, &new_album.studio_id, &new_album.slug)?;
+
+                diesel::insert_into(albums)
+                    .values(&new_album)
+                    .execute(conn)?;
+
+                albums.filter(id.eq(new_album.id)).first(conn)
+            })
+            .context(Query)?;
+
+        Ok(album)
+    }
+
+    /// Updates the album. When the name changes, a new slug is generated and the previous one
+    /// is kept in the slug history, so old links keep resolving to this album.
+    pub fn update(&self, conn: &Conn, name: String, description: Option<String>) -> Result<Album> {
+        let album: Album = conn
+            .transaction(|| {
+                let new_slug = self.rename_slug(conn, &name)?;
+                let updated = self.prepare_update(name, description, new_slug);
+
+                {
+                    use crate::schema::albums::dsl::*;
+
+                    diesel::update(albums)
+                        .filter(id.eq(self.id))
+                        .set(updated)
+                        .execute(conn)?;
+
+                    albums.filter(id.eq(self.id)).first(conn)
+                }
+            })
+            .context(Query)?;
+
+        Ok(album)
+    }
+
+    /// Changes only the given fields of the album. Fails with `PreconditionFailed` when the album
+    /// was modified since the version the client expects.
+    pub fn patch(
+        &self,
+        conn: &Conn,
+        changes: &AlbumChanges,
+        precondition: Option<&Precondition>,
+    ) -> Result<Album> {
+        let album: Option<Album> = conn
+            .transaction(|| {
+                use crate::schema::albums::dsl::*;
+
+                let current: Album = albums.filter(id.eq(self.id)).first(conn)?;
+                if let Some(precondition) = precondition {
+                    if !precondition.holds(&current.updated_at) {
+                        return Ok(None);
+                    }
+                }
+
+                let new_slug = match &changes.name {
+                    Some(new_name) => current.rename_slug(conn, new_name)?,
+                    None => current.slug.clone(),
+                };
+
+                diesel::update(albums.filter(id.eq(self.id)))
+                    .set((
+                        changes,
+                        slug.eq(new_slug),
+                        updated_at.eq(Utc::now().naive_utc()),
+                    ))
+                    .execute(conn)?;
+
+                albums.filter(id.eq(self.id)).first(conn).map(Some)
+            })
+            .context(Query)?;
+
+        album.context(PreconditionFailed)
+    }
+
+    /// Slug the album gets when renamed, the current one is kept in its slug history so old links
+    /// still work.
+    fn rename_slug(&self, conn: &Conn, new_name: &str) -> QueryResult<String> {
+        if new_name == self.name {
+            return Ok(self.slug.clone());
+        }
+
+        let new_slug = Album::available_slug(conn, self, new_name)?;
+        if new_slug != self.slug {
+            AlbumSlug::release(conn, &self.studio_id, &new_slug)?;
+            AlbumSlug::new(self).insert(conn)?;
+        }
+
+        Ok(new_slug)
+    }
+
+    pub fn etag(&self) -> String {
+        etag(&self.updated_at)
+    }
+
+    pub fn delete(&self, conn: &Conn) -> Result<()> {
+        use crate::schema::albums::dsl::*;
+
+        conn.execute("PRAGMA foreign_keys = ON").context(Query)?;
+
+        diesel::delete(albums.filter(id.eq(self.id)))
+            .execute(conn)
+            .context(Query)?;
+
+        Ok(())
+    }
+
+    pub fn find_by_id(conn: &Conn, a_id: &str) -> Result<Album> {
+        let album: Album = {
+            use crate::schema::albums::dsl::*;
+
+            albums.filter(id.eq(a_id)).first(conn).context(Query)?
+        };
+
+        Ok(album)
+    }
+
+    /// Finds an album of a studio the user is a member of. Fails with `AlbumNotFound` when it does
+    /// not exist, was deleted or belongs to another studio, and with `Forbidden` when the role of
+    /// the user is lower than `role`.
+    pub fn find_for_member(conn: &Conn, user: &User, a_id: &str, role: Role) -> Result<Album> {
+        use crate::schema::albums::dsl::*;
+
+        let album: Album = albums
+            .filter(deleted.eq(false))
+            .filter(id.eq(a_id))
+            .first(conn)
+            .optional()
+            .context(Query)?
+            .context(AlbumNotFound)?;
+
+        match Studio::role_of(conn, &album.studio_id, user)? {
+            None => AlbumNotFound.fail(),
+            Some(r) if r < role => Forbidden.fail(),
+            Some(_) => Ok(album),
+        }
+    }
+
+    /// Finds an album of the personal studio of the user by its current slug or, if the slug was
+    /// renamed, by its slug history. Fails with `AlbumNotFound` when no album has the slug or the
+    /// album was deleted.
+    pub fn find_by_slug(conn: &Conn, user: &User, a_slug: &str) -> Result<Album> {
+        let s_id = Studio::personal_id(conn, user)?.context(AlbumNotFound)?;
+
+        let album: Album = {
+            use crate::schema::albums::dsl::*;
+
+            let current = albums
+                .filter(studio_id.eq(s_id))
+                .filter(slug.eq(a_slug))
+                .filter(deleted.eq(false))
+                .first(conn)
+                .optional()
+                .context(Query)?;
+
+            match current {
+                Some(album) => album,
+                None => {
+                    let previous = AlbumSlug::find_by_slug(conn, &s_id, a_slug)?;
+
+                    albums
+                        .filter(id.eq(previous.album_id))
+                        .filter(deleted.eq(false))
+                        .first(conn)
+                        .optional()
+                        .context(Query)?
+                        .context(AlbumNotFound)?
+                }
+            }
+        };
+
+        Ok(album)
+    }
+
+    /// Find all albums of the studios of a user along with their cover photo, photo counts and the
+    /// last time the album or any of its photos was updated, in a single query regardless of the
+    /// number of albums.
+    pub fn find_all(conn: &Conn, user: &User) -> Result<Vec<AlbumSummary>> {
+        Album::find_summaries(conn, user, false, true, None)
+    }
+
+    /// Albums of the personal studio of the user directly inside `parent`, or its root albums when
+    /// there is no parent, for the public collections. Albums of their other studios are left out.
+    /// Like in the tree, albums whose parent is deleted are roots.
+    pub fn find_children(
+        conn: &Conn,
+        user: &User,
+        parent: Option<&Album>,
+    ) -> Result<Vec<AlbumSummary>> {
+        Album::find_summaries(conn, user, true, false, parent.map(|p| p.id))
+    }
+
+    /// Find all albums of the studios of a user arranged as a tree. Albums whose parent is deleted
+    /// are returned as roots.
+    pub fn find_tree(conn: &Conn, user: &User) -> Result<Vec<AlbumNode>> {
+        let summaries = Album::find_all(conn, user)?;
+        let ids: HashSet<Uuid> = summaries.iter().map(|s| s.album.id).collect();
+
+        let mut by_parent: HashMap<Option<Uuid>, Vec<AlbumSummary>> = HashMap::new();
+        for summary in summaries.into_iter() {
+            let parent = summary.album.parent_id.filter(|p| ids.contains(p));
+            by_parent
+                .entry(parent)
+                .or_insert_with(Vec::new)
+                .push(summary);
+        }
+
+        Ok(build_tree(&mut by_parent, None))
+    }
+
+    /// Summaries of the albums of the studios of the user, or with `public_only` of the albums of
+    /// their personal studio. Only the ones directly inside `parent` unless `all` is set, where the albums
+    /// whose parent is deleted or out of reach count as being at the root.
+    fn find_summaries(
+        conn: &Conn,
+        user: &User,
+        public_only: bool,
+        all: bool,
+        parent: Option<Uuid>,
+    ) -> Result<Vec<AlbumSummary>> {
+        let scope = |table: &str| {
+            if public_only {
+                format!(
+                    "{}.studio_id = (SELECT u.personal_studio_id FROM users u WHERE u.id = ?)",
+                    table
+                )
+            } else {
+                format!(
+                    "{}.studio_id IN (SELECT m.studio_id FROM studio_members m WHERE m.user_id = ?)",
+                    table
+                )
+            }
+        };
+        let rows: Vec<AlbumSummaryRow> = diesel::sql_query(format!(
+            r#"
+            SELECT a.*,
+              COUNT(p.id) AS photos_count,
+              COALESCE(SUM(CASE WHEN p.is_favorite THEN 1 ELSE 0 END), 0) AS favorites_count,
+              MAX(a.updated_at, COALESCE(MAX(p.updated_at), a.updated_at)) AS last_updated_at,
+              (
+                SELECT f.id FROM photos f
+                WHERE f.album_id = a.id AND f.deleted = 0
+                ORDER BY f.index_in_album ASC, f.created_at ASC
+                LIMIT 1
+              ) AS cover_id
+            FROM albums a
+            LEFT JOIN photos p ON p.album_id = a.id AND p.deleted = 0
+            WHERE {} AND a.deleted = 0 AND (
+              ? OR a.parent_id IS ? OR (? IS NULL AND NOT EXISTS (
+                SELECT 1 FROM albums pa
+                WHERE pa.id = a.parent_id AND pa.deleted = 0 AND {}
+              ))
+            )
+            GROUP BY a.id
+            ORDER BY a.created_at ASC
+            "#,
+            scope("a"),
+            scope("pa")
+        ))
+        .bind::<Text, _>(user.id)
+        .bind::<Bool, _>(all)
+        .bind::<Nullable<Text>, _>(parent)
+        .bind::<Nullable<Text>, _>(parent)
+        .bind::<Text, _>(user.id)
+        .load(conn)
+        .context(Query)?;
+
+        // Covers are loaded as typed photos rather than joined, so new photo columns can't be missed.
+        let cover_ids: Vec<Uuid> = rows.iter().filter_map(|row| row.cover_id).collect();
+        let mut covers: HashMap<Uuid, Photo> = photos::table
+            .filter(photos::id.eq_any(cover_ids))
+            .load::<Photo>(conn)
+            .context(Query)?
+            .into_iter()
+            .map(|photo| (photo.id, photo))
+            .collect();
+
+        let data = rows
+            .into_iter()
+            .map(|row| AlbumSummary {
+                cover: row.cover_id.and_then(|c_id| covers.remove(&c_id)),
+                album: row.album,
+                photos_count: row.photos_count,
+                favorites_count: row.favorites_count,
+                last_updated_at: row.last_updated_at,
+            })
+            .collect();
+
+        Ok(data)
+    }
+
+    /// Protects the album with a password, or removes the protection when there is none.
+    pub fn set_password(&self, conn: &Conn, password: Option<&str>) -> Result<Album> {
+        let hash = match password {
+            Some(p) => Some(hash_password(p).context(PasswordHash)?),
+            None => None,
+        };
+
+        let album: Album = {
+            use crate::schema::albums::dsl::*;
+
+            let now = Utc::now().naive_utc();
+
+            diesel::update(albums)
+                .filter(id.eq(self.id))
+                .set((password_hash.eq(hash), updated_at.eq(now)))
+                .execute(conn)
+                .context(Query)?;
+
+            albums.filter(id.eq(self.id)).first(conn).context(Query)?
+        };
+
+        Ok(album)
+    }
+
+    pub fn is_protected(&self) -> bool {
+        self.password_hash.is_some()
+    }
+
+    pub fn verify_password(&self, password: &str) -> bool {
+        match &self.password_hash {
+            Some(hash) => verify_password(hash, password),
+            None => false,
+        }
+    }
+
+    /// Moves the album inside `parent`, or to the root when there is no parent. Fails when the
+    /// parent belongs to another studio or when the move would create a cycle. The ancestors are
+    /// checked in the same transaction as the move, so concurrent moves can't create a cycle.
+    pub fn move_to(&self, conn: &Conn, parent: Option<&Album>) -> Result<Album> {
+        conn.transaction(|| {
+            use crate::schema::albums::dsl::*;
+
+            if let Some(parent) = parent {
+                if parent.studio_id != self.studio_id {
+                    return Err(ModelError::InvalidParent);
+                }
+
+                let mut ancestor = Some(Album::find_by_id(conn, &parent.id.to_string())?);
+                while let Some(current) = ancestor {
+                    if current.id == self.id {
+                        return Err(ModelError::AlbumCycle);
+                    }
+
+                    ancestor = match current.parent_id {
+                        Some(p_id) => Some(Album::find_by_id(conn, &p_id.to_string())?),
+                        None => None,
+                    };
+                }
+            }
+
+            let now = Utc::now().naive_utc();
+
+            diesel::update(albums)
+                .filter(id.eq(self.id))
+                .set((parent_id.eq(parent.map(|p| p.id)), updated_at.eq(now)))
+                .execute(conn)
+                .context(Query)?;
+
+            albums.filter(id.eq(self.id)).first(conn).context(Query)
+        })
+    }
+
+    pub fn find_main_public(conn: &Conn, user: &User) -> Result<Album> {
+        // TODO: Implement public & main album functionality. For now it'll return the first album.
+        let s_id = Studio::personal_id(conn, user)?.context(AlbumNotFound)?;
+
+        let album: Album = {
+            use crate::schema::albums::dsl::*;
+
+            albums
+                .filter(deleted.eq(false))
+                .filter(studio_id.eq(s_id))
+                .first(conn)
+                .context(Query)?
+        };
+
+        Ok(album)
+    }
+
+    /// A page of the photos of the album, only the ones labeled with `tag` when there is one.
+    pub fn photo_page(
+        &self,
+        conn: &Conn,
+        tag: Option<&str>,
+        pagination: &PhotoPagination,
+    ) -> Result<PhotoPage> {
+        let tag_slug = tag.map(slugify);
+
+        PhotoPage::load(
+            conn,
+            || {
+                let mut query = photos::table
+                    .filter(photos::deleted.eq(false))
+                    .filter(photos::album_id.eq(self.id))
+                    .into_boxed();
+
+                // The tags are matched in a subquery, so the page and the count only see the
+                // tagged photos.
+                if let Some(tag_slug) = &tag_slug {
+                    let tagged = photo_tags::table
+                        .inner_join(tags::table)
+                        .filter(tags::studio_id.eq(self.studio_id))
+                        .filter(tags::slug.eq(tag_slug.clone()))
+                        .select(photo_tags::photo_id);
+
+                    query = query.filter(photos::id.eq_any(tagged));
+                }
+
+                query
+            },
+            pagination,
+        )
+    }
+
+    pub fn photos(&self, conn: &Conn) -> Result<Vec<Photo>> {
+        use crate::schema::photos::dsl::*;
+
+        let results: Vec<Photo> = photos
+            .filter(deleted.eq(false))
+            .filter(album_id.eq(self.id))
+            .order((index_in_album.asc(), created_at.asc(), id.asc()))
+            .load::<Photo>(conn)
+            .context(Query)?;
+
+        Ok(results)
+    }
+
+    /// Generates a slug from the given name that is not used by any other album of the studio,
+    /// appending a numeric suffix when needed.
+    pub fn available_slug(conn: &Conn, album: &Album, a_name: &str) -> QueryResult<String> {
+        use crate::schema::albums::dsl::*;
+
+        let base = match slugify(a_name) {
+            s if s.is_empty() => String::from("album"),
+            s => s,
+        };
+
+        let mut candidate = base.clone();
+        let mut suffix = 1;
+
+        loop {
+            let taken: i64 = albums
+                .filter(studio_id.eq(album.studio_id))
+                .filter(slug.eq(&candidate))
+                .filter(id.ne(album.id))
+                .count()
+                .get_result(conn)?;
+
+            if taken == 0 {
+                return Ok(candidate);
+            }
+
+            suffix += 1;
+            candidate = format!("{}-{}", base, suffix);
+        }
+    }
+
+    fn prepare_update(
+        &self,
+        name: String,
+        description: Option<String>,
+        slug: String,
+    ) -> UpdateAlbum {
+        let now = Utc::now().naive_utc();
+
+        UpdateAlbum {
+            name,
+            description,
+            slug,
+            updated_at: now,
+        }
+    }
+}
+
+fn build_tree(
+    by_parent: &mut HashMap<Option<Uuid>, Vec<AlbumSummary>>,
+    parent: Option<Uuid>,
+) -> Vec<AlbumNode> {
+    let summaries = by_parent.remove(&parent).unwrap_or_default();
+
+    summaries
+        .into_iter()
+        .map(|summary| {
+            let children = build_tree(by_parent, Some(summary.album.id));
+
+            AlbumNode { summary, children }
+        })
+        .collect()
+}
+
+/// Previous slug of an album, kept so renamed albums can still be found by their old links.
+#[derive(
+    Serialize,
+    Deserialize,
+    Debug,
+    PartialEq,
+    Clone,
+    Insertable,
+    Identifiable,
+    Associations,
+    Queryable,
+)]
+#[table_name = "album_slugs"]
+#[belongs_to(Album)]
+#[belongs_to(Studio)]
+#[serde(rename_all = "camelCase")]
+pub struct AlbumSlug {
+    pub id: Uuid,
+    pub album_id: Uuid,
+    pub studio_id: Uuid,
+    pub slug: String,
+    #[serde(with = "ts_seconds")]
+    pub created_at: NaiveDateTime,
+}
+
+impl AlbumSlug {
+    pub fn new(album: &Album) -> Self {
+        let now = Utc::now().naive_utc();
+
+        Self {
+            id: Uuid::new_v4(),
+            album_id: album.id,
+            studio_id: album.studio_id,
+            slug: album.slug.clone(),
+            created_at: now,
+        }
+    }
+
+    pub fn insert(&self, conn: &Conn) -> QueryResult<AlbumSlug> {
+        use crate::schema::album_slugs::dsl::*;
+
+        AlbumSlug::release(conn, &self.studio_id, &self.slug)?;
+
+        diesel::insert_into(album_slugs)
+            .values(self)
+            .execute(conn)?;
+
+        album_slugs.filter(id.eq(self.id)).first(conn)
+    }
+
+    pub fn find_by_slug(conn: &Conn, s_id: &Uuid, a_slug: &str) -> Result<AlbumSlug> {
+        use crate::schema::album_slugs::dsl::*;
+
+        let previous = album_slugs
+            .filter(studio_id.eq(s_id))
+            .filter(slug.eq(a_slug))
+            .first(conn)
+            .optional()
+            .context(Query)?
+            .context(AlbumNotFound)?;
+
+        Ok(previous)
+    }
+
+    /// Removes a slug from the history, used when an album takes it as its current slug.
+    pub fn release(conn: &Conn, s_id: &Uuid, a_slug: &str) -> QueryResult<usize> {
+        use crate::schema::album_slugs::dsl::*;
+
+        diesel::delete(
+            album_slugs
+                .filter(studio_id.eq(s_id))
+                .filter(slug.eq(a_slug)),
+        )
+        .execute(conn)
+    }
+}
+
+#[derive(
+    Serialize,
+    Deserialize,
+    Debug,
+    PartialEq,
+    Clone,
+    Insertable,
+    Identifiable,
+    Associations,
+    Queryable,
+    QueryableByName,
+)]
+#[table_name = "photos"]
+#[belongs_to(Album)]
+#[belongs_to(User)]
+#[serde(rename_all = "camelCase")]
+pub struct Photo {
+    pub id: Uuid,
+    pub album_id: Uuid,
+    pub user_id: Uuid,
+    pub index_in_album: i32,
+    pub s3_id: String,
+    pub src: String,
+    pub main_color: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub width: i32,
+    pub height: i32,
+    pub is_favorite: bool,
+    #[serde(with = "ts_seconds")]
+    pub created_at: NaiveDateTime,
+    #[serde(with = "ts_seconds")]
+    pub updated_at: NaiveDateTime,
+    pub deleted: bool,
+    /// Name of the file as it was uploaded.
+    pub filename: Option<String>,
+    /// When the photo was captured, usually read from its EXIF data.
+    #[serde(with = "ts_seconds_option")]
+    pub taken_at: Option<NaiveDateTime>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, AsChangeset)]
+#[table_name = "photos"]
+struct UpdatePhoto {
+    pub index_in_album: i32,
+    pub is_favorite: bool,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    #[serde(with = "ts_seconds")]
+    pub updated_at: NaiveDateTime,
+}
+
+/// Fields of a photo to change, the ones left as `None` are kept as they are.
+#[derive(Debug, Clone, Default, AsChangeset)]
+#[table_name = "photos"]
+pub struct PhotoChanges {
+    pub index_in_album: Option<i32>,
+    pub is_favorite: Option<bool>,
+    /// `Some(None)` removes the title.
+    pub title: Option<Option<String>>,
+    /// `Some(None)` removes the description.
+    pub description: Option<Option<String>>,
+    pub taken_at: Option<Option<NaiveDateTime>>,
+}
+
+impl Photo {
+    pub fn new(
+        album: &Album,
+        user: &User,
+        index_in_album: i32,
+        s3_id: String,
+        src: String,
+        main_color: String,
+        title: Option<String>,
+        description: Option<String>,
+        width: i32,
+        height: i32,
+        is_favorite: bool,
+        filename: Option<String>,
+        taken_at: Option<NaiveDateTime>,
+    ) -> Self {
+        let now = Utc::now().naive_utc();
+
+        Self {
+            id: Uuid::new_v4(),
+            album_id: album.id.clone(),
+            user_id: user.id.clone(),
+            index_in_album,
+            s3_id,
+            src: src.clone(),
+            main_color,
+            title,
+            description,
+            width,
+            height,
+            is_favorite,
+            created_at: now,
+            updated_at: now,
+            deleted: false,
+            filename,
+            taken_at,
+        }
+    }
+
+    pub fn insert(&self, conn: &Conn) -> Result<Photo> {
+        let photo: Photo = {
+            use crate::schema::photos::dsl::*;
+
+            diesel::insert_into(photos)
+                .values(self)
+                .execute(conn)
+                .context(Query)?;
+
+            photos.order(created_at.desc()).first(conn).context(Query)?
+        };
+
+        Ok(photo)
+    }
+
+    /// Page of the favorite photos across the albums of the studios of the user, along with the
+    /// albums the photos of the page belong to. With `public_only`, only the photos of the albums of
+    /// their personal studio that are not password protected are returned.
+    pub fn find_favorites(
+        conn: &Conn,
+        user: &User,
+        public_only: bool,
+        pagination: &PhotoPagination,
+    ) -> Result<(PhotoPage, Vec<Album>)> {
+        let studio_ids = if public_only {
+            Studio::personal_id(conn, user)?.into_iter().collect()
+        } else {
+            Studio::ids_for_member(conn, user, Role::Viewer)?
+        };
+
+        let page = PhotoPage::load(
+            conn,
+            || {
+                let mut visible = albums::table
+                    .filter(albums::deleted.eq(false))
+                    .filter(albums::studio_id.eq_any(studio_ids.clone()))
+                    .select(albums::id)
+                    .into_boxed();
+                if public_only {
+                    visible = visible.filter(albums::password_hash.is_null());
+                }
+
+                photos::table
+                    .filter(photos::is_favorite.eq(true))
+                    .filter(photos::deleted.eq(false))
+                    .filter(photos::album_id.eq_any(visible))
+                    .into_boxed()
+            },
+            pagination,
+        )?;
+
+        let album_ids: Vec<Uuid> = page.list.iter().map(|photo| photo.album_id).collect();
+        let found = albums::table
+            .filter(albums::id.eq_any(album_ids))
+            .load::<Album>(conn)
+            .context(Query)?;
+
+        Ok((page, found))
+    }
+
+    pub fn update(
+        &self,
+        conn: &Conn,
+        index_in_album: i32,
+        is_favorite: bool,
+        title: Option<String>,
+        description: Option<String>,
+    ) -> Result<Photo> {
+        let updated = self.prepare_update(index_in_album, is_favorite, title, description);
+        let photo: Photo = {
+            use crate::schema::photos::dsl::*;
+
+            diesel::update(photos)
+                .filter(id.eq(self.id))
+                .set(updated)
+                .execute(conn)
+                .context(Query)?;
+
+            photos.filter(id.eq(self.id)).first(conn).context(Query)?
+        };
+
+        Ok(photo)
+    }
+
+    /// Applies the operation to the photos of the studios the user edits in a single transaction.
+    /// Ids of photos that do not exist, were deleted or belong to other studios are reported as not
+    /// found and left untouched. Photos are only moved between albums of the same studio, since their
+    /// tags belong to it. Fails with `TooManyPhotos` above `MAX_BULK_PHOTOS` ids.
+    pub fn bulk(
+        conn: &Conn,
+        user: &User,
+        p_ids: &[String],
+        operation: &PhotoOperation,
+    ) -> Result<BulkOutcome> {
+        if p_ids.len() > MAX_BULK_PHOTOS {
+            return TooManyPhotos.fail();
+        }
+
+        if let PhotoOperation::Delete = operation {
+            conn.execute("PRAGMA foreign_keys = ON").context(Query)?;
+        }
+
+        let mut studio_ids = Studio::ids_for_member(conn, user, Role::Editor)?;
+        if let PhotoOperation::MoveTo(album) = operation {
+            studio_ids.retain(|s_id| *s_id == album.studio_id);
+        }
+        let album_ids = albums::table
+            .filter(albums::studio_id.eq_any(studio_ids))
+            .select(albums::id);
+
+        conn.transaction(|| {
+            use crate::schema::photos::dsl::*;
+
+            let found: Vec<Photo> = photos
+                .filter(album_id.eq_any(album_ids))
+                .filter(deleted.eq(false))
+                .filter(id.eq_any(p_ids))
+                .order((index_in_album.asc(), created_at.asc()))
+                .load(conn)?;
+            let found_ids: Vec<Uuid> = found.iter().map(|p| p.id).collect();
+            let now = Utc::now().naive_utc();
+            let mut unused_objects = Vec::new();
+
+            match operation {
+                PhotoOperation::Delete => {
+                    let previous: Vec<String> = photo_versions::table
+                        .filter(photo_versions::photo_id.eq_any(&found_ids))
+                        .select(photo_versions::s3_id)
+                        .load(conn)?;
+
+                    diesel::delete(photos.filter(id.eq_any(&found_ids))).execute(conn)?;
+
+                    unused_objects = found.iter().map(|p| p.s3_id.clone()).collect();
+                    unused_objects.extend(previous);
+                }
+                PhotoOperation::SetFavorite(favorite) => {
+                    diesel::update(photos.filter(id.eq_any(&found_ids)))
+                        .set((is_favorite.eq(favorite), updated_at.eq(now)))
+                        .execute(conn)?;
+                }
+                PhotoOperation::MoveTo(album) => {
+                    let last: Option<i32> = photos
+                        .filter(album_id.eq(album.id))
+                        .filter(deleted.eq(false))
+                        .select(diesel::dsl::max(index_in_album))
+                        .first(conn)?;
+                    let mut next = last.map_or(0, |l| l + 1);
+
+                    for photo in found.iter().filter(|p| p.album_id != album.id) {
+                        diesel::update(photos.filter(id.eq(photo.id)))
+                            .set((
+                                album_id.eq(album.id),
+                                index_in_album.eq(next),
+                                updated_at.eq(now),
+                            ))
+                            .execute(conn)?;
+                        next += 1;
+                    }
+                }
+                PhotoOperation::AddTag(t_name) => {
+                    Tag::add_to_photos(conn, user, &[t_name.clone()], &found)?
+                }
+                PhotoOperation::SetDescription(value) => {
+                    diesel::update(photos.filter(id.eq_any(&found_ids)))
+                        .set((description.eq(value), updated_at.eq(now)))
+                        .execute(conn)?;
+                }
+            }
+
+            let results = p_ids
+                .iter()
+                .map(|p_id| BulkResult {
+                    photo_id: p_id.clone(),
+                    status: if found.iter().any(|p| p.id.to_string() == *p_id) {
+                        BulkStatus::Done
+                    } else {
+                        BulkStatus::NotFound
+                    },
+                })
+                .collect();
+
+            let updated: Vec<Photo> = photos.filter(id.eq_any(&found_ids)).load(conn)?;
+            let changes = found
+                .into_iter()
+                .map(|before| {
+                    let after = updated.iter().find(|p| p.id == before.id).cloned();
+                    (before, after)
+                })
+                .collect();
+
+            Ok(BulkOutcome {
+                results,
+                unused_objects,
+                changes,
+            })
+        })
+    }
+
+    /// Swaps the image of the photo while keeping everything else. The current image is kept in
+    /// the history of the photo.
+    pub fn replace_image(&self, conn: &Conn, image: &PhotoImage) -> Result<Photo> {
+        let photo = conn
+            .transaction(|| {
+                PhotoVersion::new(self).insert(conn)?;
+                self.set_image(conn, image)
+            })
+            .context(Query)?;
+
+        Ok(photo)
+    }
+
+    /// Brings back a previous image of the photo. The image it replaces goes to the history, so
+    /// reverting can be undone as well.
+    pub fn revert(&self, conn: &Conn, version: &PhotoVersion) -> Result<Photo> {
+        let photo = conn
+            .transaction(|| {
+                PhotoVersion::new(self).insert(conn)?;
+                diesel::delete(version).execute(conn)?;
+                self.set_image(conn, &version.image())
+            })
+            .context(Query)?;
+
+        Ok(photo)
+    }
+
+    fn set_image(&self, conn: &Conn, image: &PhotoImage) -> QueryResult<Photo> {
+        use crate::schema::photos::dsl::*;
+
+        diesel::update(photos.filter(id.eq(self.id)))
+            .set((image, updated_at.eq(Utc::now().naive_utc())))
+            .execute(conn)?;
+
+        photos.filter(id.eq(self.id)).first(conn)
+    }
+
+    /// Changes only the given fields of the photo. Fails with `PreconditionFailed` when the photo
+    /// was modified since the version the client expects.
+    pub fn patch(
+        &self,
+        conn: &Conn,
+        changes: &PhotoChanges,
+        precondition: Option<&Precondition>,
+    ) -> Result<Photo> {
+        let photo: Option<Photo> = conn
+            .transaction(|| {
+                use crate::schema::photos::dsl::*;
+
+                let current: Photo = photos.filter(id.eq(self.id)).first(conn)?;
+                if let Some(precondition) = precondition {
+                    if !precondition.holds(&current.updated_at) {
+                        return Ok(None);
+                    }
+                }
+
+                diesel::update(photos.filter(id.eq(self.id)))
+                    .set((changes, updated_at.eq(Utc::now().naive_utc())))
+                    .execute(conn)?;
+
+                photos.filter(id.eq(self.id)).first(conn).map(Some)
+            })
+            .context(Query)?;
+
+        photo.context(PreconditionFailed)
+    }
+
+    pub fn etag(&self) -> String {
+        etag(&self.updated_at)
+    }
+
+    /// Deletes the photo together with its previous images. Files on S3 are left to the caller.
     pub fn delete(&self, conn: &Conn) -> Result<()> {
         use crate::schema::photos::dsl::*;
-        conn.execute("PRAGMA foreign_keys = ON").context(Query)?;
+        conn.execute("PRAGMA foreign_keys = ON").context(Query)?;
+
+        conn.transaction(|| {
+            diesel::delete(PhotoVersion::belonging_to(self)).execute(conn)?;
+            diesel::delete(photos.filter(id.eq(self.id))).execute(conn)
+        })
+        .context(Query)?;
+
+        Ok(())
+    }
+
+    pub fn find_by_id(conn: &Conn, p_id: &str) -> Result<Photo> {
+        use crate::schema::photos::dsl::*;
+
+        let photo = photos.filter(id.eq(p_id)).first(conn).context(Query)?;
+
+        Ok(photo)
+    }
+
+    /// Finds a photo of a studio the user is a member of. Fails with `PhotoNotFound` when it does
+    /// not exist, was deleted or belongs to another studio, and with `Forbidden` when the role of
+    /// the user is lower than `role`.
+    pub fn find_for_member(conn: &Conn, user: &User, p_id: &str, role: Role) -> Result<Photo> {
+        let found: Option<(Photo, Album)> = photos::table
+            .inner_join(albums::table)
+            .filter(photos::deleted.eq(false))
+            .filter(photos::id.eq(p_id))
+            .first(conn)
+            .optional()
+            .context(Query)?;
+        let (photo, album) = found.context(PhotoNotFound)?;
+
+        match Studio::role_of(conn, &album.studio_id, user)? {
+            None => PhotoNotFound.fail(),
+            Some(r) if r < role => Forbidden.fail(),
+            Some(_) => Ok(photo),
+        }
+    }
+
+    /// Finds photos of the studios the user is a member of by id. Fails with `PhotoNotFound` when
+    /// any of them does not exist, was deleted or belongs to another studio, and with `Forbidden`
+    /// when the role of the user is lower than `role` for any of them.
+    pub fn find_all_for_member(
+        conn: &Conn,
+        user: &User,
+        p_ids: &[String],
+        role: Role,
+    ) -> Result<Vec<Photo>> {
+        let unique: HashSet<&String> = p_ids.iter().collect();
+        let studio_ids = Studio::ids_for_member(conn, user, Role::Viewer)?;
+        let results: Vec<(Photo, Album)> = photos::table
+            .inner_join(albums::table)
+            .filter(albums::studio_id.eq_any(studio_ids))
+            .filter(photos::deleted.eq(false))
+            .filter(photos::id.eq_any(p_ids))
+            .load(conn)
+            .context(Query)?;
+
+        if results.len() != unique.len() {
+            return Err(ModelError::PhotoNotFound);
+        }
+
+        let allowed: HashSet<Uuid> = Studio::ids_for_member(conn, user, role)?
+            .into_iter()
+            .collect();
+        if results
+            .iter()
+            .any(|(_, album)| !allowed.contains(&album.studio_id))
+        {
+            return Err(ModelError::Forbidden);
+        }
+
+        Ok(results.into_iter().map(|(photo, _)| photo).collect())
+    }
+
+    /// Finds a photo that is part of the given album. Fails with `PhotoNotInAlbum` when the photo
+    /// does not exist, was deleted or belongs to another album.
+    pub fn find_in_album(conn: &Conn, album: &Album, p_id: &str) -> Result<Photo> {
+        use crate::schema::photos::dsl::*;
+
+        let photo = photos
+            .filter(id.eq(p_id))
+            .filter(album_id.eq(album.id))
+            .filter(deleted.eq(false))
+            .first(conn)
+            .optional()
+            .context(Query)?
+            .context(PhotoNotInAlbum)?;
+
+        Ok(photo)
+    }
+
+    fn prepare_update(
+        &self,
+        index_in_album: i32,
+        is_favorite: bool,
+        title: Option<String>,
+        description: Option<String>,
+    ) -> UpdatePhoto {
+        let now = Utc::now().naive_utc();
+
+        UpdatePhoto {
+            index_in_album,
+            is_favorite,
+            title,
+            description,
+            updated_at: now,
+        }
+    }
+}
+
+/// Orders in which the photos of an album can be listed.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum PhotoSort {
+    /// Position in the album, as arranged by its owner.
+    Index,
+    /// Capture date, photos without one go last.
+    TakenAt,
+    /// Most recently uploaded first.
+    CreatedAt,
+    /// Favorites first, then by position in the album.
+    Favorites,
+}
+
+impl Default for PhotoSort {
+    fn default() -> Self {
+        PhotoSort::Index
+    }
+}
+
+impl PhotoSort {
+    fn name(self) -> &'static str {
+        match self {
+            PhotoSort::Index => "index",
+            PhotoSort::TakenAt => "takenAt",
+            PhotoSort::CreatedAt => "createdAt",
+            PhotoSort::Favorites => "favorites",
+        }
+    }
+}
+
+/// Which page of photos to return. Without a limit, every photo following the cursor is returned.
+#[derive(Debug, Clone, Default)]
+pub struct PhotoPagination {
+    pub sort: PhotoSort,
+    pub limit: Option<usize>,
+    pub cursor: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PhotoPage {
+    pub list: Vec<Photo>,
+    /// Number of photos across all the pages.
+    pub total: usize,
+    /// Cursor to get the following page, missing on the last one.
+    pub next_cursor: Option<String>,
+}
+
+/// Condition on the photos table, used to build queries from parts.
+type PhotoCondition = Box<dyn BoxableExpression<photos::table, Sqlite, SqlType = Bool>>;
+
+impl PhotoSort {
+    /// Orders the query, the id always comes last so no two photos share the same position.
+    fn order<'a>(self, query: photos::BoxedQuery<'a, Sqlite>) -> photos::BoxedQuery<'a, Sqlite> {
+        use crate::schema::photos::dsl::*;
+
+        match self {
+            PhotoSort::Index => query.order((index_in_album.asc(), created_at.asc(), id.asc())),
+            PhotoSort::TakenAt => query.order((
+                taken_at.is_null().asc(),
+                taken_at.asc(),
+                index_in_album.asc(),
+                id.asc(),
+            )),
+            PhotoSort::CreatedAt => query.order((created_at.desc(), id.asc())),
+            PhotoSort::Favorites => query.order((
+                is_favorite.desc(),
+                index_in_album.asc(),
+                created_at.asc(),
+                id.asc(),
+            )),
+        }
+    }
+}
+
+impl PhotoPage {
+    /// Loads the page of the photos returned by `query`, which is built twice: once to count all
+    /// the photos, once to get the page itself. The cursor holds the sort values of the last
+    /// photo of the previous page, so pages do not skip or repeat photos when others are added
+    /// or removed in between.
+    pub fn load<'a, Q>(conn: &Conn, query: Q, pagination: &PhotoPagination) -> Result<PhotoPage>
+    where
+        Q: Fn() -> photos::BoxedQuery<'a, Sqlite>,
+    {
+        let sort = pagination.sort;
+        let total: i64 = query().count().get_result(conn).context(Query)?;
+
+        let mut page = sort.order(query());
+        if let Some(cursor) = &pagination.cursor {
+            let after = PhotoCursor::decode(sort, cursor).context(InvalidCursor)?;
+            page = page.filter(after.following(sort));
+        }
+        if let Some(limit) = pagination.limit {
+            // One more than the limit tells whether there is a following page.
+            page = page.limit(limit as i64 + 1);
+        }
+
+        let mut photos: Vec<Photo> = page.load(conn).context(Query)?;
+
+        let next_cursor = match pagination.limit {
+            Some(limit) if photos.len() > limit => {
+                photos.truncate(limit);
+                photos
+                    .last()
+                    .map(|photo| PhotoCursor::from(photo).encode(sort))
+            }
+            _ => None,
+        };
+
+        Ok(PhotoPage {
+            list: photos,
+            total: total as usize,
+            next_cursor,
+        })
+    }
+}
+
+/// Sort values of the last photo of a page. Dates keep their nanoseconds, so they compare equal
+/// to the stored ones.
+#[derive(Debug, Clone)]
+struct PhotoCursor {
+    index_in_album: i32,
+    created_at: NaiveDateTime,
+    taken_at: Option<NaiveDateTime>,
+    is_favorite: bool,
+    id: String,
+}
+
+impl From<&Photo> for PhotoCursor {
+    fn from(photo: &Photo) -> Self {
+        PhotoCursor {
+            index_in_album: photo.index_in_album,
+            created_at: photo.created_at,
+            taken_at: photo.taken_at,
+            is_favorite: photo.is_favorite,
+            id: photo.id.to_string(),
+        }
+    }
+}
+
+impl PhotoCursor {
+    fn encode(&self, sort: PhotoSort) -> String {
+        format!(
+            "{}.{}.{}.{}.{}.{}",
+            sort.name(),
+            self.index_in_album,
+            encode_time(&self.created_at),
+            self.taken_at
+                .as_ref()
+                .map(encode_time)
+                .unwrap_or_else(|| String::from("-")),
+            u8::from(self.is_favorite),
+            self.id
+        )
+    }
+
+    fn decode(sort: PhotoSort, cursor: &str) -> Option<PhotoCursor> {
+        let mut parts = cursor.splitn(6, '.');
+
+        if parts.next()? != sort.name() {
+            return None;
+        }
+
+        let index_in_album = parts.next()?.parse().ok()?;
+        let created_at = decode_time(parts.next()?)?;
+        let taken_at = match parts.next()? {
+            "-" => None,
+            taken => Some(decode_time(taken)?),
+        };
+        let is_favorite = match parts.next()? {
+            "0" => false,
+            "1" => true,
+            _ => return None,
+        };
+        let id = parts.next()?.to_string();
+
+        Some(PhotoCursor {
+            index_in_album,
+            created_at,
+            taken_at,
+            is_favorite,
+            id,
+        })
+    }
+
+    /// Photos placed after this one in the given order.
+    fn following(&self, sort: PhotoSort) -> PhotoCondition {
+        use crate::schema::photos::dsl::*;
+
+        let after_id: PhotoCondition = Box::new(id.gt(self.id.clone()));
+
+        match sort {
+            PhotoSort::Index => after(
+                index_in_album.gt(self.index_in_album),
+                index_in_album.eq(self.index_in_album),
+                after(
+                    created_at.gt(self.created_at),
+                    created_at.eq(self.created_at),
+                    after_id,
+                ),
+            ),
+            PhotoSort::TakenAt => {
+                let same_index = after(
+                    index_in_album.gt(self.index_in_album),
+                    index_in_album.eq(self.index_in_album),
+                    after_id,
+                );
+
+                match self.taken_at {
+                    // Nullable comparisons can't be combined with `or`, hence the SQL.
+                    Some(taken) => Box::new(
+                        taken_at.is_null().or(sql::<Bool>("photos.taken_at > ")
+                            .bind::<Timestamp, _>(taken)
+                            .or(sql::<Bool>("photos.taken_at = ")
+                                .bind::<Timestamp, _>(taken)
+                                .and(same_index))),
+                    ),
+                    None => Box::new(taken_at.is_null().and(same_index)),
+                }
+            }
+            PhotoSort::CreatedAt => after(
+                created_at.lt(self.created_at),
+                created_at.eq(self.created_at),
+                after_id,
+            ),
+            PhotoSort::Favorites => after(
+                is_favorite.lt(self.is_favorite),
+                is_favorite.eq(self.is_favorite),
+                after(
+                    index_in_album.gt(self.index_in_album),
+                    index_in_album.eq(self.index_in_album),
+                    after(
+                        created_at.gt(self.created_at),
+                        created_at.eq(self.created_at),
+                        after_id,
+                    ),
+                ),
+            ),
+        }
+    }
+}
+
+/// Rows placed after the cursor on one column (`beyond`), or tied on it (`tied`) and placed after
+/// it on the following columns (`then`).
+fn after<B, T>(beyond: B, tied: T, then: PhotoCondition) -> PhotoCondition
+where
+    B: BoxableExpression<photos::table, Sqlite, SqlType = Bool> + 'static,
+    T: BoxableExpression<photos::table, Sqlite, SqlType = Bool> + 'static,
+{
+    Box::new(beyond.or(tied.and(then)))
+}
+
+fn encode_time(time: &NaiveDateTime) -> String {
+    format!("{}:{}", time.timestamp(), time.timestamp_subsec_nanos())
+}
+
+fn decode_time(value: &str) -> Option<NaiveDateTime> {
+    let mut parts = value.splitn(2, ':');
+    let secs = parts.next()?.parse().ok()?;
+    let nanos = parts.next()?.parse().ok()?;
+
+    NaiveDateTime::from_timestamp_opt(secs, nanos)
+}
+
+/// Largest number of photos a bulk operation accepts. Their ids are bound as SQL variables, which
+/// older SQLite builds limit to 500 per query, and they are all changed in a single transaction.
+pub const MAX_BULK_PHOTOS: usize = 200;
+
+/// Change applied to many photos at once.
+#[derive(Debug, Clone)]
+pub enum PhotoOperation {
+    Delete,
+    SetFavorite(bool),
+    /// Moves the photos to the end of the album. Photos of other studios are not found.
+    MoveTo(Album),
+    AddTag(String),
+    SetDescription(Option<String>),
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum BulkStatus {
+    Done,
+    NotFound,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkResult {
+    pub photo_id: String,
+    pub status: BulkStatus,
+}
+
+#[derive(Debug, Clone)]
+pub struct BulkOutcome {
+    pub results: Vec<BulkResult>,
+    /// Files of the deleted photos and of their previous versions, no longer used by any photo.
+    pub unused_objects: Vec<String>,
+    /// Photos the operation was applied to, as they were before and after it. Deleted photos have
+    /// nothing after.
+    pub changes: Vec<(Photo, Option<Photo>)>,
+}
+
+/// Image file of a photo along with the values derived from it.
+#[derive(Debug, Clone, AsChangeset)]
+#[table_name = "photos"]
+#[changeset_options(treat_none_as_null = "true")]
+pub struct PhotoImage {
+    pub s3_id: String,
+    pub src: String,
+    pub main_color: String,
+    pub width: i32,
+    pub height: i32,
+    pub filename: Option<String>,
+}
+
+/// Image a photo used before it was replaced.
+#[derive(
+    Serialize,
+    Deserialize,
+    Debug,
+    PartialEq,
+    Clone,
+    Insertable,
+    Identifiable,
+    Associations,
+    Queryable,
+)]
+#[table_name = "photo_versions"]
+#[belongs_to(Photo)]
+#[serde(rename_all = "camelCase")]
+pub struct PhotoVersion {
+    pub id: Uuid,
+    pub photo_id: Uuid,
+    pub s3_id: String,
+    pub src: String,
+    pub main_color: String,
+    pub width: i32,
+    pub height: i32,
+    pub filename: Option<String>,
+    /// When the image stopped being used by the photo.
+    #[serde(with = "ts_seconds")]
+    pub created_at: NaiveDateTime,
+}
+
+impl PhotoVersion {
+    /// Keeps the current image of the photo.
+    fn new(photo: &Photo) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            photo_id: photo.id,
+            s3_id: photo.s3_id.clone(),
+            src: photo.src.clone(),
+            main_color: photo.main_color.clone(),
+            width: photo.width,
+            height: photo.height,
+            filename: photo.filename.clone(),
+            created_at: Utc::now().naive_utc(),
+        }
+    }
+
+    fn insert(&self, conn: &Conn) -> QueryResult<usize> {
+        use crate::schema::photo_versions::dsl::*;
+
+        diesel::insert_into(photo_versions)
+            .values(self)
+            .execute(conn)
+    }
+
+    /// Previous images of the photo, the most recently replaced first.
+    pub fn find_by_photo(conn: &Conn, photo: &Photo) -> Result<Vec<PhotoVersion>> {
+        use crate::schema::photo_versions::dsl::*;
+
+        let versions = PhotoVersion::belonging_to(photo)
+            .order((created_at.desc(), id.asc()))
+            .load::<PhotoVersion>(conn)
+            .context(Query)?;
+
+        Ok(versions)
+    }
+
+    pub fn find_by_id(conn: &Conn, photo: &Photo, v_id: &str) -> Result<PhotoVersion> {
+        use crate::schema::photo_versions::dsl::*;
+
+        let version = PhotoVersion::belonging_to(photo)
+            .filter(id.eq(v_id))
+            .first::<PhotoVersion>(conn)
+            .optional()
+            .context(Query)?
+            .context(PhotoVersionNotFound)?;
+
+        Ok(version)
+    }
+
+    pub fn image(&self) -> PhotoImage {
+        PhotoImage {
+            s3_id: self.s3_id.clone(),
+            src: self.src.clone(),
+            main_color: self.main_color.clone(),
+            width: self.width,
+            height: self.height,
+            filename: self.filename.clone(),
+        }
+    }
+}
+
+/// Title, description and keywords read from an uploaded file, kept until the photo using the file
+/// is created.
+#[derive(
+    Serialize,
+    Deserialize,
+    Debug,
+    PartialEq,
+    Clone,
+    Insertable,
+    Identifiable,
+    Associations,
+    Queryable,
+)]
+#[table_name = "photo_uploads"]
+#[belongs_to(User)]
+#[serde(rename_all = "camelCase")]
+pub struct PhotoUpload {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub s3_id: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    /// JSON array of keywords.
+    keywords: String,
+    #[serde(with = "ts_seconds")]
+    pub created_at: NaiveDateTime,
+}
+
+impl PhotoUpload {
+    pub fn new(
+        user: &User,
+        s3_id: String,
+        title: Option<String>,
+        description: Option<String>,
+        keywords: &[String],
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            user_id: user.id,
+            s3_id,
+            title,
+            description,
+            keywords: serde_json::to_string(keywords).expect("Failed to serialize keywords"),
+            created_at: Utc::now().naive_utc(),
+        }
+    }
+
+    pub fn insert(&self, conn: &Conn) -> Result<PhotoUpload> {
+        use crate::schema::photo_uploads::dsl::*;
+
+        diesel::insert_into(photo_uploads)
+            .values(self)
+            .execute(conn)
+            .context(Query)?;
+
+        let upload = photo_uploads
+            .filter(id.eq(self.id))
+            .first(conn)
+            .context(Query)?;
+
+        Ok(upload)
+    }
+
+    /// Finds the metadata of an uploaded file and forgets about it, since it is only needed once.
+    pub fn take(conn: &Conn, user: &User, u_s3_id: &str) -> Result<Option<PhotoUpload>> {
+        use crate::schema::photo_uploads::dsl::*;
+
+        let upload: Option<PhotoUpload> = photo_uploads
+            .filter(user_id.eq(user.id))
+            .filter(s3_id.eq(u_s3_id))
+            .first(conn)
+            .optional()
+            .context(Query)?;
+
+        if let Some(found) = &upload {
+            diesel::delete(photo_uploads.filter(id.eq(found.id)))
+                .execute(conn)
+                .context(Query)?;
+        }
+
+        Ok(upload)
+    }
+
+    pub fn keywords(&self) -> Vec<String> {
+        serde_json::from_str(&self.keywords).unwrap_or_default()
+    }
+}
+
+#[derive(
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    AsChangeset,
+    Insertable,
+    Identifiable,
+    Associations,
+    Queryable,
+)]
+#[table_name = "book_me"]
+#[belongs_to(User)]
+#[serde(rename_all = "camelCase")]
+pub struct BookMe {
+    pub id: Uuid,
+    user_id: Uuid,
+    pub email: String,
+}
+
+impl BookMe {
+    pub fn new(email: String, user: &User) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            user_id: user.id,
+            email,
+        }
+    }
+
+    pub fn find_by_user(conn: &Conn, user: &User) -> Result<BookMe> {
+        use crate::schema::book_me::dsl::*;
+
+        let info = book_me
+            .filter(user_id.eq(user.id))
+            .first(conn)
+            .optional()
+            .context(Query)?
+            .context(BookMeNotFound)?;
+
+        Ok(info)
+    }
+
+    pub fn update_or_create(conn: &Conn, book_email: &str, user: &User) -> Result<BookMe> {
+        let existing: Result<BookMe> = BookMe::find_by_user(conn, user);
+
+        let book_me_info: BookMe = match existing {
+            Ok(book_me_info) => {
+                use crate::schema::book_me::dsl::*;
+
+                diesel::update(book_me)
+                    .filter(id.eq(book_me_info.id))
+                    .set(email.eq(book_email))
+                    .execute(conn)
+                    .context(Query)?;
+
+                book_me
+                    .filter(id.eq(book_me_info.id))
+                    .first(conn)
+                    .context(Query)?
+            }
+            Err(ModelError::BookMeNotFound) => {
+                use crate::schema::book_me::dsl::*;
+
+                let info = BookMe::new(String::from(book_email), user);
+
+                diesel::insert_into(book_me)
+                    .values(info)
+                    .execute(conn)
+                    .context(Query)?;
+
+                book_me
+                    .filter(user_id.eq(user.id))
+                    .first(conn)
+                    .context(Query)?
+            }
+            Err(e) => return Err(e),
+        };
+
+        Ok(book_me_info)
+    }
+}
+
+/// What was done to the target of an audit event.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum AuditAction {
+    Create,
+    Update,
+    Delete,
+}
+
+impl AuditAction {
+    fn name(self) -> &'static str {
+        match self {
+            AuditAction::Create => "create",
+            AuditAction::Update => "update",
+            AuditAction::Delete => "delete",
+        }
+    }
+}
+
+/// Kinds of records whose changes are audited.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum AuditTarget {
+    Album,
+    Photo,
+    BookMe,
+    User,
+}
+
+impl AuditTarget {
+    fn name(self) -> &'static str {
+        match self {
+            AuditTarget::Album => "album",
+            AuditTarget::Photo => "photo",
+            AuditTarget::BookMe => "bookMe",
+            AuditTarget::User => "user",
+        }
+    }
+}
+
+/// Who made a change and from where, shared by the events of a request.
+#[derive(Debug, Clone, Default)]
+pub struct AuditContext {
+    pub actor_id: Option<Uuid>,
+    pub ip: Option<String>,
+    pub request_id: Option<String>,
+}
+
+impl AuditContext {
+    /// Records a change in the log: creations have nothing `before` and deletions nothing
+    /// `after`. Run it in the same transaction as the change, see `transaction`, so a saved change
+    /// can't be missing from the log.
+    pub fn record<T: Audited>(
+        &self,
+        conn: &Conn,
+        action: AuditAction,
+        before: Option<&T>,
+        after: Option<&T>,
+    ) -> Result<()> {
+        let target = match before.or(after) {
+            Some(target) => target,
+            None => return Ok(()),
+        };
+
+        let event = AuditEvent {
+            id: Uuid::new_v4(),
+            actor_id: self.actor_id,
+            action: String::from(action.name()),
+            target_type: String::from(T::TARGET.name()),
+            target_id: target.audit_id(),
+            changes: diff(before, after),
+            ip: self.ip.clone(),
+            request_id: self.request_id.clone(),
+            created_at: Utc::now().naive_utc(),
+            studio_id: target.audit_studio(conn)?,
+        };
+
+        event.insert(conn)
+    }
+}
+
+/// Fields that differ between two versions of a record, as they are serialized for the API so
+/// secrets like password hashes never end up in the log.
+fn diff<T: Serialize>(before: Option<&T>, after: Option<&T>) -> String {
+    let to_fields = |value: Option<&T>| match value.map(serde_json::to_value) {
+        Some(Ok(serde_json::Value::Object(fields))) => fields,
+        _ => serde_json::Map::new(),
+    };
+    let before = to_fields(before);
+    let after = to_fields(after);
+
+    let mut changes = serde_json::Map::new();
+    for key in before.keys().chain(after.keys()) {
+        let old = before.get(key).cloned().unwrap_or(serde_json::Value::Null);
+        let new = after.get(key).cloned().unwrap_or(serde_json::Value::Null);
+        if old != new && !changes.contains_key(key) {
+            changes.insert(
+                key.clone(),
+                serde_json::json!({ "before": old, "after": new }),
+            );
+        }
+    }
+
+    serde_json::Value::Object(changes).to_string()
+}
+
+/// Records whose changes are kept in the audit log.
+pub trait Audited: Serialize {
+    const TARGET: AuditTarget;
+
+    fn audit_id(&self) -> Uuid;
+
+    /// Studio whose owners can see the changes of the record, if it belongs to one.
+    fn audit_studio(&self, _conn: &Conn) -> Result<Option<Uuid>> {
+        Ok(None)
+    }
+}
+
+impl Audited for Album {
+    const TARGET: AuditTarget = AuditTarget::Album;
+
+    fn audit_id(&self) -> Uuid {
+        self.id
+    }
+
+    fn audit_studio(&self, _conn: &Conn) -> Result<Option<Uuid>> {
+        Ok(Some(self.studio_id))
+    }
+}
+
+impl Audited for Photo {
+    const TARGET: AuditTarget = AuditTarget::Photo;
+
+    fn audit_id(&self) -> Uuid {
+        self.id
+    }
+
+    fn audit_studio(&self, conn: &Conn) -> Result<Option<Uuid>> {
+        albums::table
+            .filter(albums::id.eq(self.album_id))
+            .select(albums::studio_id)
+            .first(conn)
+            .optional()
+            .context(Query)
+    }
+}
+
+impl Audited for BookMe {
+    const TARGET: AuditTarget = AuditTarget::BookMe;
+
+    fn audit_id(&self) -> Uuid {
+        self.id
+    }
+}
+
+impl Audited for User {
+    const TARGET: AuditTarget = AuditTarget::User;
+
+    fn audit_id(&self) -> Uuid {
+        self.id
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Insertable, Identifiable, Queryable)]
+#[table_name = "audit_events"]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEvent {
+    pub id: Uuid,
+    pub actor_id: Option<Uuid>,
+    pub action: String,
+    pub target_type: String,
+    pub target_id: Uuid,
+    /// Fields that changed, as `{ "field": { "before": .., "after": .. } }`.
+    #[serde(serialize_with = "serialize_json")]
+    pub changes: String,
+    pub ip: Option<String>,
+    pub request_id: Option<String>,
+    #[serde(with = "ts_seconds")]
+    pub created_at: NaiveDateTime,
+    /// Studio of the changed album or photo.
+    pub studio_id: Option<Uuid>,
+}
+
+/// Criteria to browse the audit log, the ones left out match every event.
+#[derive(Debug, Clone, Default)]
+pub struct AuditFilter {
+    /// Only the changes this user made or that were made in the studios they own.
+    pub visible_to: Option<User>,
+    pub actor_id: Option<String>,
+    pub action: Option<AuditAction>,
+    pub target: Option<AuditTarget>,
+    pub target_id: Option<String>,
+    /// Events created at or after this time.
+    pub since: Option<NaiveDateTime>,
+    /// Events created strictly before this time.
+    pub until: Option<NaiveDateTime>,
+}
+
+/// Page of the audit log, most recent events first.
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditPage {
+    pub list: Vec<AuditEvent>,
+    /// Cursor to get the following page, missing on the last one.
+    pub next_cursor: Option<String>,
+}
+
+impl AuditEvent {
+    pub fn insert(&self, conn: &Conn) -> Result<()> {
+        diesel::insert_into(audit_events::table)
+            .values(self)
+            .execute(conn)
+            .context(Query)?;
+
+        Ok(())
+    }
+
+    /// Page of the events matching the filter, most recent first. Like for photos, the cursor holds
+    /// the date and id of the last event of the previous page, so events recorded in between do not
+    /// shift the pages.
+    pub fn find(
+        conn: &Conn,
+        filter: &AuditFilter,
+        limit: i64,
+        cursor: Option<&str>,
+    ) -> Result<AuditPage> {
+        use crate::schema::audit_events::dsl::*;
+
+        let mut query = audit_events.into_boxed();
+
+        if let Some(viewer) = &filter.visible_to {
+            let owned = Studio::ids_for_member(conn, viewer, Role::Owner)?;
+            query = query.filter(actor_id.eq(viewer.id).or(studio_id.eq_any(owned)));
+        }
+        if let Some(a_id) = &filter.actor_id {
+            query = query.filter(actor_id.eq(a_id));
+        }
+        if let Some(a) = filter.action {
+            query = query.filter(action.eq(a.name()));
+        }
+        if let Some(t) = filter.target {
+            query = query.filter(target_type.eq(t.name()));
+        }
+        if let Some(t_id) = &filter.target_id {
+            query = query.filter(target_id.eq(t_id));
+        }
+        if let Some(s) = filter.since {
+            query = query.filter(created_at.ge(s));
+        }
+        if let Some(u) = filter.until {
+            query = query.filter(created_at.lt(u));
+        }
+        if let Some(cursor) = cursor {
+            let (after, after_id) = decode_audit_cursor(cursor).context(InvalidCursor)?;
+            query = query.filter(
+                created_at
+                    .lt(after)
+                    .or(created_at.eq(after).and(id.gt(after_id))),
+            );
+        }
+
+        // One more than the limit tells whether there is a following page.
+        let mut events = query
+            .order((created_at.desc(), id.asc()))
+            .limit(limit + 1)
+            .load::<AuditEvent>(conn)
+            .context(Query)?;
+
+        let next_cursor = if events.len() as i64 > limit {
+            events.truncate(limit as usize);
+            events
+                .last()
+                .map(|e| format!("{}.{}", encode_time(&e.created_at), e.id))
+        } else {
+            None
+        };
+
+        Ok(AuditPage {
+            list: events,
+            next_cursor,
+        })
+    }
+}
+
+fn decode_audit_cursor(cursor: &str) -> Option<(NaiveDateTime, String)> {
+    let mut parts = cursor.splitn(2, '.');
+    let created_at = decode_time(parts.next()?)?;
+    let id = parts.next()?.to_string();
+
+    Some((created_at, id))
+}
+
+/// Link that gives access to a private album to anyone holding its token, until it expires,
+/// reaches its maximum number of views or gets revoked.
+#[derive(
+    Serialize,
+    Deserialize,
+    Debug,
+    PartialEq,
+    Clone,
+    Insertable,
+    Identifiable,
+    Associations,
+    Queryable,
+)]
+#[table_name = "share_links"]
+#[belongs_to(Album)]
+#[belongs_to(User)]
+#[serde(rename_all = "camelCase")]
+pub struct ShareLink {
+    pub id: Uuid,
+    pub album_id: Uuid,
+    pub user_id: Uuid,
+    pub token: String,
+    #[serde(with = "ts_seconds_option")]
+    pub expires_at: Option<NaiveDateTime>,
+    pub max_views: Option<i32>,
+    pub views: i32,
+    pub revoked: bool,
+    #[serde(with = "ts_seconds")]
+    pub created_at: NaiveDateTime,
+    #[serde(with = "ts_seconds")]
+    pub updated_at: NaiveDateTime,
+    #[serde(
+        rename = "protected",
+        serialize_with = "serialize_is_some",
+        skip_deserializing
+    )]
+    pub password_hash: Option<String>,
+    /// Whether clients can pick and comment photos through this link.
+    pub proofing: bool,
+    /// Whether the album can be downloaded as a ZIP archive through this link.
+    pub allow_downloads: bool,
+}
+
+impl ShareLink {
+    pub fn new(album: &Album, expires_at: Option<NaiveDateTime>, max_views: Option<i32>) -> Self {
+        let now = Utc::now().naive_utc();
+
+        Self {
+            id: Uuid::new_v4(),
+            album_id: album.id,
+            user_id: album.user_id,
+            token: random_token(32),
+            expires_at,
+            max_views,
+            views: 0,
+            revoked: false,
+            created_at: now,
+            updated_at: now,
+            password_hash: None,
+            proofing: false,
+            allow_downloads: false,
+        }
+    }
+
+    pub fn insert(&self, conn: &Conn) -> Result<ShareLink> {
+        use crate::schema::share_links::dsl::*;
+
+        diesel::insert_into(share_links)
+            .values(self)
+            .execute(conn)
+            .context(Query)?;
+
+        let link = share_links
+            .filter(id.eq(self.id))
+            .first(conn)
+            .context(Query)?;
+
+        Ok(link)
+    }
+
+    /// Finds a link to an album of a studio the user is a member of. Fails with `ShareLinkNotFound`
+    /// when it does not exist or the user is not a member, and with `Forbidden` when the role of
+    /// the user is lower than `role`.
+    pub fn find_for_member(conn: &Conn, user: &User, l_id: &str, role: Role) -> Result<ShareLink> {
+        let (link, album): (ShareLink, Album) = share_links::table
+            .inner_join(albums::table)
+            .filter(share_links::id.eq(l_id))
+            .first(conn)
+            .optional()
+            .context(Query)?
+            .context(ShareLinkNotFound)?;
+
+        match Studio::role_of(conn, &album.studio_id, user)? {
+            None => ShareLinkNotFound.fail(),
+            Some(r) if r < role => Forbidden.fail(),
+            Some(_) => Ok(link),
+        }
+    }
+
+    pub fn find_by_album(conn: &Conn, album: &Album) -> Result<Vec<ShareLink>> {
+        use crate::schema::share_links::dsl::*;
+
+        let links = share_links
+            .filter(album_id.eq(album.id))
+            .order(created_at.desc())
+            .load::<ShareLink>(conn)
+            .context(Query)?;
+
+        Ok(links)
+    }
+
+    pub fn revoke(&self, conn: &Conn) -> Result<ShareLink> {
+        use crate::schema::share_links::dsl::*;
+
+        let now = Utc::now().naive_utc();
+
+        diesel::update(share_links)
+            .filter(id.eq(self.id))
+            .set((revoked.eq(true), updated_at.eq(now)))
+            .execute(conn)
+            .context(Query)?;
+
+        let link = share_links
+            .filter(id.eq(self.id))
+            .first(conn)
+            .context(Query)?;
+
+        Ok(link)
+    }
+
+    /// Protects the link with a password, or removes the protection when there is none.
+    pub fn set_password(&self, conn: &Conn, password: Option<&str>) -> Result<ShareLink> {
+        use crate::schema::share_links::dsl::*;
+
+        let hash = match password {
+            Some(p) => Some(hash_password(p).context(PasswordHash)?),
+            None => None,
+        };
+        let now = Utc::now().naive_utc();
+
+        diesel::update(share_links)
+            .filter(id.eq(self.id))
+            .set((password_hash.eq(hash), updated_at.eq(now)))
+            .execute(conn)
+            .context(Query)?;
+
+        let link = share_links
+            .filter(id.eq(self.id))
+            .first(conn)
+            .context(Query)?;
+
+        Ok(link)
+    }
+
+    pub fn is_protected(&self) -> bool {
+        self.password_hash.is_some()
+    }
+
+    pub fn verify_password(&self, password: &str) -> bool {
+        match &self.password_hash {
+            Some(hash) => verify_password(hash, password),
+            None => false,
+        }
+    }
+
+    pub fn set_proofing(&self, conn: &Conn, enabled: bool) -> Result<ShareLink> {
+        use crate::schema::share_links::dsl::*;
+
+        let now = Utc::now().naive_utc();
+
+        diesel::update(share_links)
+            .filter(id.eq(self.id))
+            .set((proofing.eq(enabled), updated_at.eq(now)))
+            .execute(conn)
+            .context(Query)?;
+
+        let link = share_links
+            .filter(id.eq(self.id))
+            .first(conn)
+            .context(Query)?;
+
+        Ok(link)
+    }
+
+    pub fn set_allow_downloads(&self, conn: &Conn, enabled: bool) -> Result<ShareLink> {
+        use crate::schema::share_links::dsl::*;
+
+        let now = Utc::now().naive_utc();
+
+        diesel::update(share_links)
+            .filter(id.eq(self.id))
+            .set((allow_downloads.eq(enabled), updated_at.eq(now)))
+            .execute(conn)
+            .context(Query)?;
+
+        let link = share_links
+            .filter(id.eq(self.id))
+            .first(conn)
+            .context(Query)?;
+
+        Ok(link)
+    }
+
+    pub fn is_valid(&self) -> bool {
+        let now = Utc::now().naive_utc();
+
+        let expired = match self.expires_at {
+            Some(expiry) => expiry <= now,
+            None => false,
+        };
+
+        let exhausted = match self.max_views {
+            Some(max) => self.views >= max,
+            None => false,
+        };
+
+        !self.revoked && !expired && !exhausted
+    }
+
+    /// Finds a valid link by its token along with its album. Fails with `InvalidShareLink` when
+    /// the token does not exist, is no longer valid or the album was deleted.
+    pub fn find_valid(conn: &Conn, l_token: &str) -> Result<(ShareLink, Album)> {
+        let link: Option<ShareLink> = {
+            use crate::schema::share_links::dsl::*;
+
+            share_links
+                .filter(token.eq(l_token))
+                .first(conn)
+                .optional()
+                .context(Query)?
+        };
+
+        let link = match link {
+            Some(link) if link.is_valid() => link,
+            _ => return Err(ModelError::InvalidShareLink),
+        };
+
+        let album: Album = {
+            use crate::schema::albums::dsl::*;
+
+            albums
+                .filter(id.eq(link.album_id))
+                .filter(deleted.eq(false))
+                .first(conn)
+                .optional()
+                .context(Query)?
+                .context(InvalidShareLink)?
+        };
+
+        Ok((link, album))
+    }
+
+    /// Counts a visit to the link. Fails with `InvalidShareLink` if the link reached its maximum
+    /// number of views in the meantime.
+    pub fn add_view(&self, conn: &Conn) -> Result<ShareLink> {
+        use crate::schema::share_links::dsl::*;
+
+        let updated = diesel::update(share_links)
+            .filter(id.eq(self.id))
+            .filter(sql::<Bool>("max_views IS NULL OR views < max_views"))
+            .set(views.eq(views + 1))
+            .execute(conn)
+            .context(Query)?;
+
+        if updated == 0 {
+            return Err(ModelError::InvalidShareLink);
+        }
+
+        let link = share_links
+            .filter(id.eq(self.id))
+            .first(conn)
+            .context(Query)?;
+
+        Ok(link)
+    }
+}
+
+/// Client reviewing an album through a share link with proofing enabled. Clients have no account,
+/// they register with their email on the share link and act with the secret they get back.
+#[derive(
+    Serialize,
+    Deserialize,
+    Debug,
+    PartialEq,
+    Clone,
+    Insertable,
+    Identifiable,
+    Associations,
+    Queryable,
+)]
+#[table_name = "proofing_clients"]
+#[belongs_to(ShareLink)]
+#[serde(rename_all = "camelCase")]
+pub struct ProofingClient {
+    pub id: Uuid,
+    pub share_link_id: Uuid,
+    pub name: String,
+    pub email: String,
+    #[serde(with = "ts_seconds")]
+    pub created_at: NaiveDateTime,
+    #[serde(with = "ts_seconds")]
+    pub updated_at: NaiveDateTime,
+    #[serde(skip)]
+    pub secret_hash: Option<String>,
+}
+
+/// Photo picked by a client. Kept apart from `Photo::is_favorite`, which belongs to the owner.
+#[derive(
+    Serialize,
+    Deserialize,
+    Debug,
+    PartialEq,
+    Clone,
+    Insertable,
+    Identifiable,
+    Associations,
+    Queryable,
+)]
+#[table_name = "proofing_selections"]
+#[belongs_to(ProofingClient, foreign_key = "client_id")]
+#[belongs_to(Photo)]
+#[serde(rename_all = "camelCase")]
+pub struct ProofingSelection {
+    pub id: Uuid,
+    pub client_id: Uuid,
+    pub photo_id: Uuid,
+    #[serde(with = "ts_seconds")]
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(
+    Serialize,
+    Deserialize,
+    Debug,
+    PartialEq,
+    Clone,
+    Insertable,
+    Identifiable,
+    Associations,
+    Queryable,
+)]
+#[table_name = "proofing_comments"]
+#[belongs_to(ProofingClient, foreign_key = "client_id")]
+#[belongs_to(Photo)]
+#[serde(rename_all = "camelCase")]
+pub struct ProofingComment {
+    pub id: Uuid,
+    pub client_id: Uuid,
+    pub photo_id: Uuid,
+    pub body: String,
+    #[serde(with = "ts_seconds")]
+    pub created_at: NaiveDateTime,
+}
 
-        diesel::delete(photos.filter(id.eq(self.id)))
-            .execute(conn)
+/// Length of the secrets proofing clients act with.
+const PROOFING_SECRET_LENGTH: usize = 40;
+
+/// Selected photos and comments left by a client.
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProofingSummary {
+    pub client: ProofingClient,
+    pub selections: Vec<Photo>,
+    pub comments: Vec<ProofingComment>,
+}
+
+impl ProofingClient {
+    pub fn new(link: &ShareLink, name: String, email: String) -> Self {
+        let now = Utc::now().naive_utc();
+
+        Self {
+            id: Uuid::new_v4(),
+            share_link_id: link.id,
+            name,
+            email,
+            created_at: now,
+            updated_at: now,
+            secret_hash: None,
+        }
+    }
+
+    /// Registers a client with the given email for the link. Returns it along with the secret it
+    /// picks and comments photos with, which is only handed out once: fails with
+    /// `ProofingClientTaken` when the email was already registered, so knowing the link and an
+    /// email is not enough to act as that client. Clients registered before secrets existed get
+    /// one the next time they register.
+    pub fn register(
+        conn: &Conn,
+        link: &ShareLink,
+        c_name: &str,
+        c_email: &str,
+    ) -> Result<(Self, String)> {
+        use crate::schema::proofing_clients::dsl::*;
+
+        let c_email = c_email.trim().to_lowercase();
+        let c_name = c_name.trim().to_string();
+        let secret = random_token(PROOFING_SECRET_LENGTH);
+        let hash = hash_token(&secret);
+        let now = Utc::now().naive_utc();
+
+        let client = conn.transaction(|| {
+            let existing: Option<ProofingClient> = proofing_clients
+                .filter(share_link_id.eq(link.id))
+                .filter(email.eq(&c_email))
+                .first(conn)
+                .optional()
+                .context(Query)?;
+
+            let client_id = match existing {
+                Some(client) if client.secret_hash.is_some() => {
+                    return Err(ModelError::ProofingClientTaken);
+                }
+                Some(client) => {
+                    diesel::update(proofing_clients)
+                        .filter(id.eq(client.id))
+                        .set((name.eq(&c_name), secret_hash.eq(&hash), updated_at.eq(now)))
+                        .execute(conn)
+                        .context(Query)?;
+
+                    client.id
+                }
+                None => {
+                    let mut client = ProofingClient::new(link, c_name.clone(), c_email.clone());
+                    client.secret_hash = Some(hash.clone());
+
+                    diesel::insert_into(proofing_clients)
+                        .values(&client)
+                        .execute(conn)
+                        .context(Query)?;
+
+                    client.id
+                }
+            };
+
+            proofing_clients
+                .filter(id.eq(client_id))
+                .first(conn)
+                .context(Query)
+        })?;
+
+        Ok((client, secret))
+    }
+
+    /// Finds the client of the link the secret was given to. Fails with `InvalidProofingSecret`
+    /// when no client of the link has it.
+    pub fn authenticate(conn: &Conn, link: &ShareLink, secret: &str) -> Result<Self> {
+        use crate::schema::proofing_clients::dsl::*;
+
+        let client = proofing_clients
+            .filter(share_link_id.eq(link.id))
+            .filter(secret_hash.eq(hash_token(secret)))
+            .first(conn)
+            .optional()
+            .context(Query)?
+            .context(InvalidProofingSecret)?;
+
+        Ok(client)
+    }
+
+    pub fn find_by_link(conn: &Conn, link: &ShareLink) -> Result<Vec<ProofingClient>> {
+        use crate::schema::proofing_clients::dsl::*;
+
+        let clients = proofing_clients
+            .filter(share_link_id.eq(link.id))
+            .order(created_at.asc())
+            .load::<ProofingClient>(conn)
             .context(Query)?;
 
+        Ok(clients)
+    }
+
+    /// Picks or unpicks a photo. Picking a photo twice has no effect.
+    pub fn select(&self, conn: &Conn, photo: &Photo, selected: bool) -> Result<()> {
+        use crate::schema::proofing_selections::dsl::*;
+
+        diesel::delete(
+            proofing_selections
+                .filter(client_id.eq(self.id))
+                .filter(photo_id.eq(photo.id)),
+        )
+        .execute(conn)
+        .context(Query)?;
+
+        if selected {
+            let selection = ProofingSelection {
+                id: Uuid::new_v4(),
+                client_id: self.id,
+                photo_id: photo.id,
+                created_at: Utc::now().naive_utc(),
+            };
+
+            diesel::insert_into(proofing_selections)
+                .values(&selection)
+                .execute(conn)
+                .context(Query)?;
+        }
+
         Ok(())
     }
 
-    pub fn find_by_id(conn: &Conn, p_id: &str) -> Result<Photo> {
-        use crate::schema::photos::dsl::*;
+    pub fn comment(&self, conn: &Conn, photo: &Photo, c_body: String) -> Result<ProofingComment> {
+        use crate::schema::proofing_comments::dsl::*;
 
-        let photo = photos.filter(id.eq(p_id)).first(conn).context(Query)?;
+        let new_comment = ProofingComment {
+            id: Uuid::new_v4(),
+            client_id: self.id,
+            photo_id: photo.id,
+            body: c_body,
+            created_at: Utc::now().naive_utc(),
+        };
 
-        Ok(photo)
+        diesel::insert_into(proofing_comments)
+            .values(&new_comment)
+            .execute(conn)
+            .context(Query)?;
+
+        let saved = proofing_comments
+            .filter(id.eq(new_comment.id))
+            .first(conn)
+            .context(Query)?;
+
+        Ok(saved)
     }
 
-    fn prepare_update(
-        &self,
-        index_in_album: i32,
-        is_favorite: bool,
-        title: Option<String>,
-        description: Option<String>,
-    ) -> UpdatePhoto {
-        let now = Utc::now().naive_utc();
+    pub fn summary(self, conn: &Conn) -> Result<ProofingSummary> {
+        let mut summaries = ProofingClient::summaries(conn, vec![self])?;
 
-        UpdatePhoto {
-            index_in_album,
-            is_favorite,
-            title,
-            description,
-            updated_at: now,
-        }
+        Ok(summaries.remove(0))
+    }
+
+    /// Loads the selections and comments of every client of the link, in client order.
+    pub fn summaries_by_link(conn: &Conn, link: &ShareLink) -> Result<Vec<ProofingSummary>> {
+        let clients = ProofingClient::find_by_link(conn, link)?;
+
+        ProofingClient::summaries(conn, clients)
+    }
+
+    fn summaries(conn: &Conn, clients: Vec<ProofingClient>) -> Result<Vec<ProofingSummary>> {
+        let ids: Vec<Uuid> = clients.iter().map(|c| c.id).collect();
+
+        let selections: Vec<(ProofingSelection, Photo)> = proofing_selections::table
+            .inner_join(photos::table)
+            .filter(proofing_selections::client_id.eq_any(&ids))
+            .filter(photos::deleted.eq(false))
+            .order((photos::index_in_album.asc(), photos::created_at.asc()))
+            .load(conn)
+            .context(Query)?;
+
+        let comments: Vec<ProofingComment> = proofing_comments::table
+            .filter(proofing_comments::client_id.eq_any(&ids))
+            .order(proofing_comments::created_at.asc())
+            .load(conn)
+            .context(Query)?;
+
+        let summaries = clients
+            .into_iter()
+            .map(|client| {
+                let client_selections = selections
+                    .iter()
+                    .filter(|(s, _)| s.client_id == client.id)
+                    .map(|(_, p)| p.clone())
+                    .collect();
+                let client_comments = comments
+                    .iter()
+                    .filter(|c| c.client_id == client.id)
+                    .cloned()
+                    .collect();
+
+                ProofingSummary {
+                    client,
+                    selections: client_selections,
+                    comments: client_comments,
+                }
+            })
+            .collect();
+
+        Ok(summaries)
     }
 }
 
+/// Keyword used to label and filter photos, e.g. "ceremony" or "first dance".
 #[derive(
     Serialize,
     Deserialize,
     Debug,
+    PartialEq,
     Clone,
-    AsChangeset,
     Insertable,
     Identifiable,
     Associations,
     Queryable,
+    QueryableByName,
 )]
-#[table_name = "book_me"]
+#[table_name = "tags"]
 #[belongs_to(User)]
 #[serde(rename_all = "camelCase")]
-pub struct BookMe {
+pub struct Tag {
+    pub id: Uuid,
+    /// Member who created the tag.
+    pub user_id: Uuid,
+    pub name: String,
+    pub slug: String,
+    #[serde(with = "ts_seconds")]
+    pub created_at: NaiveDateTime,
+    pub studio_id: Uuid,
+}
+
+#[derive(Insertable)]
+#[table_name = "photo_tags"]
+struct NewPhotoTag {
     id: Uuid,
-    user_id: Uuid,
-    pub email: String,
+    photo_id: Uuid,
+    tag_id: Uuid,
+    created_at: NaiveDateTime,
 }
 
-impl BookMe {
-    pub fn new(email: String, user: &User) -> Self {
+#[derive(Serialize, Debug, Clone, QueryableByName)]
+#[serde(rename_all = "camelCase")]
+pub struct TagCount {
+    #[diesel(embed)]
+    #[serde(flatten)]
+    pub tag: Tag,
+    #[sql_type = "BigInt"]
+    pub photos_count: i64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PhotoTags {
+    pub photo_id: Uuid,
+    pub tags: Vec<Tag>,
+}
+
+impl Tag {
+    pub fn new(s_id: Uuid, user: &User, name: String) -> Self {
         Self {
             id: Uuid::new_v4(),
             user_id: user.id,
-            email,
+            slug: slugify(&name),
+            name,
+            created_at: Utc::now().naive_utc(),
+            studio_id: s_id,
         }
     }
 
-    pub fn find_by_user(conn: &Conn, user: &User) -> Result<BookMe> {
-        use crate::schema::book_me::dsl::*;
+    /// Finds the tags of the studio with the given names, creating the missing ones on behalf of
+    /// the user. Names are compared by their slug, so "First Dance" and "first dance" are the same
+    /// tag.
+    pub fn find_or_create(
+        conn: &Conn,
+        s_id: &Uuid,
+        user: &User,
+        names: &[String],
+    ) -> Result<Vec<Tag>> {
+        use crate::schema::tags::dsl::*;
 
-        let info = book_me
-            .filter(user_id.eq(user.id))
-            .first(conn)
+        let mut wanted: Vec<Tag> = Vec::new();
+        for t_name in names {
+            let tag = Tag::new(*s_id, user, t_name.trim().to_string());
+            if !tag.slug.is_empty() && !wanted.iter().any(|w| w.slug == tag.slug) {
+                wanted.push(tag);
+            }
+        }
+
+        conn.transaction(|| {
+            let slugs: Vec<&String> = wanted.iter().map(|w| &w.slug).collect();
+            let existing: Vec<Tag> = tags
+                .filter(studio_id.eq(s_id))
+                .filter(slug.eq_any(&slugs))
+                .load(conn)?;
+
+            for tag in &wanted {
+                if !existing.iter().any(|e| e.slug == tag.slug) {
+                    diesel::insert_into(tags).values(tag).execute(conn)?;
+                }
+            }
+
+            tags.filter(studio_id.eq(s_id))
+                .filter(slug.eq_any(&slugs))
+                .order(name.asc())
+                .load::<Tag>(conn)
+        })
+        .context(Query)
+    }
+
+    /// Lists the tags of the studios the user is a member of, with the number of photos using
+    /// each of them.
+    pub fn find_all(conn: &Conn, user: &User) -> Result<Vec<TagCount>> {
+        let counts = diesel::sql_query(
+            r#"
+            SELECT t.*, COUNT(p.id) AS photos_count
+            FROM tags t
+            INNER JOIN studio_members sm ON sm.studio_id = t.studio_id AND sm.user_id = ?
+            LEFT JOIN photo_tags pt ON pt.tag_id = t.id
+            LEFT JOIN photos p ON p.id = pt.photo_id AND p.deleted = 0
+            GROUP BY t.id
+            ORDER BY t.name COLLATE NOCASE ASC
+            "#,
+        )
+        .bind::<Text, _>(user.id)
+        .load(conn)
+        .context(Query)?;
+
+        Ok(counts)
+    }
+
+    /// Ids of the photos of a studio labeled with the given tag name or slug.
+    pub fn photo_ids(conn: &Conn, s_id: &Uuid, tag: &str) -> Result<HashSet<Uuid>> {
+        let ids: Vec<Uuid> = photo_tags::table
+            .inner_join(tags::table)
+            .filter(tags::studio_id.eq(s_id))
+            .filter(tags::slug.eq(slugify(tag)))
+            .select(photo_tags::photo_id)
+            .load(conn)
             .context(Query)?;
 
-        Ok(info)
+        Ok(ids.into_iter().collect())
     }
 
-    pub fn update_or_create(conn: &Conn, book_email: &str, user: &User) -> Result<BookMe> {
-        let existing: Result<BookMe> = BookMe::find_by_user(conn, user);
+    /// Tags of every given photo, in the same order.
+    pub fn find_by_photos(conn: &Conn, p_photos: &[Photo]) -> Result<Vec<PhotoTags>> {
+        let ids: Vec<Uuid> = p_photos.iter().map(|p| p.id).collect();
+        let rows: Vec<(Uuid, Tag)> = photo_tags::table
+            .inner_join(tags::table)
+            .filter(photo_tags::photo_id.eq_any(&ids))
+            .select((photo_tags::photo_id, tags::all_columns))
+            .order(tags::name.asc())
+            .load(conn)
+            .context(Query)?;
 
-        let book_me_info: BookMe = match existing {
-            Ok(book_me_info) => {
-                use crate::schema::book_me::dsl::*;
+        let list = ids
+            .into_iter()
+            .map(|p_id| PhotoTags {
+                photo_id: p_id,
+                tags: rows
+                    .iter()
+                    .filter(|(id, _)| *id == p_id)
+                    .map(|(_, tag)| tag.clone())
+                    .collect(),
+            })
+            .collect();
 
-                diesel::update(book_me)
-                    .filter(id.eq(book_me_info.id))
-                    .set(email.eq(book_email))
-                    .execute(conn)
-                    .context(Query)?;
+        Ok(list)
+    }
 
-                book_me
-                    .filter(id.eq(book_me_info.id))
-                    .first(conn)
-                    .context(Query)?
+    /// Labels every photo with the tags of its studio with the given names, the missing ones are
+    /// created on behalf of the user. Photos already labeled with a tag are left as they are.
+    pub fn add_to_photos(
+        conn: &Conn,
+        user: &User,
+        names: &[String],
+        p_photos: &[Photo],
+    ) -> Result<()> {
+        conn.transaction(|| {
+            let a_ids: Vec<Uuid> = p_photos.iter().map(|p| p.album_id).collect();
+            let studio_of: HashMap<Uuid, Uuid> = albums::table
+                .filter(albums::id.eq_any(a_ids))
+                .select((albums::id, albums::studio_id))
+                .load::<(Uuid, Uuid)>(conn)
+                .context(Query)?
+                .into_iter()
+                .collect();
+
+            let s_ids: HashSet<Uuid> = studio_of.values().cloned().collect();
+
+            for s_id in s_ids {
+                let in_studio: Vec<Photo> = p_photos
+                    .iter()
+                    .filter(|p| studio_of.get(&p.album_id) == Some(&s_id))
+                    .cloned()
+                    .collect();
+                let t_tags = Tag::find_or_create(conn, &s_id, user, names)?;
+                Tag::link(conn, &t_tags, &in_studio).context(Query)?;
             }
-            Err(_) => {
-                use crate::schema::book_me::dsl::*;
 
-                let info = BookMe::new(String::from(book_email), user);
+            Ok(())
+        })
+    }
 
-                diesel::insert_into(book_me)
-                    .values(info)
-                    .execute(conn)
-                    .context(Query)?;
+    fn link(conn: &Conn, t_tags: &[Tag], p_photos: &[Photo]) -> QueryResult<()> {
+        use crate::schema::photo_tags::dsl::*;
 
-                book_me
-                    .filter(user_id.eq(user.id))
-                    .first(conn)
-                    .context(Query)?
+        let now = Utc::now().naive_utc();
+        let rows: Vec<NewPhotoTag> = p_photos
+            .iter()
+            .flat_map(|photo| {
+                t_tags.iter().map(move |tag| NewPhotoTag {
+                    id: Uuid::new_v4(),
+                    photo_id: photo.id,
+                    tag_id: tag.id,
+                    created_at: now,
+                })
+            })
+            .collect();
+
+        for row in &rows {
+            diesel::insert_or_ignore_into(photo_tags)
+                .values(row)
+                .execute(conn)?;
+        }
+
+        Ok(())
+    }
+
+    /// Removes the tags with the given names from every photo. Photos are only labeled with tags
+    /// of their own studio, so only those are matched.
+    pub fn remove_from_photos(conn: &Conn, names: &[String], p_photos: &[Photo]) -> Result<()> {
+        use crate::schema::photo_tags::dsl::*;
+
+        let slugs: Vec<String> = names.iter().map(|n| slugify(n)).collect();
+        let t_ids = tags::table
+            .filter(tags::slug.eq_any(slugs))
+            .select(tags::id);
+        let p_ids: Vec<Uuid> = p_photos.iter().map(|p| p.id).collect();
+
+        diesel::delete(
+            photo_tags
+                .filter(photo_id.eq_any(p_ids))
+                .filter(tag_id.eq_any(t_ids)),
+        )
+        .execute(conn)
+        .context(Query)?;
+
+        Ok(())
+    }
+}
+
+/// Albums and photos matching a full-text search, best matches first.
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResults {
+    pub albums: Vec<AlbumMatch>,
+    pub photos: Vec<PhotoMatch>,
+}
+
+#[derive(Serialize, Debug, Clone, QueryableByName)]
+#[serde(rename_all = "camelCase")]
+pub struct AlbumMatch {
+    #[diesel(embed)]
+    pub album: Album,
+    /// Matching text as escaped HTML, with the search terms wrapped in `<mark>` tags.
+    #[sql_type = "Text"]
+    pub snippet: String,
+}
+
+#[derive(Serialize, Debug, Clone, QueryableByName)]
+#[serde(rename_all = "camelCase")]
+pub struct PhotoMatch {
+    #[diesel(embed)]
+    pub photo: Photo,
+    /// Matching text as escaped HTML, with the search terms wrapped in `<mark>` tags.
+    #[sql_type = "Text"]
+    pub snippet: String,
+}
+
+impl SearchResults {
+    /// Searches the albums of the user's studios (by name and description) and photos (by title and
+    /// description). Every word of the query must match, the last one as a prefix so results
+    /// show up while typing.
+    pub fn find(conn: &Conn, user: &User, terms: &str, limit: i64) -> Result<SearchResults> {
+        let query = match fts_query(terms) {
+            Some(query) => query,
+            None => {
+                return Ok(SearchResults {
+                    albums: Vec::new(),
+                    photos: Vec::new(),
+                })
             }
         };
 
-        Ok(book_me_info)
+        // The terms are delimited with control characters rather than `<mark>` tags, so the text
+        // can be escaped before the tags are added.
+        let mut album_matches: Vec<AlbumMatch> = diesel::sql_query(
+            r#"
+            SELECT a.*,
+              snippet(albums_search, -1, char(2), char(3), '…', 16) AS snippet
+            FROM albums_search
+            INNER JOIN albums a ON a.id = albums_search.id
+            WHERE albums_search MATCH ? AND a.deleted = 0
+              AND a.studio_id IN (SELECT m.studio_id FROM studio_members m WHERE m.user_id = ?)
+            ORDER BY bm25(albums_search, 0.0, 0.0, 10.0, 1.0)
+            LIMIT ?
+            "#,
+        )
+        .bind::<Text, _>(&query)
+        .bind::<Text, _>(user.id)
+        .bind::<BigInt, _>(limit)
+        .load(conn)
+        .context(Query)?;
+
+        let mut photo_matches: Vec<PhotoMatch> = diesel::sql_query(
+            r#"
+            SELECT p.*,
+              snippet(photos_search, -1, char(2), char(3), '…', 16) AS snippet
+            FROM photos_search
+            INNER JOIN photos p ON p.id = photos_search.id
+            INNER JOIN albums a ON a.id = p.album_id
+            WHERE photos_search MATCH ? AND p.deleted = 0 AND a.deleted = 0
+              AND a.studio_id IN (SELECT m.studio_id FROM studio_members m WHERE m.user_id = ?)
+            ORDER BY bm25(photos_search, 0.0, 0.0, 10.0, 1.0)
+            LIMIT ?
+            "#,
+        )
+        .bind::<Text, _>(&query)
+        .bind::<Text, _>(user.id)
+        .bind::<BigInt, _>(limit)
+        .load(conn)
+        .context(Query)?;
+
+        for found in album_matches.iter_mut() {
+            found.snippet = highlight(&found.snippet);
+        }
+        for found in photo_matches.iter_mut() {
+            found.snippet = highlight(&found.snippet);
+        }
+
+        Ok(SearchResults {
+            albums: album_matches,
+            photos: photo_matches,
+        })
+    }
+}
+
+/// Escapes a snippet for HTML, then turns the delimiters around the search terms into `<mark>`
+/// tags.
+fn highlight(snippet: &str) -> String {
+    let mut html = String::with_capacity(snippet.len());
+
+    for c in snippet.chars() {
+        match c {
+            '\u{2}' => html.push_str("<mark>"),
+            '\u{3}' => html.push_str("</mark>"),
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            '\'' => html.push_str("&#39;"),
+            c => html.push(c),
+        }
+    }
+
+    html
+}
+
+/// Turns user input into an FTS5 query, quoting every word so characters with a meaning in the
+/// FTS5 syntax are searched literally.
+fn fts_query(terms: &str) -> Option<String> {
+    let words: Vec<String> = terms
+        .split_whitespace()
+        .map(|word| word.replace('"', ""))
+        .filter(|word| !word.is_empty())
+        .map(|word| format!("\"{}\"", word))
+        .collect();
+
+    if words.is_empty() {
+        return None;
     }
+
+    Some(format!("{}*", words.join(" ")))
+}
+
+fn serialize_is_some<T, S>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serializer.serialize_bool(value.is_some())
+}
+
+/// Serializes JSON stored as text as is, rather than as a string.
+fn serialize_json<S>(value: &str, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    let json: serde_json::Value = serde_json::from_str(value).map_err(serde::ser::Error::custom)?;
+    json.serialize(serializer)
+}
+
+/// Runs the changes in a single transaction, rolled back when any of them fails. Foreign keys are
+/// turned on first: SQLite ignores the pragma inside a transaction, and deletes need it to cascade.
+pub fn transaction<T, F>(conn: &Conn, changes: F) -> Result<T>
+where
+    F: FnOnce() -> Result<T>,
+{
+    conn.execute("PRAGMA foreign_keys = ON").context(Query)?;
+
+    conn.transaction(changes)
 }
 
 pub type Result<T, E = ModelError> = std::result::Result<T, E>;
@@ -524,4 +4249,95 @@ pub enum ModelError {
 
     #[snafu(display("Query Failed: {}", source))]
     Query { source: diesel::result::Error },
+
+    #[snafu(display("User does not exist"))]
+    UserNotFound,
+
+    #[snafu(display("Booking information does not exist"))]
+    BookMeNotFound,
+
+    #[snafu(display("Album does not exist"))]
+    AlbumNotFound,
+
+    #[snafu(display("An album cannot be moved inside itself or one of its children"))]
+    AlbumCycle,
+
+    #[snafu(display("Parent album belongs to another studio"))]
+    InvalidParent,
+
+    #[snafu(display("Share link does not exist, expired or was revoked"))]
+    InvalidShareLink,
+
+    #[snafu(display("Share link does not exist"))]
+    ShareLinkNotFound,
+
+    #[snafu(display("One or more photos do not exist"))]
+    PhotoNotFound,
+
+    #[snafu(display("Photo does not belong to the album"))]
+    PhotoNotInAlbum,
+
+    #[snafu(display("Too many photos for a single operation"))]
+    TooManyPhotos,
+
+    #[snafu(display("A client with that email already joined the proofing"))]
+    ProofingClientTaken,
+
+    #[snafu(display("Proofing secret does not belong to any client of the link"))]
+    InvalidProofingSecret,
+
+    #[snafu(display("Pagination cursor is not valid for the requested order"))]
+    InvalidCursor,
+
+    #[snafu(display("Photo version does not exist"))]
+    PhotoVersionNotFound,
+
+    #[snafu(display("Modified since the version the client expected"))]
+    PreconditionFailed,
+
+    #[snafu(display("Login state is unknown or expired"))]
+    InvalidOAuthState,
+
+    #[snafu(display("Refresh token is unknown, was already used or expired"))]
+    InvalidRefreshToken,
+
+    #[snafu(display("Session does not exist"))]
+    SessionNotFound,
+
+    #[snafu(display("Login code is unknown, was already used or expired"))]
+    InvalidLoginCode,
+
+    #[snafu(display("Invitation does not exist"))]
+    InvitationNotFound,
+
+    #[snafu(display("Studio does not exist"))]
+    StudioNotFound,
+
+    #[snafu(display("User is not a member of the studio"))]
+    MemberNotFound,
+
+    #[snafu(display("The role of the user in the studio does not allow it"))]
+    Forbidden,
+
+    #[snafu(display("A studio must keep at least one owner"))]
+    LastOwner,
+
+    #[snafu(display("Login link is unknown, was already used or expired"))]
+    InvalidLoginLink,
+
+    #[snafu(display("API key is unknown or was revoked"))]
+    InvalidApiKey,
+
+    #[snafu(display("API key does not exist"))]
+    ApiKeyNotFound,
+
+    #[snafu(display("Could not hash password: {}", source))]
+    PasswordHash { source: PasswordError },
+}
+
+/// Lets model methods returning `Result` run inside a transaction.
+impl From<diesel::result::Error> for ModelError {
+    fn from(source: diesel::result::Error) -> Self {
+        ModelError::Query { source }
+    }
 }
@@ -1,3 +1,26 @@
+table! {
+    api_keys (id) {
+        id -> Text,
+        user_id -> Text,
+        name -> Text,
+        key_hash -> Text,
+        prefix -> Text,
+        scope -> Text,
+        last_used_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+    }
+}
+
+table! {
+    album_slugs (id) {
+        id -> Text,
+        album_id -> Text,
+        studio_id -> Text,
+        slug -> Text,
+        created_at -> Timestamp,
+    }
+}
+
 table! {
     albums (id) {
         id -> Text,
@@ -7,6 +30,25 @@ table! {
         created_at -> Timestamp,
         updated_at -> Timestamp,
         deleted -> Bool,
+        slug -> Text,
+        parent_id -> Nullable<Text>,
+        password_hash -> Nullable<Text>,
+        studio_id -> Text,
+    }
+}
+
+table! {
+    audit_events (id) {
+        id -> Text,
+        actor_id -> Nullable<Text>,
+        action -> Text,
+        target_type -> Text,
+        target_id -> Text,
+        changes -> Text,
+        ip -> Nullable<Text>,
+        request_id -> Nullable<Text>,
+        created_at -> Timestamp,
+        studio_id -> Nullable<Text>,
     }
 }
 
@@ -26,6 +68,79 @@ table! {
     }
 }
 
+table! {
+    invitations (id) {
+        id -> Text,
+        email -> Text,
+        invited_by -> Nullable<Text>,
+        created_at -> Timestamp,
+        expires_at -> Nullable<Timestamp>,
+        accepted_at -> Nullable<Timestamp>,
+    }
+}
+
+table! {
+    login_codes (code_hash) {
+        code_hash -> Text,
+        user_id -> Text,
+        created_at -> Timestamp,
+        expires_at -> Timestamp,
+    }
+}
+
+table! {
+    login_links (token_hash) {
+        token_hash -> Text,
+        email -> Text,
+        created_at -> Timestamp,
+        expires_at -> Timestamp,
+    }
+}
+
+table! {
+    oauth_states (state) {
+        state -> Text,
+        pkce_verifier -> Text,
+        created_at -> Timestamp,
+        provider -> Text,
+    }
+}
+
+table! {
+    photo_tags (id) {
+        id -> Text,
+        photo_id -> Text,
+        tag_id -> Text,
+        created_at -> Timestamp,
+    }
+}
+
+table! {
+    photo_uploads (id) {
+        id -> Text,
+        user_id -> Text,
+        s3_id -> Text,
+        title -> Nullable<Text>,
+        description -> Nullable<Text>,
+        keywords -> Text,
+        created_at -> Timestamp,
+    }
+}
+
+table! {
+    photo_versions (id) {
+        id -> Text,
+        photo_id -> Text,
+        s3_id -> Text,
+        src -> Text,
+        main_color -> Text,
+        width -> Integer,
+        height -> Integer,
+        filename -> Nullable<Text>,
+        created_at -> Timestamp,
+    }
+}
+
 table! {
     photos (id) {
         id -> Text,
@@ -43,6 +158,108 @@ table! {
         created_at -> Timestamp,
         updated_at -> Timestamp,
         deleted -> Bool,
+        filename -> Nullable<Text>,
+        taken_at -> Nullable<Timestamp>,
+    }
+}
+
+table! {
+    proofing_clients (id) {
+        id -> Text,
+        share_link_id -> Text,
+        name -> Text,
+        email -> Text,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        secret_hash -> Nullable<Text>,
+    }
+}
+
+table! {
+    proofing_comments (id) {
+        id -> Text,
+        client_id -> Text,
+        photo_id -> Text,
+        body -> Text,
+        created_at -> Timestamp,
+    }
+}
+
+table! {
+    proofing_selections (id) {
+        id -> Text,
+        client_id -> Text,
+        photo_id -> Text,
+        created_at -> Timestamp,
+    }
+}
+
+table! {
+    revoked_tokens (jti) {
+        jti -> Text,
+        expires_at -> Timestamp,
+        created_at -> Timestamp,
+    }
+}
+
+table! {
+    sessions (id) {
+        id -> Text,
+        user_id -> Text,
+        token_hash -> Text,
+        previous_token_hash -> Nullable<Text>,
+        user_agent -> Nullable<Text>,
+        ip -> Nullable<Text>,
+        created_at -> Timestamp,
+        last_used_at -> Timestamp,
+        expires_at -> Timestamp,
+    }
+}
+
+table! {
+    share_links (id) {
+        id -> Text,
+        album_id -> Text,
+        user_id -> Text,
+        token -> Text,
+        expires_at -> Nullable<Timestamp>,
+        max_views -> Nullable<Integer>,
+        views -> Integer,
+        revoked -> Bool,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        password_hash -> Nullable<Text>,
+        proofing -> Bool,
+        allow_downloads -> Bool,
+    }
+}
+
+table! {
+    studio_members (studio_id, user_id) {
+        studio_id -> Text,
+        user_id -> Text,
+        role -> Text,
+        created_at -> Timestamp,
+    }
+}
+
+table! {
+    studios (id) {
+        id -> Text,
+        name -> Text,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+table! {
+    tags (id) {
+        id -> Text,
+        user_id -> Text,
+        name -> Text,
+        slug -> Text,
+        created_at -> Timestamp,
+        studio_id -> Text,
     }
 }
 
@@ -53,12 +270,62 @@ table! {
         picture -> Nullable<Text>,
         created_at -> Timestamp,
         updated_at -> Timestamp,
+        is_admin -> Bool,
+        personal_studio_id -> Nullable<Text>,
     }
 }
 
+joinable!(album_slugs -> albums (album_id));
+joinable!(album_slugs -> studios (studio_id));
+joinable!(albums -> studios (studio_id));
 joinable!(albums -> users (user_id));
+joinable!(api_keys -> users (user_id));
+joinable!(audit_events -> users (actor_id));
 joinable!(book_me -> users (user_id));
+joinable!(invitations -> users (invited_by));
+joinable!(login_codes -> users (user_id));
+joinable!(photo_tags -> photos (photo_id));
+joinable!(photo_tags -> tags (tag_id));
+joinable!(photo_uploads -> users (user_id));
+joinable!(photo_versions -> photos (photo_id));
 joinable!(photos -> albums (album_id));
 joinable!(photos -> users (user_id));
+joinable!(proofing_clients -> share_links (share_link_id));
+joinable!(proofing_comments -> photos (photo_id));
+joinable!(proofing_comments -> proofing_clients (client_id));
+joinable!(proofing_selections -> photos (photo_id));
+joinable!(proofing_selections -> proofing_clients (client_id));
+joinable!(sessions -> users (user_id));
+joinable!(share_links -> albums (album_id));
+joinable!(share_links -> users (user_id));
+joinable!(studio_members -> studios (studio_id));
+joinable!(studio_members -> users (user_id));
+joinable!(tags -> studios (studio_id));
+joinable!(tags -> users (user_id));
 
-allow_tables_to_appear_in_same_query!(albums, book_me, custom_migrations, photos, users,);
+allow_tables_to_appear_in_same_query!(
+    album_slugs,
+    albums,
+    api_keys,
+    audit_events,
+    book_me,
+    custom_migrations,
+    invitations,
+    login_codes,
+    login_links,
+    oauth_states,
+    photo_tags,
+    photo_uploads,
+    photo_versions,
+    photos,
+    proofing_clients,
+    proofing_comments,
+    proofing_selections,
+    revoked_tokens,
+    sessions,
+    share_links,
+    studio_members,
+    studios,
+    tags,
+    users,
+);
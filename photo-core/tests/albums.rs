@@ -0,0 +1,309 @@
+mod common;
+
+use common::{album, conn, mark_deleted, photo, user};
+use photo_core::models::{Album, ModelError};
+
+#[test]
+fn summaries_count_photos_and_pick_the_first_as_cover() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let beach = album(&conn, &owner, "Beach");
+    let second = photo(&conn, &beach, &owner, 1, true);
+    let first = photo(&conn, &beach, &owner, 0, false);
+    photo(&conn, &beach, &owner, 2, true);
+    second.delete(&conn).unwrap();
+
+    let summaries = Album::find_all(&conn, &owner).unwrap();
+
+    assert_eq!(summaries.len(), 1);
+    let summary = &summaries[0];
+    assert_eq!(summary.album.id, beach.id);
+    assert_eq!(summary.photos_count, 2);
+    assert_eq!(summary.favorites_count, 1);
+    assert_eq!(summary.cover.as_ref().map(|p| p.id), Some(first.id));
+}
+
+#[test]
+fn summaries_of_empty_albums_have_no_cover() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let empty = album(&conn, &owner, "Empty");
+
+    let summaries = Album::find_all(&conn, &owner).unwrap();
+
+    assert_eq!(summaries.len(), 1);
+    assert_eq!(summaries[0].photos_count, 0);
+    assert_eq!(summaries[0].favorites_count, 0);
+    assert_eq!(summaries[0].cover, None);
+    assert_eq!(summaries[0].last_updated_at, empty.updated_at);
+}
+
+#[test]
+fn summaries_only_include_albums_of_the_user() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let other = user(&conn, "other@example.com");
+    let mine = album(&conn, &owner, "Mine");
+    album(&conn, &other, "Theirs");
+
+    let summaries = Album::find_all(&conn, &owner).unwrap();
+
+    let ids: Vec<_> = summaries.iter().map(|s| s.album.id).collect();
+    assert_eq!(ids, vec![mine.id]);
+}
+
+#[test]
+fn renamed_albums_are_found_by_their_previous_slug() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let summer = album(&conn, &owner, "Summer Trip");
+    let renamed = summer
+        .update(&conn, String::from("Summer in Italy"), None)
+        .unwrap();
+
+    let current = Album::find_by_slug(&conn, &owner, "summer-in-italy").unwrap();
+    let previous = Album::find_by_slug(&conn, &owner, "summer-trip").unwrap();
+
+    assert_eq!(renamed.slug, "summer-in-italy");
+    assert_eq!(current.id, summer.id);
+    assert_eq!(previous.id, summer.id);
+    assert_eq!(previous.slug, "summer-in-italy");
+}
+
+#[test]
+fn taking_a_previous_slug_removes_it_from_the_history() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let first = album(&conn, &owner, "Portraits");
+    first
+        .update(&conn, String::from("Old Portraits"), None)
+        .unwrap();
+    let second = album(&conn, &owner, "Portraits");
+
+    let found = Album::find_by_slug(&conn, &owner, "portraits").unwrap();
+
+    assert_eq!(second.slug, "portraits");
+    assert_eq!(found.id, second.id);
+}
+
+#[test]
+fn slugs_taken_by_other_albums_get_a_suffix() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let other = user(&conn, "other@example.com");
+    album(&conn, &owner, "Weddings");
+
+    let second = album(&conn, &owner, "Weddings");
+    let third = album(&conn, &owner, "Weddings!");
+    let of_other_user = album(&conn, &other, "Weddings");
+
+    assert_eq!(second.slug, "weddings-2");
+    assert_eq!(third.slug, "weddings-3");
+    assert_eq!(of_other_user.slug, "weddings");
+}
+
+#[test]
+fn unknown_slugs_are_not_found() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    album(&conn, &owner, "Landscapes");
+
+    let result = Album::find_by_slug(&conn, &owner, "portraits");
+
+    assert!(matches!(result, Err(ModelError::AlbumNotFound)));
+}
+
+#[test]
+fn slugs_of_other_users_are_not_found() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let other = user(&conn, "other@example.com");
+    album(&conn, &owner, "Landscapes");
+
+    let result = Album::find_by_slug(&conn, &other, "landscapes");
+
+    assert!(matches!(result, Err(ModelError::AlbumNotFound)));
+}
+
+#[test]
+fn deleted_albums_are_not_found_by_current_or_previous_slug() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let landscapes = album(&conn, &owner, "Landscapes")
+        .update(&conn, String::from("Mountains"), None)
+        .unwrap();
+    mark_deleted(&conn, &landscapes);
+
+    let current = Album::find_by_slug(&conn, &owner, "mountains");
+    let previous = Album::find_by_slug(&conn, &owner, "landscapes");
+
+    assert!(matches!(current, Err(ModelError::AlbumNotFound)));
+    assert!(matches!(previous, Err(ModelError::AlbumNotFound)));
+}
+
+#[test]
+fn albums_cannot_be_moved_inside_themselves_or_their_children() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let trips = album(&conn, &owner, "Trips");
+    let italy = album(&conn, &owner, "Italy")
+        .move_to(&conn, Some(&trips))
+        .unwrap();
+    let rome = album(&conn, &owner, "Rome")
+        .move_to(&conn, Some(&italy))
+        .unwrap();
+
+    let into_itself = trips.move_to(&conn, Some(&trips));
+    let into_grandchild = trips.move_to(&conn, Some(&rome));
+
+    assert!(matches!(into_itself, Err(ModelError::AlbumCycle)));
+    assert!(matches!(into_grandchild, Err(ModelError::AlbumCycle)));
+}
+
+#[test]
+fn cycles_are_checked_against_the_stored_parents() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let trips = album(&conn, &owner, "Trips");
+    let italy = album(&conn, &owner, "Italy");
+    // Loaded before it is moved inside `trips`, so its own `parent_id` is stale.
+    let stale_italy = italy.clone();
+    italy.move_to(&conn, Some(&trips)).unwrap();
+
+    let result = trips.move_to(&conn, Some(&stale_italy));
+
+    assert!(matches!(result, Err(ModelError::AlbumCycle)));
+}
+
+#[test]
+fn albums_cannot_be_moved_inside_albums_of_other_users() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let other = user(&conn, "other@example.com");
+    let mine = album(&conn, &owner, "Mine");
+    let theirs = album(&conn, &other, "Theirs");
+
+    let result = mine.move_to(&conn, Some(&theirs));
+
+    assert!(matches!(result, Err(ModelError::InvalidParent)));
+}
+
+#[test]
+fn moved_albums_can_go_back_to_the_root() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let trips = album(&conn, &owner, "Trips");
+    let italy = album(&conn, &owner, "Italy")
+        .move_to(&conn, Some(&trips))
+        .unwrap();
+
+    let moved = italy.move_to(&conn, None).unwrap();
+
+    assert_eq!(italy.parent_id, Some(trips.id));
+    assert_eq!(moved.parent_id, None);
+}
+
+#[test]
+fn tree_nests_albums_inside_their_parents() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let trips = album(&conn, &owner, "Trips");
+    let italy = album(&conn, &owner, "Italy")
+        .move_to(&conn, Some(&trips))
+        .unwrap();
+    let rome = album(&conn, &owner, "Rome")
+        .move_to(&conn, Some(&italy))
+        .unwrap();
+    let portraits = album(&conn, &owner, "Portraits");
+
+    let tree = Album::find_tree(&conn, &owner).unwrap();
+
+    let roots: Vec<_> = tree.iter().map(|n| n.summary.album.id).collect();
+    assert_eq!(roots, vec![trips.id, portraits.id]);
+    assert_eq!(tree[0].children.len(), 1);
+    assert_eq!(tree[0].children[0].summary.album.id, italy.id);
+    assert_eq!(tree[0].children[0].children[0].summary.album.id, rome.id);
+    assert!(tree[1].children.is_empty());
+}
+
+#[test]
+fn albums_inside_deleted_albums_become_roots() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let trips = album(&conn, &owner, "Trips");
+    let italy = album(&conn, &owner, "Italy")
+        .move_to(&conn, Some(&trips))
+        .unwrap();
+    mark_deleted(&conn, &trips);
+
+    let tree = Album::find_tree(&conn, &owner).unwrap();
+
+    let roots: Vec<_> = tree.iter().map(|n| n.summary.album.id).collect();
+    assert_eq!(roots, vec![italy.id]);
+}
+
+#[test]
+fn children_only_include_albums_directly_inside_the_parent() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let trips = album(&conn, &owner, "Trips");
+    let italy = album(&conn, &owner, "Italy")
+        .move_to(&conn, Some(&trips))
+        .unwrap();
+    album(&conn, &owner, "Rome")
+        .move_to(&conn, Some(&italy))
+        .unwrap();
+
+    let roots = Album::find_children(&conn, &owner, None).unwrap();
+    let inside_trips = Album::find_children(&conn, &owner, Some(&trips)).unwrap();
+
+    let root_ids: Vec<_> = roots.iter().map(|s| s.album.id).collect();
+    let child_ids: Vec<_> = inside_trips.iter().map(|s| s.album.id).collect();
+    assert_eq!(root_ids, vec![trips.id]);
+    assert_eq!(child_ids, vec![italy.id]);
+}
+
+#[test]
+fn albums_inside_deleted_albums_are_root_children() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let trips = album(&conn, &owner, "Trips");
+    let italy = album(&conn, &owner, "Italy")
+        .move_to(&conn, Some(&trips))
+        .unwrap();
+    let portraits = album(&conn, &owner, "Portraits");
+    mark_deleted(&conn, &trips);
+
+    let roots = Album::find_children(&conn, &owner, None).unwrap();
+
+    let root_ids: Vec<_> = roots.iter().map(|s| s.album.id).collect();
+    assert_eq!(root_ids, vec![italy.id, portraits.id]);
+}
+
+#[test]
+fn protected_albums_only_accept_their_password() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let wedding = album(&conn, &owner, "Wedding");
+
+    let protected = wedding.set_password(&conn, Some("s3cret")).unwrap();
+
+    assert!(!wedding.is_protected());
+    assert!(protected.is_protected());
+    assert!(protected.verify_password("s3cret"));
+    assert!(!protected.verify_password("S3cret"));
+    assert!(!protected.verify_password(""));
+}
+
+#[test]
+fn removing_the_password_unprotects_the_album() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let wedding = album(&conn, &owner, "Wedding");
+    let protected = wedding.set_password(&conn, Some("s3cret")).unwrap();
+
+    let public = protected.set_password(&conn, None).unwrap();
+
+    assert!(!public.is_protected());
+    assert!(!public.verify_password("s3cret"));
+}
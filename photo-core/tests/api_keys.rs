@@ -0,0 +1,68 @@
+mod common;
+
+use common::{conn, user};
+use photo_core::models::{ApiKey, ApiKeyScope, ModelError, API_KEY_PREFIX};
+
+#[test]
+fn keys_are_found_by_their_value() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let (api_key, key) = ApiKey::new(&owner, String::from("backup"), ApiKeyScope::Read);
+    let api_key = api_key.insert(&conn).unwrap();
+
+    let found = ApiKey::authenticate(&conn, &key).unwrap();
+    let unknown = ApiKey::authenticate(&conn, "pk_unknown");
+
+    assert!(key.starts_with(API_KEY_PREFIX));
+    assert!(key.starts_with(&api_key.prefix));
+    assert_eq!(found.id, api_key.id);
+    assert!(found.last_used_at.is_some());
+    assert!(matches!(unknown, Err(ModelError::InvalidApiKey)));
+}
+
+#[test]
+fn only_write_keys_can_write() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let (read, _) = ApiKey::new(&owner, String::from("read"), ApiKeyScope::Read);
+    let (write, _) = ApiKey::new(&owner, String::from("write"), ApiKeyScope::Write);
+
+    assert!(!read.insert(&conn).unwrap().can_write());
+    assert!(write.insert(&conn).unwrap().can_write());
+}
+
+#[test]
+fn revoked_keys_stop_working() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let other = user(&conn, "other@example.com");
+    let (api_key, key) = ApiKey::new(&owner, String::from("backup"), ApiKeyScope::Write);
+    let api_key = api_key.insert(&conn).unwrap();
+    let id = api_key.id.to_string();
+
+    let by_other = ApiKey::revoke(&conn, &other, &id);
+    let still_valid = ApiKey::authenticate(&conn, &key);
+    ApiKey::revoke(&conn, &owner, &id).unwrap();
+    let revoked = ApiKey::authenticate(&conn, &key);
+
+    assert!(matches!(by_other, Err(ModelError::ApiKeyNotFound)));
+    assert!(still_valid.is_ok());
+    assert!(matches!(revoked, Err(ModelError::InvalidApiKey)));
+    assert!(ApiKey::find_by_user(&conn, &owner).unwrap().is_empty());
+}
+
+#[test]
+fn users_only_list_their_own_keys() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let other = user(&conn, "other@example.com");
+    let (mine, _) = ApiKey::new(&owner, String::from("mine"), ApiKeyScope::Read);
+    let (theirs, _) = ApiKey::new(&other, String::from("theirs"), ApiKeyScope::Read);
+    mine.insert(&conn).unwrap();
+    theirs.insert(&conn).unwrap();
+
+    let list = ApiKey::find_by_user(&conn, &owner).unwrap();
+
+    assert_eq!(list.len(), 1);
+    assert_eq!(list[0].name, "mine");
+}
@@ -0,0 +1,181 @@
+mod common;
+
+use common::{album_in, conn, photo, studio, user};
+use photo_core::connection::Conn;
+use photo_core::models::{
+    transaction, AuditAction, AuditContext, AuditEvent, AuditFilter, AuditTarget, ModelError, Role,
+    User,
+};
+use serde_json::Value;
+
+fn context(user: &User) -> AuditContext {
+    AuditContext {
+        actor_id: Some(user.id),
+        ip: Some(String::from("127.0.0.1")),
+        request_id: None,
+    }
+}
+
+fn events(conn: &Conn, filter: &AuditFilter) -> Vec<AuditEvent> {
+    AuditEvent::find(conn, filter, 50, None).unwrap().list
+}
+
+#[test]
+fn only_changed_fields_are_recorded() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let team = studio(&conn, &owner, "Team");
+    let album = album_in(&conn, &team, &owner, "Wedding");
+    let updated = album
+        .update(&conn, String::from("Reception"), album.description.clone())
+        .unwrap();
+
+    let audit = context(&owner);
+    audit
+        .record(&conn, AuditAction::Create, None, Some(&album))
+        .unwrap();
+    audit
+        .record(&conn, AuditAction::Update, Some(&album), Some(&updated))
+        .unwrap();
+
+    let events = events(&conn, &AuditFilter::default());
+    let update = events.iter().find(|e| e.action == "update").unwrap();
+    let create = events.iter().find(|e| e.action == "create").unwrap();
+    let changes: Value = serde_json::from_str(&update.changes).unwrap();
+    let created: Value = serde_json::from_str(&create.changes).unwrap();
+
+    assert_eq!(events.len(), 2);
+    assert_eq!(update.target_type, "album");
+    assert_eq!(update.target_id, album.id);
+    assert_eq!(update.actor_id, Some(owner.id));
+    assert_eq!(update.studio_id, Some(team.id));
+    assert_eq!(changes["name"]["before"], "Wedding");
+    assert_eq!(changes["name"]["after"], "Reception");
+    assert!(changes.get("id").is_none());
+    assert_eq!(created["name"]["before"], Value::Null);
+    assert_eq!(created["name"]["after"], "Wedding");
+}
+
+#[test]
+fn events_are_rolled_back_with_the_change() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let team = studio(&conn, &owner, "Team");
+    let album = album_in(&conn, &team, &owner, "Wedding");
+
+    let failed: Result<(), ModelError> = transaction(&conn, || {
+        context(&owner).record(&conn, AuditAction::Delete, Some(&album), None)?;
+        Err(ModelError::AlbumNotFound)
+    });
+
+    assert!(failed.is_err());
+    assert!(events(&conn, &AuditFilter::default()).is_empty());
+}
+
+#[test]
+fn deleted_photos_stay_visible_to_the_studio_owners() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let editor = user(&conn, "editor@example.com");
+    let stranger = user(&conn, "stranger@example.com");
+    let team = studio(&conn, &owner, "Team");
+    team.set_member(&conn, &editor, Role::Editor).unwrap();
+    let album = album_in(&conn, &team, &owner, "Wedding");
+    let picture = photo(&conn, &album, &editor, 0, false);
+
+    transaction(&conn, || {
+        picture.delete(&conn)?;
+        context(&editor).record(&conn, AuditAction::Delete, Some(&picture), None)
+    })
+    .unwrap();
+
+    let visible_to = |user: &User| AuditFilter {
+        visible_to: Some(user.clone()),
+        ..AuditFilter::default()
+    };
+    let seen_by_owner = events(&conn, &visible_to(&owner));
+    let seen_by_editor = events(&conn, &visible_to(&editor));
+
+    assert_eq!(seen_by_owner.len(), 1);
+    assert_eq!(seen_by_owner[0].target_id, picture.id);
+    assert_eq!(seen_by_owner[0].studio_id, Some(team.id));
+    assert_eq!(seen_by_editor.len(), 1);
+    assert!(events(&conn, &visible_to(&stranger)).is_empty());
+}
+
+#[test]
+fn events_are_filtered_by_action_and_target() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let team = studio(&conn, &owner, "Team");
+    let album = album_in(&conn, &team, &owner, "Wedding");
+    let picture = photo(&conn, &album, &owner, 0, false);
+
+    let audit = context(&owner);
+    audit
+        .record(&conn, AuditAction::Create, None, Some(&album))
+        .unwrap();
+    audit
+        .record(&conn, AuditAction::Create, None, Some(&picture))
+        .unwrap();
+    audit
+        .record(&conn, AuditAction::Delete, Some(&picture), None)
+        .unwrap();
+
+    let created_photos = events(
+        &conn,
+        &AuditFilter {
+            action: Some(AuditAction::Create),
+            target: Some(AuditTarget::Photo),
+            ..AuditFilter::default()
+        },
+    );
+    let of_album = events(
+        &conn,
+        &AuditFilter {
+            target_id: Some(album.id.to_string()),
+            ..AuditFilter::default()
+        },
+    );
+
+    assert_eq!(created_photos.len(), 1);
+    assert_eq!(created_photos[0].target_id, picture.id);
+    assert_eq!(of_album.len(), 1);
+    assert_eq!(of_album[0].target_type, "album");
+}
+
+#[test]
+fn pages_are_stable_when_events_are_recorded_in_between() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let team = studio(&conn, &owner, "Team");
+    let albums: Vec<_> = (0..3)
+        .map(|i| album_in(&conn, &team, &owner, &format!("Album {}", i)))
+        .collect();
+    let audit = context(&owner);
+    for album in &albums {
+        audit
+            .record(&conn, AuditAction::Create, None, Some(album))
+            .unwrap();
+    }
+    let filter = AuditFilter::default();
+
+    let first = AuditEvent::find(&conn, &filter, 2, None).unwrap();
+    let late = album_in(&conn, &team, &owner, "Late");
+    audit
+        .record(&conn, AuditAction::Create, None, Some(&late))
+        .unwrap();
+    let second = AuditEvent::find(&conn, &filter, 2, first.next_cursor.as_deref()).unwrap();
+    let invalid = AuditEvent::find(&conn, &filter, 2, Some("not-a-cursor"));
+
+    let seen: Vec<_> = first
+        .list
+        .iter()
+        .chain(second.list.iter())
+        .map(|e| e.target_id)
+        .collect();
+    assert_eq!(seen.len(), 3);
+    assert!(albums.iter().all(|album| seen.contains(&album.id)));
+    assert!(second.next_cursor.is_none());
+    assert!(matches!(invalid, Err(ModelError::InvalidCursor)));
+}
@@ -0,0 +1,153 @@
+mod common;
+
+use common::{album, album_in, conn, photo, studio, user};
+use photo_core::models::{
+    BulkStatus, ModelError, Photo, PhotoImage, PhotoOperation, Tag, MAX_BULK_PHOTOS,
+};
+
+fn ids(photos: &[&Photo]) -> Vec<String> {
+    photos.iter().map(|p| p.id.to_string()).collect()
+}
+
+#[test]
+fn photos_of_other_users_are_not_found() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let other = user(&conn, "other@example.com");
+    let mine = photo(&conn, &album(&conn, &owner, "Mine"), &owner, 0, false);
+    let theirs = photo(&conn, &album(&conn, &other, "Theirs"), &other, 0, false);
+
+    let outcome = Photo::bulk(
+        &conn,
+        &owner,
+        &ids(&[&mine, &theirs]),
+        &PhotoOperation::SetFavorite(true),
+    )
+    .unwrap();
+
+    let statuses: Vec<_> = outcome.results.iter().map(|r| r.status).collect();
+    assert_eq!(statuses, vec![BulkStatus::Done, BulkStatus::NotFound]);
+    assert!(
+        Photo::find_by_id(&conn, &mine.id.to_string())
+            .unwrap()
+            .is_favorite
+    );
+    assert!(
+        !Photo::find_by_id(&conn, &theirs.id.to_string())
+            .unwrap()
+            .is_favorite
+    );
+}
+
+#[test]
+fn moved_photos_go_to_the_end_of_the_album() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let wedding = album(&conn, &owner, "Wedding");
+    let party = album(&conn, &owner, "Party");
+    photo(&conn, &party, &owner, 0, false);
+    let first = photo(&conn, &wedding, &owner, 0, false);
+    let second = photo(&conn, &wedding, &owner, 1, false);
+
+    Photo::bulk(
+        &conn,
+        &owner,
+        &ids(&[&second, &first]),
+        &PhotoOperation::MoveTo(party.clone()),
+    )
+    .unwrap();
+
+    let first = Photo::find_by_id(&conn, &first.id.to_string()).unwrap();
+    let second = Photo::find_by_id(&conn, &second.id.to_string()).unwrap();
+    assert_eq!(first.album_id, party.id);
+    assert_eq!((first.index_in_album, second.index_in_album), (1, 2));
+}
+
+#[test]
+fn photos_are_not_moved_to_another_studio() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let wedding = album(&conn, &owner, "Wedding");
+    let mine = photo(&conn, &wedding, &owner, 0, false);
+    let agency = studio(&conn, &owner, "Agency");
+    let portfolio = album_in(&conn, &agency, &owner, "Portfolio");
+
+    let outcome = Photo::bulk(
+        &conn,
+        &owner,
+        &ids(&[&mine]),
+        &PhotoOperation::MoveTo(portfolio),
+    )
+    .unwrap();
+
+    assert_eq!(outcome.results[0].status, BulkStatus::NotFound);
+    let mine = Photo::find_by_id(&conn, &mine.id.to_string()).unwrap();
+    assert_eq!(mine.album_id, wedding.id);
+}
+
+#[test]
+fn tags_are_added_to_every_photo() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let wedding = album(&conn, &owner, "Wedding");
+    let first = photo(&conn, &wedding, &owner, 0, false);
+    let second = photo(&conn, &wedding, &owner, 1, false);
+
+    Photo::bulk(
+        &conn,
+        &owner,
+        &ids(&[&first, &second]),
+        &PhotoOperation::AddTag(String::from("Ceremony")),
+    )
+    .unwrap();
+
+    let tagged = Tag::photo_ids(&conn, &wedding.studio_id, "ceremony").unwrap();
+    assert!(tagged.contains(&first.id));
+    assert!(tagged.contains(&second.id));
+}
+
+#[test]
+fn deleting_returns_the_files_left_behind() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let wedding = album(&conn, &owner, "Wedding");
+    let edited = photo(&conn, &wedding, &owner, 0, false);
+    let original_key = edited.s3_id.clone();
+    let edited = edited
+        .replace_image(
+            &conn,
+            &PhotoImage {
+                s3_id: String::from("edited"),
+                src: String::from("https://cdn.example.com/edited"),
+                main_color: String::from("#000000"),
+                width: 800,
+                height: 600,
+                filename: None,
+            },
+        )
+        .unwrap();
+
+    let outcome = Photo::bulk(&conn, &owner, &ids(&[&edited]), &PhotoOperation::Delete).unwrap();
+
+    assert_eq!(outcome.results[0].status, BulkStatus::Done);
+    assert!(outcome.unused_objects.contains(&String::from("edited")));
+    assert!(outcome.unused_objects.contains(&original_key));
+    assert!(Photo::find_by_id(&conn, &edited.id.to_string()).is_err());
+}
+
+#[test]
+fn too_many_photos_are_rejected() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let mine = photo(&conn, &album(&conn, &owner, "Mine"), &owner, 0, false);
+    let p_ids = vec![mine.id.to_string(); MAX_BULK_PHOTOS + 1];
+
+    let outcome = Photo::bulk(&conn, &owner, &p_ids, &PhotoOperation::SetFavorite(true));
+
+    assert!(matches!(outcome, Err(ModelError::TooManyPhotos)));
+    assert!(
+        !Photo::find_by_id(&conn, &mine.id.to_string())
+            .unwrap()
+            .is_favorite
+    );
+}
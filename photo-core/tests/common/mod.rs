@@ -0,0 +1,72 @@
+#![allow(dead_code)]
+
+use diesel::prelude::*;
+use diesel_migrations::run_pending_migrations_in_directory;
+use photo_core::connection::Conn;
+use photo_core::models::{Album, Photo, Studio, User};
+use photo_core::schema::albums;
+use std::io;
+use std::path::Path;
+
+/// In-memory database with every migration applied.
+pub fn conn() -> Conn {
+    let conn = Conn::establish(":memory:").unwrap();
+    let migrations = Path::new(env!("CARGO_MANIFEST_DIR")).join("migrations");
+    run_pending_migrations_in_directory(&conn, &migrations, &mut io::sink()).unwrap();
+
+    conn
+}
+
+pub fn user(conn: &Conn, email: &str) -> User {
+    User::new(String::from(email), None).insert(conn).unwrap()
+}
+
+/// Album in the studio of the user, created along with the first album like at sign up.
+pub fn album(conn: &Conn, user: &User, name: &str) -> Album {
+    let studio = match Studio::find_personal(conn, user) {
+        Ok(studio) => studio,
+        Err(_) => Studio::new(user.email.clone())
+            .insert_personal(conn, user)
+            .unwrap(),
+    };
+
+    album_in(conn, &studio, user, name)
+}
+
+pub fn studio(conn: &Conn, owner: &User, name: &str) -> Studio {
+    Studio::new(String::from(name)).insert(conn, owner).unwrap()
+}
+
+pub fn album_in(conn: &Conn, studio: &Studio, user: &User, name: &str) -> Album {
+    Album::new(user, studio, String::from(name), None)
+        .insert(conn)
+        .unwrap()
+}
+
+pub fn photo(conn: &Conn, album: &Album, user: &User, index: i32, is_favorite: bool) -> Photo {
+    Photo::new(
+        album,
+        user,
+        index,
+        format!("s3-{}", index),
+        format!("https://photos.example.com/{}.jpg", index),
+        String::from("#fff"),
+        None,
+        None,
+        100,
+        100,
+        is_favorite,
+        Some(format!("IMG_{:04}.jpg", index)),
+        None,
+    )
+    .insert(conn)
+    .unwrap()
+}
+
+/// Flags the album as deleted without removing its row.
+pub fn mark_deleted(conn: &Conn, album: &Album) {
+    diesel::update(albums::table.find(album.id))
+        .set(albums::deleted.eq(true))
+        .execute(conn)
+        .unwrap();
+}
@@ -0,0 +1,96 @@
+mod common;
+
+use common::{album, conn, mark_deleted, photo, user};
+use photo_core::models::{ModelError, Photo, PhotoPagination, PhotoSort, User};
+
+fn by_index(limit: Option<usize>, cursor: Option<String>) -> PhotoPagination {
+    PhotoPagination {
+        sort: PhotoSort::Index,
+        limit,
+        cursor,
+    }
+}
+
+#[test]
+fn favorites_come_from_every_album_of_the_user() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let other = user(&conn, "other@example.com");
+    let wedding = album(&conn, &owner, "Wedding");
+    let party = album(&conn, &owner, "Party");
+    let theirs = album(&conn, &other, "Theirs");
+    let first = photo(&conn, &wedding, &owner, 0, true);
+    photo(&conn, &wedding, &owner, 1, false);
+    let second = photo(&conn, &party, &owner, 2, true);
+    photo(&conn, &theirs, &other, 3, true);
+
+    let (page, albums) =
+        Photo::find_favorites(&conn, &owner, false, &by_index(None, None)).unwrap();
+
+    let ids: Vec<_> = page.list.iter().map(|p| p.id).collect();
+    assert_eq!(ids, vec![first.id, second.id]);
+    assert_eq!(page.total, 2);
+    assert_eq!(albums.len(), 2);
+}
+
+#[test]
+fn public_favorites_skip_protected_and_deleted_albums() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let public = album(&conn, &owner, "Public");
+    let protected = album(&conn, &owner, "Protected")
+        .set_password(&conn, Some("s3cret"))
+        .unwrap();
+    let deleted = album(&conn, &owner, "Deleted");
+    let shown = photo(&conn, &public, &owner, 0, true);
+    photo(&conn, &protected, &owner, 1, true);
+    photo(&conn, &deleted, &owner, 2, true);
+    mark_deleted(&conn, &deleted);
+
+    let (public_page, _) =
+        Photo::find_favorites(&conn, &owner, true, &by_index(None, None)).unwrap();
+    let (private_page, _) =
+        Photo::find_favorites(&conn, &owner, false, &by_index(None, None)).unwrap();
+
+    assert_eq!(public_page.total, 1);
+    assert_eq!(public_page.list[0].id, shown.id);
+    assert_eq!(private_page.total, 2);
+}
+
+#[test]
+fn favorites_are_paginated_with_the_albums_of_the_page() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let wedding = album(&conn, &owner, "Wedding");
+    let party = album(&conn, &owner, "Party");
+    photo(&conn, &wedding, &owner, 0, true);
+    photo(&conn, &wedding, &owner, 1, true);
+    let last = photo(&conn, &party, &owner, 2, true);
+
+    let (first, first_albums) =
+        Photo::find_favorites(&conn, &owner, false, &by_index(Some(2), None)).unwrap();
+    let (second, second_albums) = Photo::find_favorites(
+        &conn,
+        &owner,
+        false,
+        &by_index(Some(2), first.next_cursor.clone()),
+    )
+    .unwrap();
+
+    assert_eq!(first.total, 3);
+    assert_eq!(first.list.len(), 2);
+    assert_eq!(first_albums.len(), 1);
+    assert_eq!(first_albums[0].id, wedding.id);
+    assert_eq!(second.list[0].id, last.id);
+    assert_eq!(second_albums[0].id, party.id);
+    assert!(second.next_cursor.is_none());
+}
+
+#[test]
+fn unknown_users_are_not_found() {
+    let conn = conn();
+
+    let result = User::find_by_id(&conn, "00000000-0000-0000-0000-000000000000");
+
+    assert!(matches!(result, Err(ModelError::UserNotFound)));
+}
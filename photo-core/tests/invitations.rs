@@ -0,0 +1,128 @@
+mod common;
+
+use chrono::{Duration, Utc};
+use common::{conn, user};
+use photo_core::models::{Invitation, ModelError, User};
+
+#[test]
+fn only_invitations_that_did_not_expire_allow_signing_up() {
+    let conn = conn();
+    let now = Utc::now().naive_utc();
+    Invitation::new(String::from("forever@example.com"), None, None)
+        .insert(&conn)
+        .unwrap();
+    Invitation::new(
+        String::from("soon@example.com"),
+        None,
+        Some(now + Duration::days(1)),
+    )
+    .insert(&conn)
+    .unwrap();
+    Invitation::new(
+        String::from("late@example.com"),
+        None,
+        Some(now - Duration::days(1)),
+    )
+    .insert(&conn)
+    .unwrap();
+
+    assert!(Invitation::is_invited(&conn, "forever@example.com").unwrap());
+    assert!(Invitation::is_invited(&conn, "soon@example.com").unwrap());
+    assert!(!Invitation::is_invited(&conn, "late@example.com").unwrap());
+    assert!(!Invitation::is_invited(&conn, "stranger@example.com").unwrap());
+}
+
+#[test]
+fn inviting_again_replaces_the_invitation() {
+    let conn = conn();
+    let admin = user(&conn, "admin@example.com");
+    let expired = Utc::now().naive_utc() - Duration::days(1);
+    Invitation::new(String::from("guest@example.com"), None, Some(expired))
+        .insert(&conn)
+        .unwrap();
+
+    let invitation = Invitation::new(String::from("guest@example.com"), Some(&admin), None)
+        .insert(&conn)
+        .unwrap();
+
+    let all = Invitation::find_all(&conn).unwrap();
+    assert_eq!(all.len(), 1);
+    assert_eq!(all[0].id, invitation.id);
+    assert_eq!(all[0].invited_by, Some(admin.id));
+    assert!(Invitation::is_invited(&conn, "guest@example.com").unwrap());
+}
+
+#[test]
+fn import_keeps_existing_invitations() {
+    let conn = conn();
+    let admin = user(&conn, "admin@example.com");
+    Invitation::new(String::from("guest@example.com"), Some(&admin), None)
+        .insert(&conn)
+        .unwrap();
+
+    let imported = Invitation::import(
+        &conn,
+        &[
+            String::from("guest@example.com"),
+            String::from("friend@example.com"),
+        ],
+    )
+    .unwrap();
+
+    let all = Invitation::find_all(&conn).unwrap();
+    let guest = all.iter().find(|i| i.email == "guest@example.com").unwrap();
+    assert_eq!(imported, 1);
+    assert_eq!(all.len(), 2);
+    assert_eq!(guest.invited_by, Some(admin.id));
+}
+
+#[test]
+fn imported_emails_are_invited_whatever_their_case() {
+    let conn = conn();
+
+    Invitation::import(&conn, &[String::from("Guest@Example.com")]).unwrap();
+
+    let all = Invitation::find_all(&conn).unwrap();
+    assert_eq!(all[0].email, "guest@example.com");
+    assert!(Invitation::is_invited(&conn, "GUEST@example.com").unwrap());
+}
+
+#[test]
+fn accepting_records_when_the_account_was_created() {
+    let conn = conn();
+    Invitation::new(String::from("guest@example.com"), None, None)
+        .insert(&conn)
+        .unwrap();
+
+    Invitation::accept(&conn, "guest@example.com").unwrap();
+
+    let all = Invitation::find_all(&conn).unwrap();
+    assert!(all[0].accepted_at.is_some());
+}
+
+#[test]
+fn revoked_invitations_no_longer_allow_signing_up() {
+    let conn = conn();
+    let invitation = Invitation::new(String::from("guest@example.com"), None, None)
+        .insert(&conn)
+        .unwrap();
+
+    Invitation::revoke(&conn, &invitation.id.to_string()).unwrap();
+    let again = Invitation::revoke(&conn, &invitation.id.to_string());
+
+    assert!(!Invitation::is_invited(&conn, "guest@example.com").unwrap());
+    assert!(matches!(again, Err(ModelError::InvitationNotFound)));
+}
+
+#[test]
+fn has_admin_only_counts_admins() {
+    let conn = conn();
+    user(&conn, "user@example.com");
+    assert!(!User::has_admin(&conn).unwrap());
+
+    let mut admin = User::new(String::from("admin@example.com"), None);
+    admin.is_admin = true;
+    admin.insert(&conn).unwrap();
+
+    assert!(User::has_admin(&conn).unwrap());
+}
@@ -0,0 +1,64 @@
+mod common;
+
+use chrono::{Duration, Utc};
+use common::conn;
+use photo_core::models::{LoginLink, ModelError};
+
+#[test]
+fn links_can_only_be_used_once() {
+    let conn = conn();
+    let (link, token) = LoginLink::new(String::from("guest@example.com"));
+    link.insert(&conn).unwrap();
+
+    let taken = LoginLink::take(&conn, &token).unwrap();
+    let again = LoginLink::take(&conn, &token);
+
+    assert_eq!(taken.email, "guest@example.com");
+    assert!(matches!(again, Err(ModelError::InvalidLoginLink)));
+}
+
+#[test]
+fn unknown_links_are_rejected() {
+    let conn = conn();
+    let (link, _) = LoginLink::new(String::from("guest@example.com"));
+    link.insert(&conn).unwrap();
+
+    let result = LoginLink::take(&conn, "unknown");
+
+    assert!(matches!(result, Err(ModelError::InvalidLoginLink)));
+}
+
+#[test]
+fn expired_links_are_rejected_and_cleaned_up() {
+    let conn = conn();
+    let (mut expired, expired_token) = LoginLink::new(String::from("guest@example.com"));
+    expired.expires_at = Utc::now().naive_utc() - Duration::minutes(1);
+    expired.insert(&conn).unwrap();
+    let (mut abandoned, abandoned_token) = LoginLink::new(String::from("guest@example.com"));
+    abandoned.expires_at = expired.expires_at;
+    abandoned.insert(&conn).unwrap();
+
+    let result = LoginLink::take(&conn, &expired_token);
+    let (fresh, fresh_token) = LoginLink::new(String::from("guest@example.com"));
+    fresh.insert(&conn).unwrap();
+
+    assert!(matches!(result, Err(ModelError::InvalidLoginLink)));
+    assert!(matches!(
+        LoginLink::take(&conn, &abandoned_token),
+        Err(ModelError::InvalidLoginLink)
+    ));
+    assert!(LoginLink::take(&conn, &fresh_token).is_ok());
+}
+
+#[test]
+fn tokens_are_not_stored() {
+    let conn = conn();
+    let (link, token) = LoginLink::new(String::from("guest@example.com"));
+    link.insert(&conn).unwrap();
+
+    assert_ne!(link.token_hash, token);
+    assert!(matches!(
+        LoginLink::take(&conn, &link.token_hash),
+        Err(ModelError::InvalidLoginLink)
+    ));
+}
@@ -0,0 +1,84 @@
+mod common;
+
+use chrono::{Duration, Utc};
+use common::conn;
+use photo_core::models::{ModelError, OAuthState, OAUTH_STATE_EXPIRY};
+
+#[test]
+fn states_can_only_be_taken_once() {
+    let conn = conn();
+    OAuthState::new(
+        String::from("google"),
+        String::from("state"),
+        String::from("verifier"),
+    )
+    .insert(&conn)
+    .unwrap();
+
+    let taken = OAuthState::take(&conn, "google", "state").unwrap();
+    let again = OAuthState::take(&conn, "google", "state");
+
+    assert_eq!(taken.pkce_verifier, "verifier");
+    assert!(matches!(again, Err(ModelError::InvalidOAuthState)));
+}
+
+#[test]
+fn unknown_states_are_rejected() {
+    let conn = conn();
+
+    let result = OAuthState::take(&conn, "google", "unknown");
+
+    assert!(matches!(result, Err(ModelError::InvalidOAuthState)));
+}
+
+#[test]
+fn expired_states_are_rejected_and_cleaned_up() {
+    let conn = conn();
+    let mut abandoned = OAuthState::new(
+        String::from("google"),
+        String::from("abandoned"),
+        String::from("verifier"),
+    );
+    abandoned.created_at = Utc::now().naive_utc() - Duration::seconds(OAUTH_STATE_EXPIRY + 1);
+    abandoned.insert(&conn).unwrap();
+    let mut expired = OAuthState::new(
+        String::from("google"),
+        String::from("expired"),
+        String::from("verifier"),
+    );
+    expired.created_at = abandoned.created_at;
+    expired.insert(&conn).unwrap();
+
+    let result = OAuthState::take(&conn, "google", "expired");
+    OAuthState::new(
+        String::from("google"),
+        String::from("fresh"),
+        String::from("verifier"),
+    )
+    .insert(&conn)
+    .unwrap();
+
+    assert!(matches!(result, Err(ModelError::InvalidOAuthState)));
+    assert!(matches!(
+        OAuthState::take(&conn, "google", "abandoned"),
+        Err(ModelError::InvalidOAuthState)
+    ));
+    assert!(OAuthState::take(&conn, "google", "fresh").is_ok());
+}
+
+#[test]
+fn states_are_only_taken_by_their_provider() {
+    let conn = conn();
+    OAuthState::new(
+        String::from("google"),
+        String::from("state"),
+        String::from("verifier"),
+    )
+    .insert(&conn)
+    .unwrap();
+
+    let other = OAuthState::take(&conn, "gitlab", "state");
+
+    assert!(matches!(other, Err(ModelError::InvalidOAuthState)));
+    assert!(OAuthState::take(&conn, "google", "state").is_ok());
+}
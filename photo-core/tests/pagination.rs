@@ -0,0 +1,199 @@
+mod common;
+
+use chrono::{Duration, NaiveDate};
+use common::{album, conn, photo, user};
+use diesel::prelude::*;
+use photo_core::connection::Conn;
+use photo_core::helpers::uuid::Uuid;
+use photo_core::models::{Album, ModelError, Photo, PhotoPagination, PhotoSort, Tag, User};
+use photo_core::schema::photos;
+
+fn set_taken_at(conn: &Conn, photo: &Photo, days: Option<i64>) {
+    let taken = days.map(|d| NaiveDate::from_ymd(2020, 6, 1).and_hms(12, 0, 0) + Duration::days(d));
+
+    diesel::update(photos::table.find(photo.id))
+        .set(photos::taken_at.eq(taken))
+        .execute(conn)
+        .unwrap();
+}
+
+fn page(sort: PhotoSort, limit: Option<usize>, cursor: Option<String>) -> PhotoPagination {
+    PhotoPagination {
+        sort,
+        limit,
+        cursor,
+    }
+}
+
+/// Ids of every photo of the album, reading pages of `limit` photos until the last one.
+fn walk(conn: &Conn, album: &Album, sort: PhotoSort, limit: usize) -> Vec<Uuid> {
+    let mut ids = Vec::new();
+    let mut cursor = None;
+
+    loop {
+        let found = album
+            .photo_page(conn, None, &page(sort, Some(limit), cursor))
+            .unwrap();
+        assert!(found.list.len() <= limit);
+        ids.extend(found.list.iter().map(|p| p.id));
+
+        cursor = found.next_cursor;
+        if cursor.is_none() {
+            return ids;
+        }
+    }
+}
+
+/// Album with 5 photos: indexes 3, 1, 4, 0, 2 (in creation order), two favorites and capture
+/// dates for all but one.
+fn wedding(conn: &Conn) -> (User, Album, Vec<Photo>) {
+    let owner = user(conn, "owner@example.com");
+    let wedding = album(conn, &owner, "Wedding");
+    let list: Vec<Photo> = [(3, false), (1, true), (4, false), (0, false), (2, true)]
+        .iter()
+        .map(|(index, favorite)| photo(conn, &wedding, &owner, *index, *favorite))
+        .collect();
+
+    set_taken_at(conn, &list[0], Some(2));
+    set_taken_at(conn, &list[1], None);
+    set_taken_at(conn, &list[2], Some(0));
+    set_taken_at(conn, &list[3], Some(1));
+    set_taken_at(conn, &list[4], Some(1));
+
+    (owner, wedding, list)
+}
+
+#[test]
+fn pages_follow_each_sort() {
+    let conn = conn();
+    let (_, wedding, list) = wedding(&conn);
+    let ids = |order: &[usize]| order.iter().map(|i| list[*i].id).collect::<Vec<_>>();
+
+    assert_eq!(
+        walk(&conn, &wedding, PhotoSort::Index, 2),
+        ids(&[3, 1, 4, 0, 2])
+    );
+    assert_eq!(
+        walk(&conn, &wedding, PhotoSort::TakenAt, 2),
+        ids(&[2, 3, 4, 0, 1])
+    );
+    assert_eq!(
+        walk(&conn, &wedding, PhotoSort::Favorites, 2),
+        ids(&[1, 4, 3, 0, 2])
+    );
+    assert_eq!(
+        walk(&conn, &wedding, PhotoSort::CreatedAt, 2).len(),
+        list.len()
+    );
+}
+
+#[test]
+fn paging_matches_the_full_listing() {
+    let conn = conn();
+    let (_, wedding, _) = wedding(&conn);
+
+    for sort in &[
+        PhotoSort::Index,
+        PhotoSort::TakenAt,
+        PhotoSort::CreatedAt,
+        PhotoSort::Favorites,
+    ] {
+        let all = wedding
+            .photo_page(&conn, None, &page(*sort, None, None))
+            .unwrap();
+        let all: Vec<Uuid> = all.list.iter().map(|p| p.id).collect();
+
+        for limit in 1..=5 {
+            assert_eq!(walk(&conn, &wedding, *sort, limit), all, "{:?}", sort);
+        }
+    }
+}
+
+#[test]
+fn pages_report_the_total_and_the_next_cursor() {
+    let conn = conn();
+    let (_, wedding, _) = wedding(&conn);
+
+    let first = wedding
+        .photo_page(&conn, None, &page(PhotoSort::Index, Some(3), None))
+        .unwrap();
+    let last = wedding
+        .photo_page(
+            &conn,
+            None,
+            &page(PhotoSort::Index, Some(3), first.next_cursor.clone()),
+        )
+        .unwrap();
+
+    assert_eq!(first.total, 5);
+    assert_eq!(first.list.len(), 3);
+    assert!(first.next_cursor.is_some());
+    assert_eq!(last.total, 5);
+    assert_eq!(last.list.len(), 2);
+    assert!(last.next_cursor.is_none());
+}
+
+#[test]
+fn photos_added_between_pages_are_not_repeated() {
+    let conn = conn();
+    let (owner, wedding, list) = wedding(&conn);
+
+    let first = wedding
+        .photo_page(&conn, None, &page(PhotoSort::Index, Some(2), None))
+        .unwrap();
+    let before = photo(&conn, &wedding, &owner, 0, false);
+    let second = wedding
+        .photo_page(
+            &conn,
+            None,
+            &page(PhotoSort::Index, Some(2), first.next_cursor),
+        )
+        .unwrap();
+
+    let ids: Vec<Uuid> = second.list.iter().map(|p| p.id).collect();
+    assert_eq!(ids, vec![list[4].id, list[0].id]);
+    assert!(!ids.contains(&before.id));
+}
+
+#[test]
+fn tag_filter_applies_to_pages_and_total() {
+    let conn = conn();
+    let (owner, wedding, list) = wedding(&conn);
+    let sunset = vec![String::from("Sun set")];
+    Tag::add_to_photos(&conn, &owner, &sunset, &[list[0].clone(), list[2].clone()]).unwrap();
+
+    let tagged = wedding
+        .photo_page(
+            &conn,
+            Some("sun set"),
+            &page(PhotoSort::Index, Some(1), None),
+        )
+        .unwrap();
+
+    assert_eq!(tagged.total, 2);
+    assert_eq!(tagged.list[0].id, list[0].id);
+    assert!(tagged.next_cursor.is_some());
+}
+
+#[test]
+fn invalid_cursors_are_rejected() {
+    let conn = conn();
+    let (_, wedding, _) = wedding(&conn);
+    let first = wedding
+        .photo_page(&conn, None, &page(PhotoSort::Index, Some(2), None))
+        .unwrap();
+
+    let garbage = wedding.photo_page(
+        &conn,
+        None,
+        &page(PhotoSort::Index, Some(2), Some(String::from("index.x"))),
+    );
+    let other_sort = wedding.photo_page(
+        &conn,
+        None,
+        &page(PhotoSort::TakenAt, Some(2), first.next_cursor),
+    );
+
+    assert!(matches!(garbage, Err(ModelError::InvalidCursor)));
+    assert!(matches!(other_sort, Err(ModelError::InvalidCursor)));
+}
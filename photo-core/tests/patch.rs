@@ -0,0 +1,94 @@
+mod common;
+
+use chrono::NaiveDate;
+use common::{album, conn, photo, user};
+use diesel::prelude::*;
+use photo_core::helpers::precondition::Precondition;
+use photo_core::models::{AlbumChanges, ModelError, PhotoChanges};
+use photo_core::schema::photos;
+
+#[test]
+fn only_the_given_fields_are_changed() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let wedding = album(&conn, &owner, "Wedding");
+    let original = photo(&conn, &wedding, &owner, 0, true);
+
+    let changes = PhotoChanges {
+        title: Some(Some(String::from("First dance"))),
+        description: Some(None),
+        ..Default::default()
+    };
+    let patched = original.patch(&conn, &changes, None).unwrap();
+
+    assert_eq!(patched.title.as_deref(), Some("First dance"));
+    assert_eq!(patched.description, None);
+    assert_eq!(patched.is_favorite, original.is_favorite);
+    assert_eq!(patched.index_in_album, original.index_in_album);
+}
+
+#[test]
+fn stale_etags_are_rejected() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let wedding = album(&conn, &owner, "Wedding");
+    let original = photo(&conn, &wedding, &owner, 0, false);
+    let stale = Precondition::Matches(original.etag());
+
+    let favorite = PhotoChanges {
+        is_favorite: Some(true),
+        ..Default::default()
+    };
+    let patched = original.patch(&conn, &favorite, Some(&stale)).unwrap();
+    let result = original.patch(&conn, &favorite, Some(&stale));
+    let fresh = Precondition::Matches(patched.etag());
+
+    assert_ne!(patched.etag(), original.etag());
+    assert!(matches!(result, Err(ModelError::PreconditionFailed)));
+    assert!(original.patch(&conn, &favorite, Some(&fresh)).is_ok());
+}
+
+#[test]
+fn albums_are_patched_with_preconditions() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let wedding = album(&conn, &owner, "Wedding");
+    let stale = Precondition::Matches(wedding.etag());
+
+    let renamed = wedding
+        .patch(
+            &conn,
+            &AlbumChanges {
+                name: Some(String::from("Our wedding")),
+                ..Default::default()
+            },
+            Some(&stale),
+        )
+        .unwrap();
+    let result = wedding.patch(&conn, &AlbumChanges::default(), Some(&stale));
+
+    assert_eq!(renamed.name, "Our wedding");
+    assert_eq!(renamed.id, wedding.id);
+    assert!(matches!(result, Err(ModelError::PreconditionFailed)));
+}
+
+#[test]
+fn updating_returns_the_updated_photo() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let wedding = album(&conn, &owner, "Wedding");
+    let first = photo(&conn, &wedding, &owner, 0, false);
+    let second = photo(&conn, &wedding, &owner, 1, false);
+    // The other photo looks more recently updated than the one being changed.
+    diesel::update(photos::table.find(second.id))
+        .set(photos::updated_at.eq(NaiveDate::from_ymd(2999, 1, 1).and_hms(0, 0, 0)))
+        .execute(&conn)
+        .unwrap();
+
+    let updated = first
+        .update(&conn, 0, true, Some(String::from("Earlier")), None)
+        .unwrap();
+
+    assert_eq!(updated.id, first.id);
+    assert_eq!(updated.title.as_deref(), Some("Earlier"));
+}
@@ -0,0 +1,99 @@
+mod common;
+
+use common::{album, conn, photo, user};
+use photo_core::models::{ModelError, ProofingClient, ShareLink};
+
+#[test]
+fn clients_are_registered_once_per_email() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let wedding = album(&conn, &owner, "Wedding");
+    let link = ShareLink::new(&wedding, None, None).insert(&conn).unwrap();
+
+    let (client, _) = ProofingClient::register(&conn, &link, "Jane", "Jane@Example.com ").unwrap();
+    let again = ProofingClient::register(&conn, &link, "Mallory", "jane@example.com");
+
+    assert_eq!(client.email, "jane@example.com");
+    assert!(matches!(again, Err(ModelError::ProofingClientTaken)));
+    assert_eq!(ProofingClient::find_by_link(&conn, &link).unwrap().len(), 1);
+}
+
+#[test]
+fn clients_are_found_by_their_secret_on_their_link() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let wedding = album(&conn, &owner, "Wedding");
+    let link = ShareLink::new(&wedding, None, None).insert(&conn).unwrap();
+    let other_link = ShareLink::new(&wedding, None, None).insert(&conn).unwrap();
+    let (client, secret) =
+        ProofingClient::register(&conn, &link, "Jane", "jane@example.com").unwrap();
+
+    let found = ProofingClient::authenticate(&conn, &link, &secret).unwrap();
+    let unknown = ProofingClient::authenticate(&conn, &link, "unknown");
+    let elsewhere = ProofingClient::authenticate(&conn, &other_link, &secret);
+
+    assert_eq!(found.id, client.id);
+    assert!(matches!(unknown, Err(ModelError::InvalidProofingSecret)));
+    assert!(matches!(elsewhere, Err(ModelError::InvalidProofingSecret)));
+}
+
+#[test]
+fn selecting_twice_keeps_a_single_selection() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let wedding = album(&conn, &owner, "Wedding");
+    let first = photo(&conn, &wedding, &owner, 0, false);
+    let second = photo(&conn, &wedding, &owner, 1, false);
+    let link = ShareLink::new(&wedding, None, None).insert(&conn).unwrap();
+    let (client, _) = ProofingClient::register(&conn, &link, "Jane", "jane@example.com").unwrap();
+
+    client.select(&conn, &second, true).unwrap();
+    client.select(&conn, &first, true).unwrap();
+    client.select(&conn, &first, true).unwrap();
+    let summary = client.summary(&conn).unwrap();
+
+    let selected: Vec<_> = summary.selections.iter().map(|p| p.id).collect();
+    assert_eq!(selected, vec![first.id, second.id]);
+}
+
+#[test]
+fn unselecting_removes_the_photo() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let wedding = album(&conn, &owner, "Wedding");
+    let first = photo(&conn, &wedding, &owner, 0, false);
+    let link = ShareLink::new(&wedding, None, None).insert(&conn).unwrap();
+    let (client, _) = ProofingClient::register(&conn, &link, "Jane", "jane@example.com").unwrap();
+
+    client.select(&conn, &first, true).unwrap();
+    client.select(&conn, &first, false).unwrap();
+    let summary = client.summary(&conn).unwrap();
+
+    assert!(summary.selections.is_empty());
+}
+
+#[test]
+fn summaries_are_split_by_client() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let wedding = album(&conn, &owner, "Wedding");
+    let first = photo(&conn, &wedding, &owner, 0, false);
+    let second = photo(&conn, &wedding, &owner, 1, false);
+    let link = ShareLink::new(&wedding, None, None).insert(&conn).unwrap();
+    let (jane, _) = ProofingClient::register(&conn, &link, "Jane", "jane@example.com").unwrap();
+    let (john, _) = ProofingClient::register(&conn, &link, "John", "john@example.com").unwrap();
+
+    jane.select(&conn, &first, true).unwrap();
+    john.select(&conn, &second, true).unwrap();
+    john.comment(&conn, &second, String::from("Brighter please"))
+        .unwrap();
+    let summaries = ProofingClient::summaries_by_link(&conn, &link).unwrap();
+
+    assert_eq!(summaries.len(), 2);
+    assert_eq!(summaries[0].client.id, jane.id);
+    assert_eq!(summaries[0].selections[0].id, first.id);
+    assert!(summaries[0].comments.is_empty());
+    assert_eq!(summaries[1].client.id, john.id);
+    assert_eq!(summaries[1].selections[0].id, second.id);
+    assert_eq!(summaries[1].comments[0].body, "Brighter please");
+}
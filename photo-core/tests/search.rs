@@ -0,0 +1,114 @@
+mod common;
+
+use common::{album, conn, mark_deleted, photo, studio, user};
+use diesel::prelude::*;
+use photo_core::connection::Conn;
+use photo_core::models::{Album, Photo, SearchResults};
+use photo_core::schema::photos;
+
+fn set_title(conn: &Conn, photo: &Photo, title: &str) {
+    diesel::update(photos::table.find(photo.id))
+        .set(photos::title.eq(title))
+        .execute(conn)
+        .unwrap();
+}
+
+#[test]
+fn albums_match_by_prefix_while_typing() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let wedding = album(&conn, &owner, "Summer wedding");
+    album(&conn, &owner, "Birthday");
+
+    let results = SearchResults::find(&conn, &owner, "summer wed", 10).unwrap();
+
+    assert_eq!(results.albums.len(), 1);
+    assert_eq!(results.albums[0].album.id, wedding.id);
+    assert_eq!(
+        results.albums[0].snippet,
+        "<mark>Summer</mark> <mark>wedding</mark>"
+    );
+}
+
+#[test]
+fn snippets_escape_html_around_highlights() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let studio = studio(&conn, &owner, "Owner");
+    Album::new(
+        &owner,
+        &studio,
+        String::from("Party"),
+        Some(String::from("<b>Tom & Jerry's</b> \"wedding\"")),
+    )
+    .insert(&conn)
+    .unwrap();
+
+    let results = SearchResults::find(&conn, &owner, "jerry", 10).unwrap();
+
+    assert_eq!(
+        results.albums[0].snippet,
+        "&lt;b&gt;Tom &amp; <mark>Jerry</mark>&#39;s&lt;/b&gt; &quot;wedding&quot;"
+    );
+}
+
+#[test]
+fn query_syntax_is_searched_literally() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    album(&conn, &owner, "Wedding");
+
+    let quoted = SearchResults::find(&conn, &owner, "\"wedding", 10).unwrap();
+    let operators = SearchResults::find(&conn, &owner, "wedding AND (NEAR", 10).unwrap();
+    let empty = SearchResults::find(&conn, &owner, " \" ", 10).unwrap();
+
+    assert_eq!(quoted.albums.len(), 1);
+    assert!(operators.albums.is_empty());
+    assert!(empty.albums.is_empty() && empty.photos.is_empty());
+}
+
+#[test]
+fn diacritics_are_ignored() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    album(&conn, &owner, "Été à Nice");
+
+    let results = SearchResults::find(&conn, &owner, "ete a nice", 10).unwrap();
+
+    assert_eq!(results.albums.len(), 1);
+}
+
+#[test]
+fn photos_match_by_title_after_updates() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let wedding = album(&conn, &owner, "Wedding");
+    let dance = photo(&conn, &wedding, &owner, 0, false);
+    set_title(&conn, &dance, "Cake");
+    set_title(&conn, &dance, "First dance");
+
+    let old = SearchResults::find(&conn, &owner, "cake", 10).unwrap();
+    let new = SearchResults::find(&conn, &owner, "dance", 10).unwrap();
+
+    assert!(old.photos.is_empty());
+    assert_eq!(new.photos.len(), 1);
+    assert_eq!(new.photos[0].photo.id, dance.id);
+    assert_eq!(new.photos[0].snippet, "First <mark>dance</mark>");
+}
+
+#[test]
+fn other_users_and_deleted_albums_are_not_searched() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let other = user(&conn, "other@example.com");
+    let deleted = album(&conn, &owner, "Wedding rehearsal");
+    let photo_in_deleted = photo(&conn, &deleted, &owner, 0, false);
+    set_title(&conn, &photo_in_deleted, "Wedding rings");
+    mark_deleted(&conn, &deleted);
+    album(&conn, &other, "Wedding");
+
+    let results = SearchResults::find(&conn, &owner, "wedding", 10).unwrap();
+
+    assert!(results.albums.is_empty());
+    assert!(results.photos.is_empty());
+}
@@ -0,0 +1,119 @@
+mod common;
+
+use chrono::{Duration, Utc};
+use common::{conn, user};
+use photo_core::models::{LoginCode, ModelError, RevokedToken, Session};
+
+#[test]
+fn refresh_tokens_rotate() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let (session, token) = Session::new(&owner, None, None);
+    let session = session.insert(&conn).unwrap();
+
+    let (rotated, found, new_token) =
+        Session::rotate(&conn, &token, Some(String::from("Firefox")), None).unwrap();
+    let (_, _, newest_token) = Session::rotate(&conn, &new_token, None, None).unwrap();
+
+    assert_eq!(rotated.id, session.id);
+    assert_eq!(found.id, owner.id);
+    assert_eq!(rotated.user_agent.as_deref(), Some("Firefox"));
+    assert_ne!(new_token, token);
+    assert_ne!(newest_token, new_token);
+}
+
+#[test]
+fn reusing_a_rotated_token_revokes_the_session() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let (session, token) = Session::new(&owner, None, None);
+    let session = session.insert(&conn).unwrap();
+    let (_, _, new_token) = Session::rotate(&conn, &token, None, None).unwrap();
+
+    let reused = Session::rotate(&conn, &token, None, None);
+    let after = Session::rotate(&conn, &new_token, None, None);
+
+    assert!(matches!(reused, Err(ModelError::InvalidRefreshToken)));
+    assert!(matches!(after, Err(ModelError::InvalidRefreshToken)));
+    assert!(!Session::is_active(&conn, &session.id.to_string()).unwrap());
+}
+
+#[test]
+fn expired_sessions_can_not_be_refreshed() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let (mut session, token) = Session::new(&owner, None, None);
+    session.expires_at = Utc::now().naive_utc() - Duration::minutes(1);
+    let session = session.insert(&conn).unwrap();
+
+    let result = Session::rotate(&conn, &token, None, None);
+
+    assert!(matches!(result, Err(ModelError::InvalidRefreshToken)));
+    assert!(!Session::is_active(&conn, &session.id.to_string()).unwrap());
+    assert!(Session::find_by_user(&conn, &owner).unwrap().is_empty());
+}
+
+#[test]
+fn users_only_revoke_their_own_sessions() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let other = user(&conn, "other@example.com");
+    let (session, _) = Session::new(&owner, None, None);
+    let session = session.insert(&conn).unwrap();
+    let (second, _) = Session::new(&owner, None, None);
+    second.insert(&conn).unwrap();
+    let id = session.id.to_string();
+
+    let foreign = Session::revoke(&conn, &other, &id);
+    Session::revoke(&conn, &owner, &id).unwrap();
+
+    assert!(matches!(foreign, Err(ModelError::SessionNotFound)));
+    assert!(!Session::is_active(&conn, &id).unwrap());
+    assert_eq!(Session::find_by_user(&conn, &owner).unwrap().len(), 1);
+
+    Session::revoke_all(&conn, &owner).unwrap();
+    assert!(Session::find_by_user(&conn, &owner).unwrap().is_empty());
+}
+
+#[test]
+fn login_codes_can_only_be_used_once() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let (login_code, code) = LoginCode::new(&owner);
+    login_code.insert(&conn).unwrap();
+
+    let found = LoginCode::take(&conn, &code).unwrap();
+    let again = LoginCode::take(&conn, &code);
+
+    assert_eq!(found.id, owner.id);
+    assert!(matches!(again, Err(ModelError::InvalidLoginCode)));
+}
+
+#[test]
+fn expired_login_codes_are_rejected() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let (mut login_code, code) = LoginCode::new(&owner);
+    login_code.expires_at = Utc::now().naive_utc() - Duration::seconds(1);
+    login_code.insert(&conn).unwrap();
+
+    let result = LoginCode::take(&conn, &code);
+
+    assert!(matches!(result, Err(ModelError::InvalidLoginCode)));
+}
+
+#[test]
+fn revoked_tokens_are_denied() {
+    let conn = conn();
+    let expires_at = Utc::now().naive_utc() + Duration::hours(1);
+    RevokedToken::new(String::from("jti"), expires_at)
+        .insert(&conn)
+        .unwrap();
+    // Revoking twice, e.g. from two tabs, is not an error.
+    RevokedToken::new(String::from("jti"), expires_at)
+        .insert(&conn)
+        .unwrap();
+
+    assert!(RevokedToken::is_revoked(&conn, "jti").unwrap());
+    assert!(!RevokedToken::is_revoked(&conn, "other").unwrap());
+}
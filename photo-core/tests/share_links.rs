@@ -0,0 +1,150 @@
+mod common;
+
+use chrono::{Duration, Utc};
+use common::{album, conn, mark_deleted, user};
+use photo_core::connection::Conn;
+use photo_core::models::{Album, ModelError, Result, Role, ShareLink};
+
+/// Opens a link the way a visitor does: finds it, then counts the view.
+fn open(conn: &Conn, token: &str) -> Result<(ShareLink, Album)> {
+    let (link, album) = ShareLink::find_valid(conn, token)?;
+    let link = link.add_view(conn)?;
+
+    Ok((link, album))
+}
+
+#[test]
+fn opening_a_link_counts_the_visit() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let wedding = album(&conn, &owner, "Wedding");
+    let link = ShareLink::new(&wedding, None, None).insert(&conn).unwrap();
+
+    let (first, opened_album) = open(&conn, &link.token).unwrap();
+    let (second, _) = open(&conn, &link.token).unwrap();
+
+    assert_eq!(opened_album.id, wedding.id);
+    assert_eq!(first.views, 1);
+    assert_eq!(second.views, 2);
+}
+
+#[test]
+fn expired_links_cannot_be_opened() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let wedding = album(&conn, &owner, "Wedding");
+    let yesterday = Utc::now().naive_utc() - Duration::days(1);
+    let tomorrow = Utc::now().naive_utc() + Duration::days(1);
+    let expired = ShareLink::new(&wedding, Some(yesterday), None)
+        .insert(&conn)
+        .unwrap();
+    let active = ShareLink::new(&wedding, Some(tomorrow), None)
+        .insert(&conn)
+        .unwrap();
+
+    let expired_result = open(&conn, &expired.token);
+    let active_result = open(&conn, &active.token);
+
+    assert!(matches!(expired_result, Err(ModelError::InvalidShareLink)));
+    assert!(active_result.is_ok());
+}
+
+#[test]
+fn links_stop_working_after_their_last_view() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let wedding = album(&conn, &owner, "Wedding");
+    let link = ShareLink::new(&wedding, None, Some(2))
+        .insert(&conn)
+        .unwrap();
+
+    open(&conn, &link.token).unwrap();
+    open(&conn, &link.token).unwrap();
+    let third = open(&conn, &link.token);
+
+    assert!(matches!(third, Err(ModelError::InvalidShareLink)));
+}
+
+#[test]
+fn revoked_links_cannot_be_opened() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let wedding = album(&conn, &owner, "Wedding");
+    let link = ShareLink::new(&wedding, None, None).insert(&conn).unwrap();
+
+    let revoked = link.revoke(&conn).unwrap();
+    let result = open(&conn, &link.token);
+
+    assert!(revoked.revoked);
+    assert!(matches!(result, Err(ModelError::InvalidShareLink)));
+}
+
+#[test]
+fn links_to_deleted_albums_cannot_be_opened() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let wedding = album(&conn, &owner, "Wedding");
+    let link = ShareLink::new(&wedding, None, None).insert(&conn).unwrap();
+    mark_deleted(&conn, &wedding);
+
+    let result = open(&conn, &link.token);
+
+    assert!(matches!(result, Err(ModelError::InvalidShareLink)));
+}
+
+#[test]
+fn unknown_tokens_cannot_be_opened() {
+    let conn = conn();
+
+    let result = open(&conn, "not-a-token");
+
+    assert!(matches!(result, Err(ModelError::InvalidShareLink)));
+}
+
+#[test]
+fn links_of_other_studios_are_not_found() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let other = user(&conn, "other@example.com");
+    let wedding = album(&conn, &owner, "Wedding");
+    let link = ShareLink::new(&wedding, None, None).insert(&conn).unwrap();
+    let id = link.id.to_string();
+
+    let own = ShareLink::find_for_member(&conn, &owner, &id, Role::Owner);
+    let foreign = ShareLink::find_for_member(&conn, &other, &id, Role::Viewer);
+    let unknown = ShareLink::find_for_member(&conn, &owner, "unknown", Role::Viewer);
+
+    assert_eq!(own.unwrap().id, link.id);
+    assert!(matches!(foreign, Err(ModelError::ShareLinkNotFound)));
+    assert!(matches!(unknown, Err(ModelError::ShareLinkNotFound)));
+}
+
+#[test]
+fn link_passwords_are_independent_from_the_album() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let wedding = album(&conn, &owner, "Wedding")
+        .set_password(&conn, Some("album-pass"))
+        .unwrap();
+    let link = ShareLink::new(&wedding, None, None)
+        .insert(&conn)
+        .unwrap()
+        .set_password(&conn, Some("link-pass"))
+        .unwrap();
+
+    assert!(link.is_protected());
+    assert!(link.verify_password("link-pass"));
+    assert!(!link.verify_password("album-pass"));
+    assert!(wedding.verify_password("album-pass"));
+}
+
+#[test]
+fn links_without_password_are_not_protected() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let wedding = album(&conn, &owner, "Wedding");
+    let link = ShareLink::new(&wedding, None, None).insert(&conn).unwrap();
+
+    assert!(!link.is_protected());
+    assert!(!link.verify_password(""));
+}
@@ -0,0 +1,164 @@
+mod common;
+
+use common::{album, album_in, conn, photo, studio, user};
+use photo_core::models::{Album, ModelError, Photo, PhotoPagination, PhotoSort, Role, Studio};
+
+#[test]
+fn albums_are_found_according_to_the_role_of_members() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let viewer = user(&conn, "viewer@example.com");
+    let stranger = user(&conn, "stranger@example.com");
+    let team = studio(&conn, &owner, "Team");
+    team.set_member(&conn, &viewer, Role::Viewer).unwrap();
+    let wedding = album_in(&conn, &team, &owner, "Wedding");
+    let id = wedding.id.to_string();
+
+    let browsed = Album::find_for_member(&conn, &viewer, &id, Role::Viewer);
+    let edited = Album::find_for_member(&conn, &viewer, &id, Role::Editor);
+    let foreign = Album::find_for_member(&conn, &stranger, &id, Role::Viewer);
+    let owned = Album::find_for_member(&conn, &owner, &id, Role::Owner);
+
+    assert_eq!(browsed.unwrap().id, wedding.id);
+    assert!(matches!(edited, Err(ModelError::Forbidden)));
+    assert!(matches!(foreign, Err(ModelError::AlbumNotFound)));
+    assert!(owned.is_ok());
+}
+
+#[test]
+fn studios_keep_an_owner() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let partner = user(&conn, "partner@example.com");
+    let team = studio(&conn, &owner, "Team");
+
+    let demoted = team.set_member(&conn, &owner, Role::Editor);
+    let removed = team.remove_member(&conn, &owner.id);
+    team.set_member(&conn, &partner, Role::Owner).unwrap();
+    team.remove_member(&conn, &owner.id).unwrap();
+    let unknown = team.remove_member(&conn, &owner.id);
+
+    assert!(matches!(demoted, Err(ModelError::LastOwner)));
+    assert!(matches!(removed, Err(ModelError::LastOwner)));
+    assert!(matches!(unknown, Err(ModelError::MemberNotFound)));
+    assert_eq!(Studio::role_of(&conn, &team.id, &owner).unwrap(), None);
+}
+
+#[test]
+fn members_list_their_studios_with_their_role() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let editor = user(&conn, "editor@example.com");
+    let mine = Studio::new(String::from("Mine"))
+        .insert_personal(&conn, &editor)
+        .unwrap();
+    let team = studio(&conn, &owner, "Team");
+    team.set_member(&conn, &editor, Role::Editor).unwrap();
+
+    let list = Studio::find_by_member(&conn, &editor).unwrap();
+    let personal = Studio::find_personal(&conn, &editor).unwrap();
+    let editing = Studio::ids_for_member(&conn, &editor, Role::Editor).unwrap();
+    let owning = Studio::ids_for_member(&conn, &editor, Role::Owner).unwrap();
+
+    assert_eq!(list.len(), 2);
+    assert_eq!(list[0].role, Role::Owner);
+    assert_eq!(list[1].role, Role::Editor);
+    assert_eq!(personal.id, mine.id);
+    assert_eq!(editing.len(), 2);
+    assert_eq!(owning, vec![mine.id]);
+}
+
+#[test]
+fn personal_studio_is_kept_when_owning_older_studios() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let partner = user(&conn, "partner@example.com");
+    let team = studio(&conn, &owner, "Team");
+    let personal = Studio::new(String::from("Partner"))
+        .insert_personal(&conn, &partner)
+        .unwrap();
+
+    team.set_member(&conn, &partner, Role::Owner).unwrap();
+    let found = Studio::find_personal(&conn, &partner).unwrap();
+    let missing = Studio::find_personal(&conn, &owner);
+    personal.set_member(&conn, &owner, Role::Owner).unwrap();
+    personal.remove_member(&conn, &partner.id).unwrap();
+    let left = Studio::find_personal(&conn, &partner);
+
+    assert_eq!(found.id, personal.id);
+    assert!(matches!(missing, Err(ModelError::StudioNotFound)));
+    assert!(matches!(left, Err(ModelError::StudioNotFound)));
+}
+
+#[test]
+fn public_listings_show_the_albums_of_the_personal_studio() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let editor = user(&conn, "editor@example.com");
+    let published = album(&conn, &owner, "Published");
+    let personal = Studio::find_personal(&conn, &owner).unwrap();
+    personal.set_member(&conn, &editor, Role::Editor).unwrap();
+    let contributed = album_in(&conn, &personal, &editor, "Contributed");
+    let team = studio(&conn, &owner, "Team");
+    album_in(&conn, &team, &owner, "Draft");
+
+    let all = Album::find_all(&conn, &owner).unwrap();
+    let public = Album::find_children(&conn, &owner, None).unwrap();
+    let public_of_editor = Album::find_children(&conn, &editor, None).unwrap();
+
+    let ids: Vec<_> = public.iter().map(|s| s.album.id).collect();
+    assert_eq!(all.len(), 3);
+    assert_eq!(ids, vec![published.id, contributed.id]);
+    assert!(public_of_editor.is_empty());
+}
+
+#[test]
+fn slugs_are_unique_in_the_studio() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let editor = user(&conn, "editor@example.com");
+    let first = album(&conn, &owner, "Wedding");
+    let personal = Studio::find_personal(&conn, &owner).unwrap();
+    personal.set_member(&conn, &editor, Role::Editor).unwrap();
+    let second = album_in(&conn, &personal, &editor, "Wedding");
+
+    let found = Album::find_by_slug(&conn, &owner, "wedding-2").unwrap();
+    let of_editor = Album::find_by_slug(&conn, &editor, "wedding-2");
+
+    assert_eq!(first.slug, "wedding");
+    assert_eq!(found.id, second.id);
+    assert!(matches!(of_editor, Err(ModelError::AlbumNotFound)));
+}
+
+#[test]
+fn public_favorites_are_the_ones_of_the_personal_studio() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let editor = user(&conn, "editor@example.com");
+    let wedding = album(&conn, &owner, "Wedding");
+    let personal = Studio::find_personal(&conn, &owner).unwrap();
+    personal.set_member(&conn, &editor, Role::Editor).unwrap();
+    let favorite = photo(&conn, &wedding, &editor, 0, true);
+    let team = studio(&conn, &owner, "Team");
+    photo(
+        &conn,
+        &album_in(&conn, &team, &owner, "Draft"),
+        &owner,
+        0,
+        true,
+    );
+    let pagination = PhotoPagination {
+        sort: PhotoSort::Index,
+        limit: None,
+        cursor: None,
+    };
+
+    let (private, _) = Photo::find_favorites(&conn, &editor, false, &pagination).unwrap();
+    let (public, _) = Photo::find_favorites(&conn, &editor, true, &pagination).unwrap();
+    let (owners, _) = Photo::find_favorites(&conn, &owner, true, &pagination).unwrap();
+
+    assert_eq!(private.list[0].id, favorite.id);
+    assert!(public.list.is_empty());
+    assert_eq!(owners.total, 1);
+    assert_eq!(owners.list[0].id, favorite.id);
+}
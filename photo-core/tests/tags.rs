@@ -0,0 +1,177 @@
+mod common;
+
+use common::{album, album_in, conn, photo, studio, user};
+use diesel::prelude::*;
+use photo_core::models::{PhotoPagination, PhotoSort, Role, Tag};
+use photo_core::schema::photos;
+
+fn names(list: &[&str]) -> Vec<String> {
+    list.iter().map(|name| String::from(*name)).collect()
+}
+
+#[test]
+fn tags_are_matched_by_slug() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let wedding = album(&conn, &owner, "Wedding");
+    let studio_id = wedding.studio_id;
+
+    let first =
+        Tag::find_or_create(&conn, &studio_id, &owner, &names(&["First Dance", "Cake"])).unwrap();
+    let second = Tag::find_or_create(
+        &conn,
+        &studio_id,
+        &owner,
+        &names(&["first dance", " ", "FIRST-DANCE"]),
+    )
+    .unwrap();
+
+    assert_eq!(first.len(), 2);
+    assert_eq!(first[0].name, "Cake");
+    assert_eq!(second.len(), 1);
+    assert_eq!(second[0].id, first[1].id);
+    assert_eq!(second[0].slug, "first-dance");
+}
+
+#[test]
+fn studios_have_their_own_tags() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let other = user(&conn, "other@example.com");
+    let mine = album(&conn, &owner, "Wedding");
+    let theirs = album(&conn, &other, "Wedding");
+
+    let mine = Tag::find_or_create(&conn, &mine.studio_id, &owner, &names(&["Cake"])).unwrap();
+    let theirs = Tag::find_or_create(&conn, &theirs.studio_id, &other, &names(&["Cake"])).unwrap();
+
+    assert_ne!(mine[0].id, theirs[0].id);
+    assert_eq!(Tag::find_all(&conn, &owner).unwrap().len(), 1);
+}
+
+#[test]
+fn tagging_twice_labels_photos_once() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let wedding = album(&conn, &owner, "Wedding");
+    let first = photo(&conn, &wedding, &owner, 0, false);
+    let second = photo(&conn, &wedding, &owner, 1, false);
+
+    let both = vec![first.clone(), second.clone()];
+    Tag::add_to_photos(&conn, &owner, &names(&["Cake"]), &both).unwrap();
+    Tag::add_to_photos(&conn, &owner, &names(&["cake"]), &[first.clone()]).unwrap();
+    let counts = Tag::find_all(&conn, &owner).unwrap();
+    let by_photo = Tag::find_by_photos(&conn, &both).unwrap();
+
+    assert_eq!(counts.len(), 1);
+    assert_eq!(counts[0].photos_count, 2);
+    assert_eq!(by_photo[0].photo_id, first.id);
+    assert_eq!(by_photo[0].tags.len(), 1);
+    assert_eq!(by_photo[1].tags[0].id, counts[0].tag.id);
+}
+
+#[test]
+fn counts_skip_deleted_photos() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let wedding = album(&conn, &owner, "Wedding");
+    let kept = photo(&conn, &wedding, &owner, 0, false);
+    let deleted = photo(&conn, &wedding, &owner, 1, false);
+    Tag::find_or_create(&conn, &wedding.studio_id, &owner, &names(&["Unused"])).unwrap();
+    Tag::add_to_photos(&conn, &owner, &names(&["Cake"]), &[kept, deleted.clone()]).unwrap();
+    diesel::update(photos::table.find(deleted.id))
+        .set(photos::deleted.eq(true))
+        .execute(&conn)
+        .unwrap();
+
+    let counts = Tag::find_all(&conn, &owner).unwrap();
+
+    assert_eq!(counts[0].tag.name, "Cake");
+    assert_eq!(counts[0].photos_count, 1);
+    assert_eq!(counts[1].tag.name, "Unused");
+    assert_eq!(counts[1].photos_count, 0);
+}
+
+#[test]
+fn photo_ids_find_tagged_photos_by_name_or_slug() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let wedding = album(&conn, &owner, "Wedding");
+    let dance = photo(&conn, &wedding, &owner, 0, false);
+    photo(&conn, &wedding, &owner, 1, false);
+    Tag::add_to_photos(&conn, &owner, &names(&["First Dance"]), &[dance.clone()]).unwrap();
+
+    let by_name = Tag::photo_ids(&conn, &wedding.studio_id, "First Dance").unwrap();
+    let by_slug = Tag::photo_ids(&conn, &wedding.studio_id, "first-dance").unwrap();
+
+    assert_eq!(by_name.len(), 1);
+    assert!(by_name.contains(&dance.id));
+    assert_eq!(by_name, by_slug);
+}
+
+#[test]
+fn removing_tags_keeps_the_others() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let wedding = album(&conn, &owner, "Wedding");
+    let dance = photo(&conn, &wedding, &owner, 0, false);
+    Tag::add_to_photos(&conn, &owner, &names(&["Cake", "Dance"]), &[dance.clone()]).unwrap();
+
+    Tag::remove_from_photos(&conn, &names(&["cake"]), &[dance.clone()]).unwrap();
+    let by_photo = Tag::find_by_photos(&conn, &[dance]).unwrap();
+
+    assert_eq!(by_photo[0].tags.len(), 1);
+    assert_eq!(by_photo[0].tags[0].name, "Dance");
+}
+
+#[test]
+fn tags_of_an_editor_are_shared_with_the_studio() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let editor = user(&conn, "editor@example.com");
+    let team = studio(&conn, &owner, "Team");
+    team.set_member(&conn, &editor, Role::Editor).unwrap();
+    let wedding = album_in(&conn, &team, &owner, "Wedding");
+    let dance = photo(&conn, &wedding, &owner, 0, false);
+    photo(&conn, &wedding, &owner, 1, false);
+
+    Tag::add_to_photos(&conn, &editor, &names(&["First Dance"]), &[dance.clone()]).unwrap();
+    let pagination = PhotoPagination {
+        sort: PhotoSort::Index,
+        limit: None,
+        cursor: None,
+    };
+    let filtered = wedding
+        .photo_page(&conn, Some("first dance"), &pagination)
+        .unwrap();
+    let counts = Tag::find_all(&conn, &owner).unwrap();
+
+    assert_eq!(filtered.total, 1);
+    assert_eq!(filtered.list[0].id, dance.id);
+    assert_eq!(counts.len(), 1);
+    assert_eq!(counts[0].tag.studio_id, team.id);
+    assert_eq!(counts[0].tag.user_id, editor.id);
+}
+
+#[test]
+fn photos_get_the_tags_of_their_own_studio() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let personal = album(&conn, &owner, "Personal");
+    let team = studio(&conn, &owner, "Team");
+    let shared = album_in(&conn, &team, &owner, "Shared");
+    let first = photo(&conn, &personal, &owner, 0, false);
+    let second = photo(&conn, &shared, &owner, 0, false);
+
+    Tag::add_to_photos(
+        &conn,
+        &owner,
+        &names(&["Cake"]),
+        &[first.clone(), second.clone()],
+    )
+    .unwrap();
+    let by_photo = Tag::find_by_photos(&conn, &[first, second]).unwrap();
+
+    assert_eq!(by_photo[0].tags[0].studio_id, personal.studio_id);
+    assert_eq!(by_photo[1].tags[0].studio_id, team.id);
+    assert_eq!(Tag::find_all(&conn, &owner).unwrap().len(), 2);
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use common::{conn, user};
+use photo_core::models::PhotoUpload;
+
+#[test]
+fn uploads_can_only_be_taken_once() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let keywords = vec![String::from("cake"), String::from("dance")];
+    PhotoUpload::new(
+        &owner,
+        String::from("s3-42"),
+        Some(String::from("First dance")),
+        None,
+        &keywords,
+    )
+    .insert(&conn)
+    .unwrap();
+
+    let first = PhotoUpload::take(&conn, &owner, "s3-42").unwrap().unwrap();
+    let second = PhotoUpload::take(&conn, &owner, "s3-42").unwrap();
+
+    assert_eq!(first.title.as_deref(), Some("First dance"));
+    assert_eq!(first.keywords(), keywords);
+    assert!(second.is_none());
+}
+
+#[test]
+fn uploads_of_other_users_are_not_taken() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let other = user(&conn, "other@example.com");
+    PhotoUpload::new(&owner, String::from("s3-42"), None, None, &[])
+        .insert(&conn)
+        .unwrap();
+
+    let taken_by_other = PhotoUpload::take(&conn, &other, "s3-42").unwrap();
+    let taken_by_owner = PhotoUpload::take(&conn, &owner, "s3-42").unwrap();
+
+    assert!(taken_by_other.is_none());
+    assert!(taken_by_owner.unwrap().keywords().is_empty());
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use common::{album, conn, photo, user};
+use photo_core::models::{ModelError, Photo, PhotoImage, PhotoVersion};
+
+fn image(key: &str) -> PhotoImage {
+    PhotoImage {
+        s3_id: key.to_string(),
+        src: format!("https://cdn.example.com/{}", key),
+        main_color: String::from("#000000"),
+        width: 800,
+        height: 600,
+        filename: Some(format!("{}.jpg", key)),
+    }
+}
+
+#[test]
+fn replacing_keeps_the_previous_image() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let wedding = album(&conn, &owner, "Wedding");
+    let original = photo(&conn, &wedding, &owner, 0, true);
+
+    let replaced = original.replace_image(&conn, &image("edited")).unwrap();
+    let versions = PhotoVersion::find_by_photo(&conn, &replaced).unwrap();
+
+    assert_eq!(replaced.s3_id, "edited");
+    assert_eq!(replaced.title, original.title);
+    assert_eq!(replaced.is_favorite, original.is_favorite);
+    assert_eq!(replaced.index_in_album, original.index_in_album);
+    assert_eq!(versions.len(), 1);
+    assert_eq!(versions[0].s3_id, original.s3_id);
+}
+
+#[test]
+fn reverting_swaps_the_images() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let wedding = album(&conn, &owner, "Wedding");
+    let original = photo(&conn, &wedding, &owner, 0, false);
+    let replaced = original.replace_image(&conn, &image("edited")).unwrap();
+    let version = PhotoVersion::find_by_photo(&conn, &replaced)
+        .unwrap()
+        .remove(0);
+
+    let reverted = replaced.revert(&conn, &version).unwrap();
+    let versions = PhotoVersion::find_by_photo(&conn, &reverted).unwrap();
+
+    assert_eq!(reverted.s3_id, original.s3_id);
+    assert_eq!(versions.len(), 1);
+    assert_eq!(versions[0].s3_id, "edited");
+}
+
+#[test]
+fn versions_of_other_photos_are_not_found() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let wedding = album(&conn, &owner, "Wedding");
+    let first = photo(&conn, &wedding, &owner, 0, false);
+    let second = photo(&conn, &wedding, &owner, 1, false);
+    let first = first.replace_image(&conn, &image("edited")).unwrap();
+    let version = PhotoVersion::find_by_photo(&conn, &first)
+        .unwrap()
+        .remove(0);
+
+    let result = PhotoVersion::find_by_id(&conn, &second, &version.id.to_string());
+
+    assert!(matches!(result, Err(ModelError::PhotoVersionNotFound)));
+}
+
+#[test]
+fn deleting_removes_the_versions() {
+    let conn = conn();
+    let owner = user(&conn, "owner@example.com");
+    let wedding = album(&conn, &owner, "Wedding");
+    let original = photo(&conn, &wedding, &owner, 0, false);
+    let replaced = original.replace_image(&conn, &image("edited")).unwrap();
+
+    replaced.delete(&conn).unwrap();
+
+    assert!(Photo::find_by_id(&conn, &replaced.id.to_string()).is_err());
+    assert!(PhotoVersion::find_by_photo(&conn, &replaced)
+        .unwrap()
+        .is_empty());
+}
@@ -1,7 +1,7 @@
 import { Store } from 'redux';
 import merge from 'lodash/merge';
 import { selectToken, logout, AuthenticatedUser as User } from '../store/auth';
-import { AlbumWithPhotos, Photo } from '../store/albums';
+import { AlbumSummary, Photo } from '../store/albums';
 import { BookMe } from '../store/bookMe';
 
 export const ApiFactory = {
@@ -125,10 +125,27 @@ export const ApiFactory = {
 
   apiInstance: (store: Store) => {
     return {
+      exchangeLoginCode: async function exchangeLoginCode(
+        code: string
+      ): Promise<LoginCodeResponse> {
+        const res = await fetch(`${ApiFactory.forgeUrl()}/api/token/exchange`, {
+          method: 'POST',
+          headers: {
+            'Content-Type': 'application/json',
+          },
+          body: JSON.stringify({ code }),
+        });
+
+        if (!res.ok) {
+          throw new Error(res.statusText);
+        }
+
+        return res.json();
+      },
       getMe: function getMe(): Promise<AuthenticatedUser | null> {
         return ApiFactory.get(store, '/api/me');
       },
-      getAlbums: function getAlbums(): Promise<{ list: AlbumWithPhotos[] }> {
+      getAlbums: function getAlbums(): Promise<{ list: AlbumSummary[] }> {
         return ApiFactory.getOrFail(store, '/api/albums');
       },
       getAlbumPhotos: function getAlbumPhotos(albumId: string): Promise<{ list: Photo[] }> {
@@ -201,6 +218,11 @@ export type AuthenticatedUser = {
   token: string;
 };
 
+export type LoginCodeResponse = AuthenticatedUser & {
+  refreshToken: string;
+  expiresIn: number;
+};
+
 type RequestOptions = {
   method: 'GET' | 'POST' | 'PUT' | 'DELETE';
   body?: Blob | BufferSource | FormData | URLSearchParams | ReadableStream<Uint8Array> | string;
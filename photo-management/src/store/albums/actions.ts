@@ -1,12 +1,12 @@
 import { createAsyncAction, createAction } from 'typesafe-actions';
-import { AlbumWithPhotos, Album, Photo } from './types';
+import { AlbumSummary, Album, Photo } from './types';
 
 export const fetchAllAlbums = createAsyncAction(
   'albums/fetch_all',
   'albums/fetch_all_success',
   'albums/fetch_all_error',
   'albums/fetch_all_cancel'
-)<void, AlbumWithPhotos[], Error, void>();
+)<void, AlbumSummary[], Error, void>();
 
 export const openAlbum = createAction('albums/open')<Album>();
 
@@ -1,6 +1,6 @@
 import { createSelector } from 'reselect';
 import { ApplicationState } from '../index';
-import { AlbumWithPhotos } from './types';
+import { AlbumSummary } from './types';
 
 export const selectAlbums = (state: ApplicationState) => {
   const albums = state.albums.list.data;
@@ -15,10 +15,10 @@ export const selectAlbums = (state: ApplicationState) => {
 export const selectAlbumById = createSelector(
   selectAlbums,
   (_state: ApplicationState, albumId: string) => albumId,
-  (albums, albumId): AlbumWithPhotos | null => {
+  (albums, albumId): AlbumSummary | null => {
     if (!albums) return null;
 
-    const album = albums.find(([album]) => album.id === albumId);
+    const album = albums.find(({ album }) => album.id === albumId);
 
     return album || null;
   }
@@ -1,6 +1,6 @@
 import { AsyncData, AsyncStatus } from '../../utils/types';
 
-export type AlbumsState = AsyncData<AlbumWithPhotos[]>;
+export type AlbumsState = AsyncData<AlbumSummary[]>;
 
 export type AlbumOpenedState = AsyncData<AlbumWithPhotos> & {
   upload: AsyncStatus;
@@ -9,6 +9,14 @@ export type AlbumOpenedState = AsyncData<AlbumWithPhotos> & {
 
 export type AlbumWithPhotos = [Album, Photo[]];
 
+export type AlbumSummary = {
+  album: Album;
+  cover: Photo | null;
+  photosCount: number;
+  favoritesCount: number;
+  lastUpdatedAt: number;
+};
+
 export type Album = {
   id: string;
   userId: string;
@@ -13,7 +13,8 @@ export default function* authSaga() {
 function* handleAuthentication(action: ActionType<typeof authenticate.request>) {
   const api = getApi();
 
-  yield* put(setToken(action.payload));
+  const { token } = yield* call(api.exchangeLoginCode, action.payload);
+  yield* put(setToken(token));
 
   const me = yield* call(api.getMe);
 
@@ -17,7 +17,7 @@ const AlbumView: FC<Props> = ({ openAlbum, fetchAllAlbums, match, album, albumsL
       return;
     }
 
-    openAlbum(album[0]);
+    openAlbum(album.album);
   }, [id, openAlbum, album]);
 
   useEffect(() => {
@@ -32,7 +32,7 @@ const AlbumView: FC<Props> = ({ openAlbum, fetchAllAlbums, match, album, albumsL
 
   return (
     <div className="home__default-album">
-      <AlbumOpened albumId={album[0].id} />
+      <AlbumOpened albumId={album.album.id} />
     </div>
   );
 };
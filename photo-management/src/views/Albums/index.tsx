@@ -15,21 +15,24 @@ const Albums: FC<Props> = ({ fetchAllAlbums, albums }) => {
     <div className="albums">
       <h1 className="albums__title">Albums</h1>
       <div className="albums__list">
-        {albums.map(([album, photos]) => (
+        {albums.map(({ album, cover, photosCount }) => (
           <div className="album__wrapper" data-testid={`album-${album.id}`} key={album.id}>
             <Link to={`/album/${album.id}`}>
               <ul className="album__photos">
-                {photos.map((photo) => (
-                  <li key={photo.id} className="album__photo__wrapper">
+                {cover && (
+                  <li className="album__photo__wrapper">
                     <div
                       className="album__photo"
-                      style={{ backgroundImage: `url(${photo.src})` }}
+                      style={{ backgroundImage: `url(${cover.src})` }}
                     />
                   </li>
-                ))}
+                )}
               </ul>
             </Link>
             <p className="album__name">{album.name}</p>
+            <p className="album__count">
+              {photosCount} {photosCount === 1 ? 'photo' : 'photos'}
+            </p>
             <p className="album__description">{album.description}</p>
           </div>
         ))}
@@ -10,8 +10,8 @@ const Authorize: FC<Props> = ({ authenticate }) => {
   useEffect(() => {
     const search = parse(location.search.substring(1));
 
-    if (search.token && typeof search.token === 'string') {
-      authenticate(search.token);
+    if (search.code && typeof search.code === 'string') {
+      authenticate(search.code);
     }
   }, [location.search, authenticate]);
 